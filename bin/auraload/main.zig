@@ -125,6 +125,13 @@ pub fn main() !void {
         return error.UnsupportedVersion;
     }
 
+    // Built with --load-base: .data would be expected somewhere other than
+    // the fixed address below.
+    if ((header.flags & 0x02) != 0) {
+        std.debug.print("Error: Binary requires a custom data load base\n", .{});
+        return error.UnsupportedLoadBase;
+    }
+
     var out_buf: [8192]u8 = undefined;
     var out = OutStream{ .file = std.fs.File.stdout(), .buffer = &out_buf };
 
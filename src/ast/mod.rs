@@ -1,3 +1,7 @@
+use crate::lexer::Span;
+
+pub mod visit;
+
 #[derive(Debug, Clone)]
 pub struct Program {
     pub items: Vec<Item>,
@@ -15,6 +19,7 @@ pub enum Item {
     TopologyDecl(TopologyDecl),
     BitRegionDecl(BitRegionDecl),
     EntropyDecl(EntropyDecl),
+    StaticAssert(StaticAssert),
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +29,10 @@ pub struct Function {
     pub return_type: Box<Type>,
     pub body: Vec<Stmt>,
     pub attrs: Vec<FunctionAttribute>,
+    pub span: Span,
+    /// `...` after the last parameter: the call may pass any number of
+    /// extra arguments (only allowed on `extern` declarations)
+    pub variadic: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +48,11 @@ pub enum FunctionAttribute {
     StdCall,
     Inline,
     Entry(Option<String>),
+    Const,
+    /// `@must_use`: dropping the call's result is warned about
+    MustUse,
+    /// `extern fn`: declared without a body and defined outside the program
+    Extern,
 }
 
 #[derive(Debug, Clone)]
@@ -46,12 +60,18 @@ pub struct Struct {
     pub name: String,
     pub fields: Vec<StructField>,
     pub topology: Option<MemoryTopology>,
+    /// `@align(N)`: minimum alignment, never below the natural one
+    pub align: Option<usize>,
+    /// `@packed`: fields are laid out back to back with no padding
+    pub packed: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct StructField {
     pub name: String,
     pub ty: Box<Type>,
+    /// `@align(N)` on the field: minimum alignment of its offset
+    pub align: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -85,6 +105,14 @@ pub struct ConstDecl {
     pub value: Box<Expr>,
 }
 
+/// `static_assert(condition);` or `static_assert(condition, "message");`,
+/// checked by the const evaluator and gone before code generation.
+#[derive(Debug, Clone)]
+pub struct StaticAssert {
+    pub condition: Box<Expr>,
+    pub message: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct VarDecl {
     pub name: String,
@@ -198,8 +226,9 @@ pub enum Stmt {
     Const(ConstStmt),
     Expr(Expr),
     Return(Option<Expr>),
-    Break,
-    Continue,
+    /// The span is the keyword's
+    Break(Option<Expr>, Span),
+    Continue(Span),
     Block(Vec<Stmt>),
     If(IfStmt),
     While(WhileStmt),
@@ -219,8 +248,13 @@ pub struct EntropyAssertStmt {
 pub struct LetStmt {
     pub name: String,
     pub ty: Option<Box<Type>>,
-    pub value: Box<Expr>,
+    pub value: Option<Box<Expr>>,
     pub is_const: bool,
+    /// `static`: one slot in `.data` for the whole program, initialized once
+    /// with a constant, but only visible inside the enclosing function
+    pub is_static: bool,
+    /// The span of the name
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -268,23 +302,29 @@ pub struct AsmOperand {
 #[derive(Debug, Clone)]
 pub enum Expr {
     Literal(Literal),
-    Identifier(String),
+    Identifier(String, Span),
     Unary(UnaryOp, Box<Expr>),
-    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// The span is the operator's
+    Binary(BinaryOp, Box<Expr>, Box<Expr>, Span),
     Call(Box<Expr>, Vec<Expr>),
     Syscall(String, Vec<Expr>),
+    // syscall(n, args...): the syscall number, then up to six arguments
+    RawSyscall(Vec<Expr>),
     Index(Box<Expr>, Box<Expr>),
     Field(Box<Expr>, String),
     PtrField(Box<Expr>, String),
     Cast(Box<Expr>, Type),
     Sizeof(Type),
     Alignof(Type),
+    /// `offsetof(T, a.b)`: the field path, dot-separated
     Offsetof(Type, String),
+    Zeroed(Type),
+    ArrayLit(Vec<Expr>),
     Assign(Box<Expr>, Box<Expr>),
     AddrOf(Box<Expr>),
-    Deref(Box<Expr>),
     Block(Vec<Stmt>, Option<Box<Expr>>),
     If(Box<IfExpr>),
+    Loop(Vec<Stmt>),
     Alloc(Box<Type>, Box<Expr>),
     Free(Box<Expr>, Box<Expr>),
     PhysAddr(PhysAddrExpr),
@@ -465,7 +505,7 @@ impl Type {
                 } else if *bits <= 128 {
                     16
                 } else {
-                    (*bits as usize + 7) / 8
+                    (*bits as usize).div_ceil(8)
                 }
             }
             Type::Ptr(_) | Type::MutPtr(_) | Type::ConstPtr(_) => 8,
@@ -503,8 +543,6 @@ impl Type {
                     2
                 } else if *bits <= 32 {
                     4
-                } else if *bits <= 64 {
-                    8
                 } else if *bits <= 128 {
                     8
                 } else {
@@ -546,6 +584,14 @@ impl Type {
         )
     }
 
+    pub fn int_bits(&self) -> Option<u32> {
+        match self {
+            Type::BitInt(bits, _) => Some(*bits as u32),
+            _ if self.is_integer() => Some(self.size() as u32 * 8),
+            _ => None,
+        }
+    }
+
     pub fn is_float(&self) -> bool {
         matches!(self, Type::F32 | Type::F64)
     }
@@ -0,0 +1,148 @@
+// Read-only traversal of the AST. A pass implements `Visitor` and overrides
+// only the nodes it cares about; every other method falls back to the
+// matching `walk_*` function, which visits the node's children. An override
+// that still wants the children visited calls the `walk_*` function itself.
+
+use super::*;
+
+pub trait Visitor<'ast> {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_item(&mut self, item: &'ast Item) {
+        walk_item(self, item);
+    }
+
+    fn visit_function(&mut self, function: &'ast Function) {
+        walk_function(self, function);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast Program) {
+    for item in &program.items {
+        visitor.visit_item(item);
+    }
+}
+
+/// Visits the function bodies, the initializers of global constants and
+/// variables, and static assertions; type declarations hold no expressions.
+pub fn walk_item<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, item: &'ast Item) {
+    match item {
+        Item::Function(f) => visitor.visit_function(f),
+        Item::Const(c) => visitor.visit_expr(&c.value),
+        Item::Var(v) => visitor.visit_expr(&v.value),
+        Item::StaticAssert(a) => visitor.visit_expr(&a.condition),
+        Item::Struct(_)
+        | Item::Union(_)
+        | Item::Enum(_)
+        | Item::CapabilityDecl(_)
+        | Item::TopologyDecl(_)
+        | Item::BitRegionDecl(_)
+        | Item::EntropyDecl(_) => {}
+    }
+}
+
+pub fn walk_function<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, function: &'ast Function) {
+    walk_stmts(visitor, &function.body);
+}
+
+pub fn walk_stmts<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmts: &'ast [Stmt]) {
+    for stmt in stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Stmt) {
+    match stmt {
+        Stmt::Let(l) => {
+            if let Some(value) = &l.value {
+                visitor.visit_expr(value);
+            }
+        }
+        Stmt::Const(c) => visitor.visit_expr(&c.value),
+        Stmt::Expr(e) | Stmt::Return(Some(e)) | Stmt::Break(Some(e), _) => visitor.visit_expr(e),
+        Stmt::Block(stmts) => walk_stmts(visitor, stmts),
+        Stmt::If(i) => {
+            visitor.visit_expr(&i.condition);
+            walk_stmts(visitor, &i.then_branch);
+            if let Some(else_branch) = &i.else_branch {
+                walk_stmts(visitor, else_branch);
+            }
+        }
+        Stmt::While(w) => {
+            visitor.visit_expr(&w.condition);
+            walk_stmts(visitor, &w.body);
+        }
+        Stmt::For(f) => {
+            visitor.visit_stmt(&f.init);
+            visitor.visit_expr(&f.condition);
+            visitor.visit_stmt(&f.update);
+            walk_stmts(visitor, &f.body);
+        }
+        Stmt::Asm(a) => {
+            for operand in a.inputs.iter().chain(&a.outputs) {
+                visitor.visit_expr(&operand.expr);
+            }
+        }
+        Stmt::Defer(s) => visitor.visit_stmt(s),
+        Stmt::EntropyAssert(e) => visitor.visit_expr(&e.expr),
+        Stmt::Return(None) | Stmt::Break(None, _) | Stmt::Continue(_) => {}
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expr) {
+    match expr {
+        Expr::Unary(_, e)
+        | Expr::Field(e, _)
+        | Expr::PtrField(e, _)
+        | Expr::Cast(e, _)
+        | Expr::AddrOf(e)
+        | Expr::Alloc(_, e) => visitor.visit_expr(e),
+        Expr::Binary(_, a, b, _) | Expr::Index(a, b) | Expr::Assign(a, b) | Expr::Free(a, b) => {
+            visitor.visit_expr(a);
+            visitor.visit_expr(b);
+        }
+        Expr::Call(callee, args) => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        Expr::Syscall(_, args) | Expr::RawSyscall(args) | Expr::ArrayLit(args) => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        Expr::Block(stmts, result) => {
+            walk_stmts(visitor, stmts);
+            if let Some(result) = result {
+                visitor.visit_expr(result);
+            }
+        }
+        Expr::Loop(body) => walk_stmts(visitor, body),
+        Expr::If(i) => {
+            visitor.visit_expr(&i.condition);
+            visitor.visit_expr(&i.then_expr);
+            visitor.visit_expr(&i.else_expr);
+        }
+        Expr::EntropyCheck(e) => visitor.visit_expr(&e.expr),
+        Expr::BitRegionAccess(b) => visitor.visit_expr(&b.base),
+        Expr::TopologyCast(t) => visitor.visit_expr(&t.expr),
+        Expr::Literal(_)
+        | Expr::Identifier(..)
+        | Expr::Sizeof(_)
+        | Expr::Alignof(_)
+        | Expr::Offsetof(..)
+        | Expr::Zeroed(_)
+        | Expr::PhysAddr(_) => {}
+    }
+}
@@ -1,30 +1,43 @@
 use std::fs::File;
 use std::io::Write;
 
-use super::{Relocation, Symbol};
+use super::{IntType, Relocation, RelocationKind, RelocationSection, Symbol, SymbolKind};
+
+/// Header flag: a build note follows the last record section
+pub const FLAG_BUILD_NOTE: u8 = 0x01;
+/// Header flag: .data must be mapped at the build note's load base rather
+/// than the default address
+pub const FLAG_CUSTOM_LOAD_BASE: u8 = 0x02;
+
+const RECORD_SIZE: usize = 280;
 
 pub fn write_aura_binary(
     object: &super::AuraObject,
     path: &std::path::Path,
 ) -> std::io::Result<()> {
-    let mut file = File::create(path)?;
+    let bytes = aura_object_to_bytes(object)?;
+    File::create(path)?.write_all(&bytes)
+}
 
+/// Serializes `object` in the `.aura` format. Fails if a relocation, symbol
+/// or section record does not fit the fixed record size, which would
+/// otherwise be cut short.
+pub fn aura_object_to_bytes(object: &super::AuraObject) -> std::io::Result<Vec<u8>> {
     let header_size = std::mem::size_of::<AuraBinaryHeader>();
     let text_offset = header_size as u64;
     let aligned_text_size = align_to(object.text.len(), 16);
     let data_offset = text_offset + aligned_text_size as u64;
     let aligned_data_size = align_to(object.data.len(), 16);
 
-    let reloc_offset = data_offset + aligned_data_size as u64;
-    let symbol_offset = reloc_offset + object.relocations.len() as u64 * 280;
-    let cap_offset = symbol_offset + object.symbols.len() as u64 * 280;
-    let topo_offset = cap_offset + object.capability_sections.len() as u64 * 280;
-    let br_offset = topo_offset + object.topology_sections.len() as u64 * 280;
+    let mut flags = FLAG_BUILD_NOTE;
+    if object.load_base != super::DEFAULT_LOAD_BASE {
+        flags |= FLAG_CUSTOM_LOAD_BASE;
+    }
 
     let header = AuraBinaryHeader {
         magic: *b"AURA",
         version: 2,
-        flags: 0,
+        flags,
         reserved: 0,
         entry_point: object.entry_point,
         stack_size: 4096,
@@ -40,63 +53,211 @@ pub fn write_aura_binary(
         bitregion_count: object.bit_region_sections.len() as u64,
     };
 
-    file.write_all(&header.as_bytes())?;
+    let mut out = header.as_bytes();
 
-    let mut written = header_size;
-    file.write_all(&object.text)?;
-    written += object.text.len();
+    out.extend_from_slice(&object.text);
+    out.resize(text_offset as usize + aligned_text_size, 0);
 
-    if aligned_text_size > object.text.len() {
-        file.write_all(&vec![0u8; aligned_text_size - object.text.len()])?;
-        written += aligned_text_size - object.text.len();
-    }
-
-    file.write_all(&object.data)?;
-    written += object.data.len();
-
-    if aligned_data_size > object.data.len() {
-        file.write_all(&vec![0u8; aligned_data_size - object.data.len()])?;
-        written += aligned_data_size - object.data.len();
-    }
+    out.extend_from_slice(&object.data);
+    out.resize(data_offset as usize + aligned_data_size, 0);
 
     for reloc in &object.relocations {
-        let mut bytes = reloc.as_bytes();
-        bytes.resize(280, 0);
-        file.write_all(&bytes)?;
+        push_record(&mut out, reloc.as_bytes(), "relocation", &reloc.symbol)?;
     }
 
-    for sym in &object.symbols {
-        let mut bytes = sym.as_bytes();
-        bytes.resize(280, 0);
-        file.write_all(&bytes)?;
+    // Codegen records symbols in discovery order; sort them so identical
+    // sources always serialize to identical bytes. Relocations name their
+    // target symbol, so the table order is free to change.
+    let mut symbols: Vec<&Symbol> = object.symbols.iter().collect();
+    symbols.sort_by(|a, b| a.offset.cmp(&b.offset).then_with(|| a.name.cmp(&b.name)));
+
+    for sym in symbols {
+        push_record(&mut out, sym.as_bytes(), "symbol", &sym.name)?;
     }
 
     for cap in &object.capability_sections {
-        let mut bytes = cap.as_bytes();
-        bytes.resize(280, 0);
-        file.write_all(&bytes)?;
+        push_record(&mut out, cap.as_bytes(), "capability", &cap.name)?;
     }
 
     for topo in &object.topology_sections {
-        let mut bytes = topo.as_bytes();
-        bytes.resize(280, 0);
-        file.write_all(&bytes)?;
+        push_record(&mut out, topo.as_bytes(), "topology section", &topo.name)?;
     }
 
     for br in &object.bit_region_sections {
-        let mut bytes = br.as_bytes();
-        bytes.resize(280, 0);
-        file.write_all(&bytes)?;
+        push_record(&mut out, br.as_bytes(), "bit region section", &br.name)?;
     }
 
+    out.extend_from_slice(&BuildNote::for_object(object).as_bytes());
+
+    Ok(out)
+}
+
+fn push_record(
+    out: &mut Vec<u8>,
+    mut bytes: Vec<u8>,
+    what: &str,
+    name: &str,
+) -> std::io::Result<()> {
+    if bytes.len() > RECORD_SIZE {
+        return Err(invalid_data(format!(
+            "{} record for '{}' needs {} bytes, more than the {}-byte record size",
+            what,
+            name,
+            bytes.len(),
+            RECORD_SIZE
+        )));
+    }
+    bytes.resize(RECORD_SIZE, 0);
+    out.extend_from_slice(&bytes);
     Ok(())
 }
 
+/// Reads back an object written by [`aura_object_to_bytes`]. Symbols come
+/// back in the writer's sorted order, and the load base is that of the build
+/// note (the default when there is none).
+pub fn aura_object_from_bytes(data: &[u8]) -> std::io::Result<super::AuraObject> {
+    if data.len() < std::mem::size_of::<AuraBinaryHeader>() {
+        return Err(invalid_data("file too small for header".to_string()));
+    }
+    let header = AuraBinaryHeader::from_bytes(data);
+    if &header.magic != b"AURA" {
+        return Err(invalid_data("not an Aura binary (bad magic)".to_string()));
+    }
+
+    let section = |offset: u64, size: u64, name: &str| {
+        let start = offset as usize;
+        start
+            .checked_add(size as usize)
+            .and_then(|end| data.get(start..end))
+            .ok_or_else(|| invalid_data(format!("{} section extends past end of file", name)))
+    };
+    let text = section(header.text_offset, header.text_size, "text")?.to_vec();
+    let data_bytes = section(header.data_offset, header.data_size, "data")?.to_vec();
+
+    let mut next_record = header.data_offset as usize + align_to(header.data_size as usize, 16);
+    let pos = &mut next_record;
+    let relocations = read_records(
+        data,
+        pos,
+        header.reloc_count,
+        "relocation",
+        Relocation::from_bytes,
+    )?;
+    let symbols = read_records(data, pos, header.symbol_count, "symbol", Symbol::from_bytes)?;
+    let capability_sections = read_records(
+        data,
+        pos,
+        header.capability_count,
+        "capability",
+        CapabilitySection::from_bytes,
+    )?;
+    let topology_sections = read_records(
+        data,
+        pos,
+        header.topology_count,
+        "topology section",
+        TopologySection::from_bytes,
+    )?;
+    let bit_region_sections = read_records(
+        data,
+        pos,
+        header.bitregion_count,
+        "bit region section",
+        BitRegionSection::from_bytes,
+    )?;
+
+    let load_base = if header.flags & FLAG_BUILD_NOTE != 0 {
+        data.get(next_record..)
+            .and_then(BuildNote::from_bytes)
+            .ok_or_else(|| invalid_data("build note is missing or malformed".to_string()))?
+            .load_base
+    } else {
+        super::DEFAULT_LOAD_BASE
+    };
+
+    Ok(super::AuraObject {
+        entry_point: header.entry_point,
+        load_base,
+        text,
+        data: data_bytes,
+        bss_size: header.bss_size as usize,
+        relocations,
+        symbols,
+        capability_sections,
+        topology_sections,
+        bit_region_sections,
+    })
+}
+
+// Parse `count` consecutive records starting at `*pos`, leaving `*pos` just
+// past the last one
+fn read_records<T>(
+    data: &[u8],
+    pos: &mut usize,
+    count: u64,
+    what: &str,
+    parse: fn(&[u8]) -> Option<T>,
+) -> std::io::Result<Vec<T>> {
+    let mut records = Vec::new();
+    for i in 0..count {
+        let record = pos
+            .checked_add(RECORD_SIZE)
+            .and_then(|end| data.get(*pos..end))
+            .ok_or_else(|| invalid_data(format!("{} record {} is truncated", what, i)))?;
+        let parsed = parse(record)
+            .ok_or_else(|| invalid_data(format!("malformed {} record {}", what, i)))?;
+        records.push(parsed);
+        *pos += RECORD_SIZE;
+    }
+    Ok(records)
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+// Bounds-checked little-endian reads over one record
+struct FieldReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        FieldReader { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    // A u64 length, the UTF-8 bytes, then a NUL
+    fn string(&mut self) -> Option<String> {
+        let len = usize::try_from(self.u64()?).ok()?;
+        let s = std::str::from_utf8(self.bytes(len)?).ok()?.to_string();
+        (self.u8()? == 0).then_some(s)
+    }
+}
+
 fn align_to(size: usize, align: usize) -> usize {
     if align == 0 {
         size
     } else {
-        ((size + align - 1) / align) * align
+        size.div_ceil(align) * align
     }
 }
 
@@ -152,8 +313,42 @@ impl Relocation {
         bytes.extend_from_slice(self.symbol.as_bytes());
         bytes.push(0);
         bytes.push(self.kind.clone() as u8);
+        bytes.push(self.section as u8);
         bytes
     }
+
+    fn from_bytes(data: &[u8]) -> Option<Relocation> {
+        let mut r = FieldReader::new(data);
+        let offset = usize::try_from(r.u64()?).ok()?;
+        let symbol = r.string()?;
+        let kind = match r.u8()? {
+            0 => RelocationKind::Absolute64,
+            1 => RelocationKind::Relative32,
+            2 => RelocationKind::Absolute32,
+            _ => return None,
+        };
+        // Records are zero-padded, so ones written before data relocations
+        // existed read back as text
+        let section = match r.u8()? {
+            0 => RelocationSection::Text,
+            1 => RelocationSection::Data,
+            _ => return None,
+        };
+        Some(Relocation {
+            offset,
+            symbol,
+            kind,
+            section,
+        })
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            RelocationKind::Absolute64 => "Absolute64",
+            RelocationKind::Relative32 => "Relative32",
+            RelocationKind::Absolute32 => "Absolute32",
+        }
+    }
 }
 
 impl Symbol {
@@ -166,11 +361,61 @@ impl Symbol {
         bytes.extend_from_slice(&self.offset.to_le_bytes());
         bytes.extend_from_slice(&self.size.to_le_bytes());
         bytes.push(self.kind.clone() as u8);
+        // Integer width (0 for anything else) and signedness
+        let int_type = self.int_type.map_or([0, 0], |t| [t.bits, t.signed as u8]);
+        bytes.extend_from_slice(&int_type);
         bytes
     }
+
+    fn from_bytes(data: &[u8]) -> Option<Symbol> {
+        let mut r = FieldReader::new(data);
+        let name = r.string()?;
+        let offset = r.u64()?;
+        let size = r.u64()?;
+        let kind = match r.u8()? {
+            0 => SymbolKind::Function,
+            1 => SymbolKind::Data,
+            2 => SymbolKind::Object,
+            3 => SymbolKind::Capability,
+            4 => SymbolKind::Topology,
+            5 => SymbolKind::BitRegion,
+            _ => return None,
+        };
+        let int_type = match (r.u8()?, r.u8()?) {
+            (0, _) => None,
+            (bits, signed) => Some(IntType {
+                bits,
+                signed: signed != 0,
+            }),
+        };
+        Some(Symbol {
+            name,
+            offset,
+            size,
+            kind,
+            int_type,
+        })
+    }
+
+    // The integer value stored for this symbol in `data`, decoded at its
+    // declared width and signedness
+    fn int_value(&self, data: &[u8]) -> Option<String> {
+        let int_type = self.int_type?;
+        let start = usize::try_from(self.offset).ok()?;
+        let bytes = data.get(start..start.checked_add((self.size as usize).min(8))?)?;
+        let mut raw = [0u8; 8];
+        raw[..bytes.len()].copy_from_slice(bytes);
+        let value = u64::from_le_bytes(raw) & int_type.mask();
+        let shift = 64 - int_type.bits.min(64) as u32;
+        Some(if int_type.signed {
+            (((value << shift) as i64) >> shift).to_string()
+        } else {
+            value.to_string()
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CapabilitySection {
     pub name: String,
     pub base_address: u64,
@@ -194,9 +439,21 @@ impl CapabilitySection {
         bytes.extend_from_slice(&self.element_count.to_le_bytes());
         bytes
     }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = FieldReader::new(data);
+        Some(CapabilitySection {
+            name: r.string()?,
+            base_address: r.u64()?,
+            length: r.u64()?,
+            mode: r.u8()?,
+            element_size: r.u32()?,
+            element_count: r.u64()?,
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TopologySection {
     pub name: String,
     pub numa_node: u8,
@@ -216,16 +473,26 @@ impl TopologySection {
         bytes.push(self.memory_class);
         bytes
     }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = FieldReader::new(data);
+        Some(TopologySection {
+            name: r.string()?,
+            numa_node: r.u8()?,
+            cache_level: r.u8()?,
+            memory_class: r.u8()?,
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BitRegionSection {
     pub name: String,
     pub base_type: String,
     pub regions: Vec<BitRegionInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BitRegionInfo {
     pub name: String,
     pub bit_offset: u8,
@@ -260,12 +527,116 @@ impl BitRegionSection {
 
         bytes
     }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = FieldReader::new(data);
+        let name = r.string()?;
+        let base_type = r.string()?;
+        let count = r.u64()?;
+        let mut regions = Vec::new();
+        for _ in 0..count {
+            regions.push(BitRegionInfo {
+                name: r.string()?,
+                bit_offset: r.u8()?,
+                bit_width: r.u8()?,
+                access: r.u8()?,
+            });
+        }
+        Some(BitRegionSection {
+            name,
+            base_type,
+            regions,
+        })
+    }
+}
+
+/// Provenance record identifying the toolchain that produced a binary
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildNote {
+    pub compiler: String,
+    pub target: String,
+    /// FNV-1a hash over text and data, stable across identical builds
+    pub content_hash: u64,
+    /// Virtual address the code expects .data to be mapped at
+    pub load_base: u64,
+}
+
+impl BuildNote {
+    pub fn for_object(object: &super::AuraObject) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for &b in object.text.iter().chain(object.data.iter()) {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        BuildNote {
+            compiler: format!("aura {}", env!("CARGO_PKG_VERSION")),
+            target: "x86_64".to_string(),
+            content_hash: hash,
+            load_base: object.load_base,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for s in [&self.compiler, &self.target] {
+            bytes.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(s.as_bytes());
+            bytes.push(0);
+        }
+        bytes.extend_from_slice(&self.content_hash.to_le_bytes());
+        bytes.extend_from_slice(&self.load_base.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut pos = 0;
+        let mut strings = Vec::new();
+        for _ in 0..2 {
+            let len = u64::from_le_bytes(data.get(pos..pos + 8)?.try_into().ok()?) as usize;
+            pos += 8;
+            let s = std::str::from_utf8(data.get(pos..pos + len)?).ok()?;
+            strings.push(s.to_string());
+            pos += len + 1;
+        }
+        let content_hash = u64::from_le_bytes(data.get(pos..pos + 8)?.try_into().ok()?);
+        pos += 8;
+        let load_base = u64::from_le_bytes(data.get(pos..pos + 8)?.try_into().ok()?);
+        let target = strings.pop()?;
+        let compiler = strings.pop()?;
+        Some(BuildNote {
+            compiler,
+            target,
+            content_hash,
+            load_base,
+        })
+    }
+}
+
+/// Byte range of each section shown by the hex views of [`AuraBinary::dump`]
+#[derive(Debug, Clone, Default)]
+pub struct DumpOptions {
+    /// Section offset the hex view starts at
+    pub skip: usize,
+    /// Maximum number of bytes shown, or the rest of the section
+    pub length: Option<usize>,
+    /// After the data section, list integer data symbols with their values
+    pub annotate: bool,
 }
 
 pub struct AuraBinary;
 
 impl AuraBinary {
     pub fn dump(data: &[u8]) -> std::io::Result<()> {
+        Self::dump_with_options(data, &DumpOptions::default())
+    }
+
+    pub fn dump_with_options(data: &[u8], options: &DumpOptions) -> std::io::Result<()> {
+        Self::dump_to(&mut std::io::stdout().lock(), data, options)
+    }
+
+    /// Writes the dump to `out`. A truncated or malformed file is reported
+    /// on stderr and the dump stops at the first section it cannot read.
+    pub fn dump_to(out: &mut dyn Write, data: &[u8], options: &DumpOptions) -> std::io::Result<()> {
         if data.len() < std::mem::size_of::<AuraBinaryHeader>() {
             eprintln!("File too small for header");
             return Ok(());
@@ -273,45 +644,239 @@ impl AuraBinary {
 
         let header = AuraBinaryHeader::from_bytes(data);
 
-        println!("=== Aura Binary Dump ===");
-        println!(
+        writeln!(out, "=== Aura Binary Dump ===")?;
+        writeln!(
+            out,
             "Magic: {}",
             std::str::from_utf8(&header.magic).unwrap_or("INVALID")
-        );
-        println!("Version: {}", header.version);
-        println!("Entry Point: 0x{:016x}", header.entry_point);
-        println!("Stack Size: {}", header.stack_size);
-        println!(
+        )?;
+        writeln!(out, "Version: {}", header.version)?;
+        writeln!(out, "Flags: 0x{:02x}", header.flags)?;
+        writeln!(out, "Entry Point: 0x{:016x}", header.entry_point)?;
+        writeln!(out, "Stack Size: {}", header.stack_size)?;
+        writeln!(
+            out,
             "Text Offset: {}, Size: {}",
             header.text_offset, header.text_size
-        );
-        println!(
+        )?;
+        writeln!(
+            out,
             "Data Offset: {}, Size: {}",
             header.data_offset, header.data_size
-        );
-        println!("BSS Size: {}", header.bss_size);
-        println!("Relocations: {}", header.reloc_count);
-        println!("Symbols: {}", header.symbol_count);
-        println!("Capabilities: {}", header.capability_count);
-        println!("Topology Sections: {}", header.topology_count);
-        println!("Bit Region Sections: {}", header.bitregion_count);
+        )?;
+        writeln!(out, "BSS Size: {}", header.bss_size)?;
+        writeln!(out, "Relocations: {}", header.reloc_count)?;
+        writeln!(out, "Symbols: {}", header.symbol_count)?;
+        writeln!(out, "Capabilities: {}", header.capability_count)?;
+        writeln!(out, "Topology Sections: {}", header.topology_count)?;
+        writeln!(out, "Bit Region Sections: {}", header.bitregion_count)?;
+
+        let section = |offset: u64, size: u64| {
+            let start = usize::try_from(offset).ok()?;
+            let end = start.checked_add(usize::try_from(size).ok()?)?;
+            data.get(start..end)
+        };
+        let Some(text) = section(header.text_offset, header.text_size) else {
+            eprintln!("Text section extends past end of file");
+            return Ok(());
+        };
+        if !text.is_empty() {
+            writeln!(out, "\n=== Text Section ({} bytes) ===", header.text_size)?;
+            Self::print_hex(out, text, options)?;
+        }
+
+        let Some(data_section) = section(header.data_offset, header.data_size) else {
+            eprintln!("Data section extends past end of file");
+            return Ok(());
+        };
+        if !data_section.is_empty() {
+            writeln!(out, "\n=== Data Section ({} bytes) ===", header.data_size)?;
+            Self::print_hex(out, data_section, options)?;
+        }
+
+        // Relocation and symbol records follow .data, padded to 16 bytes
+        let reloc_start = header.data_offset as usize + align_to(data_section.len(), 16);
+        let Some(relocations) = Self::dump_records(
+            data,
+            reloc_start,
+            header.reloc_count,
+            "relocation",
+            Relocation::from_bytes,
+        ) else {
+            return Ok(());
+        };
+        let symbol_start = reloc_start + relocations.len() * RECORD_SIZE;
+        let Some(symbols) = Self::dump_records(
+            data,
+            symbol_start,
+            header.symbol_count,
+            "symbol",
+            Symbol::from_bytes,
+        ) else {
+            return Ok(());
+        };
+
+        if options.annotate {
+            writeln!(out, "\n=== Data Values ===")?;
+            for sym in symbols
+                .iter()
+                .filter(|s| s.kind == SymbolKind::Data && s.int_type.is_some())
+            {
+                let int_type = sym.int_type.unwrap();
+                match sym.int_value(data_section) {
+                    Some(value) => writeln!(
+                        out,
+                        "{:08x}: {} = {} ({}{})",
+                        sym.offset,
+                        sym.name,
+                        value,
+                        if int_type.signed { 'i' } else { 'u' },
+                        int_type.bits
+                    )?,
+                    None => eprintln!("Symbol {} lies outside the data section", sym.name),
+                }
+            }
+        }
 
+        if !relocations.is_empty() {
+            writeln!(out, "\n=== Relocations ===")?;
+        }
+        for reloc in &relocations {
+            // Show the value currently in place for absolute fixups; fields
+            // in .data are marked as such, since offsets are section-relative
+            let (section, marker) = match reloc.section {
+                RelocationSection::Text => (text, ""),
+                RelocationSection::Data => (data_section, " (.data)"),
+            };
+            let value = reloc
+                .offset
+                .checked_add(8)
+                .and_then(|end| section.get(reloc.offset..end))
+                .filter(|_| reloc.kind == RelocationKind::Absolute64)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap()));
+            match value {
+                Some(value) => writeln!(
+                    out,
+                    "0x{:08x} {:<12} {} = 0x{:016x}{}",
+                    reloc.offset,
+                    reloc.kind_name(),
+                    reloc.symbol,
+                    value,
+                    marker
+                )?,
+                None => writeln!(
+                    out,
+                    "0x{:08x} {:<12} {}{}",
+                    reloc.offset,
+                    reloc.kind_name(),
+                    reloc.symbol,
+                    marker
+                )?,
+            }
+        }
+
+        if !symbols.is_empty() {
+            writeln!(out, "\n=== Symbols ===")?;
+        }
+        for sym in &symbols {
+            writeln!(
+                out,
+                "0x{:08x} {:>8} {:<10} {}",
+                sym.offset,
+                sym.size,
+                format!("{:?}", sym.kind),
+                sym.name
+            )?;
+        }
+
+        if header.flags & FLAG_BUILD_NOTE != 0 {
+            let records = header.reloc_count
+                + header.symbol_count
+                + header.capability_count
+                + header.topology_count
+                + header.bitregion_count;
+            let note_start = usize::try_from(records)
+                .ok()
+                .and_then(|n| n.checked_mul(RECORD_SIZE))
+                .and_then(|len| len.checked_add(reloc_start));
+            match note_start
+                .and_then(|start| data.get(start..))
+                .and_then(BuildNote::from_bytes)
+            {
+                Some(note) => {
+                    writeln!(out, "\n=== Build Note ===")?;
+                    writeln!(out, "Compiler: {}", note.compiler)?;
+                    writeln!(out, "Target: {}", note.target)?;
+                    writeln!(out, "Content Hash: 0x{:016x}", note.content_hash)?;
+                    writeln!(out, "Load Base: 0x{:016x}", note.load_base)?;
+                }
+                None => eprintln!("Build note flag set but note is missing or malformed"),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Like `read_records`, but a bad record is reported on stderr rather
+    // than failing the dump
+    fn dump_records<T>(
+        data: &[u8],
+        start: usize,
+        count: u64,
+        what: &str,
+        parse: fn(&[u8]) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut pos = start;
+        read_records(data, &mut pos, count, what, parse)
+            .map_err(|e| eprintln!("Cannot read the {} table: {}", what, e))
+            .ok()
+    }
+
+    pub fn disasm(data: &[u8]) -> std::io::Result<()> {
+        if data.len() < std::mem::size_of::<AuraBinaryHeader>() {
+            eprintln!("File too small for header");
+            return Ok(());
+        }
+
+        let header = AuraBinaryHeader::from_bytes(data);
         let text_start = header.text_offset as usize;
         let text_end = text_start + header.text_size as usize;
-        if text_end <= data.len() && header.text_size > 0 {
-            println!("\n=== Text Section ({} bytes) ===", header.text_size);
-            Self::print_hex(&data[text_start..text_end]);
+        if text_end > data.len() {
+            eprintln!("Text section extends past end of file");
+            return Ok(());
+        }
+
+        for insn in super::disasm::disassemble(&data[text_start..text_end]) {
+            println!("{:08x}: {}", insn.offset, insn.text);
         }
 
         Ok(())
     }
 
-    fn print_hex(data: &[u8]) {
-        for (i, chunk) in data.chunks(16).enumerate() {
-            let offset = i * 16;
+    // xxd-style rows: offset, 16 hex bytes, then the same bytes as ASCII
+    // with anything unprintable shown as '.'
+    fn print_hex(out: &mut dyn Write, data: &[u8], options: &DumpOptions) -> std::io::Result<()> {
+        let start = options.skip.min(data.len());
+        let end = match options.length {
+            Some(length) => start.saturating_add(length).min(data.len()),
+            None => data.len(),
+        };
+        for (i, chunk) in data[start..end].chunks(16).enumerate() {
+            let offset = start + i * 16;
             let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
-            println!("{:08x}: {:<48}", offset, hex.join(" "));
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            writeln!(out, "{:08x}: {:<48} {}", offset, hex.join(" "), ascii)?;
         }
+        Ok(())
     }
 }
 
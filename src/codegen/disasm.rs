@@ -0,0 +1,541 @@
+// Minimal x86-64 decoder for the instruction forms the code generator emits.
+// Anything it doesn't recognise is printed as a raw `.byte` so output never
+// desynchronises silently.
+
+pub struct Instruction {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub text: String,
+}
+
+pub fn disassemble(code: &[u8]) -> Vec<Instruction> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < code.len() {
+        let (len, text) = match decode(code, pos) {
+            Some(decoded) => decoded,
+            None => (1, format!(".byte 0x{:02x}", code[pos])),
+        };
+        out.push(Instruction {
+            offset: pos,
+            bytes: code[pos..pos + len].to_vec(),
+            text,
+        });
+        pos += len;
+    }
+    out
+}
+
+const REG64: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+const REG32: [&str; 16] = [
+    "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d", "r12d",
+    "r13d", "r14d", "r15d",
+];
+const REG16: [&str; 16] = [
+    "ax", "cx", "dx", "bx", "sp", "bp", "si", "di", "r8w", "r9w", "r10w", "r11w", "r12w", "r13w",
+    "r14w", "r15w",
+];
+const REG8: [&str; 16] = [
+    "al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil", "r8b", "r9b", "r10b", "r11b", "r12b",
+    "r13b", "r14b", "r15b",
+];
+const REG8_LEGACY: [&str; 8] = ["al", "cl", "dl", "bl", "ah", "ch", "dh", "bh"];
+
+const CONDITIONS: [&str; 16] = [
+    "o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g",
+];
+const GROUP1: [&str; 8] = ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"];
+const GROUP2: [&str; 8] = ["rol", "ror", "rcl", "rcr", "shl", "shr", "sal", "sar"];
+const GROUP3: [&str; 8] = ["test", "test", "not", "neg", "mul", "imul", "div", "idiv"];
+
+#[derive(Clone, Copy)]
+struct Prefixes {
+    rex: u8,
+    opsize: bool,
+    rep: bool,
+    repne: bool,
+}
+
+impl Prefixes {
+    fn w(&self) -> bool {
+        self.rex & 0x08 != 0
+    }
+
+    fn r(&self) -> usize {
+        ((self.rex >> 2) & 1) as usize * 8
+    }
+
+    fn x(&self) -> usize {
+        ((self.rex >> 1) & 1) as usize * 8
+    }
+
+    fn b(&self) -> usize {
+        (self.rex & 1) as usize * 8
+    }
+
+    // Operand size in bits for instructions that aren't byte-sized
+    fn size(&self) -> u8 {
+        if self.w() {
+            64
+        } else if self.opsize {
+            16
+        } else {
+            32
+        }
+    }
+}
+
+fn reg_name(index: usize, bits: u8, prefixes: Prefixes) -> &'static str {
+    match bits {
+        64 => REG64[index],
+        32 => REG32[index],
+        16 => REG16[index],
+        _ if prefixes.rex == 0 && index < 8 => REG8_LEGACY[index],
+        _ => REG8[index],
+    }
+}
+
+fn ptr_name(bits: u8) -> &'static str {
+    match bits {
+        64 => "qword ptr",
+        32 => "dword ptr",
+        16 => "word ptr",
+        _ => "byte ptr",
+    }
+}
+
+fn hex(value: i64) -> String {
+    if value < 0 {
+        format!("-0x{:x}", value.unsigned_abs())
+    } else {
+        format!("0x{:x}", value)
+    }
+}
+
+struct Cursor<'a> {
+    code: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn u8(&mut self) -> Option<u8> {
+        let b = *self.code.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn i8(&mut self) -> Option<i64> {
+        self.u8().map(|b| b as i8 as i64)
+    }
+
+    fn i16(&mut self) -> Option<i64> {
+        let bytes = self.code.get(self.pos..self.pos + 2)?;
+        self.pos += 2;
+        Some(i16::from_le_bytes([bytes[0], bytes[1]]) as i64)
+    }
+
+    fn i32(&mut self) -> Option<i64> {
+        let bytes = self.code.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(i32::from_le_bytes(bytes.try_into().ok()?) as i64)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let bytes = self.code.get(self.pos..self.pos + 8)?;
+        self.pos += 8;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn imm(&mut self, bits: u8) -> Option<i64> {
+        match bits {
+            8 => self.i8(),
+            16 => self.i16(),
+            _ => self.i32(),
+        }
+    }
+}
+
+struct ModRm {
+    reg: usize,
+    // Register number when the r/m operand is a register
+    rm_reg: Option<usize>,
+    // Formatted address when the r/m operand is memory
+    mem: String,
+}
+
+impl ModRm {
+    fn rm(&self, bits: u8, prefixes: Prefixes) -> String {
+        match self.rm_reg {
+            Some(r) => reg_name(r, bits, prefixes).to_string(),
+            None => format!("{} {}", ptr_name(bits), self.mem),
+        }
+    }
+
+    // The r/m operand of an SSE instruction, an xmm register when direct
+    fn xmm_rm(&self, bits: u8) -> String {
+        match self.rm_reg {
+            Some(r) => format!("xmm{}", r),
+            None => format!("{} {}", ptr_name(bits), self.mem),
+        }
+    }
+}
+
+fn modrm(c: &mut Cursor, prefixes: Prefixes) -> Option<ModRm> {
+    let byte = c.u8()?;
+    let md = byte >> 6;
+    let reg = ((byte >> 3) & 7) as usize + prefixes.r();
+    let rm = (byte & 7) as usize;
+
+    if md == 3 {
+        return Some(ModRm {
+            reg,
+            rm_reg: Some(rm + prefixes.b()),
+            mem: String::new(),
+        });
+    }
+
+    let mut base = None;
+    let mut index = None;
+    if rm == 4 {
+        let sib = c.u8()?;
+        let scale = 1u8 << (sib >> 6);
+        let idx = ((sib >> 3) & 7) as usize + prefixes.x();
+        if idx != 4 {
+            index = Some((idx, scale));
+        }
+        let b = (sib & 7) as usize;
+        if !(b == 5 && md == 0) {
+            base = Some(REG64[b + prefixes.b()].to_string());
+        }
+    } else if rm == 5 && md == 0 {
+        base = Some("rip".to_string());
+    } else {
+        base = Some(REG64[rm + prefixes.b()].to_string());
+    }
+
+    let disp = match md {
+        1 => c.i8()?,
+        2 => c.i32()?,
+        _ if rm == 5 || (rm == 4 && base.is_none()) => c.i32()?,
+        _ => 0,
+    };
+
+    let mut mem = String::from("[");
+    if let Some(base) = &base {
+        mem.push_str(base);
+    }
+    if let Some((idx, scale)) = index {
+        if base.is_some() {
+            mem.push('+');
+        }
+        mem.push_str(&format!("{}*{}", REG64[idx], scale));
+    }
+    if disp != 0 || (base.is_none() && index.is_none()) {
+        if disp < 0 {
+            mem.push_str(&hex(disp));
+        } else {
+            if base.is_some() || index.is_some() {
+                mem.push('+');
+            }
+            mem.push_str(&hex(disp));
+        }
+    }
+    mem.push(']');
+
+    Some(ModRm {
+        reg,
+        rm_reg: None,
+        mem,
+    })
+}
+
+fn decode(code: &[u8], start: usize) -> Option<(usize, String)> {
+    let mut c = Cursor { code, pos: start };
+    let mut prefixes = Prefixes {
+        rex: 0,
+        opsize: false,
+        rep: false,
+        repne: false,
+    };
+
+    let mut op = c.u8()?;
+    loop {
+        match op {
+            0x66 => prefixes.opsize = true,
+            0xf3 => prefixes.rep = true,
+            0xf2 => prefixes.repne = true,
+            _ => break,
+        }
+        op = c.u8()?;
+    }
+    if (0x40..=0x4f).contains(&op) {
+        prefixes.rex = op;
+        op = c.u8()?;
+    }
+
+    let size = prefixes.size();
+    let text = match op {
+        // ALU r/m, reg and reg, r/m forms
+        0x00..=0x3b if op & 7 < 4 => {
+            let name = GROUP1[(op >> 3) as usize];
+            let bits = if op & 1 == 0 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            let reg = reg_name(m.reg, bits, prefixes);
+            if op & 2 == 0 {
+                format!("{} {}, {}", name, m.rm(bits, prefixes), reg)
+            } else {
+                format!("{} {}, {}", name, reg, m.rm(bits, prefixes))
+            }
+        }
+        // ALU al/eax/rax, imm
+        0x04 | 0x05 | 0x0c | 0x0d | 0x24 | 0x25 | 0x2c | 0x2d | 0x34 | 0x35 | 0x3c | 0x3d => {
+            let name = GROUP1[(op >> 3) as usize];
+            let bits = if op & 1 == 0 { 8 } else { size };
+            let imm = c.imm(bits)?;
+            format!("{} {}, {}", name, reg_name(0, bits, prefixes), hex(imm))
+        }
+        0x50..=0x57 => format!("push {}", REG64[(op - 0x50) as usize + prefixes.b()]),
+        0x58..=0x5f => format!("pop {}", REG64[(op - 0x58) as usize + prefixes.b()]),
+        0x63 => {
+            let m = modrm(&mut c, prefixes)?;
+            format!(
+                "movsxd {}, {}",
+                reg_name(m.reg, size, prefixes),
+                m.rm(32, prefixes)
+            )
+        }
+        0x70..=0x7f => {
+            let rel = c.i8()?;
+            let target = c.pos as i64 + rel;
+            format!("j{} {}", CONDITIONS[(op - 0x70) as usize], hex(target))
+        }
+        0x80 | 0x81 | 0x83 => {
+            let bits = if op == 0x80 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            let imm = if op == 0x81 { c.imm(bits)? } else { c.i8()? };
+            format!(
+                "{} {}, {}",
+                GROUP1[m.reg & 7],
+                m.rm(bits, prefixes),
+                hex(imm)
+            )
+        }
+        0x84 | 0x85 => {
+            let bits = if op == 0x84 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            format!(
+                "test {}, {}",
+                m.rm(bits, prefixes),
+                reg_name(m.reg, bits, prefixes)
+            )
+        }
+        0x88 | 0x89 => {
+            let bits = if op == 0x88 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            format!(
+                "mov {}, {}",
+                m.rm(bits, prefixes),
+                reg_name(m.reg, bits, prefixes)
+            )
+        }
+        0x8a | 0x8b => {
+            let bits = if op == 0x8a { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            format!(
+                "mov {}, {}",
+                reg_name(m.reg, bits, prefixes),
+                m.rm(bits, prefixes)
+            )
+        }
+        0x8d => {
+            let m = modrm(&mut c, prefixes)?;
+            format!("lea {}, {}", reg_name(m.reg, size, prefixes), m.mem)
+        }
+        0x90 => "nop".to_string(),
+        0x99 if prefixes.w() => "cqo".to_string(),
+        0x99 => "cdq".to_string(),
+        0xaa if prefixes.rep => "rep stosb".to_string(),
+        0xaa => "stosb".to_string(),
+        0xb0..=0xb7 => {
+            let imm = c.i8()?;
+            format!(
+                "mov {}, {}",
+                reg_name((op - 0xb0) as usize + prefixes.b(), 8, prefixes),
+                hex(imm & 0xff)
+            )
+        }
+        0xb8..=0xbf => {
+            let reg = (op - 0xb8) as usize + prefixes.b();
+            if prefixes.w() {
+                let imm = c.u64()?;
+                format!("movabs {}, 0x{:x}", REG64[reg], imm)
+            } else {
+                let imm = c.imm(size)?;
+                let mask = if size == 16 { 0xffff } else { 0xffff_ffff };
+                format!("mov {}, 0x{:x}", reg_name(reg, size, prefixes), imm & mask)
+            }
+        }
+        0xc0 | 0xc1 | 0xd0 | 0xd1 | 0xd2 | 0xd3 => {
+            let bits = if op & 1 == 0 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            let count = match op {
+                0xc0 | 0xc1 => hex(c.u8()? as i64),
+                0xd0 | 0xd1 => "1".to_string(),
+                _ => "cl".to_string(),
+            };
+            format!("{} {}, {}", GROUP2[m.reg & 7], m.rm(bits, prefixes), count)
+        }
+        0xc3 => "ret".to_string(),
+        0xc6 | 0xc7 => {
+            let bits = if op == 0xc6 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            let imm = c.imm(bits)?;
+            format!("mov {}, {}", m.rm(bits, prefixes), hex(imm))
+        }
+        0xcc => "int3".to_string(),
+        0xe8 | 0xe9 => {
+            let rel = c.i32()?;
+            let target = c.pos as i64 + rel;
+            let name = if op == 0xe8 { "call" } else { "jmp" };
+            format!("{} {}", name, hex(target))
+        }
+        0xeb => {
+            let rel = c.i8()?;
+            format!("jmp {}", hex(c.pos as i64 + rel))
+        }
+        0xf6 | 0xf7 => {
+            let bits = if op == 0xf6 { 8 } else { size };
+            let m = modrm(&mut c, prefixes)?;
+            let name = GROUP3[m.reg & 7];
+            if m.reg & 7 < 2 {
+                let imm = c.imm(bits)?;
+                format!("{} {}, {}", name, m.rm(bits, prefixes), hex(imm))
+            } else {
+                format!("{} {}", name, m.rm(bits, prefixes))
+            }
+        }
+        0xff => {
+            let m = modrm(&mut c, prefixes)?;
+            match m.reg & 7 {
+                0 => format!("inc {}", m.rm(size, prefixes)),
+                1 => format!("dec {}", m.rm(size, prefixes)),
+                2 => format!("call {}", m.rm(64, prefixes)),
+                4 => format!("jmp {}", m.rm(64, prefixes)),
+                6 => format!("push {}", m.rm(64, prefixes)),
+                _ => return None,
+            }
+        }
+        0x0f => decode_0f(&mut c, prefixes)?,
+        _ => return None,
+    };
+
+    Some((c.pos - start, text))
+}
+
+fn decode_0f(c: &mut Cursor, prefixes: Prefixes) -> Option<String> {
+    let op = c.u8()?;
+    let size = prefixes.size();
+    // Scalar SSE: f2 selects the double form, f3 (and ucomiss) the single
+    let (suffix, float_bits) = if prefixes.repne || prefixes.opsize {
+        ("sd", 64)
+    } else {
+        ("ss", 32)
+    };
+    let text = match op {
+        0x05 => "syscall".to_string(),
+        0x0b => "ud2".to_string(),
+        0x2e => {
+            let m = modrm(c, prefixes)?;
+            format!("ucomi{} xmm{}, {}", suffix, m.reg, m.xmm_rm(float_bits))
+        }
+        0x2a if prefixes.rep || prefixes.repne => {
+            let m = modrm(c, prefixes)?;
+            format!("cvtsi2{} xmm{}, {}", suffix, m.reg, m.rm(size, prefixes))
+        }
+        0x2c if prefixes.rep || prefixes.repne => {
+            let m = modrm(c, prefixes)?;
+            format!(
+                "cvtt{}2si {}, {}",
+                suffix,
+                reg_name(m.reg, size, prefixes),
+                m.xmm_rm(float_bits)
+            )
+        }
+        0x58 | 0x59 | 0x5c | 0x5e if prefixes.rep || prefixes.repne => {
+            let name = match op {
+                0x58 => "add",
+                0x59 => "mul",
+                0x5c => "sub",
+                _ => "div",
+            };
+            let m = modrm(c, prefixes)?;
+            format!("{}{} xmm{}, {}", name, suffix, m.reg, m.xmm_rm(float_bits))
+        }
+        0x5a if prefixes.rep || prefixes.repne => {
+            let name = if prefixes.rep { "cvtss2sd" } else { "cvtsd2ss" };
+            let m = modrm(c, prefixes)?;
+            let bits = if prefixes.rep { 32 } else { 64 };
+            format!("{} xmm{}, {}", name, m.reg, m.xmm_rm(bits))
+        }
+        0xd6 if prefixes.opsize => {
+            let m = modrm(c, prefixes)?;
+            format!("movq {}, xmm{}", m.xmm_rm(64), m.reg)
+        }
+        0x6e | 0x7e if prefixes.opsize => {
+            let bits = if prefixes.w() { 64 } else { 32 };
+            let name = if prefixes.w() { "movq" } else { "movd" };
+            let m = modrm(c, prefixes)?;
+            if op == 0x6e {
+                format!("{} xmm{}, {}", name, m.reg, m.rm(bits, prefixes))
+            } else {
+                format!("{} {}, xmm{}", name, m.rm(bits, prefixes), m.reg)
+            }
+        }
+        0x40..=0x4f => {
+            let m = modrm(c, prefixes)?;
+            format!(
+                "cmov{} {}, {}",
+                CONDITIONS[(op - 0x40) as usize],
+                reg_name(m.reg, size, prefixes),
+                m.rm(size, prefixes)
+            )
+        }
+        0x80..=0x8f => {
+            let rel = c.i32()?;
+            let target = c.pos as i64 + rel;
+            format!("j{} {}", CONDITIONS[(op - 0x80) as usize], hex(target))
+        }
+        0x90..=0x9f => {
+            let m = modrm(c, prefixes)?;
+            format!(
+                "set{} {}",
+                CONDITIONS[(op - 0x90) as usize],
+                m.rm(8, prefixes)
+            )
+        }
+        0xaf => {
+            let m = modrm(c, prefixes)?;
+            format!(
+                "imul {}, {}",
+                reg_name(m.reg, size, prefixes),
+                m.rm(size, prefixes)
+            )
+        }
+        0xb6 | 0xb7 | 0xbe | 0xbf => {
+            let src_bits = if op & 1 == 0 { 8 } else { 16 };
+            let name = if op < 0xbe { "movzx" } else { "movsx" };
+            let m = modrm(c, prefixes)?;
+            format!(
+                "{} {}, {}",
+                name,
+                reg_name(m.reg, size, prefixes),
+                m.rm(src_bits, prefixes)
+            )
+        }
+        _ => return None,
+    };
+    Some(text)
+}
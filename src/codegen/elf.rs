@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+
+use super::{
+    link_in_memory, AuraObject, CodegenError, RelocationKind, RelocationSection, Symbol, SymbolKind,
+};
+
+const ELF_HEADER_SIZE: usize = 64;
+const PROGRAM_HEADER_SIZE: usize = 56;
+const PAGE_SIZE: usize = 4096;
+const SECTION_HEADER_SIZE: usize = 64;
+const SYM_SIZE: usize = 24;
+const RELA_SIZE: usize = 24;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_NOTYPE: u8 = 0;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_SECTION: u8 = 3;
+
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+const PF_R: u32 = 0x4;
+
+const R_X86_64_64: u32 = 1;
+const R_X86_64_PLT32: u32 = 4;
+const R_X86_64_GOTPCREL: u32 = 9;
+const R_X86_64_32: u32 = 10;
+
+// Section header indices, in the order they are written
+const TEXT: u16 = 1;
+const DATA: u16 = 2;
+const BSS: u16 = 3;
+const SYMTAB: u32 = 6;
+const STRTAB: u32 = 7;
+const SHSTRTAB: u16 = 9;
+
+// Symbol indices of the section symbols that follow the null symbol
+const DATA_SYM: u64 = 2;
+const BSS_SYM: u64 = 3;
+
+/// Writes `object` as an ELF64 relocatable (`.o`) for linking with a C
+/// toolchain. See [`elf_object_bytes`].
+pub fn write_elf_object(object: &AuraObject, path: &std::path::Path) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(&elf_object_bytes(object))
+}
+
+/// Lays out `object` as an x86-64 ELF relocatable with `.text`, `.data`,
+/// `.bss`, `.rela.text`, `.rela.data` and a symbol table.
+///
+/// Functions and data symbols become global definitions, and names that
+/// relocations refer to but the object does not define become undefined
+/// globals for the linker to resolve. Codegen bakes `.data` addresses
+/// against its load base; those relocations are rewritten as section-relative
+/// `R_X86_64_64` entries against `.data` (or `.bss` past the end of `.data`).
+pub fn elf_object_bytes(object: &AuraObject) -> Vec<u8> {
+    let mut text = object.text.clone();
+    let mut data = object.data.clone();
+
+    let mut strtab = StringTable::new();
+    let mut symtab = Vec::new();
+    push_sym(&mut symtab, 0, STB_LOCAL, STT_NOTYPE, 0, 0, 0);
+    push_sym(&mut symtab, 0, STB_LOCAL, STT_SECTION, TEXT, 0, 0);
+    push_sym(&mut symtab, 0, STB_LOCAL, STT_SECTION, DATA, 0, 0);
+    push_sym(&mut symtab, 0, STB_LOCAL, STT_SECTION, BSS, 0, 0);
+
+    // Same order as the .aura symbol table, so the output is reproducible
+    let mut defined: Vec<&Symbol> = object
+        .symbols
+        .iter()
+        .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Data))
+        .collect();
+    defined.sort_by(|a, b| a.offset.cmp(&b.offset).then_with(|| a.name.cmp(&b.name)));
+
+    // A name defined twice (say, consts of the same name in two functions)
+    // cannot be a global definition, so those stay local to the object
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+    for sym in &defined {
+        *name_counts.entry(&sym.name).or_default() += 1;
+    }
+    let (locals, globals): (Vec<&Symbol>, Vec<&Symbol>) = defined
+        .into_iter()
+        .partition(|s| name_counts[s.name.as_str()] > 1);
+
+    let mut indices: HashMap<&str, u64> = HashMap::new();
+    for (bind, syms) in [(STB_LOCAL, &locals), (STB_GLOBAL, &globals)] {
+        for sym in syms {
+            let (kind, section) = match sym.kind {
+                SymbolKind::Function => (STT_FUNC, TEXT),
+                _ => (STT_OBJECT, DATA),
+            };
+            indices
+                .entry(&sym.name)
+                .or_insert((symtab.len() / SYM_SIZE) as u64);
+            let name = strtab.add(&sym.name);
+            push_sym(&mut symtab, name, bind, kind, section, sym.offset, sym.size);
+        }
+    }
+    let first_global = 4 + locals.len();
+
+    for reloc in &object.relocations {
+        if reloc.symbol != ".data" && !indices.contains_key(reloc.symbol.as_str()) {
+            indices.insert(&reloc.symbol, (symtab.len() / SYM_SIZE) as u64);
+            let name = strtab.add(&reloc.symbol);
+            push_sym(&mut symtab, name, STB_GLOBAL, STT_NOTYPE, 0, 0, 0);
+        }
+    }
+
+    let (mut rela_text, mut rela_data) = (Vec::new(), Vec::new());
+    for reloc in &object.relocations {
+        let (section, rela) = match reloc.section {
+            RelocationSection::Text => (&mut text, &mut rela_text),
+            RelocationSection::Data => (&mut data, &mut rela_data),
+        };
+        let (sym, kind, addend) = match reloc.kind {
+            RelocationKind::Absolute64 if reloc.symbol == ".data" => {
+                let at = reloc.offset..reloc.offset + 8;
+                let baked = u64::from_le_bytes(section[at].try_into().unwrap());
+                let offset = baked - object.load_base;
+                if offset < object.data.len() as u64 {
+                    (DATA_SYM, R_X86_64_64, offset as i64)
+                } else {
+                    let offset = offset - object.data.len() as u64;
+                    (BSS_SYM, R_X86_64_64, offset as i64)
+                }
+            }
+            RelocationKind::Absolute64 => (indices[reloc.symbol.as_str()], R_X86_64_64, 0),
+            RelocationKind::Absolute32 => (indices[reloc.symbol.as_str()], R_X86_64_32, 0),
+            // rel32 is either a direct `call` (e8) or `call [rip + slot]`
+            // (ff 15), where the slot is the symbol's GOT entry; the field
+            // is relative to its own end
+            RelocationKind::Relative32 if section[reloc.offset - 1] == 0xe8 => {
+                (indices[reloc.symbol.as_str()], R_X86_64_PLT32, -4)
+            }
+            RelocationKind::Relative32 => (indices[reloc.symbol.as_str()], R_X86_64_GOTPCREL, -4),
+        };
+        // The addend carries the whole value; leave the field itself zeroed
+        let width = match reloc.kind {
+            RelocationKind::Absolute64 => 8,
+            _ => 4,
+        };
+        section[reloc.offset..reloc.offset + width].fill(0);
+
+        rela.extend_from_slice(&(reloc.offset as u64).to_le_bytes());
+        rela.extend_from_slice(&((sym << 32) | kind as u64).to_le_bytes());
+        rela.extend_from_slice(&addend.to_le_bytes());
+    }
+
+    let mut shstrtab = StringTable::new();
+    let names = [
+        shstrtab.add(".text"),
+        shstrtab.add(".data"),
+        shstrtab.add(".bss"),
+        shstrtab.add(".rela.text"),
+        shstrtab.add(".rela.data"),
+        shstrtab.add(".symtab"),
+        shstrtab.add(".strtab"),
+        shstrtab.add(".note.GNU-stack"),
+        shstrtab.add(".shstrtab"),
+    ];
+
+    let mut out = vec![0u8; ELF_HEADER_SIZE];
+    let text_offset = append_aligned(&mut out, &text, 16);
+    let data_offset = append_aligned(&mut out, &data, 16);
+    let bss_offset = out.len();
+    let rela_text_offset = append_aligned(&mut out, &rela_text, 8);
+    let rela_data_offset = append_aligned(&mut out, &rela_data, 8);
+    let symtab_offset = append_aligned(&mut out, &symtab, 8);
+    let strtab_offset = append_aligned(&mut out, &strtab.bytes, 1);
+    let shstrtab_offset = append_aligned(&mut out, &shstrtab.bytes, 1);
+
+    let section_count = SHSTRTAB as usize + 1;
+    let shoff = out.len().next_multiple_of(8);
+    out.resize(shoff, 0);
+    out.extend_from_slice(&[0u8; SECTION_HEADER_SIZE]);
+    let headers = [
+        SectionHeader {
+            name: names[0],
+            kind: SHT_PROGBITS,
+            flags: SHF_ALLOC | SHF_EXECINSTR,
+            offset: text_offset,
+            size: text.len(),
+            link: 0,
+            info: 0,
+            align: 16,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: names[1],
+            kind: SHT_PROGBITS,
+            flags: SHF_WRITE | SHF_ALLOC,
+            offset: data_offset,
+            size: object.data.len(),
+            link: 0,
+            info: 0,
+            align: 16,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: names[2],
+            kind: SHT_NOBITS,
+            flags: SHF_WRITE | SHF_ALLOC,
+            offset: bss_offset,
+            size: object.bss_size,
+            link: 0,
+            info: 0,
+            align: 16,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: names[3],
+            kind: SHT_RELA,
+            flags: SHF_INFO_LINK,
+            offset: rela_text_offset,
+            size: rela_text.len(),
+            link: SYMTAB,
+            info: TEXT as u32,
+            align: 8,
+            entsize: RELA_SIZE,
+        },
+        SectionHeader {
+            name: names[4],
+            kind: SHT_RELA,
+            flags: SHF_INFO_LINK,
+            offset: rela_data_offset,
+            size: rela_data.len(),
+            link: SYMTAB,
+            info: DATA as u32,
+            align: 8,
+            entsize: RELA_SIZE,
+        },
+        SectionHeader {
+            name: names[5],
+            kind: SHT_SYMTAB,
+            flags: 0,
+            offset: symtab_offset,
+            size: symtab.len(),
+            link: STRTAB,
+            info: first_global as u32,
+            align: 8,
+            entsize: SYM_SIZE,
+        },
+        SectionHeader {
+            name: names[6],
+            kind: SHT_STRTAB,
+            flags: 0,
+            offset: strtab_offset,
+            size: strtab.bytes.len(),
+            link: 0,
+            info: 0,
+            align: 1,
+            entsize: 0,
+        },
+        // Empty marker telling the linker the stack need not be executable
+        SectionHeader {
+            name: names[7],
+            kind: SHT_PROGBITS,
+            flags: 0,
+            offset: shstrtab_offset,
+            size: 0,
+            link: 0,
+            info: 0,
+            align: 1,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: names[8],
+            kind: SHT_STRTAB,
+            flags: 0,
+            offset: shstrtab_offset,
+            size: shstrtab.bytes.len(),
+            link: 0,
+            info: 0,
+            align: 1,
+            entsize: 0,
+        },
+    ];
+    for header in &headers {
+        out.extend_from_slice(&header.as_bytes());
+    }
+
+    let mut ident = [0u8; 16];
+    // Magic, 64-bit, little endian, ELF version 1, System V ABI
+    ident[..7].copy_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+    let mut header = Vec::with_capacity(ELF_HEADER_SIZE);
+    header.extend_from_slice(&ident);
+    header.extend_from_slice(&1u16.to_le_bytes()); // ET_REL
+    header.extend_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    header.extend_from_slice(&1u32.to_le_bytes()); // EV_CURRENT
+    header.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+    header.extend_from_slice(&0u64.to_le_bytes()); // e_phoff
+    header.extend_from_slice(&(shoff as u64).to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+    header.extend_from_slice(&(ELF_HEADER_SIZE as u16).to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes()); // e_phentsize
+    header.extend_from_slice(&0u16.to_le_bytes()); // e_phnum
+    header.extend_from_slice(&(SECTION_HEADER_SIZE as u16).to_le_bytes());
+    header.extend_from_slice(&(section_count as u16).to_le_bytes());
+    header.extend_from_slice(&SHSTRTAB.to_le_bytes());
+    out[..ELF_HEADER_SIZE].copy_from_slice(&header);
+
+    out
+}
+
+/// Writes `object` as a static ELF64 executable that Linux runs directly.
+/// See [`elf_executable_bytes`].
+pub fn write_elf_binary(object: &AuraObject, path: &std::path::Path) -> std::io::Result<()> {
+    let bytes = elf_executable_bytes(object)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.message))?;
+    let mut file = File::create(path)?;
+    file.write_all(&bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// Lays out `object` as an `ET_EXEC` ELF64 with no sections and a single
+/// read-write-execute `PT_LOAD` segment: text, then `.data` on the next page
+/// boundary, then the zeroed bss.
+///
+/// The segment is placed so `.data` starts at the object's load base, where
+/// codegen already baked its addresses, and every relocation is resolved as
+/// by [`link_in_memory`]; a reference to a symbol the object does not define
+/// is an error. Execution starts at the object's entry point.
+pub fn elf_executable_bytes(object: &AuraObject) -> Result<Vec<u8>, CodegenError> {
+    let data_offset = object.text.len().next_multiple_of(PAGE_SIZE);
+    let base = object
+        .load_base
+        .checked_sub(data_offset as u64)
+        .ok_or_else(|| CodegenError {
+            message: format!(
+                "Load base 0x{:x} leaves no room for {} bytes of text below it",
+                object.load_base,
+                object.text.len()
+            ),
+            span: None,
+        })?;
+    let image = link_in_memory(object, base)?;
+    let file_size = data_offset + object.data.len();
+
+    let mut out = Vec::with_capacity(PAGE_SIZE + file_size);
+    let mut ident = [0u8; 16];
+    // Magic, 64-bit, little endian, ELF version 1, System V ABI
+    ident[..7].copy_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+    out.extend_from_slice(&ident);
+    out.extend_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+    out.extend_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    out.extend_from_slice(&1u32.to_le_bytes()); // EV_CURRENT
+    out.extend_from_slice(&(base + object.entry_point).to_le_bytes());
+    out.extend_from_slice(&(ELF_HEADER_SIZE as u64).to_le_bytes()); // e_phoff
+    out.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+    out.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+    out.extend_from_slice(&(ELF_HEADER_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&(PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // e_phnum
+    out.extend_from_slice(&(SECTION_HEADER_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shnum
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shstrndx
+
+    // The image starts on its own page so its file offset and address agree
+    // modulo the page size, as the loader requires
+    out.extend_from_slice(&PT_LOAD.to_le_bytes());
+    out.extend_from_slice(&(PF_R | PF_W | PF_X).to_le_bytes());
+    out.extend_from_slice(&(PAGE_SIZE as u64).to_le_bytes()); // p_offset
+    out.extend_from_slice(&base.to_le_bytes()); // p_vaddr
+    out.extend_from_slice(&base.to_le_bytes()); // p_paddr
+    out.extend_from_slice(&(file_size as u64).to_le_bytes());
+    out.extend_from_slice(&(image.len() as u64).to_le_bytes()); // p_memsz
+    out.extend_from_slice(&(PAGE_SIZE as u64).to_le_bytes()); // p_align
+
+    out.resize(PAGE_SIZE, 0);
+    out.extend_from_slice(&image[..file_size]);
+    Ok(out)
+}
+
+fn append_aligned(out: &mut Vec<u8>, bytes: &[u8], align: usize) -> usize {
+    let offset = out.len().next_multiple_of(align);
+    out.resize(offset, 0);
+    out.extend_from_slice(bytes);
+    offset
+}
+
+fn push_sym(
+    symtab: &mut Vec<u8>,
+    name: u32,
+    bind: u8,
+    kind: u8,
+    section: u16,
+    value: u64,
+    size: u64,
+) {
+    symtab.extend_from_slice(&name.to_le_bytes());
+    symtab.push((bind << 4) | kind);
+    symtab.push(0); // st_other: default visibility
+    symtab.extend_from_slice(&section.to_le_bytes());
+    symtab.extend_from_slice(&value.to_le_bytes());
+    symtab.extend_from_slice(&size.to_le_bytes());
+}
+
+// NUL-terminated names, referenced by their offset; offset 0 is the empty name
+struct StringTable {
+    bytes: Vec<u8>,
+}
+
+impl StringTable {
+    fn new() -> Self {
+        StringTable { bytes: vec![0] }
+    }
+
+    fn add(&mut self, name: &str) -> u32 {
+        let offset = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(name.as_bytes());
+        self.bytes.push(0);
+        offset
+    }
+}
+
+struct SectionHeader {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    offset: usize,
+    size: usize,
+    link: u32,
+    info: u32,
+    align: u64,
+    entsize: usize,
+}
+
+impl SectionHeader {
+    fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SECTION_HEADER_SIZE);
+        bytes.extend_from_slice(&self.name.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(&self.flags.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes()); // sh_addr
+        bytes.extend_from_slice(&(self.offset as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.size as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.link.to_le_bytes());
+        bytes.extend_from_slice(&self.info.to_le_bytes());
+        bytes.extend_from_slice(&self.align.to_le_bytes());
+        bytes.extend_from_slice(&(self.entsize as u64).to_le_bytes());
+        bytes
+    }
+}
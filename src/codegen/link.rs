@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use super::{AuraObject, CodegenError, Relocation, RelocationKind, RelocationSection, SymbolKind};
+
+const PAGE_SIZE: usize = 4096;
+
+// Alignment of each object's text and data within the merged sections
+const SECTION_ALIGN: usize = 16;
+
+/// Merges separately compiled objects into one, in order: text and data are
+/// concatenated (each object's part 16-byte aligned), bss sizes are summed,
+/// and symbols and `.data` addresses are moved to their new offsets.
+///
+/// A function or data symbol defined by more than one object is an error.
+/// Direct calls are re-resolved at their new offsets, and calls through an
+/// `extern fn` to a function another object defines become direct calls;
+/// references to names no object defines stay as relocations. The entry
+/// point is that of the first object.
+pub fn link(objects: &[AuraObject]) -> Result<AuraObject, CodegenError> {
+    let Some(first) = objects.first() else {
+        return Err(CodegenError {
+            message: "Nothing to link".to_string(),
+            span: None,
+        });
+    };
+    let load_base = first.load_base;
+    if let Some(other) = objects.iter().find(|o| o.load_base != load_base) {
+        return Err(CodegenError {
+            message: format!(
+                "Cannot link objects built for load base 0x{:x} and 0x{:x}",
+                load_base, other.load_base
+            ),
+            span: None,
+        });
+    }
+
+    // Lay out every object first: bss addresses depend on the total data size
+    let mut text_offsets = Vec::with_capacity(objects.len());
+    let mut data_offsets = Vec::with_capacity(objects.len());
+    let mut bss_offsets = Vec::with_capacity(objects.len());
+    let (mut text_len, mut data_len, mut bss_size) = (0usize, 0usize, 0usize);
+    for object in objects {
+        text_len = text_len.next_multiple_of(SECTION_ALIGN);
+        data_len = data_len.next_multiple_of(SECTION_ALIGN);
+        text_offsets.push(text_len);
+        data_offsets.push(data_len);
+        bss_offsets.push(bss_size);
+        text_len += object.text.len();
+        data_len += object.data.len();
+        bss_size += object.bss_size;
+    }
+
+    let mut linked = AuraObject {
+        entry_point: first.entry_point,
+        load_base,
+        text: Vec::with_capacity(text_len),
+        data: Vec::with_capacity(data_len),
+        bss_size,
+        relocations: Vec::new(),
+        symbols: Vec::new(),
+        capability_sections: Vec::new(),
+        topology_sections: Vec::new(),
+        bit_region_sections: Vec::new(),
+    };
+
+    // Names defined by each object, to reject duplicates across objects
+    let mut owners: HashMap<String, usize> = HashMap::new();
+    for (index, object) in objects.iter().enumerate() {
+        // Pad text with int3 so a stray jump into the gap traps
+        linked.text.resize(text_offsets[index], 0xcc);
+        linked.data.resize(data_offsets[index], 0);
+        let text_base = linked.text.len();
+        linked.text.extend_from_slice(&object.text);
+        linked.data.extend_from_slice(&object.data);
+
+        for sym in &object.symbols {
+            let mut sym = sym.clone();
+            match sym.kind {
+                SymbolKind::Function => sym.offset += text_offsets[index] as u64,
+                SymbolKind::Data => sym.offset += data_offsets[index] as u64,
+                _ => {}
+            }
+            if matches!(sym.kind, SymbolKind::Function | SymbolKind::Data) {
+                match owners.get(&sym.name) {
+                    Some(&owner) if owner != index => {
+                        return Err(CodegenError {
+                            message: format!(
+                                "Duplicate symbol '{}' defined in objects {} and {}",
+                                sym.name, owner, index
+                            ),
+                            span: None,
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        owners.insert(sym.name.clone(), index);
+                    }
+                }
+            }
+            linked.symbols.push(sym);
+        }
+
+        for reloc in &object.relocations {
+            let (offset, section) = match reloc.section {
+                RelocationSection::Text => (text_base + reloc.offset, &mut linked.text),
+                RelocationSection::Data => (data_offsets[index] + reloc.offset, &mut linked.data),
+            };
+            // Addresses stored in .data always point at this object's own
+            // data, baked like the text's `.data` references
+            if reloc.symbol == ".data" || reloc.section == RelocationSection::Data {
+                let at = offset..offset + 8;
+                let baked = u64::from_le_bytes(section[at.clone()].try_into().unwrap());
+                let old = (baked - load_base) as usize;
+                let new = if old < object.data.len() {
+                    data_offsets[index] + old
+                } else {
+                    data_len + bss_offsets[index] + (old - object.data.len())
+                };
+                section[at].copy_from_slice(&(load_base + new as u64).to_le_bytes());
+            }
+            linked.relocations.push(Relocation {
+                offset,
+                ..reloc.clone()
+            });
+        }
+
+        linked
+            .capability_sections
+            .extend(object.capability_sections.iter().cloned());
+        linked
+            .topology_sections
+            .extend(object.topology_sections.iter().cloned());
+        linked
+            .bit_region_sections
+            .extend(object.bit_region_sections.iter().cloned());
+    }
+
+    let functions: HashMap<&str, u64> = linked
+        .symbols
+        .iter()
+        .filter(|s| s.kind == SymbolKind::Function)
+        .map(|s| (s.name.as_str(), s.offset))
+        .collect();
+    let mut unresolved = Vec::new();
+    for reloc in std::mem::take(&mut linked.relocations) {
+        match (&reloc.kind, functions.get(reloc.symbol.as_str())) {
+            (RelocationKind::Relative32, Some(&target))
+                if reloc.section == RelocationSection::Text =>
+            {
+                let opcode = reloc.offset - 2..reloc.offset;
+                if linked.text[reloc.offset - 1] == 0xe8 {
+                    // Already a direct call; only the displacement moves
+                } else if linked.text.get(opcode.clone()) == Some(&[0xff, 0x15][..]) {
+                    // Relax `call [rip + slot]` (ff 15) into `addr32 call
+                    // rel32` (67 e8), which keeps the instruction length and
+                    // the field
+                    linked.text[opcode].copy_from_slice(&[0x67, 0xe8]);
+                } else {
+                    return Err(CodegenError {
+                        message: format!(
+                            "Relocation for '{}' at 0x{:x} is not a call",
+                            reloc.symbol, reloc.offset
+                        ),
+                        span: None,
+                    });
+                }
+                let rel = target as i64 - (reloc.offset as i64 + 4);
+                let rel = i32::try_from(rel).map_err(|_| CodegenError {
+                    message: format!("'{}' is out of rel32 range", reloc.symbol),
+                    span: None,
+                })?;
+                linked.text[reloc.offset..reloc.offset + 4].copy_from_slice(&rel.to_le_bytes());
+            }
+            _ => unresolved.push(reloc),
+        }
+    }
+    linked.relocations = unresolved;
+
+    Ok(linked)
+}
+
+/// Lays out `object` as a flat image meant to live at `base`: text at offset
+/// 0, then `.data` and zeroed bss starting on the next page boundary, with
+/// every relocation resolved against those addresses.
+///
+/// The returned bytes are plain memory. Copying them to `base` and making the
+/// text pages executable (and the data pages writable) is up to the caller.
+pub fn link_in_memory(object: &AuraObject, base: u64) -> Result<Vec<u8>, CodegenError> {
+    let data_offset = object.text.len().next_multiple_of(PAGE_SIZE);
+
+    let (mut image, data) = relocated_sections(object, base)?;
+    image.resize(data_offset, 0);
+    image.extend_from_slice(&data);
+    image.resize(image.len() + object.bss_size, 0);
+    Ok(image)
+}
+
+// Copies of the object's text and data with every relocation resolved for
+// `base`; see `AuraObject::apply_relocations`
+pub(super) fn relocated_sections(
+    object: &AuraObject,
+    base: u64,
+) -> Result<(Vec<u8>, Vec<u8>), CodegenError> {
+    let data_base = base + object.text.len().next_multiple_of(PAGE_SIZE) as u64;
+    let mut text = object.text.clone();
+    let mut data = object.data.clone();
+
+    for reloc in &object.relocations {
+        let width = match reloc.kind {
+            RelocationKind::Absolute64 => 8,
+            RelocationKind::Relative32 | RelocationKind::Absolute32 => 4,
+        };
+        let (section, section_base, name) = match reloc.section {
+            RelocationSection::Text => (&mut text, base, "text"),
+            RelocationSection::Data => (&mut data, data_base, "data"),
+        };
+        let Some(at) = reloc
+            .offset
+            .checked_add(width)
+            .filter(|&end| end <= section.len())
+            .map(|end| reloc.offset..end)
+        else {
+            return Err(CodegenError {
+                message: format!(
+                    "Relocation for '{}' at 0x{:x} lies outside the {} section",
+                    reloc.symbol, reloc.offset, name
+                ),
+                span: None,
+            });
+        };
+
+        let target = if reloc.symbol == ".data" {
+            // Codegen baked addresses relative to its load base; rebase them
+            let mut baked = [0u8; 8];
+            baked[..width].copy_from_slice(&section[at.clone()]);
+            let baked = u64::from_le_bytes(baked);
+            data_base + (baked - object.load_base)
+        } else {
+            match object.symbols.iter().find(|s| s.name == reloc.symbol) {
+                Some(sym) if sym.kind == SymbolKind::Function => base + sym.offset,
+                Some(sym) if sym.kind == SymbolKind::Data => data_base + sym.offset,
+                _ => {
+                    return Err(CodegenError {
+                        message: format!("Unresolved external symbol '{}'", reloc.symbol),
+                        span: None,
+                    });
+                }
+            }
+        };
+
+        match reloc.kind {
+            RelocationKind::Absolute64 => {
+                section[at].copy_from_slice(&target.to_le_bytes());
+            }
+            RelocationKind::Absolute32 => {
+                let value = u32::try_from(target).map_err(|_| CodegenError {
+                    message: format!(
+                        "Address 0x{:x} of '{}' does not fit an absolute 32-bit relocation",
+                        target, reloc.symbol
+                    ),
+                    span: None,
+                })?;
+                section[at].copy_from_slice(&value.to_le_bytes());
+            }
+            RelocationKind::Relative32 => {
+                let next = section_base + reloc.offset as u64 + 4;
+                let rel = i32::try_from(target as i64 - next as i64).map_err(|_| CodegenError {
+                    message: format!("'{}' is out of rel32 range", reloc.symbol),
+                    span: None,
+                })?;
+                section[at].copy_from_slice(&rel.to_le_bytes());
+            }
+        }
+    }
+
+    Ok((text, data))
+}
@@ -1,69 +1,84 @@
 pub mod binary;
+pub mod disasm;
+pub mod elf;
+pub mod link;
 use crate::ast::*;
+use crate::layout::{Bitfield, FieldSlot, TypeLayouts};
+use crate::lexer::Span;
 pub use binary::*;
+pub use elf::{elf_executable_bytes, write_elf_binary, write_elf_object};
+pub use link::{link, link_in_memory};
 use std::fmt;
 
 #[derive(Debug)]
 pub struct CodegenError {
     pub message: String,
+    pub span: Option<Span>,
 }
 
 impl fmt::Display for CodegenError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Codegen error: {}", self.message)
+        match &self.span {
+            Some(span) => write!(
+                f,
+                "Codegen error at {}:{}: {}",
+                span.line, span.column, self.message
+            ),
+            None => write!(f, "Codegen error: {}", self.message),
+        }
     }
 }
 
 impl std::error::Error for CodegenError {}
 
-// FEATURE 9: Bit-precise integer type tracking
+// Where a named variable lives
 #[derive(Debug, Clone, Copy)]
-struct IntType {
-    bits: u8,
-    signed: bool,
+enum Slot {
+    // Offset into `.data`: globals and statics
+    Data(usize),
+    // Distance below rbp of the start of a local in the current frame
+    Frame(usize),
+}
+
+// Jump bookkeeping for the loop being generated
+struct LoopLabels {
+    // Where `continue` goes: the top of a `loop`, but the update step of a
+    // `for`, which is not emitted until after the body
+    continue_target: Option<usize>,
+    // rel32 fields of `continue` jumps waiting for that update step
+    continues: Vec<usize>,
+    // Temporaries pushed and defer scopes open when the loop was entered;
+    // a break or continue unwinds back to these
+    stack_depth: usize,
+    defer_depth: usize,
+    // rel32 fields of `break` jumps, patched to the loop exit
+    exits: Vec<usize>,
+    // Entropy of the break values seen so far
+    state: Option<EntropyState>,
+}
+
+// FEATURE 9: Bit-precise integer type tracking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntType {
+    pub bits: u8,
+    pub signed: bool,
 }
 
 impl IntType {
     fn from_aura_type(ty: &Type) -> Option<Self> {
-        match ty {
-            Type::I8 => Some(IntType {
-                bits: 8,
-                signed: true,
-            }),
-            Type::I16 => Some(IntType {
-                bits: 16,
-                signed: true,
-            }),
-            Type::I32 => Some(IntType {
-                bits: 32,
-                signed: true,
-            }),
-            Type::I64 => Some(IntType {
-                bits: 64,
-                signed: true,
-            }),
-            Type::U8 => Some(IntType {
-                bits: 8,
-                signed: false,
-            }),
-            Type::U16 => Some(IntType {
-                bits: 16,
-                signed: false,
-            }),
-            Type::U32 => Some(IntType {
-                bits: 32,
-                signed: false,
-            }),
-            Type::U64 => Some(IntType {
-                bits: 64,
-                signed: false,
-            }),
-            Type::BitInt(bits, signed) => Some(IntType {
-                bits: *bits,
-                signed: *signed,
-            }),
-            _ => None,
-        }
+        let (bits, signed) = match ty {
+            Type::I8 => (8, true),
+            Type::I16 => (16, true),
+            Type::I32 => (32, true),
+            Type::I64 | Type::Isize => (64, true),
+            Type::U8 => (8, false),
+            Type::U16 => (16, false),
+            Type::U32 => (32, false),
+            Type::U64 | Type::Usize => (64, false),
+            Type::BitInt(bits, signed) => (*bits, *signed),
+            _ => return None,
+        };
+        Some(IntType { bits, signed })
     }
 
     fn from_suffix(suffix: &IntSuffix) -> Option<Self> {
@@ -100,19 +115,18 @@ impl IntType {
                 bits: 64,
                 signed: false,
             }),
-            IntSuffix::Usize | IntSuffix::Isize => Some(IntType {
+            IntSuffix::Usize => Some(IntType {
                 bits: 64,
                 signed: false,
             }),
+            IntSuffix::Isize => Some(IntType {
+                bits: 64,
+                signed: true,
+            }),
             IntSuffix::None => None,
         }
     }
 
-    // FEATURE 9: Get minimum storage size in bytes
-    fn storage_size(&self) -> u8 {
-        (self.bits + 7) / 8
-    }
-
     // FEATURE 9: Get mask to constrain value to bit width
     fn mask(&self) -> u64 {
         if self.bits >= 64 {
@@ -139,9 +153,10 @@ impl IntType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct AuraObject {
     pub entry_point: u64,
+    pub load_base: u64,
     pub text: Vec<u8>,
     pub data: Vec<u8>,
     pub bss_size: usize,
@@ -152,11 +167,40 @@ pub struct AuraObject {
     pub bit_region_sections: Vec<binary::BitRegionSection>,
 }
 
-#[derive(Debug, Clone)]
+impl AuraObject {
+    /// Drops the symbol table. Text and data are already laid out with their
+    /// `.data` addresses baked in, so a stripped binary still runs.
+    pub fn strip_symbols(&mut self) {
+        self.symbols.clear();
+    }
+
+    /// Fixes the text and data up to run at `base`, with `.data` on the first
+    /// page boundary after the text as [`link_in_memory`] lays it out. Each
+    /// relocation's symbol resolves to its symbol-table offset, and the
+    /// relocations are consumed. A symbol the object does not define, or a
+    /// value that does not fit its field, is an error and leaves the object
+    /// untouched.
+    pub fn apply_relocations(&mut self, base: u64) -> Result<(), CodegenError> {
+        (self.text, self.data) = link::relocated_sections(self, base)?;
+        self.relocations.clear();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Relocation {
     pub offset: usize,
     pub symbol: String,
     pub kind: RelocationKind,
+    /// Section holding the field; `offset` is relative to its start
+    pub section: RelocationSection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelocationSection {
+    Text,
+    /// An address stored in `.data`, such as a `const` pointer to a global
+    Data,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -166,12 +210,14 @@ pub enum RelocationKind {
     Absolute32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Symbol {
     pub name: String,
     pub offset: u64,
     pub size: u64,
     pub kind: SymbolKind,
+    /// Type of an integer data symbol, so `dump` can show its value
+    pub int_type: Option<IntType>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -184,11 +230,66 @@ pub enum SymbolKind {
     BitRegion,
 }
 
+/// Virtual address auraload maps .data at
+pub const DEFAULT_LOAD_BASE: u64 = 0x600000000000;
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// Trap instead of wrapping when integer add/sub/mul overflows
+    pub checked_arith: bool,
+    /// Virtual base address of .data used for absolute data references
+    pub load_base: u64,
+    /// Leave the symbol table out of the written binary
+    pub strip: bool,
+    /// Output file format written by `build`
+    pub emit: EmitKind,
+    /// Fail the build when any unit raises a warning
+    pub warnings_as_errors: bool,
+}
+
+/// Output format of a compiled file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmitKind {
+    /// The native `.aura` binary
+    #[default]
+    Aura,
+    /// An ELF64 relocatable (`.o`) for a system linker
+    Object,
+    /// A static ELF64 executable that runs without a loader
+    Executable,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            checked_arith: false,
+            load_base: DEFAULT_LOAD_BASE,
+            strip: false,
+            emit: EmitKind::Aura,
+            warnings_as_errors: false,
+        }
+    }
+}
+
 pub fn generate(typed_ast: &Program) -> Result<AuraObject, CodegenError> {
+    generate_with_options(typed_ast, &CodegenOptions::default())
+}
+
+pub fn generate_with_options(
+    typed_ast: &Program,
+    options: &CodegenOptions,
+) -> Result<AuraObject, CodegenError> {
     let mut codegen = CodeGenerator::new();
+    codegen.options = options.clone();
 
     for item in &typed_ast.items {
         if let Item::Function(f) = item {
+            // Collected up front so calls can go to functions defined later
+            if !f.attrs.contains(&FunctionAttribute::Extern) {
+                codegen
+                    .functions
+                    .insert(f.name.clone(), (*f.return_type).clone());
+            }
             for attr in &f.attrs {
                 if let FunctionAttribute::Entry(Some(entry_name)) = attr {
                     codegen.entry_point_name = Some(entry_name.clone());
@@ -199,6 +300,10 @@ pub fn generate(typed_ast: &Program) -> Result<AuraObject, CodegenError> {
             }
         }
     }
+    // Without @entry, a unit starts at `main` when it has one
+    if codegen.entry_point_name.is_none() && codegen.functions.contains_key("main") {
+        codegen.entry_point_name = Some("main".to_string());
+    }
 
     if let Some(ref entry_name) = codegen.entry_point_name {
         let mut function_exists = false;
@@ -213,6 +318,7 @@ pub fn generate(typed_ast: &Program) -> Result<AuraObject, CodegenError> {
         if !function_exists {
             return Err(CodegenError {
                 message: format!("Entry point function '{}' does not exist", entry_name),
+                span: None,
             });
         }
     }
@@ -221,8 +327,12 @@ pub fn generate(typed_ast: &Program) -> Result<AuraObject, CodegenError> {
         codegen.generate_item(item)?;
     }
 
+    codegen.emit_overflow_trap();
+    codegen.resolve_direct_calls();
+
     Ok(AuraObject {
         entry_point: codegen.entry_point,
+        load_base: codegen.options.load_base,
         text: codegen.text,
         data: codegen.data,
         bss_size: codegen.bss_size,
@@ -235,16 +345,17 @@ pub fn generate(typed_ast: &Program) -> Result<AuraObject, CodegenError> {
 }
 
 struct CodeGenerator {
+    options: CodegenOptions,
     text: Vec<u8>,
     data: Vec<u8>,
     bss_size: usize,
     relocations: Vec<Relocation>,
     symbols: Vec<Symbol>,
     entry_point: u64,
-    current_offset: usize,
-    label_positions: HashMap<String, usize>,
     entry_point_name: Option<String>,
-    variables: HashMap<String, u64>,
+    variables: HashMap<String, Slot>,
+    variable_types: HashMap<String, Type>,
+    layouts: TypeLayouts,
     capability_sections: Vec<binary::CapabilitySection>,
     topology_sections: Vec<binary::TopologySection>,
     bit_region_sections: Vec<binary::BitRegionSection>,
@@ -256,6 +367,33 @@ struct CodeGenerator {
     entropy_state: HashMap<String, EntropyState>,
     // ========== FEATURE 3: Bit-Region Validation ==========
     validated_bit_regions: HashMap<String, Vec<(u8, u8)>>,
+    // rel32 fields of overflow jumps, patched once the trap is placed
+    overflow_jumps: Vec<usize>,
+    loops: Vec<LoopLabels>,
+    // Temporaries currently pushed by the function being generated, so a
+    // jump out of an expression can drop them
+    stack_depth: usize,
+    // Whether the function being generated is the entry point, whose exits
+    // end the process instead of returning
+    in_entry: bool,
+    // Bytes of locals in the current frame so far, and the imm32 field of
+    // the prologue's `sub rsp` that reserves them
+    frame_size: usize,
+    frame_patch: usize,
+    // Deferred statements of each open block, outermost first
+    defers: Vec<Vec<Stmt>>,
+    // Name of the function being generated, used to mangle its statics
+    function_name: String,
+    // Integer type the function being generated returns, which an
+    // unsuffixed literal in `return` takes
+    return_int_type: Option<IntType>,
+    // Float type the function being generated returns in xmm0, if any
+    return_float_type: Option<Type>,
+    // `extern fn` declarations, called through a relocation on their name
+    extern_functions: HashMap<String, Function>,
+    // Functions with a body in this program, called directly, with their
+    // return types
+    functions: HashMap<String, Type>,
 }
 
 type HashMap<K, V> = std::collections::HashMap<K, V>;
@@ -263,16 +401,17 @@ type HashMap<K, V> = std::collections::HashMap<K, V>;
 impl CodeGenerator {
     fn new() -> Self {
         CodeGenerator {
+            options: CodegenOptions::default(),
             text: Vec::new(),
             data: Vec::new(),
             bss_size: 0,
             relocations: Vec::new(),
             symbols: Vec::new(),
             entry_point: 0,
-            current_offset: 0,
-            label_positions: HashMap::new(),
             entry_point_name: None,
             variables: HashMap::new(),
+            variable_types: HashMap::new(),
+            layouts: TypeLayouts::new(),
             capability_sections: Vec::new(),
             topology_sections: Vec::new(),
             bit_region_sections: Vec::new(),
@@ -284,11 +423,26 @@ impl CodeGenerator {
             entropy_state: HashMap::new(),
             // ========== FEATURE 3: Bit-Region Validation ==========
             validated_bit_regions: HashMap::new(),
+            overflow_jumps: Vec::new(),
+            loops: Vec::new(),
+            stack_depth: 0,
+            in_entry: false,
+            frame_size: 0,
+            frame_patch: 0,
+            defers: Vec::new(),
+            function_name: String::new(),
+            return_int_type: None,
+            return_float_type: None,
+            extern_functions: HashMap::new(),
+            functions: HashMap::new(),
         }
     }
 
     fn generate_item(&mut self, item: &Item) -> Result<(), CodegenError> {
         match item {
+            Item::Function(f) if f.attrs.contains(&FunctionAttribute::Extern) => {
+                self.extern_functions.insert(f.name.clone(), f.clone());
+            }
             Item::Function(f) => {
                 self.generate_function(f)?;
             }
@@ -296,6 +450,7 @@ impl CodeGenerator {
                 self.generate_const_item(c)?;
             }
             Item::Var(v) => self.generate_var_item(v)?,
+            Item::Struct(_) | Item::Union(_) | Item::Enum(_) => self.layouts.declare(item),
             // FEATURE 15: Handle capability declarations with validation
             Item::CapabilityDecl(c) => {
                 self.validate_capability_range(&c.name, c.base_address, c.length, c.mode.clone())?;
@@ -329,6 +484,7 @@ impl CodeGenerator {
                     offset: c.base_address,
                     size: c.length,
                     kind: SymbolKind::Capability,
+                    int_type: None,
                 });
             }
             // FEATURE 11: Handle topology declarations with validation
@@ -362,6 +518,7 @@ impl CodeGenerator {
                     offset: 0,
                     size: 0,
                     kind: SymbolKind::Topology,
+                    int_type: None,
                 });
             }
             // FEATURE 3: Handle bit-region declarations with validation
@@ -369,11 +526,6 @@ impl CodeGenerator {
                 // FEATURE 3: Validate bit region declarations
                 self.validate_bitregion_decl(&b.name, &b.base_type, &b.regions)?;
                 let base_type_str = format!("{:?}", b.base_type);
-                let region_tuples: Vec<(String, u8, u8, BitAccess)> = b
-                    .regions
-                    .iter()
-                    .map(|r| (r.name.clone(), r.bit_offset, r.bit_width, r.access.clone()))
-                    .collect();
                 let mut regions = Vec::new();
                 for r in &b.regions {
                     let access = match r.access {
@@ -398,6 +550,7 @@ impl CodeGenerator {
                     offset: 0,
                     size: b.base_type.size() as u64,
                     kind: SymbolKind::BitRegion,
+                    int_type: None,
                 });
             }
             // FEATURE 5: Handle entropy declarations
@@ -407,29 +560,55 @@ impl CodeGenerator {
                 if e.initial_entropy == EntropyState::Initialized {
                     let offset = self.data.len();
                     self.data.extend_from_slice(&0u64.to_le_bytes());
-                    self.variables.insert(e.name.clone(), offset as u64);
+                    self.variables.insert(e.name.clone(), Slot::Data(offset));
                 }
             }
-            _ => {}
+            Item::StaticAssert(_) => {}
         }
         Ok(())
     }
 
     fn generate_const_item(&mut self, c: &ConstDecl) -> Result<(), CodegenError> {
         match &*c.value {
-            Expr::Literal(Literal::Int(val, _)) => {
+            value @ (Expr::Literal(Literal::Int(..)) | Expr::Unary(UnaryOp::Neg, _))
+                if const_scalar(value).is_some() =>
+            {
                 let offset = self.data.len();
+                let val = const_scalar(value).unwrap_or_default();
                 self.data.extend_from_slice(&val.to_le_bytes());
                 self.symbols.push(Symbol {
                     name: c.name.clone(),
                     offset: offset as u64,
                     size: 8,
                     kind: SymbolKind::Data,
+                    int_type: const_int_type(c.ty.as_deref(), value),
                 });
                 // FEATURE 5: Constants are initialized
                 self.entropy_state
                     .insert(c.name.clone(), EntropyState::Initialized);
             }
+            Expr::Literal(Literal::Float(val, _)) => {
+                // IEEE-754 bits at the declared width
+                let bytes = match c.ty.as_deref() {
+                    Some(Type::F32) => (*val as f32).to_le_bytes().to_vec(),
+                    _ => val.to_le_bytes().to_vec(),
+                };
+                let offset = self.data.len().next_multiple_of(bytes.len());
+                self.data.resize(offset, 0);
+                self.data.extend_from_slice(&bytes);
+                self.symbols.push(Symbol {
+                    name: c.name.clone(),
+                    offset: offset as u64,
+                    size: bytes.len() as u64,
+                    kind: SymbolKind::Data,
+                    int_type: None,
+                });
+                // Uses of the const are then compared as floats
+                let ty = c.ty.as_deref().cloned().unwrap_or(Type::F64);
+                self.variable_types.insert(c.name.clone(), ty);
+                self.entropy_state
+                    .insert(c.name.clone(), EntropyState::Initialized);
+            }
             Expr::Literal(Literal::String(bytes)) => {
                 let offset = self.data.len();
                 self.data.extend_from_slice(bytes);
@@ -439,16 +618,217 @@ impl CodeGenerator {
                     offset: offset as u64,
                     size: bytes.len() as u64,
                     kind: SymbolKind::Data,
+                    int_type: None,
                 });
                 // FEATURE 5: String constants are initialized
                 self.entropy_state
                     .insert(c.name.clone(), EntropyState::Initialized);
             }
+            Expr::ArrayLit(_) => {
+                let Some(ty) = c.ty.as_deref().filter(|ty| matches!(ty, Type::Array(..))) else {
+                    return Err(CodegenError {
+                        message: format!("const {}: array literal needs an array type", c.name),
+                        span: None,
+                    });
+                };
+                let offset = self.data.len().next_multiple_of(self.type_align(ty));
+                self.data.resize(offset, 0);
+                self.emit_const_data(&c.value, ty)
+                    .ok_or_else(|| CodegenError {
+                        message: format!(
+                            "const {}: array elements must be constant scalars",
+                            c.name
+                        ),
+                        span: None,
+                    })?;
+                self.symbols.push(Symbol {
+                    name: c.name.clone(),
+                    offset: offset as u64,
+                    size: self.type_size(ty) as u64,
+                    kind: SymbolKind::Data,
+                    int_type: None,
+                });
+                if let Some(ty) = &c.ty {
+                    self.variable_types.insert(c.name.clone(), (**ty).clone());
+                }
+                self.entropy_state
+                    .insert(c.name.clone(), EntropyState::Initialized);
+            }
+            // `const b = a;` copies a's bytes, so b stands on its own rather
+            // than aliasing a's storage
+            Expr::Identifier(name, span) => {
+                let source = self
+                    .symbols
+                    .iter()
+                    .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                    .cloned()
+                    .ok_or_else(|| CodegenError {
+                        message: format!(
+                            "const {}: '{}' is not a constant declared before it",
+                            c.name, name
+                        ),
+                        span: Some(span.clone()),
+                    })?;
+                let ty = self.variable_types.get(name).cloned();
+                let align = ty.as_ref().map_or(1, |ty| self.type_align(ty));
+                let start = source.offset as usize;
+                let mut end = start + source.size as usize;
+                // Strings are the only data consts with neither an integer
+                // nor a recorded type; their NUL terminator comes along
+                if source.int_type.is_none() && ty.is_none() {
+                    end += 1;
+                }
+                let offset = self.data.len().next_multiple_of(align);
+                self.data.resize(offset, 0);
+                self.data.extend_from_within(start..end);
+                self.symbols.push(Symbol {
+                    name: c.name.clone(),
+                    offset: offset as u64,
+                    size: source.size,
+                    kind: SymbolKind::Data,
+                    int_type: c
+                        .ty
+                        .as_deref()
+                        .and_then(IntType::from_aura_type)
+                        .or(source.int_type),
+                });
+                if let Some(ty) = ty {
+                    self.variable_types.insert(c.name.clone(), ty);
+                }
+                self.entropy_state
+                    .insert(c.name.clone(), EntropyState::Initialized);
+            }
+            // `const p: *const u8 = &message;` is an 8-byte slot holding
+            // message's address, baked against the load base like text
+            // references and relocated so a linker can move either
+            Expr::Unary(UnaryOp::AddrOf, target) | Expr::AddrOf(target) => {
+                let Expr::Identifier(name, span) = &**target else {
+                    return Err(CodegenError {
+                        message: format!("const {}: can only take the address of a global", c.name),
+                        span: None,
+                    });
+                };
+                let target = self
+                    .symbols
+                    .iter()
+                    .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                    .map(|s| s.offset as usize)
+                    .ok_or_else(|| CodegenError {
+                        message: format!(
+                            "const {}: '{}' is not a global declared before it",
+                            c.name, name
+                        ),
+                        span: Some(span.clone()),
+                    })?;
+                let offset = self.data.len().next_multiple_of(8);
+                self.data.resize(offset, 0);
+                self.data
+                    .extend_from_slice(&self.get_data_address(target).to_le_bytes());
+                self.relocations.push(Relocation {
+                    offset,
+                    symbol: name.clone(),
+                    kind: RelocationKind::Absolute64,
+                    section: RelocationSection::Data,
+                });
+                self.symbols.push(Symbol {
+                    name: c.name.clone(),
+                    offset: offset as u64,
+                    size: 8,
+                    kind: SymbolKind::Data,
+                    int_type: None,
+                });
+                // Recorded even when unannotated, so a use loads the pointer
+                // rather than taking the slot's address like a string's
+                let ty = match &c.ty {
+                    Some(ty) => (**ty).clone(),
+                    None => Type::ConstPtr(Box::new(
+                        self.variable_types.get(name).cloned().unwrap_or(Type::U8),
+                    )),
+                };
+                self.variable_types.insert(c.name.clone(), ty);
+                self.entropy_state
+                    .insert(c.name.clone(), EntropyState::Initialized);
+            }
             _ => {}
         }
         Ok(())
     }
 
+    // A static gets its slot and initial value in `.data` once, with no code
+    // at the declaration, so the value persists from one call to the next.
+    // The symbol is qualified by the function so statics of the same name in
+    // different functions stay distinct.
+    fn generate_static(&mut self, l: &LetStmt) -> Result<(), CodegenError> {
+        let ty = l.ty.as_deref().cloned().unwrap_or(Type::I64);
+        let size = self.type_size(&ty).max(8);
+        let offset = self
+            .data
+            .len()
+            .next_multiple_of(self.type_align(&ty).max(8));
+        self.data.resize(offset, 0);
+        let constant = match l.value.as_deref() {
+            None | Some(Expr::Zeroed(_)) => {
+                self.data.resize(offset + size, 0);
+                Some(())
+            }
+            Some(Expr::Literal(Literal::Float(val, _))) => {
+                let bytes = match ty {
+                    Type::F32 => (*val as f32).to_bits() as u64,
+                    _ => val.to_bits(),
+                };
+                self.data.extend_from_slice(&bytes.to_le_bytes());
+                Some(())
+            }
+            Some(value @ Expr::ArrayLit(_)) if matches!(ty, Type::Array(..)) => {
+                self.emit_const_data(value, &ty)
+            }
+            Some(value) => {
+                const_scalar(value).map(|value| self.data.extend_from_slice(&value.to_le_bytes()))
+            }
+        };
+        if constant.is_none() {
+            return Err(CodegenError {
+                message: format!(
+                    "static {} must be initialized with a constant expression",
+                    l.name
+                ),
+                span: None,
+            });
+        }
+        self.data.resize(offset + size, 0);
+
+        self.symbols.push(Symbol {
+            name: format!("{}::{}", self.function_name, l.name),
+            offset: offset as u64,
+            size: size as u64,
+            kind: SymbolKind::Data,
+            int_type: IntType::from_aura_type(&ty),
+        });
+        self.variables.insert(l.name.clone(), Slot::Data(offset));
+        self.variable_types.insert(l.name.clone(), ty);
+        self.entropy_state
+            .insert(l.name.clone(), EntropyState::Initialized);
+        Ok(())
+    }
+
+    // Append a constant initializer to `.data` at the width of `ty`; nested
+    // array literals are flattened row by row
+    fn emit_const_data(&mut self, value: &Expr, ty: &Type) -> Option<()> {
+        match (value, ty) {
+            (Expr::ArrayLit(elements), Type::Array(_, elem_ty)) => {
+                for element in elements {
+                    self.emit_const_data(element, elem_ty)?;
+                }
+            }
+            _ => {
+                let size = self.type_size(ty).min(8);
+                let value = const_scalar(value)?;
+                self.data.extend_from_slice(&value.to_le_bytes()[..size]);
+            }
+        }
+        Some(())
+    }
+
     fn generate_var_item(&mut self, v: &VarDecl) -> Result<(), CodegenError> {
         self.bss_size += 8;
         // FEATURE 5: Variables are uninitialized by default
@@ -459,27 +839,53 @@ impl CodeGenerator {
 
     fn generate_function(&mut self, f: &Function) -> Result<(), CodegenError> {
         let func_start = self.text.len();
+        self.function_name = f.name.clone();
+        self.return_int_type = IntType::from_aura_type(&f.return_type);
+        self.return_float_type =
+            Some((*f.return_type).clone()).filter(|ty| matches!(ty, Type::F32 | Type::F64));
 
         self.symbols.push(Symbol {
             name: f.name.clone(),
             offset: func_start as u64,
             size: 0,
             kind: SymbolKind::Function,
+            int_type: None,
         });
 
         if let Some(entry_name) = &self.entry_point_name {
             if f.name == *entry_name {
-                println!(
-                    "DEBUG: Setting entry point for function '{}' to offset {}",
-                    f.name, func_start
-                );
                 self.entry_point = func_start as u64;
             }
         }
-
+        self.in_entry = self.entry_point_name.as_ref() == Some(&f.name);
+
+        // Locals of earlier functions belong to their own frames
+        self.variables
+            .retain(|_, slot| matches!(slot, Slot::Data(_)));
+        self.stack_depth = 0;
+        self.frame_size = 0;
+        self.generate_prologue();
+        self.spill_params(f)?;
+        self.defers.push(Vec::new());
         for stmt in &f.body {
-            self.generate_stmt(stmt)?;
+            // Errors without a more precise location point at the function.
+            self.generate_stmt(stmt).map_err(|mut e| {
+                e.span.get_or_insert_with(|| f.span.clone());
+                e
+            })?;
+        }
+
+        // Falling off the end (only possible for void functions once
+        // typecheck has run, and always the case for an empty body) must
+        // still return rather than run into the next function's code.
+        if !matches!(f.body.last(), Some(Stmt::Return(_))) {
+            self.xor_rax_rax();
+            self.generate_function_exit()?;
         }
+        self.defers.clear();
+        // Keep rsp 16-aligned below the frame, as it is after `push rbp`
+        let frame = self.frame_size.next_multiple_of(16) as u32;
+        self.text[self.frame_patch..self.frame_patch + 4].copy_from_slice(&frame.to_le_bytes());
 
         if let Some((idx, _)) = self
             .symbols
@@ -497,11 +903,10 @@ impl CodeGenerator {
         match stmt {
             Stmt::Return(Some(expr)) => {
                 self.generate_return(expr)?;
-                self.ret();
             }
             Stmt::Return(None) => {
                 self.xor_rax_rax();
-                self.ret();
+                self.generate_function_exit()?;
             }
             Stmt::Const(c) => {
                 self.generate_const_stmt(c)?;
@@ -513,26 +918,271 @@ impl CodeGenerator {
                 self.generate_expr(e)?;
             }
             Stmt::Block(stmts) => {
-                for s in stmts {
-                    self.generate_stmt(s)?;
+                self.generate_block(stmts)?;
+            }
+            Stmt::Defer(stmt) => {
+                let stmt = self.capture_deferred_args(stmt)?;
+                if let Some(scope) = self.defers.last_mut() {
+                    scope.push(stmt);
+                }
+            }
+            Stmt::Break(value, _) => {
+                self.generate_break(value.as_ref())?;
+            }
+            Stmt::Continue(_) => {
+                if let Some((target, stack_depth, defer_depth)) = self
+                    .loops
+                    .last()
+                    .map(|l| (l.continue_target, l.stack_depth, l.defer_depth))
+                {
+                    self.generate_defers_from(defer_depth)?;
+                    self.drop_temporaries_to(stack_depth);
+                    let at = self.jmp_rel32();
+                    match target {
+                        Some(target) => self.patch_rel32_to(at, target),
+                        None => {
+                            if let Some(labels) = self.loops.last_mut() {
+                                labels.continues.push(at);
+                            }
+                        }
+                    }
                 }
             }
+            Stmt::If(if_stmt) => {
+                self.generate_if_stmt(if_stmt)?;
+            }
+            Stmt::While(w) => {
+                self.generate_while(w)?;
+            }
+            Stmt::For(f) => {
+                self.generate_for(f)?;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    // Statements of a block, then its defers when control falls out of it
+    // Names declared in the block go out of scope at its end, uncovering
+    // any outer variable they shadowed; their frame slots stay reserved.
+    fn generate_block(&mut self, stmts: &[Stmt]) -> Result<(), CodegenError> {
+        let variables = self.variables.clone();
+        let variable_types = self.variable_types.clone();
+        let entropy_state = self.entropy_state.clone();
+        let depth = self.defers.len();
+        self.defers.push(Vec::new());
+        for s in stmts {
+            self.generate_stmt(s)?;
+        }
+        self.generate_defers_from(depth)?;
+        self.defers.truncate(depth);
+        self.variables = variables;
+        self.variable_types = variable_types;
+        self.entropy_state = entropy_state;
+        Ok(())
+    }
+
+    // Emit the deferred statements of every scope at or above `depth`,
+    // innermost and most recent first. The scopes stay open: this is used
+    // both when a block ends and when a jump leaves it early.
+    fn generate_defers_from(&mut self, depth: usize) -> Result<(), CodegenError> {
+        let pending: Vec<Stmt> = self.defers[depth..]
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .cloned()
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        // A jump inside a deferred statement must not replay the defers
+        // that are being emitted
+        let open = std::mem::take(&mut self.defers);
+        self.push_rax();
+        let result = pending.iter().try_for_each(|stmt| self.generate_stmt(stmt));
+        self.pop_rax();
+        self.defers = open;
+        result
+    }
+
+    // A deferred call's arguments are evaluated where the defer is
+    // registered, as in Go; only the call itself waits for the scope to
+    // end. Each argument that could change in between is stored in a hidden
+    // frame slot and the deferred call reads it from there. Any other
+    // deferred statement runs as written when the scope ends.
+    fn capture_deferred_args(&mut self, stmt: &Stmt) -> Result<Stmt, CodegenError> {
+        let expr = match stmt {
+            Stmt::Expr(Expr::Call(callee, args)) => {
+                Expr::Call(callee.clone(), self.capture_args(args)?)
+            }
+            Stmt::Expr(Expr::Syscall(name, args)) => {
+                Expr::Syscall(name.clone(), self.capture_args(args)?)
+            }
+            Stmt::Expr(Expr::RawSyscall(args)) => Expr::RawSyscall(self.capture_args(args)?),
+            Stmt::Expr(Expr::Free(ptr, size)) => Expr::Free(
+                Box::new(self.capture_arg(ptr)?),
+                Box::new(self.capture_arg(size)?),
+            ),
+            _ => return Ok(stmt.clone()),
+        };
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn capture_args(&mut self, args: &[Expr]) -> Result<Vec<Expr>, CodegenError> {
+        args.iter().map(|arg| self.capture_arg(arg)).collect()
+    }
+
+    // Literals cannot change, arrays are passed by address, and names
+    // without a slot are data symbols like string constants, so those are
+    // left in place
+    fn capture_arg(&mut self, arg: &Expr) -> Result<Expr, CodegenError> {
+        let span = match arg {
+            Expr::Literal(_) => return Ok(arg.clone()),
+            Expr::Identifier(name, span) => {
+                if !self.variables.contains_key(name)
+                    || matches!(self.variable_types.get(name), Some(Type::Array(..)))
+                {
+                    return Ok(arg.clone());
+                }
+                span.clone()
+            }
+            _ => Span::default(),
+        };
+        let ty = self.float_type(arg).or_else(|| self.static_type(arg));
+        let state = self.generate_expr(arg)?;
+        let slot = self.alloc_frame_slot(8);
+        self.store_slot(slot);
+        let Slot::Frame(offset) = slot else {
+            unreachable!("alloc_frame_slot returns a frame slot");
+        };
+        // Not a valid identifier, so it cannot clash with a source name
+        let name = format!("defer@{}", offset);
+        self.variables.insert(name.clone(), slot);
+        if let Some(ty) = ty {
+            self.variable_types.insert(name.clone(), ty);
+        }
+        self.entropy_state.insert(name.clone(), state);
+        Ok(Expr::Identifier(name, span))
+    }
+
+    fn drop_temporaries_to(&mut self, depth: usize) {
+        let extra = self.stack_depth - depth;
+        if extra > 0 {
+            // add rsp, imm32
+            self.text.extend_from_slice(&[0x48, 0x81, 0xc4]);
+            self.text
+                .extend_from_slice(&((extra * 8) as u32).to_le_bytes());
+        }
+    }
+
+    // push rbp; mov rbp, rsp; sub rsp, imm32, with the frame size patched
+    // in once the body has allocated its locals.
+    //
+    // Nothing calls the entry point: at `_start` rsp points at argc with no
+    // return address above it, and a loader may jump in with rsp anywhere.
+    // Its frame therefore starts from a zero rbp, marking the outermost
+    // frame, and realigns rsp to 16 itself instead of relying on the push.
+    fn generate_prologue(&mut self) {
+        if self.in_entry {
+            // xor ebp, ebp
+            self.text.extend_from_slice(&[0x31, 0xed]);
+        }
+        self.text.push(0x55);
+        self.text.extend_from_slice(&[0x48, 0x89, 0xe5]);
+        if self.in_entry {
+            // and rsp, -16
+            self.text.extend_from_slice(&[0x48, 0x83, 0xe4, 0xf0]);
+        }
+        self.text.extend_from_slice(&[0x48, 0x81, 0xec]);
+        self.frame_patch = self.text.len();
+        self.text.extend_from_slice(&[0u8; 4]);
+    }
+
+    // Store each incoming argument register into its own frame slot, so a
+    // parameter is read and assigned like any local. Integers arrive in rdi,
+    // rsi, rdx, rcx, r8 and r9 and floats in xmm0-xmm7, as generate_call
+    // passes them.
+    fn spill_params(&mut self, f: &Function) -> Result<(), CodegenError> {
+        const INT_REGS: [u8; 6] = [7, 6, 2, 1, 8, 9];
+        let mut int_reg = 0;
+        let mut float_reg = 0;
+        for param in &f.params {
+            let is_float = matches!(*param.ty, Type::F32 | Type::F64);
+            let slot = self.alloc_frame_slot(8);
+            let Slot::Frame(offset) = slot else {
+                unreachable!("alloc_frame_slot returns a frame slot");
+            };
+            if is_float && float_reg < 8 {
+                // movq [rbp - offset], xmmN
+                self.text.extend_from_slice(&[0x66, 0x0f, 0xd6]);
+                self.rbp_operand(float_reg, offset);
+                float_reg += 1;
+            } else if !is_float && int_reg < INT_REGS.len() {
+                // mov [rbp - offset], reg
+                let reg = INT_REGS[int_reg];
+                self.text.push(if reg >= 8 { 0x4c } else { 0x48 });
+                self.text.push(0x89);
+                self.rbp_operand(reg & 7, offset);
+                int_reg += 1;
+            } else {
+                return Err(CodegenError {
+                    message: format!(
+                        "Function '{}' needs stack parameters, which are not supported yet (at most 6 integer and 8 float parameters)",
+                        f.name
+                    ),
+                    span: Some(f.span.clone()),
+                });
+            }
+            self.variables.insert(param.name.clone(), slot);
+            self.variable_types
+                .insert(param.name.clone(), (*param.ty).clone());
+            self.entropy_state
+                .insert(param.name.clone(), EntropyState::Initialized);
+        }
+        Ok(())
+    }
+
+    // Leave the function with the return value already in rax: run every
+    // pending defer, then tear down the frame, which also drops any
+    // temporaries the return is nested inside. A float is returned in xmm0
+    // as System V has it. Nothing called the entry point, so it exits with
+    // the return value as the status instead.
+    fn generate_function_exit(&mut self) -> Result<(), CodegenError> {
+        self.generate_defers_from(0)?;
+        if !self.in_entry {
+            match self.return_float_type {
+                Some(Type::F64) => self.movq_xmm_rax(0),
+                Some(Type::F32) => self.movd_xmm0_eax(),
+                _ => {}
+            }
+        }
+        // mov rsp, rbp; pop rbp
+        self.text.extend_from_slice(&[0x48, 0x89, 0xec]);
+        self.text.push(0x5d);
+        if self.in_entry {
+            self.generate_exit();
+        } else {
+            self.ret();
+        }
+        Ok(())
+    }
+
+    // exit(rax): mov rdi, rax; mov rax, 60; syscall
+    fn generate_exit(&mut self) {
+        self.mov_rdi_rax();
+        self.mov_rax_immediate(60);
+        self.syscall();
+    }
+
     fn generate_const_stmt(&mut self, c: &ConstStmt) -> Result<(), CodegenError> {
         match &*c.value {
-            Expr::Literal(Literal::Int(val, _)) => {
-                let offset = self.data.len();
-                self.data.extend_from_slice(&val.to_le_bytes());
-                self.symbols.push(Symbol {
-                    name: c.name.clone(),
-                    offset: offset as u64,
-                    size: 8,
-                    kind: SymbolKind::Data,
-                });
+            value @ (Expr::Literal(Literal::Int(..)) | Expr::Unary(UnaryOp::Neg, _))
+                if const_scalar(value).is_some() =>
+            {
+                let slot = self.alloc_frame_slot(8);
+                self.mov_rax_immediate(const_scalar(value).unwrap_or_default() as u64);
+                self.store_slot(slot);
+                self.variables.insert(c.name.clone(), slot);
             }
             Expr::Literal(Literal::String(bytes)) => {
                 let offset = self.data.len();
@@ -543,6 +1193,7 @@ impl CodeGenerator {
                     offset: offset as u64,
                     size: bytes.len() as u64,
                     kind: SymbolKind::Data,
+                    int_type: None,
                 });
             }
             _ => {}
@@ -551,83 +1202,158 @@ impl CodeGenerator {
     }
 
     fn generate_let(&mut self, l: &LetStmt) -> Result<(), CodegenError> {
+        if l.is_static {
+            return self.generate_static(l);
+        }
+        if let Some(ty) = &l.ty {
+            self.variable_types.insert(l.name.clone(), (**ty).clone());
+        }
+        let value = match &l.value {
+            Some(value) => value,
+            None => {
+                // No initializer: reserve the slot only. Typecheck guarantees
+                // it is assigned before it is read.
+                let size = l.ty.as_ref().map_or(8, |ty| self.type_size(ty));
+                let slot = self.alloc_frame_slot(size);
+                self.variables.insert(l.name.clone(), slot);
+                self.entropy_state
+                    .insert(l.name.clone(), EntropyState::Uninitialized);
+                return Ok(());
+            }
+        };
         // FEATURE 5: Track entropy state for the variable
-        match &*l.value {
+        let state = match &**value {
             Expr::Literal(Literal::Int(val, _)) => {
-                let offset = self.data.len();
-                self.data.extend_from_slice(&val.to_le_bytes());
-                self.variables.insert(l.name.clone(), offset as u64);
+                self.mov_rax_immediate(*val as u64);
                 // FEATURE 5: Literals are initialized
+                EntropyState::Initialized
+            }
+            Expr::Zeroed(ty) => {
+                let size = self.type_size(ty);
+                let slot = self.alloc_frame_slot(size);
+                // Re-zero on every execution so the slot is clean on each entry
+                self.zero_slot(slot, size);
+                self.variables.insert(l.name.clone(), slot);
+                self.entropy_state
+                    .insert(l.name.clone(), EntropyState::Initialized);
+                return Ok(());
+            }
+            Expr::ArrayLit(_) => {
+                let ty = match &l.ty {
+                    Some(ty) => (**ty).clone(),
+                    None => self.static_type(value).ok_or_else(|| CodegenError {
+                        message: format!("Cannot infer the element type of array {}", l.name),
+                        span: None,
+                    })?,
+                };
+                let size = self.type_size(&ty);
+                let slot = self.alloc_frame_slot(size);
+                let Slot::Frame(offset) = slot else {
+                    unreachable!("locals live in the frame")
+                };
+                self.store_array_literal(value, &ty, offset)?;
+                self.variables.insert(l.name.clone(), slot);
+                self.variable_types.insert(l.name.clone(), ty);
                 self.entropy_state
                     .insert(l.name.clone(), EntropyState::Initialized);
                 return Ok(());
             }
-            Expr::Identifier(name) => {
-                if let Some(&offset) = self.variables.get(name) {
-                    let addr = self.get_data_address(offset as usize);
-                    self.mov_r10_immediate(addr);
-                    self.mov_rax_from_r10(); // Load value into RAX
+            Expr::Identifier(name, span) => {
+                if let Some(&source) = self.variables.get(name) {
+                    self.load_slot(source); // Load value into RAX
                 } else {
                     return Err(CodegenError {
                         message: format!("Undefined variable: {}", name),
+                        span: Some(span.clone()),
                     });
                 }
-                // Now RAX holds the value, store it
-                let offset = self.data.len();
-                self.data.extend_from_slice(&[0u8; 8]);
-                let var_addr = self.get_data_address(offset);
-                self.mov_r10_immediate(var_addr);
-                self.mov_rax_to_r10_mem();
-                self.variables.insert(l.name.clone(), offset as u64);
                 // FEATURE 5: Inherit entropy from source variable
-                let inherited_state = self
-                    .entropy_state
+                self.entropy_state
                     .get(name)
                     .cloned()
-                    .unwrap_or(EntropyState::Initialized);
-                self.entropy_state.insert(l.name.clone(), inherited_state);
-                return Ok(());
-            }
-            _ => {
-                // FEATURE 5: Generate expression and track resulting entropy
-                let _state = self.generate_expr(&l.value)?;
-                // Store the entropy state for this variable
-                self.entropy_state.insert(l.name.clone(), _state);
+                    .unwrap_or(EntropyState::Initialized)
             }
+            // FEATURE 5: Generate expression and track resulting entropy
+            _ => self.generate_expr(value)?,
         };
-        // Store result in data section
-        let offset = self.data.len();
-        self.data.extend_from_slice(&[0u8; 8]);
-        let var_addr = self.get_data_address(offset);
-        self.mov_r10_immediate(var_addr);
-        self.mov_rax_to_r10_mem();
-        self.variables.insert(l.name.clone(), offset as u64);
+        // Store result in the variable's frame slot
+        let slot = self.alloc_frame_slot(8);
+        self.store_slot(slot);
+        self.variables.insert(l.name.clone(), slot);
+        self.entropy_state.insert(l.name.clone(), state);
         Ok(())
     }
 
-    fn generate_return(&mut self, expr: &Expr) -> Result<(), CodegenError> {
-        match expr {
-            Expr::Literal(Literal::Int(val, _)) => {
-                self.mov_rax_immediate(*val as u64);
+    // Stores each element of an array literal in place, starting at
+    // [rbp - offset]; nested literals fill their rows in order
+    fn store_array_literal(
+        &mut self,
+        value: &Expr,
+        ty: &Type,
+        offset: usize,
+    ) -> Result<(), CodegenError> {
+        match (value, ty) {
+            (Expr::ArrayLit(elements), Type::Array(_, elem_ty)) => {
+                let elem_size = self.type_size(elem_ty);
+                for (i, element) in elements.iter().enumerate() {
+                    self.store_array_literal(element, elem_ty, offset - i * elem_size)?;
+                }
             }
-            Expr::Identifier(name) => {
-                if let Some(sym) = self
-                    .symbols
-                    .iter()
-                    .find(|s| s.name == *name && s.kind == SymbolKind::Data)
-                {
-                    self.mov_rax_from_mem(sym.offset as u64);
-                } else {
-                    self.xor_rax_rax();
-                }
+            (_, Type::Array(..) | Type::Named(_)) => {
+                return Err(CodegenError {
+                    message: format!(
+                        "Array literal elements of type {:?} must be written as nested literals",
+                        ty
+                    ),
+                    span: None,
+                });
             }
             _ => {
-                self.xor_rax_rax();
+                self.generate_expr(value)?;
+                // lea rcx, [rbp - offset]
+                self.text.extend_from_slice(&[0x48, 0x8d]);
+                self.rbp_operand(1, offset);
+                self.store_rax_to_rcx(Some(ty));
             }
         }
         Ok(())
     }
 
+    // A returned if-expression is in tail position: each arm loads its value
+    // and leaves through its own exit instead of jumping to a join point
+    // only to return from there. Simple arms still use the branchless cmov.
+    fn generate_return(&mut self, expr: &Expr) -> Result<(), CodegenError> {
+        if let Expr::If(if_expr) = expr {
+            if !(Self::is_simple_value(&if_expr.then_expr)
+                && Self::is_simple_value(&if_expr.else_expr))
+            {
+                let to_else = self.jump_if_false(&if_expr.condition)?;
+                self.generate_return(&if_expr.then_expr)?;
+                self.patch_rel32(to_else);
+                return self.generate_return(&if_expr.else_expr);
+            }
+        }
+        if let (Expr::Literal(Literal::Int(val, IntSuffix::None)), Some(int_type)) =
+            (expr, self.return_int_type)
+        {
+            if !int_type.fits(*val) {
+                return Err(CodegenError {
+                    message: format!(
+                        "Integer literal {} does not fit the return type of '{}'",
+                        val, self.function_name
+                    ),
+                    span: None,
+                });
+            }
+            // In range, the literal already reads the same at the return
+            // width, zero- or sign-extended to all of rax
+            self.mov_rax_immediate(*val as u64);
+            return self.generate_function_exit();
+        }
+        self.generate_expr(expr)?;
+        self.generate_function_exit()
+    }
+
     fn generate_expr(&mut self, expr: &Expr) -> Result<EntropyState, CodegenError> {
         match expr {
             Expr::Literal(Literal::Int(val, int_suffix)) => {
@@ -637,26 +1363,31 @@ impl CodeGenerator {
                     if !int_type.fits(*val) {
                         return Err(CodegenError {
                             message: format!("Integer literal {} does not fit in type", val),
+                            span: None,
                         });
                     }
                     let masked = *val as u64 & int_type.mask();
-                    self.emit_width_immediate(masked, int_type.bits);
+                    self.emit_width_immediate(masked, int_type);
                     Ok(EntropyState::Initialized)
                 } else {
+                    self.mov_rax_immediate(*val as u64);
                     Ok(EntropyState::Initialized)
                 }
             }
-            Expr::Identifier(name) => {
-                if let Some(sym) = self
+            Expr::Identifier(name, span) => {
+                if let Some(&slot) = self.variables.get(name) {
+                    self.load_slot(slot);
+                } else if let Some(sym) = self
                     .symbols
                     .iter()
                     .find(|s| s.name == *name && s.kind == SymbolKind::Data)
                 {
-                    self.mov_rax_from_mem(sym.offset as u64);
-                } else if let Some(&offset) = self.variables.get(name) {
-                    let addr = self.get_data_address(offset as usize);
-                    self.mov_r10_immediate(addr);
-                    self.mov_rax_from_r10();
+                    self.mov_rax_from_data(sym.offset);
+                } else {
+                    return Err(CodegenError {
+                        message: format!("Unknown identifier: {}", name),
+                        span: Some(span.clone()),
+                    });
                 }
                 let state = self
                     .entropy_state
@@ -666,11 +1397,18 @@ impl CodeGenerator {
                 Ok(state)
             }
             Expr::Syscall(method_name, args) => {
-                // FEATURE 5: Check entropy before syscall
-                self.check_syscall_entropy(args)?;
+                // FEATURE 5: Check entropy before syscall; read's buffer is
+                // only written to
+                let written = (method_name == "read").then_some(1);
+                self.check_syscall_entropy(args, written)?;
                 self.generate_syscall(method_name, args)?;
                 Ok(EntropyState::Initialized)
             }
+            Expr::RawSyscall(args) => {
+                self.check_syscall_entropy(args, None)?;
+                self.emit_syscall(None, args, None)?;
+                Ok(EntropyState::Initialized)
+            }
             Expr::Alloc(ty, count) => {
                 let _size_state = self.generate_expr(count)?;
                 let size = self.eval_expr_to_u64(count)?;
@@ -702,7 +1440,20 @@ impl CodeGenerator {
                     )?;
                 }
                 let _state = self.generate_expr(expr)?;
-                self.generate_cast_conversion(target_type)?;
+                let double = *target_type == Type::F64;
+                match (self.float_type(expr), target_type) {
+                    (Some(from), Type::F32 | Type::F64) => self.convert_float(&from, double),
+                    (Some(from), _) => {
+                        self.truncate_float_to_rax(&from);
+                        self.generate_cast_conversion(None, target_type)?;
+                    }
+                    (None, Type::F32 | Type::F64) => {
+                        self.convert_int_to_float(self.expr_int_type(expr), double)
+                    }
+                    (None, _) => {
+                        self.generate_cast_conversion(self.expr_int_type(expr), target_type)?
+                    }
+                }
                 Ok(EntropyState::Initialized)
             }
             Expr::PhysAddr(phys_addr) => {
@@ -712,7 +1463,7 @@ impl CodeGenerator {
             }
             Expr::BitRegionAccess(br) => {
                 let _base_state = self.generate_expr(&br.base)?;
-                if let Expr::Identifier(name) = &*br.base {
+                if let Expr::Identifier(name, _) = &*br.base {
                     if let Some(regions) = self.validated_bit_regions.get(name) {
                         let _region = regions
                             .iter()
@@ -721,43 +1472,742 @@ impl CodeGenerator {
                 }
                 Ok(EntropyState::Initialized)
             }
-            Expr::Deref(ptr) => {
-                let ptr_state = self.generate_expr(ptr)?;
-                self.check_mmio_entropy(ptr_state, "memory")?;
-                Ok(EntropyState::Initialized)
+            Expr::Binary(op @ (BinaryOp::LogicalAnd | BinaryOp::LogicalOr), left, right, _) => {
+                self.generate_logical(op, left, right)
             }
-            Expr::Binary(op, left, right) => {
+            Expr::Binary(op, left, right, _) => {
+                // Left operand ends up in rax, right operand in rcx
                 let left_state = self.generate_expr(left)?;
+                self.push_rax();
                 let right_state = self.generate_expr(right)?;
+                self.mov_rcx_rax();
+                self.pop_rax();
+                if self.pointer_arithmetic(op, left, right) {
+                    return Ok(self.propagate_entropy(left_state, right_state));
+                }
+                if let Some(ty) = self.float_type(left) {
+                    self.float_binary(op, &ty)?;
+                    return Ok(self.propagate_entropy(left_state, right_state));
+                }
                 match op {
-                    BinaryOp::Add => self.add_rax_rdx(),
-                    BinaryOp::Sub => self.sub_rax_rdx(),
-                    BinaryOp::Mul => self.imul_rax_rdx(),
-                    BinaryOp::Div => self.idiv_rdx(),
-                    BinaryOp::Mod => self.idiv_rdx(),
-                    BinaryOp::LShift => self.shl_rax_cl(),
-                    BinaryOp::RShift => self.shr_rax_cl(),
-                    BinaryOp::BitAnd => self.and_rax_rdx(),
-                    BinaryOp::BitOr => self.or_rax_rdx(),
-                    BinaryOp::BitXor => self.xor_rax_rdx(),
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                        if self.options.checked_arith {
+                            let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                                bits: 64,
+                                signed: true,
+                            });
+                            self.checked_arith(op, int_type);
+                        } else {
+                            match op {
+                                BinaryOp::Add => self.add_rax_rcx(),
+                                BinaryOp::Sub => self.sub_rax_rcx(),
+                                _ => self.imul_rax_rcx(),
+                            }
+                        }
+                    }
+                    BinaryOp::Div | BinaryOp::Mod => {
+                        let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                            bits: 64,
+                            signed: true,
+                        });
+                        self.divide_rax_rcx(int_type, matches!(op, BinaryOp::Mod));
+                    }
+                    BinaryOp::LShift => {
+                        let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                            bits: 64,
+                            signed: true,
+                        });
+                        self.mask_shift_count(right, int_type);
+                        self.shl_rax_cl();
+                    }
+                    BinaryOp::RShift => {
+                        // Signedness of the shifted value picks sar vs shr
+                        let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                            bits: 64,
+                            signed: true,
+                        });
+                        self.mask_shift_count(right, int_type);
+                        self.extend_rax(int_type);
+                        if int_type.signed {
+                            self.sar_rax_cl();
+                        } else {
+                            self.shr_rax_cl();
+                        }
+                    }
+                    BinaryOp::BitAnd => self.and_rax_rcx(),
+                    BinaryOp::BitOr => self.or_rax_rcx(),
+                    BinaryOp::BitXor => self.xor_rax_rcx(),
+                    BinaryOp::Eq
+                    | BinaryOp::Neq
+                    | BinaryOp::Lt
+                    | BinaryOp::Gt
+                    | BinaryOp::LtEq
+                    | BinaryOp::GtEq => {
+                        let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                            bits: 64,
+                            signed: true,
+                        });
+                        self.compare_rax_rcx(op, int_type);
+                    }
                     _ => {}
                 }
+                if !matches!(
+                    op,
+                    BinaryOp::Eq
+                        | BinaryOp::Neq
+                        | BinaryOp::Lt
+                        | BinaryOp::Gt
+                        | BinaryOp::LtEq
+                        | BinaryOp::GtEq
+                ) {
+                    self.wrap_int_result(self.expr_int_type(left));
+                }
                 Ok(self.propagate_entropy(left_state, right_state))
             }
+            Expr::Zeroed(_) => {
+                self.xor_rax_rax();
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Assign(target, value) => {
+                if let Expr::Unary(UnaryOp::Deref, ptr) = &**target {
+                    let value_state = self.generate_expr(value)?;
+                    self.check_mmio_entropy(value_state, "memory")?;
+                    self.push_rax();
+                    self.generate_expr(ptr)?;
+                    self.mov_rcx_rax();
+                    self.pop_rax();
+                    let pointee = match self.static_type(ptr) {
+                        Some(Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t)) => Some(*t),
+                        _ => None,
+                    };
+                    self.store_rax_to_rcx(pointee.as_ref());
+                } else if let Expr::Field(..) | Expr::PtrField(..) = &**target {
+                    self.generate_expr(value)?;
+                    self.push_rax();
+                    let (field_ty, bitfield) = self.generate_field_address(target)?;
+                    self.mov_rcx_rax();
+                    self.pop_rax();
+                    match bitfield {
+                        Some(bitfield) => self.store_bitfield(bitfield),
+                        None => self.store_rax_to_rcx(Some(&field_ty)),
+                    }
+                } else if let Expr::Index(base, index) = &**target {
+                    self.generate_expr(value)?;
+                    self.push_rax();
+                    let elem_ty = self.generate_index_address(base, index)?;
+                    self.mov_rcx_rax();
+                    self.pop_rax();
+                    self.store_rax_to_rcx(Some(&elem_ty));
+                } else if let Expr::Identifier(name, span) = &**target {
+                    let value_state = self.generate_expr(value)?;
+                    if let Some(&slot) = self.variables.get(name) {
+                        self.store_slot(slot);
+                    } else if let Some(sym) = self
+                        .symbols
+                        .iter()
+                        .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                    {
+                        self.mov_r10_data_address(sym.offset as usize);
+                        self.mov_rax_to_r10_mem();
+                    } else {
+                        return Err(CodegenError {
+                            message: format!("Undefined variable: {}", name),
+                            span: Some(span.clone()),
+                        });
+                    }
+                    self.entropy_state.insert(name.clone(), value_state);
+                }
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Unary(UnaryOp::AddrOf, operand) | Expr::AddrOf(operand) => {
+                self.generate_address_of(operand)?;
+                Ok(EntropyState::Initialized)
+            }
             Expr::Unary(op, operand) => {
                 let state = self.generate_expr(operand)?;
                 match op {
-                    UnaryOp::Neg => self.neg_rax(),
-                    UnaryOp::Not | UnaryOp::BitNot => self.not_rax(),
-                    UnaryOp::Deref => {}
-                    UnaryOp::AddrOf => {}
+                    UnaryOp::Neg => {
+                        self.neg_rax();
+                        self.wrap_int_result(self.expr_int_type(operand));
+                    }
+                    UnaryOp::Not => self.not_rax(),
+                    UnaryOp::BitNot => {
+                        self.not_rax();
+                        self.wrap_int_result(self.expr_int_type(operand));
+                    }
+                    UnaryOp::Deref => {
+                        let pointee = match self.static_type(operand) {
+                            Some(Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t)) => Some(*t),
+                            _ => None,
+                        };
+                        self.load_rax_from_rax(pointee.as_ref());
+                        return Ok(EntropyState::Initialized);
+                    }
+                    UnaryOp::AddrOf => unreachable!("address-of is generated above"),
                 }
                 Ok(state)
             }
+            Expr::Literal(Literal::Bool(b)) => {
+                self.mov_rax_immediate(*b as u64);
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Literal(Literal::Char(c)) => {
+                self.mov_rax_immediate(*c as u64);
+                Ok(EntropyState::Initialized)
+            }
+            // Floats travel through rax as their IEEE-754 bits
+            Expr::Literal(Literal::Float(val, FloatSuffix::F32)) => {
+                self.mov_rax_immediate((*val as f32).to_bits() as u64);
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Literal(Literal::Float(val, _)) => {
+                self.mov_rax_immediate(val.to_bits());
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Index(base, index) => {
+                let elem_ty = self.generate_index_address(base, index)?;
+                // An array element that is itself an array evaluates to its
+                // address
+                if !matches!(elem_ty, Type::Array(..)) {
+                    self.load_rax_from_rax(Some(&elem_ty));
+                }
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Field(..) | Expr::PtrField(..) => {
+                if let Some(value) = self.enum_variant_value(expr) {
+                    self.mov_rax_immediate(value as u64);
+                    return Ok(EntropyState::Initialized);
+                }
+                let (field_ty, bitfield) = self.generate_field_address(expr)?;
+                match bitfield {
+                    Some(bitfield) => self.load_bitfield(bitfield, &field_ty),
+                    None => self.load_rax_from_rax(Some(&field_ty)),
+                }
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Sizeof(ty) => {
+                self.mov_rax_immediate(self.type_size(ty) as u64);
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Alignof(ty) => {
+                self.mov_rax_immediate(self.type_align(ty) as u64);
+                Ok(EntropyState::Initialized)
+            }
+            Expr::Offsetof(ty, path) => {
+                let offset = self
+                    .field_path_offset(ty, path)
+                    .ok_or_else(|| CodegenError {
+                        message: format!("offsetof: {:?} has no field '{}'", ty, path),
+                        span: None,
+                    })?;
+                self.mov_rax_immediate(offset as u64);
+                Ok(EntropyState::Initialized)
+            }
+            Expr::If(if_expr) => self.generate_if_expr(if_expr),
+            Expr::Loop(body) => self.generate_loop_expr(body),
+            Expr::Call(callee, args) => match &**callee {
+                Expr::Identifier(name, span)
+                    if self.extern_functions.contains_key(name)
+                        || self.functions.contains_key(name) =>
+                {
+                    self.generate_call(name, args).map_err(|mut e| {
+                        e.span.get_or_insert_with(|| span.clone());
+                        e
+                    })?;
+                    Ok(EntropyState::Initialized)
+                }
+                _ => Ok(EntropyState::Initialized),
+            },
             _ => Ok(EntropyState::Initialized),
         }
     }
 
+    // System V call: integer and pointer arguments go in rdi, rsi, rdx, rcx,
+    // r8 and r9, floats in xmm0-xmm7, and rsp is 16-byte aligned at the call.
+    // A variadic callee also gets the number of vector registers used in al;
+    // an f32 in the variadic tail is promoted to f64 as in C. The result is
+    // left in rax.
+    fn generate_call(&mut self, name: &str, args: &[Expr]) -> Result<(), CodegenError> {
+        let (fixed, variadic) = match self.extern_functions.get(name) {
+            Some(f) => (f.params.len(), f.variadic),
+            None => (args.len(), false),
+        };
+        let classes: Vec<Option<Type>> = args.iter().map(|arg| self.float_type(arg)).collect();
+        let int_count = classes.iter().filter(|c| c.is_none()).count();
+        let float_count = args.len() - int_count;
+        if int_count > 6 || float_count > 8 {
+            return Err(CodegenError {
+                message: format!(
+                    "Call to '{}' needs stack arguments, which are not supported yet (at most 6 integer and 8 float arguments)",
+                    name
+                ),
+                span: None,
+            });
+        }
+
+        // Evaluate everything first so no argument clobbers an earlier one's
+        // register, then pop them into place last to first
+        for arg in args {
+            self.generate_expr(arg)?;
+            self.push_rax();
+        }
+        let mut int_reg = int_count;
+        let mut float_reg = float_count;
+        for (i, class) in classes.iter().enumerate().rev() {
+            self.pop_rax();
+            match class {
+                None => {
+                    int_reg -= 1;
+                    self.mov_arg_reg_rax(int_reg);
+                }
+                Some(ty) => {
+                    float_reg -= 1;
+                    if *ty == Type::F32 && i >= fixed && variadic {
+                        self.cvt_f32_rax_to_f64_xmm(float_reg as u8);
+                    } else {
+                        self.movq_xmm_rax(float_reg as u8);
+                    }
+                }
+            }
+        }
+
+        // The prologue leaves rsp on a 16-byte boundary; each live
+        // temporary moves it another 8
+        let pad = !self.stack_depth.is_multiple_of(2);
+        if pad {
+            // sub rsp, 8
+            self.text.extend_from_slice(&[0x48, 0x83, 0xec, 0x08]);
+        }
+        if variadic {
+            // mov al, imm8
+            self.text.push(0xb0);
+            self.text.push(float_count as u8);
+        }
+        if self.extern_functions.contains_key(name) {
+            self.call_external(name);
+        } else {
+            self.call_direct(name);
+        }
+        if pad {
+            // add rsp, 8
+            self.text.extend_from_slice(&[0x48, 0x83, 0xc4, 0x08]);
+        }
+        // A float result comes back in xmm0; carry its bits on in rax
+        match self.function_return_type(name) {
+            Some(Type::F64) => self.movq_rax_xmm0(),
+            Some(Type::F32) => self.movd_eax_xmm0(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Declared return type of a function this unit can call
+    fn function_return_type(&self, name: &str) -> Option<&Type> {
+        self.functions
+            .get(name)
+            .or_else(|| self.extern_functions.get(name).map(|f| &*f.return_type))
+    }
+
+    // Float type of an argument, if it is passed in an xmm register
+    fn float_type(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Literal(Literal::Float(_, FloatSuffix::F32)) => Some(Type::F32),
+            Expr::Literal(Literal::Float(..)) => Some(Type::F64),
+            Expr::Binary(
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div,
+                left,
+                ..,
+            ) => self.float_type(left),
+            _ => self
+                .static_type(expr)
+                .filter(|ty| matches!(ty, Type::F32 | Type::F64)),
+        }
+    }
+
+    // mov <nth integer argument register>, rax
+    fn mov_arg_reg_rax(&mut self, index: usize) {
+        const REGS: [[u8; 3]; 6] = [
+            [0x48, 0x89, 0xc7], // rdi
+            [0x48, 0x89, 0xc6], // rsi
+            [0x48, 0x89, 0xc2], // rdx
+            [0x48, 0x89, 0xc1], // rcx
+            [0x49, 0x89, 0xc0], // r8
+            [0x49, 0x89, 0xc1], // r9
+        ];
+        self.text.extend_from_slice(&REGS[index]);
+    }
+
+    // movq rax, xmm0
+    fn movq_rax_xmm0(&mut self) {
+        self.text.extend_from_slice(&[0x66, 0x48, 0x0f, 0x7e, 0xc0]);
+    }
+
+    // movd eax, xmm0
+    fn movd_eax_xmm0(&mut self) {
+        self.text.extend_from_slice(&[0x66, 0x0f, 0x7e, 0xc0]);
+    }
+
+    // movd xmm0, eax
+    fn movd_xmm0_eax(&mut self) {
+        self.text.extend_from_slice(&[0x66, 0x0f, 0x6e, 0xc0]);
+    }
+
+    // movq xmmN, rax
+    fn movq_xmm_rax(&mut self, xmm: u8) {
+        self.text
+            .extend_from_slice(&[0x66, 0x48, 0x0f, 0x6e, 0xc0 | (xmm << 3)]);
+    }
+
+    // movd xmmN, eax; cvtss2sd xmmN, xmmN
+    fn cvt_f32_rax_to_f64_xmm(&mut self, xmm: u8) {
+        self.text
+            .extend_from_slice(&[0x66, 0x0f, 0x6e, 0xc0 | (xmm << 3)]);
+        self.text
+            .extend_from_slice(&[0xf3, 0x0f, 0x5a, 0xc0 | (xmm << 3) | xmm]);
+    }
+
+    // Integer in rax to the bits of an f64 (f32 when !double) in rax. The
+    // value is first widened to 64 bits by its own signedness; cvtsi2sd only
+    // reads signed operands, so a u64 with the top bit set is halved (keeping
+    // the low bit for rounding), converted and doubled.
+    fn convert_int_to_float(&mut self, from: Option<IntType>, double: bool) {
+        let prefix = if double { 0xf2 } else { 0xf3 };
+        if let Some(int_type) = from {
+            self.wrap_rax(int_type);
+        }
+        if from.is_some_and(|t| t.bits >= 64 && !t.signed) {
+            // test rax, rax; js big
+            self.text.extend_from_slice(&[0x48, 0x85, 0xc0, 0x78, 0x07]);
+            // cvtsi2sd xmm0, rax; jmp done
+            self.text
+                .extend_from_slice(&[prefix, 0x48, 0x0f, 0x2a, 0xc0, 0xeb, 0x15]);
+            // big: mov rcx, rax; shr rcx, 1; and eax, 1; or rcx, rax
+            self.text.extend_from_slice(&[
+                0x48, 0x89, 0xc1, 0x48, 0xd1, 0xe9, 0x83, 0xe0, 0x01, 0x48, 0x09, 0xc1,
+            ]);
+            // cvtsi2sd xmm0, rcx; addsd xmm0, xmm0
+            self.text
+                .extend_from_slice(&[prefix, 0x48, 0x0f, 0x2a, 0xc1, prefix, 0x0f, 0x58, 0xc0]);
+        } else {
+            // cvtsi2sd xmm0, rax (cvtsi2ss for f32)
+            self.text
+                .extend_from_slice(&[prefix, 0x48, 0x0f, 0x2a, 0xc0]);
+        }
+        if double {
+            self.movq_rax_xmm0();
+        } else {
+            self.movd_eax_xmm0();
+        }
+    }
+
+    // Float bits in rax to a 64-bit integer in rax, rounding toward zero
+    fn truncate_float_to_rax(&mut self, from: &Type) {
+        if *from == Type::F64 {
+            self.movq_xmm_rax(0);
+            // cvttsd2si rax, xmm0
+            self.text.extend_from_slice(&[0xf2, 0x48, 0x0f, 0x2c, 0xc0]);
+        } else {
+            self.movd_xmm0_eax();
+            // cvttss2si rax, xmm0
+            self.text.extend_from_slice(&[0xf3, 0x48, 0x0f, 0x2c, 0xc0]);
+        }
+    }
+
+    // Float bits in rax to the bits of an f64 (f32 when !double)
+    fn convert_float(&mut self, from: &Type, double: bool) {
+        match (*from == Type::F64, double) {
+            (false, true) => {
+                self.movd_xmm0_eax();
+                // cvtss2sd xmm0, xmm0
+                self.text.extend_from_slice(&[0xf3, 0x0f, 0x5a, 0xc0]);
+                self.movq_rax_xmm0();
+            }
+            (true, false) => {
+                self.movq_xmm_rax(0);
+                // cvtsd2ss xmm0, xmm0
+                self.text.extend_from_slice(&[0xf2, 0x0f, 0x5a, 0xc0]);
+                self.movd_eax_xmm0();
+            }
+            _ => {}
+        }
+    }
+
+    // Literals and plain variable reads: cheap, cannot fault, and only touch
+    // rax and r10, so both arms can be evaluated unconditionally.
+    fn is_simple_value(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Literal(Literal::Int(..) | Literal::Bool(_) | Literal::Char(_))
+                | Expr::Identifier(..)
+        )
+    }
+
+    fn generate_if_expr(&mut self, if_expr: &IfExpr) -> Result<EntropyState, CodegenError> {
+        if Self::is_simple_value(&if_expr.then_expr) && Self::is_simple_value(&if_expr.else_expr) {
+            // Branchless: rcx = condition, rdx = else value, rax = then value
+            self.generate_expr(&if_expr.condition)?;
+            self.mov_rcx_rax();
+            let else_state = self.generate_expr(&if_expr.else_expr)?;
+            self.mov_rdx_rax();
+            let then_state = self.generate_expr(&if_expr.then_expr)?;
+            self.test_rcx_rcx();
+            self.cmove_rax_rdx();
+            return Ok(self.propagate_entropy(then_state, else_state));
+        }
+
+        let to_else = self.jump_if_false(&if_expr.condition)?;
+        let then_state = self.generate_expr(&if_expr.then_expr)?;
+        let to_end = self.jmp_rel32();
+        self.patch_rel32(to_else);
+        let else_state = self.generate_expr(&if_expr.else_expr)?;
+        self.patch_rel32(to_end);
+        Ok(self.propagate_entropy(then_state, else_state))
+    }
+
+    // Emit a jump taken when `condition` is false and return its rel32 field
+    // for patching. An integer comparison branches on the flags of its own
+    // cmp instead of materializing a bool with setcc and testing it.
+    fn jump_if_false(&mut self, condition: &Expr) -> Result<usize, CodegenError> {
+        if let Expr::Binary(
+            op @ (BinaryOp::Eq
+            | BinaryOp::Neq
+            | BinaryOp::Lt
+            | BinaryOp::Gt
+            | BinaryOp::LtEq
+            | BinaryOp::GtEq),
+            left,
+            right,
+            _,
+        ) = condition
+        {
+            if self.float_type(left).is_some() {
+                // An unordered result needs two flags; let the setcc
+                // sequence combine them
+                self.generate_expr(condition)?;
+                self.test_rax_rax();
+                return Ok(self.jz_rel32());
+            }
+            self.generate_expr(left)?;
+            self.push_rax();
+            self.generate_expr(right)?;
+            self.mov_rcx_rax();
+            self.pop_rax();
+            let int_type = self.expr_int_type(left).unwrap_or(IntType {
+                bits: 64,
+                signed: true,
+            });
+            self.cmp_rax_rcx(int_type);
+            // Flipping the low bit of a condition code negates it
+            return Ok(self.jcc_rel32(Self::condition_code(op, int_type.signed) ^ 1));
+        }
+        self.generate_expr(condition)?;
+        self.test_rax_rax();
+        Ok(self.jz_rel32())
+    }
+
+    // `&&` and `||` skip the right operand once the left one decides the
+    // result, which is then already in rax; either way rax ends as 0 or 1
+    fn generate_logical(
+        &mut self,
+        op: &BinaryOp,
+        left: &Expr,
+        right: &Expr,
+    ) -> Result<EntropyState, CodegenError> {
+        let left_state = self.generate_expr(left)?;
+        self.test_rax_rax();
+        let skip = match op {
+            BinaryOp::LogicalAnd => self.jz_rel32(),
+            _ => self.jcc_rel32(0x5),
+        };
+        let right_state = self.generate_expr(right)?;
+        self.patch_rel32(skip);
+        // test rax, rax; setne al; movzx eax, al
+        self.test_rax_rax();
+        self.text.extend_from_slice(&[0x0f, 0x95, 0xc0]);
+        self.text.extend_from_slice(&[0x0f, 0xb6, 0xc0]);
+        Ok(self.propagate_entropy(left_state, right_state))
+    }
+
+    fn generate_loop_expr(&mut self, body: &[Stmt]) -> Result<EntropyState, CodegenError> {
+        let start = self.text.len();
+        self.loops.push(LoopLabels {
+            continue_target: Some(start),
+            continues: Vec::new(),
+            stack_depth: self.stack_depth,
+            defer_depth: self.defers.len(),
+            exits: Vec::new(),
+            state: None,
+        });
+        self.generate_block(body)?;
+        let back = self.jmp_rel32();
+        self.patch_rel32_to(back, start);
+
+        let labels = self.loops.pop().expect("loop labels pushed above");
+        for at in labels.exits {
+            self.patch_rel32(at);
+        }
+        Ok(labels.state.unwrap_or(EntropyState::Initialized))
+    }
+
+    // Condition, then-branch and, with an else, a jump over the else-branch.
+    // Each jump is emitted with a zero rel32 and patched once its target is
+    // known.
+    fn generate_if_stmt(&mut self, if_stmt: &IfStmt) -> Result<(), CodegenError> {
+        let to_else = self.jump_if_false(&if_stmt.condition)?;
+        self.generate_block(&if_stmt.then_branch)?;
+        match &if_stmt.else_branch {
+            Some(else_branch) => {
+                let to_end = self.jmp_rel32();
+                self.patch_rel32(to_else);
+                self.generate_block(else_branch)?;
+                self.patch_rel32(to_end);
+            }
+            None => self.patch_rel32(to_else),
+        }
+        Ok(())
+    }
+
+    // The condition is checked at the top, which is also where `continue`
+    // goes; its exit jump is patched along with those of `break`
+    fn generate_while(&mut self, w: &WhileStmt) -> Result<(), CodegenError> {
+        let top = self.text.len();
+        let exit = self.jump_if_false(&w.condition)?;
+        self.loops.push(LoopLabels {
+            continue_target: Some(top),
+            continues: Vec::new(),
+            stack_depth: self.stack_depth,
+            defer_depth: self.defers.len(),
+            exits: vec![exit],
+            state: None,
+        });
+        self.generate_block(&w.body)?;
+        let back = self.jmp_rel32();
+        self.patch_rel32_to(back, top);
+
+        let labels = self.loops.pop().expect("loop labels pushed above");
+        for at in labels.exits {
+            self.patch_rel32(at);
+        }
+        Ok(())
+    }
+
+    // init, then a condition check on every iteration, body, update and a
+    // jump back to the check. `continue` lands on the update so the loop
+    // variable still advances.
+    fn generate_for(&mut self, f: &ForStmt) -> Result<(), CodegenError> {
+        // The init statement's defers run once the whole loop is done
+        let depth = self.defers.len();
+        self.defers.push(Vec::new());
+        self.generate_stmt(&f.init)?;
+
+        let check = self.text.len();
+        let exit = self.jump_if_false(&f.condition)?;
+        self.loops.push(LoopLabels {
+            continue_target: None,
+            continues: Vec::new(),
+            stack_depth: self.stack_depth,
+            defer_depth: self.defers.len(),
+            exits: vec![exit],
+            state: None,
+        });
+        self.generate_block(&f.body)?;
+
+        let labels = self.loops.pop().expect("loop labels pushed above");
+        for at in labels.continues {
+            self.patch_rel32(at);
+        }
+        self.generate_stmt(&f.update)?;
+        let back = self.jmp_rel32();
+        self.patch_rel32_to(back, check);
+        for at in labels.exits {
+            self.patch_rel32(at);
+        }
+
+        self.generate_defers_from(depth)?;
+        self.defers.truncate(depth);
+        Ok(())
+    }
+
+    // The break value is left in rax, which is where the loop expression's
+    // result is read from once the exit jump lands.
+    fn generate_break(&mut self, value: Option<&Expr>) -> Result<(), CodegenError> {
+        if self.loops.is_empty() {
+            return Ok(());
+        }
+        let state = match value {
+            Some(value) => self.generate_expr(value)?,
+            None => EntropyState::Initialized,
+        };
+        let merged = match self.loops.last().and_then(|l| l.state.clone()) {
+            Some(prev) => self.propagate_entropy(prev, state),
+            None => state,
+        };
+        if let Some((stack_depth, defer_depth)) =
+            self.loops.last().map(|l| (l.stack_depth, l.defer_depth))
+        {
+            self.generate_defers_from(defer_depth)?;
+            self.drop_temporaries_to(stack_depth);
+        }
+        let at = self.jmp_rel32();
+        if let Some(labels) = self.loops.last_mut() {
+            labels.exits.push(at);
+            labels.state = Some(merged);
+        }
+        Ok(())
+    }
+
+    fn add_rax_immediate(&mut self, val: u32) {
+        // add rax, imm32
+        self.text.extend_from_slice(&[0x48, 0x05]);
+        self.text.extend_from_slice(&val.to_le_bytes());
+    }
+
+    fn test_rax_rax(&mut self) {
+        self.text.extend_from_slice(&[0x48, 0x85, 0xc0]);
+    }
+
+    fn test_rcx_rcx(&mut self) {
+        self.text.extend_from_slice(&[0x48, 0x85, 0xc9]);
+    }
+
+    fn mov_rdx_rax(&mut self) {
+        self.text.extend_from_slice(&[0x48, 0x89, 0xc2]);
+    }
+
+    fn cmove_rax_rdx(&mut self) {
+        self.text.extend_from_slice(&[0x48, 0x0f, 0x44, 0xc2]);
+    }
+
+    // Emit `jz rel32` with a zero displacement; returns the field to patch
+    fn jz_rel32(&mut self) -> usize {
+        self.jcc_rel32(0x4)
+    }
+
+    // jcc rel32 for a condition code (the low nibble of the 0x0f 0x8x opcode)
+    fn jcc_rel32(&mut self, cc: u8) -> usize {
+        self.text.extend_from_slice(&[0x0f, 0x80 | cc]);
+        let at = self.text.len();
+        self.text.extend_from_slice(&[0u8; 4]);
+        at
+    }
+
+    fn jmp_rel32(&mut self) -> usize {
+        self.text.push(0xe9);
+        let at = self.text.len();
+        self.text.extend_from_slice(&[0u8; 4]);
+        at
+    }
+
+    // Point a rel32 field at the current end of text
+    fn patch_rel32(&mut self, at: usize) {
+        self.patch_rel32_to(at, self.text.len());
+    }
+
+    fn patch_rel32_to(&mut self, at: usize, target: usize) {
+        let rel = (target as i64 - (at as i64 + 4)) as i32;
+        self.text[at..at + 4].copy_from_slice(&rel.to_le_bytes());
+    }
+
     fn neg_rax(&mut self) {
         self.text.push(0x48);
         self.text.push(0xf7);
@@ -770,29 +2220,203 @@ impl CodeGenerator {
         self.text.push(0xd0);
     }
 
-    fn add_rax_rdx(&mut self) {
+    // Compare rax with rcx at the operand width and leave the bool result
+    // (0 or 1) zero-extended in rax
+    fn compare_rax_rcx(&mut self, op: &BinaryOp, int_type: IntType) {
+        self.cmp_rax_rcx(int_type);
+        let setcc = 0x90 | Self::condition_code(op, int_type.signed);
+        // setcc al; movzx eax, al
+        self.text.extend_from_slice(&[0x0f, setcc, 0xc0]);
+        self.text.extend_from_slice(&[0x0f, 0xb6, 0xc0]);
+    }
+
+    fn cmp_rax_rcx(&mut self, int_type: IntType) {
+        match int_type.bits {
+            // cmp al, cl / cmp ax, cx / cmp eax, ecx
+            8 => self.text.extend_from_slice(&[0x38, 0xc8]),
+            16 => self.text.extend_from_slice(&[0x66, 0x39, 0xc8]),
+            32 => self.text.extend_from_slice(&[0x39, 0xc8]),
+            // cmp rax, rcx
+            _ => self.text.extend_from_slice(&[0x48, 0x39, 0xc8]),
+        }
+    }
+
+    // rax op rcx on the IEEE-754 bits of two floats of type `ty`, through
+    // xmm0 and xmm1. Arithmetic leaves the result's bits in rax; a comparison
+    // leaves 0 or 1. ucomis reports unordered (a NaN operand) as ZF = PF =
+    // CF = 1, so == also requires PF clear, != accepts PF set, and < and <=
+    // swap the operands to use the CF-based conditions, which are false
+    // when unordered.
+    fn float_binary(&mut self, op: &BinaryOp, ty: &Type) -> Result<(), CodegenError> {
+        let double = *ty == Type::F64;
+        // movq xmm0, rax; movq xmm1, rcx (movd for f32)
+        let rex: &[u8] = if double { &[0x48] } else { &[] };
+        for modrm in [0xc0, 0xc9] {
+            self.text.push(0x66);
+            self.text.extend_from_slice(rex);
+            self.text.extend_from_slice(&[0x0f, 0x6e, modrm]);
+        }
+        let arith = match op {
+            BinaryOp::Add => Some(0x58),
+            BinaryOp::Sub => Some(0x5c),
+            BinaryOp::Mul => Some(0x59),
+            BinaryOp::Div => Some(0x5e),
+            _ => None,
+        };
+        if let Some(opcode) = arith {
+            // addsd/subsd/mulsd/divsd xmm0, xmm1 (ss for f32)
+            self.text.push(if double { 0xf2 } else { 0xf3 });
+            self.text.extend_from_slice(&[0x0f, opcode, 0xc1]);
+            // movq rax, xmm0 (movd for f32)
+            self.text.push(0x66);
+            self.text.extend_from_slice(rex);
+            self.text.extend_from_slice(&[0x0f, 0x7e, 0xc0]);
+            return Ok(());
+        }
+        let (swap, setcc) = match op {
+            BinaryOp::Eq | BinaryOp::Neq => (false, 0),
+            BinaryOp::Gt => (false, 0x97),
+            BinaryOp::GtEq => (false, 0x93),
+            BinaryOp::Lt => (true, 0x97),
+            BinaryOp::LtEq => (true, 0x93),
+            _ => {
+                return Err(CodegenError {
+                    message: format!("Operator {:?} is not supported on floats", op),
+                    span: None,
+                });
+            }
+        };
+        // ucomisd xmm0, xmm1, or xmm1, xmm0 when swapped (ucomiss for f32)
+        if double {
+            self.text.push(0x66);
+        }
+        self.text
+            .extend_from_slice(&[0x0f, 0x2e, if swap { 0xc8 } else { 0xc1 }]);
+        match op {
+            // sete al; setnp cl; and al, cl
+            BinaryOp::Eq => self
+                .text
+                .extend_from_slice(&[0x0f, 0x94, 0xc0, 0x0f, 0x9b, 0xc1, 0x20, 0xc8]),
+            // setne al; setp cl; or al, cl
+            BinaryOp::Neq => self
+                .text
+                .extend_from_slice(&[0x0f, 0x95, 0xc0, 0x0f, 0x9a, 0xc1, 0x08, 0xc8]),
+            // seta al / setae al
+            _ => self.text.extend_from_slice(&[0x0f, setcc, 0xc0]),
+        }
+        // movzx eax, al
+        self.text.extend_from_slice(&[0x0f, 0xb6, 0xc0]);
+        Ok(())
+    }
+
+    // x86 condition code (shared by setcc and jcc) under which `op` holds
+    // after cmp rax, rcx
+    fn condition_code(op: &BinaryOp, signed: bool) -> u8 {
+        match (op, signed) {
+            (BinaryOp::Eq, _) => 0x4,
+            (BinaryOp::Neq, _) => 0x5,
+            (BinaryOp::Lt, true) => 0xc,
+            (BinaryOp::Lt, false) => 0x2,
+            (BinaryOp::Gt, true) => 0xf,
+            (BinaryOp::Gt, false) => 0x7,
+            (BinaryOp::LtEq, true) => 0xe,
+            (BinaryOp::LtEq, false) => 0x6,
+            (BinaryOp::GtEq, true) => 0xd,
+            _ => 0x3,
+        }
+    }
+
+    fn add_rax_rcx(&mut self) {
         self.text.push(0x48);
         self.text.push(0x01);
-        self.text.push(0xc2);
+        self.text.push(0xc8);
     }
 
-    fn sub_rax_rdx(&mut self) {
+    fn sub_rax_rcx(&mut self) {
         self.text.push(0x48);
         self.text.push(0x29);
-        self.text.push(0xc2);
+        self.text.push(0xc8);
     }
 
-    fn imul_rax_rdx(&mut self) {
+    fn imul_rax_rcx(&mut self) {
         self.text.push(0x48);
         self.text.push(0x0f);
         self.text.push(0xaf);
-        self.text.push(0xc2);
+        self.text.push(0xc1);
+    }
+
+    // Arithmetic at the operand's own width so OF/CF reflect overflow of
+    // that type, followed by jo (signed) or jc (unsigned) to the trap
+    fn checked_arith(&mut self, op: &BinaryOp, int_type: IntType) {
+        let bytes: &[u8] = match (op, int_type.bits, int_type.signed) {
+            (BinaryOp::Add, 8, _) => &[0x00, 0xc8],
+            (BinaryOp::Add, 16, _) => &[0x66, 0x01, 0xc8],
+            (BinaryOp::Add, 32, _) => &[0x01, 0xc8],
+            (BinaryOp::Add, _, _) => &[0x48, 0x01, 0xc8],
+            (BinaryOp::Sub, 8, _) => &[0x28, 0xc8],
+            (BinaryOp::Sub, 16, _) => &[0x66, 0x29, 0xc8],
+            (BinaryOp::Sub, 32, _) => &[0x29, 0xc8],
+            (BinaryOp::Sub, _, _) => &[0x48, 0x29, 0xc8],
+            // imul cl / mul cl
+            (_, 8, true) => &[0xf6, 0xe9],
+            (_, 8, false) => &[0xf6, 0xe1],
+            // imul ax, cx / mul cx
+            (_, 16, true) => &[0x66, 0x0f, 0xaf, 0xc1],
+            (_, 16, false) => &[0x66, 0xf7, 0xe1],
+            // imul eax, ecx / mul ecx
+            (_, 32, true) => &[0x0f, 0xaf, 0xc1],
+            (_, 32, false) => &[0xf7, 0xe1],
+            // imul rax, rcx / mul rcx
+            (_, _, true) => &[0x48, 0x0f, 0xaf, 0xc1],
+            (_, _, false) => &[0x48, 0xf7, 0xe1],
+        };
+        self.text.extend_from_slice(bytes);
+        // Multiplication reports overflow through both OF and CF
+        let jcc = if int_type.signed || matches!(op, BinaryOp::Mul) {
+            0x80 // jo rel32
+        } else {
+            0x82 // jc rel32
+        };
+        self.text.push(0x0f);
+        self.text.push(jcc);
+        self.overflow_jumps.push(self.text.len());
+        self.text.extend_from_slice(&[0u8; 4]);
     }
 
-    fn idiv_rdx(&mut self) {
-        self.text.push(0x48);
-        self.text.push(0xf7);
-        self.text.push(0xfe);
+    // Single shared trap for every checked operation, placed after all code
+    fn emit_overflow_trap(&mut self) {
+        if self.overflow_jumps.is_empty() {
+            return;
+        }
+        let trap = self.text.len();
+        // ud2
+        self.text.push(0x0f);
+        self.text.push(0x0b);
+        for &at in &self.overflow_jumps {
+            let rel = (trap as i64 - (at as i64 + 4)) as i32;
+            self.text[at..at + 4].copy_from_slice(&rel.to_le_bytes());
+        }
+    }
+
+    // rax / rcx, leaving the quotient (or with `remainder` the remainder) in
+    // rax. Narrow operands are widened by their signedness first so a 64-bit
+    // divide gives the same result as one at their own width.
+    fn divide_rax_rcx(&mut self, int_type: IntType, remainder: bool) {
+        self.extend_rax(int_type);
+        self.extend_rcx(int_type);
+        if int_type.signed {
+            // cqo; idiv rcx
+            self.text.extend_from_slice(&[0x48, 0x99]);
+            self.text.extend_from_slice(&[0x48, 0xf7, 0xf9]);
+        } else {
+            // xor edx, edx; div rcx
+            self.text.extend_from_slice(&[0x31, 0xd2]);
+            self.text.extend_from_slice(&[0x48, 0xf7, 0xf1]);
+        }
+        if remainder {
+            // mov rax, rdx
+            self.text.extend_from_slice(&[0x48, 0x89, 0xd0]);
+        }
     }
 
     fn shl_rax_cl(&mut self) {
@@ -801,62 +2425,648 @@ impl CodeGenerator {
         self.text.push(0xe0);
     }
 
-    fn shr_rax_cl(&mut self) {
-        self.text.push(0x48);
-        self.text.push(0xd3);
-        self.text.push(0xe8);
+    fn shr_rax_cl(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0xd3);
+        self.text.push(0xe8);
+    }
+
+    fn sar_rax_cl(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0xd3);
+        self.text.push(0xf8);
+    }
+
+    // Literal counts are range-checked by the typechecker; anything computed
+    // at runtime is constrained to the operand width so it can't over-shift
+    fn mask_shift_count(&mut self, count: &Expr, int_type: IntType) {
+        if matches!(count, Expr::Literal(Literal::Int(..))) {
+            return;
+        }
+        if int_type.bits < 64 && int_type.bits.is_power_of_two() {
+            // and cl, bits-1
+            self.text.push(0x80);
+            self.text.push(0xe1);
+            self.text.push(int_type.bits - 1);
+        }
+    }
+
+    fn push_rax(&mut self) {
+        self.text.push(0x50);
+        self.stack_depth += 1;
+    }
+
+    fn pop_rax(&mut self) {
+        self.text.push(0x58);
+        self.stack_depth -= 1;
+    }
+
+    fn pop_rcx(&mut self) {
+        self.text.push(0x59);
+        self.stack_depth -= 1;
+    }
+
+    fn pop_rdx(&mut self) {
+        self.text.push(0x5a);
+        self.stack_depth -= 1;
+    }
+
+    fn mov_rcx_rax(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0x89);
+        self.text.push(0xc1);
+    }
+
+    // Widen a narrow value in rax to 64 bits according to its signedness
+    fn extend_rax(&mut self, int_type: IntType) {
+        match (int_type.bits, int_type.signed) {
+            // movsx rax, al / movzx rax, al
+            (8, true) => self.text.extend_from_slice(&[0x48, 0x0f, 0xbe, 0xc0]),
+            (8, false) => self.text.extend_from_slice(&[0x48, 0x0f, 0xb6, 0xc0]),
+            // movsx rax, ax / movzx rax, ax
+            (16, true) => self.text.extend_from_slice(&[0x48, 0x0f, 0xbf, 0xc0]),
+            (16, false) => self.text.extend_from_slice(&[0x48, 0x0f, 0xb7, 0xc0]),
+            // movsxd rax, eax / mov eax, eax
+            (32, true) => self.text.extend_from_slice(&[0x48, 0x63, 0xc0]),
+            (32, false) => self.text.extend_from_slice(&[0x89, 0xc0]),
+            _ => {}
+        }
+    }
+
+    fn extend_rcx(&mut self, int_type: IntType) {
+        match (int_type.bits, int_type.signed) {
+            // movsx rcx, cl / movzx rcx, cl
+            (8, true) => self.text.extend_from_slice(&[0x48, 0x0f, 0xbe, 0xc9]),
+            (8, false) => self.text.extend_from_slice(&[0x48, 0x0f, 0xb6, 0xc9]),
+            // movsx rcx, cx / movzx rcx, cx
+            (16, true) => self.text.extend_from_slice(&[0x48, 0x0f, 0xbf, 0xc9]),
+            (16, false) => self.text.extend_from_slice(&[0x48, 0x0f, 0xb7, 0xc9]),
+            // movsxd rcx, ecx / mov ecx, ecx
+            (32, true) => self.text.extend_from_slice(&[0x48, 0x63, 0xc9]),
+            (32, false) => self.text.extend_from_slice(&[0x89, 0xc9]),
+            _ => {}
+        }
+    }
+
+    // Load through the pointer in rax, widening to 64 bits by the pointee type
+    fn load_rax_from_rax(&mut self, pointee: Option<&Type>) {
+        // A bool occupies one byte holding 0 or 1
+        let int_type = match pointee {
+            Some(Type::Bool) => Some(IntType {
+                bits: 8,
+                signed: false,
+            }),
+            _ => pointee.and_then(IntType::from_aura_type),
+        };
+        let bytes: &[u8] = match int_type.map(|t| (t.bits, t.signed)) {
+            // movsx rax, byte [rax] / movzx rax, byte [rax]
+            Some((8, true)) => &[0x48, 0x0f, 0xbe, 0x00],
+            Some((8, false)) => &[0x48, 0x0f, 0xb6, 0x00],
+            // movsx rax, word [rax] / movzx rax, word [rax]
+            Some((16, true)) => &[0x48, 0x0f, 0xbf, 0x00],
+            Some((16, false)) => &[0x48, 0x0f, 0xb7, 0x00],
+            // movsxd rax, dword [rax] / mov eax, dword [rax]
+            Some((32, true)) => &[0x48, 0x63, 0x00],
+            Some((32, false)) => &[0x8b, 0x00],
+            // mov rax, [rax]
+            _ => &[0x48, 0x8b, 0x00],
+        };
+        self.text.extend_from_slice(bytes);
+    }
+
+    // Store rax through the pointer in rcx, truncated to the pointee width
+    fn store_rax_to_rcx(&mut self, pointee: Option<&Type>) {
+        let bits = match pointee {
+            Some(Type::Bool) => Some(8),
+            Some(Type::F32) => Some(32),
+            _ => pointee.and_then(IntType::from_aura_type).map(|t| t.bits),
+        };
+        let bytes: &[u8] = match bits {
+            // mov [rcx], al
+            Some(8) => &[0x88, 0x01],
+            // mov [rcx], ax
+            Some(16) => &[0x66, 0x89, 0x01],
+            // mov [rcx], eax
+            Some(32) => &[0x89, 0x01],
+            // mov [rcx], rax
+            _ => &[0x48, 0x89, 0x01],
+        };
+        self.text.extend_from_slice(bytes);
+    }
+
+    // Declared type of an expression where codegen can see it directly
+    fn static_type(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Identifier(name, _) => self.variable_types.get(name).cloned(),
+            Expr::Cast(_, ty) => Some(ty.clone()),
+            // Pointer arithmetic keeps the pointer's type, so `(p + 1) - 1`
+            // scales both steps
+            Expr::Binary(BinaryOp::Add | BinaryOp::Sub, left, right, _) => {
+                match (self.static_type(left), self.static_type(right)) {
+                    (Some(l), Some(r)) if l.is_pointer() && r.is_pointer() => Some(Type::Isize),
+                    (Some(l), _) if l.is_pointer() => Some(l),
+                    (_, Some(r)) if r.is_pointer() => Some(r),
+                    _ => None,
+                }
+            }
+            Expr::Call(callee, _) => match &**callee {
+                Expr::Identifier(name, _) => self.function_return_type(name).cloned(),
+                _ => None,
+            },
+            Expr::ArrayLit(elements) => {
+                let first = elements.first()?;
+                let elem = literal_type(first).or_else(|| self.static_type(first))?;
+                Some(Type::Array(elements.len(), Box::new(elem)))
+            }
+            Expr::Field(base, field) => {
+                let base_ty = self.static_type(base)?;
+                self.field_info(&base_ty, field).map(|(_, ty)| ty)
+            }
+            Expr::Index(base, _) => match self.static_type(base)? {
+                Type::Array(_, elem)
+                | Type::Ptr(elem)
+                | Type::MutPtr(elem)
+                | Type::ConstPtr(elem) => Some(*elem),
+                _ => None,
+            },
+            Expr::PtrField(ptr, field) => match self.static_type(ptr)? {
+                Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t) => {
+                    self.field_info(&t, field).map(|(_, ty)| ty)
+                }
+                _ => None,
+            },
+            Expr::Unary(UnaryOp::Deref, ptr) => self.pointee_type(ptr),
+            _ => None,
+        }
+    }
+
+    // Best-effort static integer type of an expression, used where codegen
+    // has to pick between signed and unsigned instructions
+    fn expr_int_type(&self, expr: &Expr) -> Option<IntType> {
+        match expr {
+            Expr::Literal(Literal::Int(_, suffix)) => IntType::from_suffix(suffix),
+            Expr::Literal(Literal::Char(_)) => Some(IntType {
+                bits: 8,
+                signed: false,
+            }),
+            Expr::Identifier(name, _) => self
+                .variable_types
+                .get(name)
+                .and_then(IntType::from_aura_type),
+            Expr::Cast(_, ty) => IntType::from_aura_type(ty),
+            Expr::Unary(UnaryOp::Deref, operand) => match self.static_type(operand) {
+                Some(Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t)) => {
+                    IntType::from_aura_type(&t)
+                }
+                _ => None,
+            },
+            Expr::Unary(_, operand) => self.expr_int_type(operand),
+            Expr::Field(..) | Expr::PtrField(..) => self
+                .static_type(expr)
+                .as_ref()
+                .and_then(IntType::from_aura_type),
+            Expr::Binary(
+                BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Mod
+                | BinaryOp::LShift
+                | BinaryOp::RShift
+                | BinaryOp::BitAnd
+                | BinaryOp::BitOr
+                | BinaryOp::BitXor,
+                left,
+                ..,
+            ) => self.expr_int_type(left),
+            _ => None,
+        }
+    }
+
+    fn and_rax_rcx(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0x21);
+        self.text.push(0xc8);
+    }
+
+    fn or_rax_rcx(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0x09);
+        self.text.push(0xc8);
+    }
+
+    fn xor_rax_rcx(&mut self) {
+        self.text.push(0x48);
+        self.text.push(0x31);
+        self.text.push(0xc8);
+    }
+
+    fn mov_rax_immediate(&mut self, val: u64) {
+        match i32::try_from(val as i64) {
+            Ok(imm) => {
+                // mov rax, imm32 (sign-extended)
+                self.text.extend_from_slice(&[0x48, 0xc7, 0xc0]);
+                self.text.extend_from_slice(&imm.to_le_bytes());
+            }
+            Err(_) => self.movabs_rax(val),
+        }
+    }
+
+    // Always the full imm64 form, for immediates a relocation patches
+    fn movabs_rax(&mut self, val: u64) {
+        self.text.push(0x48);
+        self.text.push(0xb8);
+        self.text.extend_from_slice(&val.to_le_bytes());
+    }
+
+    fn mov_rax_from_data(&mut self, offset: u64) {
+        self.movabs_rax(self.get_data_address(offset as usize));
+        self.reloc_data_address();
+        self.text.push(0x48);
+        self.text.push(0x8b);
+        self.text.push(0x00);
+    }
+
+    // rep stosb over [offset, offset + size) of .data with al = 0
+    fn zero_data(&mut self, offset: usize, size: usize) {
+        if size == 0 {
+            return;
+        }
+        self.mov_rdi_immediate(self.get_data_address(offset));
+        self.reloc_data_address();
+        self.mov_rcx_immediate(size as u64);
+        self.xor_rax_rax();
+        self.text.push(0xf3);
+        self.text.push(0xaa);
+    }
+
+    // Reserve the next `size` bytes of the frame, in whole 8-byte words
+    fn alloc_frame_slot(&mut self, size: usize) -> Slot {
+        self.frame_size += size.max(8).next_multiple_of(8);
+        Slot::Frame(self.frame_size)
+    }
+
+    // ModRM and disp32 of [rbp - offset] with the given reg field
+    fn rbp_operand(&mut self, reg: u8, offset: usize) {
+        self.text.push(0x85 | (reg << 3));
+        self.text
+            .extend_from_slice(&(-(offset as i32)).to_le_bytes());
+    }
+
+    fn load_slot(&mut self, slot: Slot) {
+        match slot {
+            Slot::Data(offset) => {
+                self.mov_r10_data_address(offset);
+                self.mov_rax_from_r10();
+            }
+            Slot::Frame(offset) => {
+                // mov rax, [rbp - offset]
+                self.text.extend_from_slice(&[0x48, 0x8b]);
+                self.rbp_operand(0, offset);
+            }
+        }
+    }
+
+    fn store_slot(&mut self, slot: Slot) {
+        match slot {
+            Slot::Data(offset) => {
+                self.mov_r10_data_address(offset);
+                self.mov_rax_to_r10_mem();
+            }
+            Slot::Frame(offset) => {
+                // mov [rbp - offset], rax
+                self.text.extend_from_slice(&[0x48, 0x89]);
+                self.rbp_operand(0, offset);
+            }
+        }
+    }
+
+    fn slot_address(&mut self, slot: Slot) {
+        match slot {
+            Slot::Data(offset) => self.mov_rax_data_address(offset),
+            Slot::Frame(offset) => {
+                // lea rax, [rbp - offset]
+                self.text.extend_from_slice(&[0x48, 0x8d]);
+                self.rbp_operand(0, offset);
+            }
+        }
+    }
+
+    fn zero_slot(&mut self, slot: Slot, size: usize) {
+        match slot {
+            Slot::Data(offset) => self.zero_data(offset, size),
+            Slot::Frame(_) if size == 0 => {}
+            Slot::Frame(offset) => {
+                // lea rdi, [rbp - offset]; then rep stosb with al = 0
+                self.text.extend_from_slice(&[0x48, 0x8d]);
+                self.rbp_operand(7, offset);
+                self.mov_rcx_immediate(size as u64);
+                self.xor_rax_rax();
+                self.text.push(0xf3);
+                self.text.push(0xaa);
+            }
+        }
+    }
+
+    // `Color.Red`: the variant's backing integer, unless a variable shadows
+    // the enum's name
+    fn enum_variant_value(&self, expr: &Expr) -> Option<i64> {
+        let Expr::Field(base, variant) = expr else {
+            return None;
+        };
+        let Expr::Identifier(name, _) = &**base else {
+            return None;
+        };
+        if self.variables.contains_key(name) {
+            return None;
+        }
+        self.layouts
+            .enum_type(name)?
+            .variants
+            .iter()
+            .find(|v| v.name == *variant)?
+            .value
     }
 
-    fn and_rax_rdx(&mut self) {
-        self.text.push(0x48);
-        self.text.push(0x21);
-        self.text.push(0xc2);
+    fn field_path_offset(&self, ty: &Type, path: &str) -> Option<usize> {
+        self.layouts.field_path_offset(ty, path)
     }
 
-    fn or_rax_rdx(&mut self) {
-        self.text.push(0x48);
-        self.text.push(0x09);
-        self.text.push(0xc2);
+    fn field_info(&self, ty: &Type, field: &str) -> Option<(FieldSlot, Type)> {
+        self.layouts.field_info(ty, field)
     }
 
-    fn xor_rax_rdx(&mut self) {
-        self.text.push(0x48);
-        self.text.push(0x31);
-        self.text.push(0xc2);
+    // Load the bitfield whose storage unit rax points at, leaving the value
+    // zero- or sign-extended to 64 bits
+    fn load_bitfield(&mut self, bitfield: Bitfield, field_ty: &Type) {
+        self.load_rax_from_rax(Some(&bitfield.unit_type()));
+        if bitfield.shift != 0 {
+            // shr rax, imm8
+            self.text
+                .extend_from_slice(&[0x48, 0xc1, 0xe8, bitfield.shift]);
+        }
+        if let Some(int_type) = IntType::from_aura_type(field_ty) {
+            self.wrap_rax(int_type);
+        }
     }
 
-    fn mov_rax_immediate(&mut self, val: u64) {
-        self.text.push(0x48);
-        self.text.push(0xb8);
-        self.text.extend_from_slice(&val.to_le_bytes());
+    // Store rax into the bitfield whose storage unit rcx points at, keeping
+    // the other bits of the unit intact
+    fn store_bitfield(&mut self, bitfield: Bitfield) {
+        let mask = bitfield.mask();
+        self.mov_rdx_immediate(mask);
+        // and rax, rdx
+        self.text.extend_from_slice(&[0x48, 0x21, 0xd0]);
+        if bitfield.shift != 0 {
+            // shl rax, imm8
+            self.text
+                .extend_from_slice(&[0x48, 0xc1, 0xe0, bitfield.shift]);
+        }
+        self.push_rax();
+        // mov rax, rcx
+        self.text.extend_from_slice(&[0x48, 0x89, 0xc8]);
+        self.load_rax_from_rax(Some(&bitfield.unit_type()));
+        self.mov_rdx_immediate(!(mask << bitfield.shift));
+        // and rax, rdx
+        self.text.extend_from_slice(&[0x48, 0x21, 0xd0]);
+        // pop rdx; or rax, rdx
+        self.pop_rdx();
+        self.text.extend_from_slice(&[0x48, 0x09, 0xd0]);
+        self.store_rax_to_rcx(Some(&bitfield.unit_type()));
+    }
+
+    // Leaves the address of a struct field lvalue in rax and returns the
+    // field's type, plus its bit range if it is a bitfield. Packed fields
+    // may be unaligned, which x86 loads and stores tolerate.
+    fn generate_field_address(
+        &mut self,
+        expr: &Expr,
+    ) -> Result<(Type, Option<Bitfield>), CodegenError> {
+        let (struct_ty, field) = match expr {
+            Expr::Field(base, field) => {
+                let ty = match &**base {
+                    Expr::Identifier(name, span) => {
+                        let slot = *self.variables.get(name).ok_or_else(|| CodegenError {
+                            message: format!("Undefined variable: {}", name),
+                            span: Some(span.clone()),
+                        })?;
+                        self.slot_address(slot);
+                        self.variable_types.get(name).cloned()
+                    }
+                    Expr::Field(..) | Expr::PtrField(..) => {
+                        Some(self.generate_field_address(base)?.0)
+                    }
+                    Expr::Index(array, index) => Some(self.generate_index_address(array, index)?),
+                    Expr::Unary(UnaryOp::Deref, ptr) => {
+                        self.generate_expr(ptr)?;
+                        self.pointee_type(ptr)
+                    }
+                    _ => None,
+                };
+                (ty, field)
+            }
+            Expr::PtrField(ptr, field) => {
+                self.generate_expr(ptr)?;
+                let ty = match self.static_type(ptr) {
+                    Some(Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t)) => Some(*t),
+                    _ => None,
+                };
+                (ty, field)
+            }
+            _ => {
+                return Err(CodegenError {
+                    message: "Expected a field access".to_string(),
+                    span: None,
+                });
+            }
+        };
+        let (slot, field_ty) = struct_ty
+            .as_ref()
+            .and_then(|ty| self.field_info(ty, field))
+            .ok_or_else(|| CodegenError {
+                message: format!("Unsupported field access .{}", field),
+                span: None,
+            })?;
+        if slot.offset != 0 {
+            self.add_rax_immediate(slot.offset as u32);
+        }
+        Ok((field_ty, slot.bitfield))
+    }
+
+    // `&operand`: the address of a variable's slot, a field, an element, or
+    // for `&*p` simply the pointer
+    fn generate_address_of(&mut self, operand: &Expr) -> Result<(), CodegenError> {
+        match operand {
+            Expr::Identifier(name, span) => {
+                let slot = match self.variables.get(name) {
+                    Some(&slot) => slot,
+                    None => self
+                        .symbols
+                        .iter()
+                        .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                        .map(|s| Slot::Data(s.offset as usize))
+                        .ok_or_else(|| CodegenError {
+                            message: format!("Undefined variable: {}", name),
+                            span: Some(span.clone()),
+                        })?,
+                };
+                self.slot_address(slot);
+            }
+            Expr::Field(..) | Expr::PtrField(..) => {
+                if self.generate_field_address(operand)?.1.is_some() {
+                    return Err(CodegenError {
+                        message: "Cannot take the address of a bitfield".to_string(),
+                        span: None,
+                    });
+                }
+            }
+            Expr::Index(base, index) => {
+                self.generate_index_address(base, index)?;
+            }
+            Expr::Unary(UnaryOp::Deref, ptr) => {
+                self.generate_expr(ptr)?;
+            }
+            _ => {
+                return Err(CodegenError {
+                    message: "Cannot take the address of a temporary value".to_string(),
+                    span: None,
+                });
+            }
+        }
+        Ok(())
     }
 
-    fn mov_rax_from_mem(&mut self, addr: u64) {
-        self.mov_rax_immediate(addr);
-        self.text.push(0x48);
-        self.text.push(0x8b);
-        self.text.push(0x00);
+    // Leaves the address of `base[index]` in rax and returns the element
+    // type. Arrays are indexed in place; pointers are indexed from the
+    // address they hold.
+    fn generate_index_address(&mut self, base: &Expr, index: &Expr) -> Result<Type, CodegenError> {
+        let (elem_ty, is_array) = match self.static_type(base) {
+            Some(Type::Array(_, elem)) => (*elem, true),
+            Some(Type::Ptr(elem) | Type::MutPtr(elem) | Type::ConstPtr(elem)) => (*elem, false),
+            _ => {
+                return Err(CodegenError {
+                    message: "Indexing requires an array or pointer".to_string(),
+                    span: None,
+                });
+            }
+        };
+        self.generate_expr(index)?;
+        // The scaling below reads all of rax, whatever the index's width
+        if let Some(int_type) = self.expr_int_type(index) {
+            self.extend_rax(int_type);
+        }
+        let elem_size = self.type_size(&elem_ty);
+        if elem_size != 1 {
+            // imul rax, rax, imm32
+            self.text.extend_from_slice(&[0x48, 0x69, 0xc0]);
+            self.text
+                .extend_from_slice(&(elem_size as u32).to_le_bytes());
+        }
+        self.push_rax();
+        match base {
+            Expr::Identifier(name, _) if is_array => {
+                let slot = match self.variables.get(name) {
+                    Some(&slot) => slot,
+                    None => self
+                        .symbols
+                        .iter()
+                        .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                        .map(|s| Slot::Data(s.offset as usize))
+                        .ok_or_else(|| CodegenError {
+                            message: format!("Cannot index '{}' in place", name),
+                            span: None,
+                        })?,
+                };
+                self.slot_address(slot);
+            }
+            Expr::Field(..) | Expr::PtrField(..) if is_array => {
+                self.generate_field_address(base)?;
+            }
+            // a[i][j]: the row a[i] is itself addressed in place, so the
+            // outer index is already scaled by the whole inner array's size
+            Expr::Index(inner_base, inner_index) if is_array => {
+                self.generate_index_address(inner_base, inner_index)?;
+            }
+            _ if is_array => {
+                return Err(CodegenError {
+                    message: "Array index base must be a variable or field".to_string(),
+                    span: None,
+                });
+            }
+            _ => {
+                self.generate_expr(base)?;
+            }
+        }
+        // pop rcx; add rax, rcx
+        self.pop_rcx();
+        self.add_rax_rcx();
+        Ok(elem_ty)
     }
 
-    fn mov_rax_to_mem(&mut self, addr: u64) {
-        self.mov_rax_immediate(addr);
-        self.text.push(0x48);
-        self.text.push(0x89);
-        self.text.push(0x00);
+    fn pointee_type(&self, expr: &Expr) -> Option<Type> {
+        match self.static_type(expr)? {
+            Type::Ptr(t) | Type::MutPtr(t) | Type::ConstPtr(t) => Some(*t),
+            _ => None,
+        }
     }
 
-    fn mov_rax_to_mem_via_register(&mut self, addr: u64) {
-        self.mov_rax_immediate(addr);
-        self.text.push(0x48);
-        self.text.push(0x89);
-        self.text.push(0x00);
+    // With the left operand in rax and the right in rcx, `p + n` and `p - n`
+    // scale n by the element size first, and `p - q` divides the byte
+    // distance by it. False, with nothing emitted, when neither operand is
+    // a pointer.
+    fn pointer_arithmetic(&mut self, op: &BinaryOp, left: &Expr, right: &Expr) -> bool {
+        if !matches!(op, BinaryOp::Add | BinaryOp::Sub) {
+            return false;
+        }
+        let (lp, rp) = (self.pointee_type(left), self.pointee_type(right));
+        let elem_size = |this: &Self, ty: &Type| this.type_size(ty).max(1) as u32;
+        match (lp, rp) {
+            (Some(l), Some(_)) => {
+                self.sub_rax_rcx();
+                let size = elem_size(self, &l);
+                if size != 1 {
+                    self.mov_rcx_immediate(size as u64);
+                    self.divide_rax_rcx(
+                        IntType {
+                            bits: 64,
+                            signed: true,
+                        },
+                        false,
+                    );
+                }
+            }
+            (Some(l), None) => {
+                let size = elem_size(self, &l);
+                if size != 1 {
+                    // imul rcx, rcx, imm32
+                    self.text.extend_from_slice(&[0x48, 0x69, 0xc9]);
+                    self.text.extend_from_slice(&size.to_le_bytes());
+                }
+                match op {
+                    BinaryOp::Add => self.add_rax_rcx(),
+                    _ => self.sub_rax_rcx(),
+                }
+            }
+            (None, Some(r)) => {
+                let size = elem_size(self, &r);
+                if size != 1 {
+                    // imul rax, rax, imm32
+                    self.text.extend_from_slice(&[0x48, 0x69, 0xc0]);
+                    self.text.extend_from_slice(&size.to_le_bytes());
+                }
+                self.add_rax_rcx();
+            }
+            (None, None) => return false,
+        }
+        true
     }
 
-    fn mov_rax_from_mem_via_register(&mut self, addr: u64) {
-        self.mov_rax_immediate(addr);
-        self.text.push(0x48);
-        self.text.push(0x8b);
-        self.text.push(0x00);
+    fn type_size(&self, ty: &Type) -> usize {
+        self.layouts.size_of(ty)
+    }
+
+    fn type_align(&self, ty: &Type) -> usize {
+        self.layouts.align_of(ty)
     }
 
     fn xor_rax_rax(&mut self) {
@@ -869,88 +3079,260 @@ impl CodeGenerator {
         self.text.push(0xc3);
     }
 
+    // The named syscalls are sugar for the numbered form: each lays out its
+    // arguments and goes through `emit_syscall`, the same as `syscall(n, ..)`
     fn generate_syscall(&mut self, method_name: &str, args: &[Expr]) -> Result<(), CodegenError> {
         match method_name {
-            "write" => self.generate_write_syscall(args)?,
-            _ => {
-                return Err(CodegenError {
-                    message: format!("Unknown syscall method: {}", method_name),
-                })
+            "write" => self.generate_write_syscall(args),
+            "read" => self.generate_read_syscall(args),
+            "exit" => {
+                if args.len() != 1 {
+                    return Err(CodegenError {
+                        message: format!(
+                            "exit syscall takes exactly one argument (the status), got {}",
+                            args.len()
+                        ),
+                        span: None,
+                    });
+                }
+                self.emit_syscall(Some(60), args, None)
             }
+            _ => Err(CodegenError {
+                message: format!("Unknown syscall method: {}", method_name),
+                span: None,
+            }),
         }
-        Ok(())
-    }
-
-    fn get_data_address(&self, offset: usize) -> u64 {
-        // For now, assume a fixed data address.
-        // This will need to be updated with actual data segment address.
-        let addr = 0x600000000000 + offset as u64;
-        addr
     }
 
-    fn generate_write_syscall(&mut self, args: &[Expr]) -> Result<(), CodegenError> {
-        if args.is_empty() {
+    // Evaluates `args` left to right into rdi, rsi, rdx, r10, r8 and r9 (r10
+    // rather than rcx, which `syscall` clobbers) and issues the syscall.
+    // With no fixed `number` the first argument is the number, bound for rax.
+    // `buffer` is the position of an argument passed by address.
+    fn emit_syscall(
+        &mut self,
+        number: Option<u64>,
+        args: &[Expr],
+        buffer: Option<usize>,
+    ) -> Result<(), CodegenError> {
+        // rax for the number, then rdi, rsi, rdx, r10, r8, r9
+        const REGS: [u8; 7] = [0, 7, 6, 2, 10, 8, 9];
+        let regs = match number {
+            Some(_) => &REGS[1..],
+            None => &REGS[..],
+        };
+        if args.len() > regs.len() {
             return Err(CodegenError {
-                message: "write syscall requires at least one argument".to_string(),
+                message: format!("syscall takes at most 6 arguments, got {}", args.len()),
+                span: None,
             });
         }
-
-        let fd = if args.len() > 1 {
-            match &args[0] {
-                Expr::Literal(Literal::Int(val, _)) => *val as u64,
-                _ => 1,
+        for (i, arg) in args.iter().enumerate() {
+            if buffer == Some(i) {
+                self.generate_buffer_address(arg)?;
+            } else {
+                self.generate_syscall_arg(arg)?;
             }
-        } else {
-            1
-        };
+            if i + 1 < args.len() {
+                self.push_rax();
+            }
+        }
+        // The last value is still in rax; the others come back off the stack
+        if let Some((&last, rest)) = regs[..args.len()].split_last() {
+            if last != 0 {
+                self.mov_reg_rax(last);
+            }
+            for &reg in rest.iter().rev() {
+                self.pop_reg(reg);
+            }
+        }
+        if let Some(number) = number {
+            self.mov_rax_immediate(number);
+        }
+        self.syscall();
+        Ok(())
+    }
 
-        let data_arg_idx = if args.len() > 1 { 1 } else { 0 };
+    fn mov_rax_data_address(&mut self, offset: usize) {
+        self.movabs_rax(self.get_data_address(offset));
+        self.reloc_data_address();
+    }
 
-        match &args[data_arg_idx] {
-            Expr::Literal(Literal::String(bytes)) => {
-                let offset = self.data.len();
-                self.data.extend_from_slice(bytes);
-                let len = bytes.len() as u64;
-                let data_addr = self.get_data_address(offset);
+    fn get_data_address(&self, offset: usize) -> u64 {
+        self.options.load_base + offset as u64
+    }
 
-                self.mov_rdi_immediate(fd);
-                self.mov_rsi_immediate(data_addr);
-                self.mov_rdx_immediate(len);
-                self.mov_rax_immediate(1);
-                self.syscall();
+    // Data addresses are baked into movabs immediates; record the one just
+    // emitted so a loader that places .data elsewhere can patch it.
+    fn reloc_data_address(&mut self) {
+        self.relocations.push(Relocation {
+            offset: self.text.len() - 8,
+            symbol: ".data".to_string(),
+            kind: RelocationKind::Absolute64,
+            section: RelocationSection::Text,
+        });
+    }
+
+    fn mov_r10_data_address(&mut self, offset: usize) {
+        self.mov_r10_immediate(self.get_data_address(offset));
+        self.reloc_data_address();
+    }
+
+    // write(buf) and write(fd, buf) take the length from a string literal,
+    // string constant or local array and default fd to 1; write(fd, buf, len)
+    // is passed through as is
+    fn generate_write_syscall(&mut self, args: &[Expr]) -> Result<(), CodegenError> {
+        let (fd, buf) = match args {
+            [_, _, _] => return self.emit_syscall(Some(1), args, Some(1)),
+            [buf] => (Expr::Literal(Literal::Int(1, IntSuffix::None)), buf),
+            [fd, buf] => (fd.clone(), buf),
+            _ => {
+                return Err(CodegenError {
+                    message: format!(
+                        "write syscall takes one to three arguments, got {}",
+                        args.len()
+                    ),
+                    span: None,
+                });
             }
-            Expr::Identifier(name) => {
-                if let Some(sym) = self
-                    .symbols
-                    .iter()
-                    .find(|s| s.name == *name && s.kind == SymbolKind::Data)
-                    .cloned()
-                {
-                    let len = sym.size;
-                    let data_addr = self.get_data_address(sym.offset as usize);
-
-                    self.mov_rdi_immediate(fd);
-                    self.mov_rsi_immediate(data_addr);
-                    self.mov_rdx_immediate(len);
-                    self.mov_rax_immediate(1);
-                    self.syscall();
-                } else {
-                    return Err(CodegenError {
-                        message: format!("Unknown identifier: {}", name),
-                    });
+        };
+        let len = match buf {
+            Expr::Literal(Literal::String(bytes)) => bytes.len() as u64,
+            Expr::Identifier(name, span) if self.variables.contains_key(name) => {
+                match self.variable_types.get(name) {
+                    Some(ty @ Type::Array(..)) => self.type_size(ty) as u64,
+                    _ => {
+                        return Err(CodegenError {
+                            message: format!(
+                                "write cannot tell the length of '{}'; pass it as the third argument",
+                                name
+                            ),
+                            span: Some(span.clone()),
+                        });
+                    }
                 }
             }
+            Expr::Identifier(name, span) => self
+                .symbols
+                .iter()
+                .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                .map(|sym| sym.size)
+                .ok_or_else(|| CodegenError {
+                    message: format!("Unknown identifier: {}", name),
+                    span: Some(span.clone()),
+                })?,
             _ => {
                 return Err(CodegenError {
                     message: "write syscall argument must be a string literal or identifier"
                         .to_string(),
+                    span: None,
                 });
             }
+        };
+        let len = Expr::Literal(Literal::Int(len as i64, IntSuffix::None));
+        self.emit_syscall(Some(1), &[fd, buf.clone(), len], Some(1))
+    }
+
+    // A syscall argument in rax. Arrays and string literals have no
+    // register-sized value, so they are passed by address; a string literal
+    // is NUL-terminated for the syscalls that take C strings.
+    fn generate_syscall_arg(&mut self, arg: &Expr) -> Result<(), CodegenError> {
+        match arg {
+            Expr::Literal(Literal::String(bytes)) => {
+                let offset = self.data.len();
+                self.data.extend_from_slice(bytes);
+                self.data.push(0);
+                self.mov_rax_data_address(offset);
+            }
+            Expr::Identifier(name, span)
+                if matches!(self.variable_types.get(name), Some(Type::Array(..))) =>
+            {
+                let slot = match self.variables.get(name) {
+                    Some(&slot) => slot,
+                    None => self
+                        .symbols
+                        .iter()
+                        .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                        .map(|s| Slot::Data(s.offset as usize))
+                        .ok_or_else(|| CodegenError {
+                            message: format!("Undefined variable: {}", name),
+                            span: Some(span.clone()),
+                        })?,
+                };
+                self.slot_address(slot);
+            }
+            _ => {
+                self.generate_expr(arg)?;
+            }
+        }
+        Ok(())
+    }
+
+    // The buffer of `write` and `read`: a string constant is passed by
+    // address too, so it works where its literal would. Any other global,
+    // a pointer constant among them, is passed by value.
+    fn generate_buffer_address(&mut self, buf: &Expr) -> Result<(), CodegenError> {
+        let data_symbol = match buf {
+            Expr::Identifier(name, _)
+                if !self.variables.contains_key(name)
+                    && !self.variable_types.contains_key(name) =>
+            {
+                // Strings are the only data consts with neither an integer
+                // nor a recorded type
+                self.symbols
+                    .iter()
+                    .find(|s| s.name == *name && s.kind == SymbolKind::Data)
+                    .filter(|s| s.int_type.is_none())
+                    .map(|s| s.offset as usize)
+            }
+            _ => None,
+        };
+        match data_symbol {
+            Some(offset) => self.mov_rax_data_address(offset),
+            None => self.generate_syscall_arg(buf)?,
         }
+        Ok(())
+    }
 
+    // read(fd, buf, len) leaves the byte count (or a negated errno) in rax.
+    // `buf` is written to, so it names an array or is a pointer expression.
+    fn generate_read_syscall(&mut self, args: &[Expr]) -> Result<(), CodegenError> {
+        let [_, buf, _] = args else {
+            return Err(CodegenError {
+                message: format!(
+                    "read syscall takes three arguments (fd, buf, len), got {}",
+                    args.len()
+                ),
+                span: None,
+            });
+        };
+        if let Expr::Literal(Literal::String(_)) = buf {
+            return Err(CodegenError {
+                message: "read syscall buffer cannot be a string literal".to_string(),
+                span: None,
+            });
+        }
+        self.emit_syscall(Some(0), args, Some(1))?;
+        if let Expr::Identifier(name, _) = buf {
+            self.entropy_state
+                .insert(name.clone(), EntropyState::Initialized);
+        }
         Ok(())
     }
 
+    // mov reg, rax
+    fn mov_reg_rax(&mut self, reg: u8) {
+        let rex = if reg >= 8 { 0x49 } else { 0x48 };
+        self.text.extend_from_slice(&[rex, 0x89, 0xc0 | (reg & 7)]);
+    }
+
+    fn pop_reg(&mut self, reg: u8) {
+        if reg >= 8 {
+            self.text.push(0x41);
+        }
+        self.text.push(0x58 | (reg & 7));
+        self.stack_depth -= 1;
+    }
+
     fn mov_rdi_immediate(&mut self, val: u64) {
         self.text.push(0x48);
         self.text.push(0xbf);
@@ -962,11 +3344,11 @@ impl CodeGenerator {
         self.text.push(0xbe);
         self.text.extend_from_slice(&val.to_le_bytes());
     }
-    fn lea_rsi_rip_relative(&mut self, offset: i32) {
+
+    fn mov_rcx_immediate(&mut self, val: u64) {
         self.text.push(0x48);
-        self.text.push(0x8d);
-        self.text.push(0x35);
-        self.text.extend_from_slice(&offset.to_le_bytes());
+        self.text.push(0xb9);
+        self.text.extend_from_slice(&val.to_le_bytes());
     }
 
     fn mov_rdx_immediate(&mut self, val: u64) {
@@ -981,12 +3363,6 @@ impl CodeGenerator {
         self.text.extend_from_slice(&val.to_le_bytes());
     }
 
-    fn mov_r10_to_mem(&mut self) {
-        self.text.push(0x49);
-        self.text.push(0x89);
-        self.text.push(0x10);
-    }
-
     fn mov_rax_to_r10_mem(&mut self) {
         self.text.push(0x49);
         self.text.push(0x89);
@@ -996,7 +3372,7 @@ impl CodeGenerator {
     fn mov_rax_from_r10(&mut self) {
         self.text.push(0x49);
         self.text.push(0x8b);
-        self.text.push(0xc2);
+        self.text.push(0x02);
     }
 
     fn syscall(&mut self) {
@@ -1013,6 +3389,7 @@ impl CodeGenerator {
                     if !int_type.fits(*val) {
                         return Err(CodegenError {
                             message: format!("Integer literal {} does not fit in type", val),
+                            span: None,
                         });
                     }
                     Ok(*val as u64 & int_type.mask())
@@ -1020,19 +3397,17 @@ impl CodeGenerator {
                     Ok(*val as u64)
                 }
             }
-            Expr::Identifier(name) => {
+            Expr::Identifier(name, _) => {
+                if let Some(&slot) = self.variables.get(name) {
+                    self.load_slot(slot);
+                    return Ok(0);
+                }
                 if let Some(sym) = self
                     .symbols
                     .iter()
                     .find(|s| s.name == *name && s.kind == SymbolKind::Data)
                 {
-                    self.mov_rax_from_mem(sym.offset as u64);
-                    return Ok(0);
-                }
-                if let Some(&offset) = self.variables.get(name) {
-                    let addr = self.get_data_address(offset as usize);
-                    self.mov_r10_immediate(addr);
-                    self.mov_rax_from_r10();
+                    self.mov_rax_from_data(sym.offset);
                     return Ok(0);
                 }
                 Ok(0)
@@ -1041,47 +3416,18 @@ impl CodeGenerator {
         }
     }
 
-    // FEATURE 1: Emit alloc<T>(count) - puts result in rax
-    fn emit_alloc(&mut self, count_expr: &Expr) -> Result<(), CodegenError> {
-        let count = self.eval_expr_to_u64(count_expr)?;
-        self.mov_rdi_immediate(count);
-        self.call_external("__aura_alloc");
-        Ok(())
-    }
-
-    // FEATURE 1: Emit free(ptr) - takes ptr from rax
-    fn emit_free(&mut self, ptr_expr: &Expr) -> Result<(), CodegenError> {
-        let _ = self.eval_expr_to_u64(ptr_expr)?;
-        self.mov_rdi_rax();
-        self.call_external("__aura_free");
-        Ok(())
-    }
-
-    // FEATURE 9: Emit immediate value respecting bit width
-    fn emit_width_immediate(&mut self, val: u64, bits: u8) {
-        match bits {
-            1..=8 => {
-                // mov al, imm8
-                self.text.push(0xb0);
-                self.text.push(val as u8);
-            }
-            9..=16 => {
-                // mov ax, imm16
-                self.text.push(0x66);
-                self.text.push(0xb8);
-                self.text.extend_from_slice(&(val as u16).to_le_bytes());
-            }
-            17..=32 => {
-                // mov eax, imm32
-                self.text.push(0xb8);
-                self.text.extend_from_slice(&(val as u32).to_le_bytes());
-            }
-            _ => {
-                // mov rax, imm64
-                self.text.push(0x48);
-                self.text.push(0xb8);
-                self.text.extend_from_slice(&val.to_le_bytes());
-            }
+    // FEATURE 9: Load a literal already reduced to its width, filling all of
+    // rax the way wrap_rax leaves a value of that type
+    fn emit_width_immediate(&mut self, val: u64, int_type: IntType) {
+        if int_type.bits > 32 {
+            self.mov_rax_immediate(val);
+        } else if int_type.signed {
+            let shift = 64 - int_type.bits;
+            self.mov_rax_immediate((((val << shift) as i64) >> shift) as u64);
+        } else {
+            // mov eax, imm32 (zero-extends into rax)
+            self.text.push(0xb8);
+            self.text.extend_from_slice(&(val as u32).to_le_bytes());
         }
     }
 
@@ -1089,7 +3435,7 @@ impl CodeGenerator {
     fn mov_rdi_rax(&mut self) {
         self.text.push(0x48);
         self.text.push(0x89);
-        self.text.push(0xf8);
+        self.text.push(0xc7);
     }
 
     // FEATURE 1: Call external function
@@ -1116,77 +3462,115 @@ impl CodeGenerator {
                     offset: self.text.len(),
                     symbol: symbol.to_string(),
                     kind: RelocationKind::Relative32,
+                    section: RelocationSection::Text,
                 });
                 self.text.extend_from_slice(&[0u8; 4]);
             }
         }
     }
 
-    // FEATURE 8: Generate explicit cast conversion
-    fn generate_cast_conversion(&mut self, target_type: &Type) -> Result<(), CodegenError> {
+    // call rel32 to a function of this program. The field is filled in by
+    // resolve_direct_calls once every function has an offset; the relocation
+    // stays so a linker moving the code can redo it.
+    fn call_direct(&mut self, symbol: &str) {
+        self.text.push(0xe8);
+        self.relocations.push(Relocation {
+            offset: self.text.len(),
+            symbol: symbol.to_string(),
+            kind: RelocationKind::Relative32,
+            section: RelocationSection::Text,
+        });
+        self.text.extend_from_slice(&[0u8; 4]);
+    }
+
+    fn resolve_direct_calls(&mut self) {
+        for reloc in &self.relocations {
+            if reloc.kind != RelocationKind::Relative32 || self.text[reloc.offset - 1] != 0xe8 {
+                continue;
+            }
+            let target = self
+                .symbols
+                .iter()
+                .find(|s| s.kind == SymbolKind::Function && s.name == reloc.symbol);
+            if let Some(target) = target {
+                let rel = (target.offset as i64 - (reloc.offset as i64 + 4)) as i32;
+                self.text[reloc.offset..reloc.offset + 4].copy_from_slice(&rel.to_le_bytes());
+            }
+        }
+    }
+
+    // FEATURE 8: Generate explicit cast conversion. `source` is the integer
+    // type being cast, if known; widening to 64 bits extends by its sign.
+    fn generate_cast_conversion(
+        &mut self,
+        source: Option<IntType>,
+        target_type: &Type,
+    ) -> Result<(), CodegenError> {
         match target_type {
-            Type::I8 => {
-                // movsx eax, al (sign-extend 8-bit to 32-bit)
-                self.text.push(0x0f);
-                self.text.push(0xbe);
-                self.text.push(0xc0);
-            }
-            Type::U8 => {
-                // movzx eax, al (zero-extend 8-bit to 32-bit)
-                self.text.push(0x0f);
-                self.text.push(0xb6);
-                self.text.push(0xc0);
-            }
-            Type::I16 => {
-                // movsx eax, ax
-                self.text.push(0x0f);
-                self.text.push(0xbf);
-                self.text.push(0xc0);
-            }
-            Type::U16 => {
-                // movzx eax, ax
-                self.text.push(0x0f);
-                self.text.push(0xb7);
-                self.text.push(0xc0);
-            }
-            Type::I32 | Type::U32 => {
-                // Already in eax, no conversion needed
-            }
-            Type::I64 | Type::U64 => {
-                // Already in rax, no conversion needed
-            }
-            Type::BitInt(bits, _) => {
+            Type::I8 | Type::U8 | Type::I16 | Type::U16 | Type::I32 | Type::U32 => {
+                // Keep the target's low bits and extend them back over rax
+                // by the target's sign: movsx/movzx rax, al or ax, movsxd
+                // rax, eax or mov eax, eax
+                if let Some(target) = IntType::from_aura_type(target_type) {
+                    self.extend_rax(target);
+                }
+            }
+            Type::I64 | Type::U64 | Type::Isize | Type::Usize => {
+                // movsxd rax, eax for a signed source, mov eax, eax for an
+                // unsigned one (and the 8/16-bit forms likewise)
+                if let Some(source) = source {
+                    self.extend_rax(source);
+                }
+            }
+            Type::BitInt(bits, signed) => {
                 // FEATURE 9: Apply mask for bit-precise type
-                self.mask_rax(*bits);
+                self.wrap_rax(IntType {
+                    bits: *bits,
+                    signed: *signed,
+                });
             }
             _ => {}
         }
         Ok(())
     }
 
-    // FEATURE 9: Apply mask to rax for bit-precise types
-    fn mask_rax(&mut self, bits: u8) {
-        if bits < 64 {
-            let mask = if bits >= 63 {
-                u64::MAX
-            } else {
-                (1u64 << bits) - 1
-            };
-            self.text.push(0x48);
-            self.text.push(0x25);
-            self.text.extend_from_slice(&mask.to_le_bytes());
+    // FEATURE 9: Reduce rax to the type's width. Unsigned values are masked;
+    // signed values are sign-extended from their top bit so that 64-bit
+    // compares and shifts on them stay correct. Clobbers rdx.
+    fn wrap_rax(&mut self, int_type: IntType) {
+        if int_type.bits >= 64 {
+            return;
+        }
+        if int_type.signed {
+            let shift = 64 - int_type.bits;
+            // shl rax, imm8 / sar rax, imm8
+            self.text.extend_from_slice(&[0x48, 0xc1, 0xe0, shift]);
+            self.text.extend_from_slice(&[0x48, 0xc1, 0xf8, shift]);
+        } else {
+            self.mov_rdx_immediate(int_type.mask());
+            // and rax, rdx
+            self.text.extend_from_slice(&[0x48, 0x21, 0xd0]);
+        }
+    }
+
+    // Arithmetic runs on all of rax, so a result narrower than 64 bits is
+    // wrapped back to the width of its type.
+    fn wrap_int_result(&mut self, int_type: Option<IntType>) {
+        if let Some(int_type) = int_type {
+            self.wrap_rax(int_type);
         }
     }
 
     // ========== FEATURE 15: Physical Memory Capability Enforcement ==========
     fn check_capability_access(&self, _addr: u64, _capability: &str) -> Result<(), CodegenError> {
-        if let Some((base, length, mode)) = self.capability_ranges.get(_capability) {
+        if let Some((base, length, _)) = self.capability_ranges.get(_capability) {
             if _addr < *base || _addr >= *base + *length {
                 return Err(CodegenError {
                     message: format!(
                         "Capability access violation: address 0x{:x} outside capability range [0x{:x}, 0x{:x})",
                         _addr, base, base + length
                     ),
+                    span: None,
                 });
             }
         }
@@ -1203,6 +3587,7 @@ impl CodeGenerator {
         if length == 0 {
             return Err(CodegenError {
                 message: format!("Capability '{}' has zero length", name),
+                span: None,
             });
         }
         if let Some((_, _, existing_mode)) = self.capability_ranges.get(name) {
@@ -1212,6 +3597,7 @@ impl CodeGenerator {
                         "Capability '{}' redeclared with different mode: {:?} vs {:?}",
                         name, mode, existing_mode
                     ),
+                    span: None,
                 });
             }
         }
@@ -1225,6 +3611,7 @@ impl CodeGenerator {
                             "Capability '{}' overlaps with capability '{}'",
                             name, other_name
                         ),
+                        span: None,
                     });
                 }
             }
@@ -1243,11 +3630,13 @@ impl CodeGenerator {
                 if *mem_class == MemoryClass::Device && target_class == MemoryClass::Encrypted {
                     return Err(CodegenError {
                         message: "Cannot cast Device memory to Encrypted memory".to_string(),
+                        span: None,
                     });
                 }
                 if *mem_class == MemoryClass::Encrypted && target_class == MemoryClass::Device {
                     return Err(CodegenError {
                         message: "Cannot cast Encrypted memory to Device memory".to_string(),
+                        span: None,
                     });
                 }
             }
@@ -1261,9 +3650,11 @@ impl CodeGenerator {
         numa_node: Option<u8>,
     ) -> Result<(), CodegenError> {
         if let Some(node) = numa_node {
-            if node > 255 {
+            // 0xFF marks "no NUMA node" in the topology section
+            if node == u8::MAX {
                 return Err(CodegenError {
                     message: format!("Invalid NUMA node: {}", node),
+                    span: None,
                 });
             }
         }
@@ -1298,9 +3689,18 @@ impl CodeGenerator {
         }
     }
 
-    fn check_syscall_entropy(&self, args: &[Expr]) -> Result<(), CodegenError> {
-        for arg in args {
-            if let Expr::Identifier(name) = arg {
+    // `written` is an argument the syscall only writes through, which may
+    // start out uninitialized
+    fn check_syscall_entropy(
+        &self,
+        args: &[Expr],
+        written: Option<usize>,
+    ) -> Result<(), CodegenError> {
+        for (i, arg) in args.iter().enumerate() {
+            if written == Some(i) {
+                continue;
+            }
+            if let Expr::Identifier(name, span) = arg {
                 if let Some(state) = self.entropy_state.get(name) {
                     if *state == EntropyState::Uninitialized {
                         return Err(CodegenError {
@@ -1308,6 +3708,7 @@ impl CodeGenerator {
                                 "Syscall argument '{}' is uninitialized - this could expose sensitive data",
                                 name
                             ),
+                            span: Some(span.clone()),
                         });
                     }
                     if *state == EntropyState::Tainted {
@@ -1316,6 +3717,7 @@ impl CodeGenerator {
                                 "Syscall argument '{}' is tainted - unsafe for system calls",
                                 name
                             ),
+                            span: Some(span.clone()),
                         });
                     }
                 }
@@ -1331,6 +3733,7 @@ impl CodeGenerator {
                     "Cannot write uninitialized value to MMIO region '{}'",
                     _region
                 ),
+                span: None,
             });
         }
         if _value == EntropyState::Tainted {
@@ -1339,6 +3742,7 @@ impl CodeGenerator {
                     "Cannot write tainted value to MMIO region '{}' - security risk",
                     _region
                 ),
+                span: None,
             });
         }
         Ok(())
@@ -1358,7 +3762,8 @@ impl CodeGenerator {
             Type::BitInt(bits, _) => *bits,
             _ => {
                 return Err(CodegenError {
-                    message: format!("Cannot define bit regions on non-integer type"),
+                    message: "Cannot define bit regions on non-integer type".to_string(),
+                    span: None,
                 })
             }
         };
@@ -1369,11 +3774,13 @@ impl CodeGenerator {
                         "Bit region '{}' offset {} exceeds type width of {} bits",
                         _name, offset, type_bits
                     ),
+                    span: None,
                 });
             }
             if *width == 0 {
                 return Err(CodegenError {
                     message: format!("Bit region '{}' has zero width", _name),
+                    span: None,
                 });
             }
             if *offset + *width > type_bits {
@@ -1385,6 +3792,7 @@ impl CodeGenerator {
                         offset + width,
                         type_bits
                     ),
+                    span: None,
                 });
             }
         }
@@ -1402,6 +3810,7 @@ impl CodeGenerator {
                         offset2,
                         offset2 + width2
                     ),
+                    span: None,
                 });
             }
         }
@@ -1414,9 +3823,10 @@ impl CodeGenerator {
         base_type: &Type,
         regions: &[BitRegion],
     ) -> Result<(), CodegenError> {
-        if let Some(existing) = self.validated_bit_regions.get(name) {
+        if self.validated_bit_regions.contains_key(name) {
             return Err(CodegenError {
                 message: format!("Bit region '{}' already defined", name),
+                span: None,
             });
         }
         let region_tuples: Vec<(u8, u8, &str)> = regions
@@ -1434,3 +3844,56 @@ impl CodeGenerator {
         Ok(())
     }
 }
+
+// Type of an integer constant: its annotation, else its literal suffix, else
+// i32 like any unsuffixed literal
+fn const_int_type(ty: Option<&Type>, value: &Expr) -> Option<IntType> {
+    match (ty, value) {
+        (Some(ty), _) => IntType::from_aura_type(ty),
+        (None, Expr::Unary(UnaryOp::Neg, inner)) => const_int_type(None, inner),
+        (None, Expr::Literal(Literal::Int(_, suffix))) => {
+            IntType::from_suffix(suffix).or(Some(IntType {
+                bits: 32,
+                signed: true,
+            }))
+        }
+        _ => None,
+    }
+}
+
+// Type of a scalar literal, negated or not; unsuffixed integers are i32 and
+// unsuffixed floats f64, as typecheck has them
+fn literal_type(expr: &Expr) -> Option<Type> {
+    let ty = match expr {
+        Expr::Unary(UnaryOp::Neg, inner) => return literal_type(inner),
+        Expr::Literal(Literal::Int(_, suffix)) => match suffix {
+            IntSuffix::I8 => Type::I8,
+            IntSuffix::I16 => Type::I16,
+            IntSuffix::I32 | IntSuffix::None => Type::I32,
+            IntSuffix::I64 => Type::I64,
+            IntSuffix::U8 => Type::U8,
+            IntSuffix::U16 => Type::U16,
+            IntSuffix::U32 => Type::U32,
+            IntSuffix::U64 => Type::U64,
+            IntSuffix::Usize => Type::Usize,
+            IntSuffix::Isize => Type::Isize,
+        },
+        Expr::Literal(Literal::Float(_, FloatSuffix::F32)) => Type::F32,
+        Expr::Literal(Literal::Float(..)) => Type::F64,
+        Expr::Literal(Literal::Bool(_)) => Type::Bool,
+        Expr::Literal(Literal::Char(_)) => Type::U8,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+// Value of a constant scalar initializer, as stored in `.data`
+fn const_scalar(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(Literal::Int(val, _)) => Some(*val),
+        Expr::Literal(Literal::Bool(b)) => Some(*b as i64),
+        Expr::Literal(Literal::Char(c)) => Some(*c as i64),
+        Expr::Unary(UnaryOp::Neg, inner) => const_scalar(inner).map(i64::wrapping_neg),
+        _ => None,
+    }
+}
@@ -0,0 +1,752 @@
+use crate::ast::visit::{walk_expr, Visitor};
+use crate::ast::*;
+use crate::layout::TypeLayouts;
+use std::collections::HashMap;
+use std::fmt;
+
+// Guards against runaway const fns: deep recursion and unbounded loops are
+// reported as errors instead of hanging or overflowing the compiler's stack.
+const MAX_CALL_DEPTH: usize = 64;
+const MAX_STEPS: usize = 100_000;
+
+#[derive(Debug)]
+pub struct ConstEvalError {
+    pub message: String,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Const evaluation error: {}", self.message)
+    }
+}
+
+impl std::error::Error for ConstEvalError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Bool(bool),
+}
+
+impl ConstValue {
+    pub fn as_int(self) -> Result<i64, ConstEvalError> {
+        match self {
+            ConstValue::Int(v) => Ok(v),
+            ConstValue::Bool(_) => Err(ConstEvalError {
+                message: "Expected an integer, got a bool".to_string(),
+            }),
+        }
+    }
+
+    fn as_bool(self) -> Result<bool, ConstEvalError> {
+        match self {
+            ConstValue::Bool(b) => Ok(b),
+            ConstValue::Int(_) => Err(ConstEvalError {
+                message: "Expected a bool, got an integer".to_string(),
+            }),
+        }
+    }
+}
+
+enum Flow {
+    Normal,
+    Return(ConstValue),
+    Break(Option<ConstValue>),
+    Continue,
+}
+
+/// Tree-walking interpreter for `const fn` bodies and constant initializers.
+/// Only pure integer and bool computation is accepted. `sizeof`, `alignof`
+/// and `offsetof` are answered from the layouts of the types declared so far.
+pub struct ConstEvaluator<'a> {
+    functions: &'a HashMap<String, Function>,
+    globals: &'a HashMap<String, ConstValue>,
+    layouts: &'a TypeLayouts,
+    depth: usize,
+    steps: usize,
+}
+
+impl<'a> ConstEvaluator<'a> {
+    pub fn new(
+        functions: &'a HashMap<String, Function>,
+        globals: &'a HashMap<String, ConstValue>,
+        layouts: &'a TypeLayouts,
+    ) -> Self {
+        ConstEvaluator {
+            functions,
+            globals,
+            layouts,
+            depth: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn eval(&mut self, expr: &Expr) -> Result<ConstValue, ConstEvalError> {
+        let mut env = vec![HashMap::new()];
+        self.eval_expr(expr, &mut env)
+    }
+
+    fn tick(&mut self) -> Result<(), ConstEvalError> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(ConstEvalError {
+                message: format!("Evaluation exceeded {} steps", MAX_STEPS),
+            });
+        }
+        Ok(())
+    }
+
+    // A layout query over a type that is not declared (yet) has no answer
+    fn check_resolves(&self, ty: &Type, query: &str) -> Result<(), ConstEvalError> {
+        if self.layouts.resolves(ty) {
+            Ok(())
+        } else {
+            Err(ConstEvalError {
+                message: format!("{}: {:?} is not a type declared before it", query, ty),
+            })
+        }
+    }
+
+    fn lookup(
+        &self,
+        name: &str,
+        env: &[HashMap<String, ConstValue>],
+    ) -> Result<ConstValue, ConstEvalError> {
+        env.iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .or_else(|| self.globals.get(name).copied())
+            .ok_or_else(|| ConstEvalError {
+                message: format!("'{}' is not a compile-time constant", name),
+            })
+    }
+
+    fn eval_expr(
+        &mut self,
+        expr: &Expr,
+        env: &mut Vec<HashMap<String, ConstValue>>,
+    ) -> Result<ConstValue, ConstEvalError> {
+        self.tick()?;
+        match expr {
+            Expr::Literal(Literal::Int(v, _)) => Ok(ConstValue::Int(*v)),
+            Expr::Literal(Literal::Char(c)) => Ok(ConstValue::Int(*c as i64)),
+            Expr::Literal(Literal::Bool(b)) => Ok(ConstValue::Bool(*b)),
+            Expr::Identifier(name, _) => self.lookup(name, env),
+            Expr::Unary(op, operand) => {
+                let value = self.eval_expr(operand, env)?;
+                match op {
+                    UnaryOp::Neg => Ok(ConstValue::Int(value.as_int()?.wrapping_neg())),
+                    UnaryOp::BitNot => Ok(ConstValue::Int(!value.as_int()?)),
+                    UnaryOp::Not => Ok(ConstValue::Bool(!value.as_bool()?)),
+                    UnaryOp::Deref | UnaryOp::AddrOf => Err(ConstEvalError {
+                        message: "Pointer operations are not allowed in const evaluation"
+                            .to_string(),
+                    }),
+                }
+            }
+            Expr::Binary(BinaryOp::LogicalAnd, l, r, _) => {
+                if !self.eval_expr(l, env)?.as_bool()? {
+                    return Ok(ConstValue::Bool(false));
+                }
+                Ok(ConstValue::Bool(self.eval_expr(r, env)?.as_bool()?))
+            }
+            Expr::Binary(BinaryOp::LogicalOr, l, r, _) => {
+                if self.eval_expr(l, env)?.as_bool()? {
+                    return Ok(ConstValue::Bool(true));
+                }
+                Ok(ConstValue::Bool(self.eval_expr(r, env)?.as_bool()?))
+            }
+            Expr::Binary(op, l, r, _) => {
+                let left = self.eval_expr(l, env)?;
+                let right = self.eval_expr(r, env)?;
+                self.eval_binary(op, left, right)
+            }
+            Expr::Cast(inner, ty) => {
+                let value = self.eval_expr(inner, env)?;
+                match (value, ty.int_bits()) {
+                    (ConstValue::Int(v), Some(bits)) if bits < 64 => {
+                        let signed = !matches!(
+                            ty,
+                            Type::U8 | Type::U16 | Type::U32 | Type::BitInt(_, false)
+                        );
+                        let shift = 64 - bits;
+                        Ok(ConstValue::Int(if signed {
+                            (v << shift) >> shift
+                        } else {
+                            ((v as u64) << shift >> shift) as i64
+                        }))
+                    }
+                    (ConstValue::Int(v), Some(_)) => Ok(ConstValue::Int(v)),
+                    (ConstValue::Bool(b), Some(_)) => Ok(ConstValue::Int(b as i64)),
+                    (ConstValue::Int(v), None) if *ty == Type::Bool => Ok(ConstValue::Bool(v != 0)),
+                    (value, None) if *ty == Type::Bool => Ok(value),
+                    _ => Err(ConstEvalError {
+                        message: format!("Cannot cast to {:?} in const evaluation", ty),
+                    }),
+                }
+            }
+            Expr::Sizeof(ty) => {
+                self.check_resolves(ty, "sizeof")?;
+                Ok(ConstValue::Int(self.layouts.size_of(ty) as i64))
+            }
+            Expr::Alignof(ty) => {
+                self.check_resolves(ty, "alignof")?;
+                Ok(ConstValue::Int(self.layouts.align_of(ty) as i64))
+            }
+            Expr::Offsetof(ty, path) => {
+                self.check_resolves(ty, "offsetof")?;
+                let offset =
+                    self.layouts
+                        .field_path_offset(ty, path)
+                        .ok_or_else(|| ConstEvalError {
+                            message: format!("offsetof: {:?} has no field '{}'", ty, path),
+                        })?;
+                Ok(ConstValue::Int(offset as i64))
+            }
+            Expr::If(if_expr) => {
+                if self.eval_expr(&if_expr.condition, env)?.as_bool()? {
+                    self.eval_expr(&if_expr.then_expr, env)
+                } else {
+                    self.eval_expr(&if_expr.else_expr, env)
+                }
+            }
+            Expr::Loop(body) => loop {
+                match self.exec_block(body, env)? {
+                    Flow::Break(Some(value)) => break Ok(value),
+                    Flow::Break(None) => {
+                        break Err(ConstEvalError {
+                            message: "Loop expression finished without a value".to_string(),
+                        });
+                    }
+                    Flow::Return(_) => {
+                        break Err(ConstEvalError {
+                            message:
+                                "Cannot return from inside a loop expression in const evaluation"
+                                    .to_string(),
+                        });
+                    }
+                    Flow::Normal | Flow::Continue => {}
+                }
+            },
+            Expr::Call(callee, args) => {
+                let name = match &**callee {
+                    Expr::Identifier(name, _) => name,
+                    _ => {
+                        return Err(ConstEvalError {
+                            message: "Only direct calls to const fns are allowed".to_string(),
+                        });
+                    }
+                };
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.eval_expr(arg, env)?);
+                }
+                self.call(name, values)
+            }
+            Expr::Assign(target, value) => {
+                let name = match &**target {
+                    Expr::Identifier(name, _) => name,
+                    _ => {
+                        return Err(ConstEvalError {
+                            message: "Only local variables can be assigned in a const fn"
+                                .to_string(),
+                        });
+                    }
+                };
+                let value = self.eval_expr(value, env)?;
+                match env.iter_mut().rev().find_map(|scope| scope.get_mut(name)) {
+                    Some(slot) => {
+                        *slot = value;
+                        Ok(value)
+                    }
+                    None => Err(ConstEvalError {
+                        message: format!("Cannot assign to '{}' in const evaluation", name),
+                    }),
+                }
+            }
+            _ => Err(ConstEvalError {
+                message: "Expression is not allowed in const evaluation".to_string(),
+            }),
+        }
+    }
+
+    fn eval_binary(
+        &self,
+        op: &BinaryOp,
+        left: ConstValue,
+        right: ConstValue,
+    ) -> Result<ConstValue, ConstEvalError> {
+        if let (ConstValue::Bool(a), ConstValue::Bool(b)) = (left, right) {
+            return match op {
+                BinaryOp::Eq => Ok(ConstValue::Bool(a == b)),
+                BinaryOp::Neq => Ok(ConstValue::Bool(a != b)),
+                _ => Err(ConstEvalError {
+                    message: format!("Operator {:?} is not defined on bools", op),
+                }),
+            };
+        }
+        let a = left.as_int()?;
+        let b = right.as_int()?;
+        let value = match op {
+            BinaryOp::Add => ConstValue::Int(a.wrapping_add(b)),
+            BinaryOp::Sub => ConstValue::Int(a.wrapping_sub(b)),
+            BinaryOp::Mul => ConstValue::Int(a.wrapping_mul(b)),
+            BinaryOp::Div | BinaryOp::Mod if b == 0 => {
+                return Err(ConstEvalError {
+                    message: "Division by zero in const evaluation".to_string(),
+                });
+            }
+            BinaryOp::Div => ConstValue::Int(a.wrapping_div(b)),
+            BinaryOp::Mod => ConstValue::Int(a.wrapping_rem(b)),
+            BinaryOp::LShift => ConstValue::Int(a.wrapping_shl(b as u32)),
+            BinaryOp::RShift => ConstValue::Int(a.wrapping_shr(b as u32)),
+            BinaryOp::BitAnd => ConstValue::Int(a & b),
+            BinaryOp::BitOr => ConstValue::Int(a | b),
+            BinaryOp::BitXor => ConstValue::Int(a ^ b),
+            BinaryOp::Eq => ConstValue::Bool(a == b),
+            BinaryOp::Neq => ConstValue::Bool(a != b),
+            BinaryOp::Lt => ConstValue::Bool(a < b),
+            BinaryOp::Gt => ConstValue::Bool(a > b),
+            BinaryOp::LtEq => ConstValue::Bool(a <= b),
+            BinaryOp::GtEq => ConstValue::Bool(a >= b),
+            BinaryOp::LogicalAnd | BinaryOp::LogicalOr => unreachable!(),
+        };
+        Ok(value)
+    }
+
+    fn call(&mut self, name: &str, args: Vec<ConstValue>) -> Result<ConstValue, ConstEvalError> {
+        let functions = self.functions;
+        let f = functions.get(name).ok_or_else(|| ConstEvalError {
+            message: format!("'{}' is not a const fn", name),
+        })?;
+        if f.params.len() != args.len() {
+            return Err(ConstEvalError {
+                message: format!(
+                    "'{}' expects {} arguments, got {}",
+                    name,
+                    f.params.len(),
+                    args.len()
+                ),
+            });
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(ConstEvalError {
+                message: format!(
+                    "Const fn call depth exceeded {} while calling '{}'",
+                    MAX_CALL_DEPTH, name
+                ),
+            });
+        }
+
+        let scope = f
+            .params
+            .iter()
+            .map(|p| p.name.clone())
+            .zip(args)
+            .collect::<HashMap<_, _>>();
+        let mut env = vec![scope];
+
+        self.depth += 1;
+        let flow = self.exec_block(&f.body, &mut env);
+        self.depth -= 1;
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            _ => Err(ConstEvalError {
+                message: format!("Const fn '{}' finished without returning a value", name),
+            }),
+        }
+    }
+
+    fn exec_block(
+        &mut self,
+        stmts: &[Stmt],
+        env: &mut Vec<HashMap<String, ConstValue>>,
+    ) -> Result<Flow, ConstEvalError> {
+        env.push(HashMap::new());
+        let mut flow = Flow::Normal;
+        for stmt in stmts {
+            flow = self.exec_stmt(stmt, env)?;
+            if !matches!(flow, Flow::Normal) {
+                break;
+            }
+        }
+        env.pop();
+        Ok(flow)
+    }
+
+    fn exec_stmt(
+        &mut self,
+        stmt: &Stmt,
+        env: &mut Vec<HashMap<String, ConstValue>>,
+    ) -> Result<Flow, ConstEvalError> {
+        self.tick()?;
+        match stmt {
+            Stmt::Let(l) => {
+                // Typecheck rejects reads before the first assignment, so the
+                // placeholder for a missing initializer is never observed.
+                let value = match &l.value {
+                    Some(value) => self.eval_expr(value, env)?,
+                    None => ConstValue::Int(0),
+                };
+                if let Some(scope) = env.last_mut() {
+                    scope.insert(l.name.clone(), value);
+                }
+                Ok(Flow::Normal)
+            }
+            Stmt::Const(c) => {
+                let value = self.eval_expr(&c.value, env)?;
+                if let Some(scope) = env.last_mut() {
+                    scope.insert(c.name.clone(), value);
+                }
+                Ok(Flow::Normal)
+            }
+            Stmt::Expr(e) => {
+                self.eval_expr(e, env)?;
+                Ok(Flow::Normal)
+            }
+            Stmt::Return(Some(e)) => Ok(Flow::Return(self.eval_expr(e, env)?)),
+            Stmt::Break(value, _) => Ok(Flow::Break(
+                value.as_ref().map(|v| self.eval_expr(v, env)).transpose()?,
+            )),
+            Stmt::Continue(_) => Ok(Flow::Continue),
+            Stmt::Block(stmts) => self.exec_block(stmts, env),
+            Stmt::If(i) => {
+                if self.eval_expr(&i.condition, env)?.as_bool()? {
+                    self.exec_block(&i.then_branch, env)
+                } else if let Some(else_branch) = &i.else_branch {
+                    self.exec_block(else_branch, env)
+                } else {
+                    Ok(Flow::Normal)
+                }
+            }
+            Stmt::While(w) => {
+                while self.eval_expr(&w.condition, env)?.as_bool()? {
+                    match self.exec_block(&w.body, env)? {
+                        Flow::Break(_) => break,
+                        Flow::Return(v) => return Ok(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            Stmt::For(f) => {
+                env.push(HashMap::new());
+                self.exec_stmt(&f.init, env)?;
+                let result = loop {
+                    if !self.eval_expr(&f.condition, env)?.as_bool()? {
+                        break Flow::Normal;
+                    }
+                    match self.exec_block(&f.body, env)? {
+                        Flow::Break(_) => break Flow::Normal,
+                        Flow::Return(v) => break Flow::Return(v),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    self.exec_stmt(&f.update, env)?;
+                };
+                env.pop();
+                Ok(result)
+            }
+            Stmt::Return(None) => Err(ConstEvalError {
+                message: "Const fn returned without a value".to_string(),
+            }),
+            Stmt::Asm(_) | Stmt::Defer(_) | Stmt::EntropyAssert(_) => Err(ConstEvalError {
+                message: "Statement is not allowed in const evaluation".to_string(),
+            }),
+        }
+    }
+}
+
+/// Rejects `const fn` bodies that contain side effects, before any of them
+/// are evaluated.
+pub fn check_const_fn(f: &Function) -> Result<(), ConstEvalError> {
+    fn stmt_pure(stmt: &Stmt) -> Result<(), String> {
+        match stmt {
+            Stmt::Let(l) if l.is_static => Err("static variables".to_string()),
+            Stmt::Let(l) => l.value.as_deref().map_or(Ok(()), expr_pure),
+            Stmt::Const(c) => expr_pure(&c.value),
+            Stmt::Expr(e) | Stmt::Return(Some(e)) | Stmt::Break(Some(e), _) => expr_pure(e),
+            Stmt::Block(stmts) => stmts.iter().try_for_each(stmt_pure),
+            Stmt::If(i) => {
+                expr_pure(&i.condition)?;
+                i.then_branch.iter().try_for_each(stmt_pure)?;
+                i.else_branch.iter().flatten().try_for_each(stmt_pure)
+            }
+            Stmt::While(w) => {
+                expr_pure(&w.condition)?;
+                w.body.iter().try_for_each(stmt_pure)
+            }
+            Stmt::For(f) => {
+                stmt_pure(&f.init)?;
+                expr_pure(&f.condition)?;
+                stmt_pure(&f.update)?;
+                f.body.iter().try_for_each(stmt_pure)
+            }
+            Stmt::Return(None) | Stmt::Break(None, _) | Stmt::Continue(_) => Ok(()),
+            Stmt::Asm(_) => Err("inline assembly".to_string()),
+            Stmt::Defer(_) => Err("defer".to_string()),
+            Stmt::EntropyAssert(_) => Err("entropy assertions".to_string()),
+        }
+    }
+
+    fn expr_pure(expr: &Expr) -> Result<(), String> {
+        match expr {
+            Expr::Syscall(name, _) => Err(format!("syscall.{}", name)),
+            Expr::RawSyscall(_) => Err("syscall".to_string()),
+            Expr::Alloc(..) => Err("alloc".to_string()),
+            Expr::Free(..) => Err("free".to_string()),
+            Expr::Unary(UnaryOp::Deref, _) => Err("pointer dereference".to_string()),
+            Expr::Unary(UnaryOp::AddrOf, _) | Expr::AddrOf(_) => Err("address-of".to_string()),
+            Expr::PhysAddr(_) => Err("physical addresses".to_string()),
+            Expr::Assign(target, value) => match &**target {
+                Expr::Identifier(..) => expr_pure(value),
+                _ => Err("assignment through memory".to_string()),
+            },
+            Expr::Unary(_, e) | Expr::Cast(e, _) => expr_pure(e),
+            Expr::Binary(_, a, b, _) => {
+                expr_pure(a)?;
+                expr_pure(b)
+            }
+            Expr::Call(callee, args) => {
+                expr_pure(callee)?;
+                args.iter().try_for_each(expr_pure)
+            }
+            Expr::Loop(body) => body.iter().try_for_each(stmt_pure),
+            Expr::If(i) => {
+                expr_pure(&i.condition)?;
+                expr_pure(&i.then_expr)?;
+                expr_pure(&i.else_expr)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    f.body
+        .iter()
+        .try_for_each(stmt_pure)
+        .map_err(|what| ConstEvalError {
+            message: format!("const fn '{}' cannot use {}", f.name, what),
+        })
+}
+
+fn contains_call(expr: &Expr) -> bool {
+    struct FindCall(bool);
+    impl<'ast> Visitor<'ast> for FindCall {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if matches!(expr, Expr::Call(..)) {
+                self.0 = true;
+            } else if !self.0 {
+                walk_expr(self, expr);
+            }
+        }
+    }
+    let mut finder = FindCall(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+// Replaces each `sizeof`/`alignof`/`offsetof` the layouts can answer with
+// the usize literal it evaluates to, so code generation sees a plain
+// constant. Queries it cannot answer are left for the typechecker to report.
+fn fold_layout_queries(expr: &mut Expr, evaluator: &mut ConstEvaluator) {
+    match expr {
+        Expr::Sizeof(_) | Expr::Alignof(_) | Expr::Offsetof(..) => {
+            if let Ok(ConstValue::Int(v)) = evaluator.eval(expr) {
+                *expr = Expr::Literal(Literal::Int(v, IntSuffix::Usize));
+            }
+        }
+        Expr::Unary(_, e) | Expr::Cast(e, _) => fold_layout_queries(e, evaluator),
+        Expr::Binary(_, a, b, _) => {
+            fold_layout_queries(a, evaluator);
+            fold_layout_queries(b, evaluator);
+        }
+        Expr::If(i) => {
+            fold_layout_queries(&mut i.condition, evaluator);
+            fold_layout_queries(&mut i.then_expr, evaluator);
+            fold_layout_queries(&mut i.else_expr, evaluator);
+        }
+        _ => {}
+    }
+}
+
+fn int_suffix_for(ty: Option<&Type>) -> IntSuffix {
+    match ty {
+        Some(Type::I8) => IntSuffix::I8,
+        Some(Type::I16) => IntSuffix::I16,
+        Some(Type::I32) => IntSuffix::I32,
+        Some(Type::I64) => IntSuffix::I64,
+        Some(Type::U8) => IntSuffix::U8,
+        Some(Type::U16) => IntSuffix::U16,
+        Some(Type::U32) => IntSuffix::U32,
+        Some(Type::U64) => IntSuffix::U64,
+        Some(Type::Usize) => IntSuffix::Usize,
+        Some(Type::Isize) => IntSuffix::Isize,
+        _ => IntSuffix::None,
+    }
+}
+
+fn fold_value(
+    value: &mut Expr,
+    ty: Option<&Type>,
+    evaluator: &mut ConstEvaluator,
+) -> Result<Option<ConstValue>, ConstEvalError> {
+    if let Expr::ArrayLit(elements) = value {
+        let elem_ty = match ty {
+            Some(Type::Array(_, elem)) => Some(&**elem),
+            _ => None,
+        };
+        for element in elements {
+            fold_value(element, elem_ty, evaluator)?;
+        }
+        return Ok(None);
+    }
+    fold_layout_queries(value, evaluator);
+    if !contains_call(value) {
+        return Ok(evaluator.eval(value).ok());
+    }
+    let result = evaluator.eval(value)?;
+    *value = match result {
+        ConstValue::Int(v) => Expr::Literal(Literal::Int(v, int_suffix_for(ty))),
+        ConstValue::Bool(b) => Expr::Literal(Literal::Bool(b)),
+    };
+    Ok(Some(result))
+}
+
+// A static's initializer is stored in `.data` before the program runs, so
+// unlike a const it must fold all the way down to a literal
+fn fold_static(
+    value: &mut Expr,
+    ty: Option<&Type>,
+    evaluator: &mut ConstEvaluator,
+) -> Result<(), ConstEvalError> {
+    match value {
+        Expr::Literal(_) | Expr::Zeroed(_) => Ok(()),
+        Expr::ArrayLit(elements) => {
+            let elem_ty = match ty {
+                Some(Type::Array(_, elem)) => Some(&**elem),
+                _ => None,
+            };
+            elements
+                .iter_mut()
+                .try_for_each(|element| fold_static(element, elem_ty, evaluator))
+        }
+        _ => {
+            *value = match evaluator.eval(value)? {
+                ConstValue::Int(v) => Expr::Literal(Literal::Int(v, int_suffix_for(ty))),
+                ConstValue::Bool(b) => Expr::Literal(Literal::Bool(b)),
+            };
+            Ok(())
+        }
+    }
+}
+
+// The condition sees the consts declared before the assertion, like a const
+// initializer does
+fn check_static_assert(
+    assert: &StaticAssert,
+    functions: &HashMap<String, Function>,
+    globals: &HashMap<String, ConstValue>,
+    layouts: &TypeLayouts,
+) -> Result<(), ConstEvalError> {
+    let mut evaluator = ConstEvaluator::new(functions, globals, layouts);
+    let holds = evaluator
+        .eval(&assert.condition)
+        .and_then(ConstValue::as_bool)
+        .map_err(|e| ConstEvalError {
+            message: format!("in static_assert: {}", e.message),
+        })?;
+    if holds {
+        return Ok(());
+    }
+    Err(ConstEvalError {
+        message: match &assert.message {
+            Some(message) => format!("static_assert failed: {}", message),
+            None => "static_assert failed".to_string(),
+        },
+    })
+}
+
+/// Const-fold pass: checks every `const fn` for purity, replaces `const`
+/// initializers that call const fns with the literal they evaluate to, and
+/// checks every `static_assert`.
+pub fn fold_program(program: &mut Program) -> Result<(), ConstEvalError> {
+    let layouts = TypeLayouts::from_program(program);
+    let mut functions = HashMap::new();
+    for item in &program.items {
+        if let Item::Function(f) = item {
+            if f.attrs.contains(&FunctionAttribute::Const) {
+                check_const_fn(f)?;
+                functions.insert(f.name.clone(), f.clone());
+            }
+        }
+    }
+
+    let mut globals = HashMap::new();
+    for item in &mut program.items {
+        match item {
+            Item::Const(c) => {
+                let mut evaluator = ConstEvaluator::new(&functions, &globals, &layouts);
+                let folded =
+                    fold_value(&mut c.value, c.ty.as_deref(), &mut evaluator).map_err(|e| {
+                        ConstEvalError {
+                            message: format!("in const {}: {}", c.name, e.message),
+                        }
+                    })?;
+                if let Some(value) = folded {
+                    globals.insert(c.name.clone(), value);
+                }
+            }
+            Item::StaticAssert(a) => check_static_assert(a, &functions, &globals, &layouts)?,
+            _ => {}
+        }
+    }
+
+    for item in &mut program.items {
+        if let Item::Function(f) = item {
+            fold_stmts(&mut f.body, &functions, &globals, &layouts)?;
+        }
+    }
+    Ok(())
+}
+
+fn fold_stmts(
+    stmts: &mut [Stmt],
+    functions: &HashMap<String, Function>,
+    globals: &HashMap<String, ConstValue>,
+    layouts: &TypeLayouts,
+) -> Result<(), ConstEvalError> {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Const(c) => {
+                let mut evaluator = ConstEvaluator::new(functions, globals, layouts);
+                fold_value(&mut c.value, c.ty.as_deref(), &mut evaluator).map_err(|e| {
+                    ConstEvalError {
+                        message: format!("in const {}: {}", c.name, e.message),
+                    }
+                })?;
+            }
+            Stmt::Let(l) if l.is_static => {
+                if let Some(value) = &mut l.value {
+                    let mut evaluator = ConstEvaluator::new(functions, globals, layouts);
+                    fold_static(value, l.ty.as_deref(), &mut evaluator).map_err(|e| {
+                        ConstEvalError {
+                            message: format!("in static {}: {}", l.name, e.message),
+                        }
+                    })?;
+                }
+            }
+            Stmt::Block(stmts) => fold_stmts(stmts, functions, globals, layouts)?,
+            Stmt::If(i) => {
+                fold_stmts(&mut i.then_branch, functions, globals, layouts)?;
+                if let Some(else_branch) = &mut i.else_branch {
+                    fold_stmts(else_branch, functions, globals, layouts)?;
+                }
+            }
+            Stmt::While(w) => fold_stmts(&mut w.body, functions, globals, layouts)?,
+            Stmt::For(f) => fold_stmts(&mut f.body, functions, globals, layouts)?,
+            Stmt::Expr(Expr::Loop(body)) => fold_stmts(body, functions, globals, layouts)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
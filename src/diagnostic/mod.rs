@@ -0,0 +1,134 @@
+use crate::codegen::CodegenError;
+use crate::consteval::ConstEvalError;
+use crate::lexer::{LexError, Span};
+use crate::parser::ParseError;
+use crate::typecheck::{TypeError, TypeWarning};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The compiler phase a diagnostic was raised in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Lex,
+    Parse,
+    ConstEval,
+    Typecheck,
+    Codegen,
+    Link,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Lex => "lex",
+            Phase::Parse => "parse",
+            Phase::ConstEval => "consteval",
+            Phase::Typecheck => "typecheck",
+            Phase::Codegen => "codegen",
+            Phase::Link => "link",
+        }
+    }
+}
+
+/// A message from any compiler phase in one shape, so drivers and tools can
+/// report every failure the same way. Each phase keeps its own error type;
+/// they all convert into this.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    pub phase: Phase,
+}
+
+impl Diagnostic {
+    pub fn error(phase: Phase, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            phase,
+        }
+    }
+
+    /// Fills in the line and column of a span that only carries byte
+    /// offsets (parse errors are reported that way).
+    pub fn locate(mut self, source: &str) -> Self {
+        if let Some(span) = &mut self.span {
+            if span.line == 0 {
+                let before = &source[..span.start.min(source.len())];
+                span.line = before.matches('\n').count() + 1;
+                span.column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}[{}]", severity, self.phase.name())?;
+        if let Some(span) = &self.span {
+            write!(f, " at {}:{}", span.line, span.column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl From<LexError> for Diagnostic {
+    fn from(e: LexError) -> Self {
+        Diagnostic::error(Phase::Lex, e.message, Some(e.span))
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(e: ParseError) -> Self {
+        let span = Span {
+            start: e.span.0,
+            end: e.span.1,
+            line: 0,
+            column: 0,
+        };
+        Diagnostic::error(Phase::Parse, e.message, Some(span))
+    }
+}
+
+impl From<ConstEvalError> for Diagnostic {
+    fn from(e: ConstEvalError) -> Self {
+        Diagnostic::error(Phase::ConstEval, e.message, None)
+    }
+}
+
+impl From<TypeError> for Diagnostic {
+    fn from(e: TypeError) -> Self {
+        Diagnostic::error(Phase::Typecheck, e.message, e.span)
+    }
+}
+
+impl From<TypeWarning> for Diagnostic {
+    fn from(w: TypeWarning) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: w.message,
+            span: w.span,
+            phase: Phase::Typecheck,
+        }
+    }
+}
+
+impl From<CodegenError> for Diagnostic {
+    fn from(e: CodegenError) -> Self {
+        Diagnostic::error(Phase::Codegen, e.message, e.span)
+    }
+}
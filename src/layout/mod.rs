@@ -0,0 +1,223 @@
+use crate::ast::*;
+use std::collections::HashMap;
+
+// Where a struct field lives: its byte offset and, for a bitfield, the bit
+// range it occupies inside the storage unit at that offset
+#[derive(Debug, Clone, Copy)]
+pub struct FieldSlot {
+    pub offset: usize,
+    pub bitfield: Option<Bitfield>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bitfield {
+    pub unit_bits: u8,
+    pub shift: u8,
+    pub width: u8,
+}
+
+impl Bitfield {
+    pub fn unit_type(&self) -> Type {
+        match self.unit_bits {
+            8 => Type::U8,
+            16 => Type::U16,
+            32 => Type::U32,
+            _ => Type::U64,
+        }
+    }
+
+    pub fn mask(&self) -> u64 {
+        if self.width >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width) - 1
+        }
+    }
+}
+
+/// The struct, union and enum declarations seen so far, and the size,
+/// alignment and field offsets they resolve to. Code generation and
+/// compile-time `sizeof`/`alignof`/`offsetof` share this one layout.
+#[derive(Debug, Clone, Default)]
+pub struct TypeLayouts {
+    structs: HashMap<String, Struct>,
+    unions: HashMap<String, Union>,
+    enums: HashMap<String, Enum>,
+}
+
+impl TypeLayouts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Layouts of every type declared in `program`
+    pub fn from_program(program: &Program) -> Self {
+        let mut layouts = Self::new();
+        for item in &program.items {
+            layouts.declare(item);
+        }
+        layouts
+    }
+
+    /// Records `item` if it declares a struct, union or enum
+    pub fn declare(&mut self, item: &Item) {
+        match item {
+            Item::Struct(s) => {
+                self.structs.insert(s.name.clone(), s.clone());
+            }
+            Item::Union(u) => {
+                self.unions.insert(u.name.clone(), u.clone());
+            }
+            Item::Enum(e) => {
+                self.enums.insert(e.name.clone(), e.clone());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn struct_type(&self, name: &str) -> Option<&Struct> {
+        self.structs.get(name)
+    }
+
+    pub fn enum_type(&self, name: &str) -> Option<&Enum> {
+        self.enums.get(name)
+    }
+
+    /// Whether every named type `ty` is laid out from is declared. Pointers
+    /// are always 8 bytes, so their pointee need not be.
+    pub fn resolves(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Named(name) => {
+                self.structs.contains_key(name)
+                    || self.unions.contains_key(name)
+                    || self.enums.contains_key(name)
+            }
+            Type::Array(_, elem) => self.resolves(elem),
+            _ => true,
+        }
+    }
+
+    // Field slots, total size and alignment of a struct. Alignment
+    // overrides only ever raise the natural alignment; a packed struct
+    // treats every field's natural alignment as 1. Consecutive sub-unit
+    // BitInt fields share a storage unit like C bitfields while their
+    // widths fit.
+    pub fn struct_layout(&self, s: &Struct) -> (Vec<FieldSlot>, usize, usize) {
+        let mut slots = Vec::with_capacity(s.fields.len());
+        let mut offset = 0usize;
+        let mut align = 1usize;
+        // (offset, size in bytes, bits used) of the open bitfield unit
+        let mut unit: Option<(usize, usize, u8)> = None;
+        for field in &s.fields {
+            let size = self.size_of(&field.ty);
+            let width = match *field.ty {
+                Type::BitInt(bits, _) if field.align.is_none() && (bits as usize) < size * 8 => {
+                    Some(bits)
+                }
+                _ => None,
+            };
+            if let (Some(width), Some((unit_offset, unit_size, used))) = (width, unit) {
+                if unit_size == size && (used + width) as usize <= size * 8 {
+                    slots.push(FieldSlot {
+                        offset: unit_offset,
+                        bitfield: Some(Bitfield {
+                            unit_bits: (size * 8) as u8,
+                            shift: used,
+                            width,
+                        }),
+                    });
+                    unit = Some((unit_offset, unit_size, used + width));
+                    continue;
+                }
+            }
+            let natural = if s.packed {
+                1
+            } else {
+                self.align_of(&field.ty)
+            };
+            let field_align = natural.max(field.align.unwrap_or(1));
+            offset = offset.next_multiple_of(field_align);
+            slots.push(FieldSlot {
+                offset,
+                bitfield: width.map(|width| Bitfield {
+                    unit_bits: (size * 8) as u8,
+                    shift: 0,
+                    width,
+                }),
+            });
+            unit = width.map(|width| (offset, size, width));
+            offset += size;
+            align = align.max(field_align);
+        }
+        let align = align.max(s.align.unwrap_or(1));
+        (slots, offset.next_multiple_of(align), align)
+    }
+
+    pub fn size_of(&self, ty: &Type) -> usize {
+        match ty {
+            Type::Named(name) => {
+                if let Some(s) = self.structs.get(name) {
+                    self.struct_layout(s).1
+                } else if let Some(u) = self.unions.get(name) {
+                    let size = u
+                        .variants
+                        .iter()
+                        .map(|v| self.size_of(&v.ty))
+                        .max()
+                        .unwrap_or(0);
+                    size.next_multiple_of(self.align_of(ty))
+                } else if self.enums.contains_key(name) {
+                    Type::I64.size()
+                } else {
+                    0
+                }
+            }
+            Type::Array(n, elem) => n * self.size_of(elem),
+            _ => ty.size(),
+        }
+    }
+
+    pub fn align_of(&self, ty: &Type) -> usize {
+        match ty {
+            Type::Named(name) => {
+                if let Some(s) = self.structs.get(name) {
+                    self.struct_layout(s).2
+                } else if let Some(u) = self.unions.get(name) {
+                    u.variants
+                        .iter()
+                        .map(|v| self.align_of(&v.ty))
+                        .max()
+                        .unwrap_or(1)
+                } else if self.enums.contains_key(name) {
+                    Type::I64.align()
+                } else {
+                    1
+                }
+            }
+            Type::Array(_, elem) => self.align_of(elem),
+            _ => ty.align(),
+        }
+    }
+
+    // A bitfield reports the offset of its storage unit
+    pub fn field_info(&self, ty: &Type, field: &str) -> Option<(FieldSlot, Type)> {
+        let Type::Named(name) = ty else {
+            return None;
+        };
+        let s = self.structs.get(name)?;
+        let index = s.fields.iter().position(|f| f.name == field)?;
+        Some((self.struct_layout(s).0[index], *s.fields[index].ty.clone()))
+    }
+
+    // Offset of `a.b.c` from the start of `ty`: the sum of each step's offset
+    pub fn field_path_offset(&self, ty: &Type, path: &str) -> Option<usize> {
+        let mut current = ty.clone();
+        let mut offset = 0;
+        for field in path.split('.') {
+            let (slot, field_ty) = self.field_info(&current, field)?;
+            offset += slot.offset;
+            current = field_ty;
+        }
+        Some(offset)
+    }
+}
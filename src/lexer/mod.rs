@@ -1,7 +1,7 @@
 use logos::Logos;
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -16,7 +16,28 @@ pub struct Token {
     pub span: Span,
 }
 
+/// Why lexing stopped. Input that matches no token gets the default (empty)
+/// message; callbacks that reject a token they matched explain why.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Lex error at {}:{}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for LexError {}
+
 #[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(error = LexError)]
 #[logos(skip r"[ \t\r\n]+")]
 #[logos(skip r"//[^\n]*\n")]
 #[logos(skip r"/\*[^*]*\*+(?:[^/*][^*]*\*+)*/")]
@@ -29,6 +50,12 @@ pub enum TokenKind {
     Const,
     #[token("var")]
     Var,
+    #[token("static")]
+    Static,
+    #[token("static_assert")]
+    StaticAssert,
+    #[token("extern")]
+    Extern,
     #[token("syscall")]
     Syscall,
     #[token("if")]
@@ -39,6 +66,8 @@ pub enum TokenKind {
     While,
     #[token("for")]
     For,
+    #[token("loop")]
+    Loop,
     #[token("return")]
     Return,
     #[token("break")]
@@ -57,6 +86,8 @@ pub enum TokenKind {
     Alignof,
     #[token("offsetof")]
     Offsetof,
+    #[token("zeroed")]
+    Zeroed,
     #[token("asm")]
     Asm,
     #[token("noreturn")]
@@ -115,13 +146,26 @@ pub enum TokenKind {
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*")]
     Identifier,
 
-    #[regex(r"[0-9][0-9_]*", priority = 1)]
+    #[regex(
+        r"[0-9][0-9_]*(i8|i16|i32|i64|isize|u8|u16|u32|u64|usize)?",
+        priority = 1
+    )]
     Integer,
-    #[regex(r"0x[0-9a-fA-F_]+")]
+    #[regex(r"0x[0-9a-fA-F_]+(i8|i16|i32|i64|isize|u8|u16|u32|u64|usize)?")]
     HexInteger,
-    #[regex(r"0o[0-7_]+")]
+    #[regex(
+        r"0x[0-9a-fA-F][0-9a-fA-F_]*(\.[0-9a-fA-F_]*)?[pP][+-]?[0-9][0-9_]*(f32|f64)?",
+        check_hex_float
+    )]
+    HexFloat,
+    #[regex(
+        r"[0-9][0-9_]*(\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?|[eE][+-]?[0-9][0-9_]*)(f32|f64)?",
+        check_float
+    )]
+    Float,
+    #[regex(r"0o[0-7_]+(i8|i16|i32|i64|isize|u8|u16|u32|u64|usize)?")]
     OctInteger,
-    #[regex(r"0b[01_]+")]
+    #[regex(r"0b[01_]+(i8|i16|i32|i64|isize|u8|u16|u32|u64|usize)?")]
     BinInteger,
     #[regex(r#""([^"]|\\.)*""#)]
     String,
@@ -194,6 +238,8 @@ pub enum TokenKind {
     RBracket,
     #[token(".")]
     Dot,
+    #[token("...")]
+    Ellipsis,
     #[token("->")]
     Arrow,
     #[token("@")]
@@ -255,11 +301,15 @@ impl fmt::Display for TokenKind {
             TokenKind::Let => write!(f, "let"),
             TokenKind::Const => write!(f, "const"),
             TokenKind::Var => write!(f, "var"),
+            TokenKind::Static => write!(f, "static"),
+            TokenKind::StaticAssert => write!(f, "static_assert"),
+            TokenKind::Extern => write!(f, "extern"),
             TokenKind::Syscall => write!(f, "syscall"),
             TokenKind::If => write!(f, "if"),
             TokenKind::Else => write!(f, "else"),
             TokenKind::While => write!(f, "while"),
             TokenKind::For => write!(f, "for"),
+            TokenKind::Loop => write!(f, "loop"),
             TokenKind::Return => write!(f, "return"),
             TokenKind::Break => write!(f, "break"),
             TokenKind::Continue => write!(f, "continue"),
@@ -269,6 +319,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Sizeof => write!(f, "sizeof"),
             TokenKind::Alignof => write!(f, "alignof"),
             TokenKind::Offsetof => write!(f, "offsetof"),
+            TokenKind::Zeroed => write!(f, "zeroed"),
             TokenKind::Asm => write!(f, "asm"),
             TokenKind::Noreturn => write!(f, "noreturn"),
             TokenKind::Pub => write!(f, "pub"),
@@ -299,6 +350,8 @@ impl fmt::Display for TokenKind {
             TokenKind::Identifier => write!(f, "identifier"),
             TokenKind::Integer => write!(f, "integer"),
             TokenKind::HexInteger => write!(f, "hex integer"),
+            TokenKind::HexFloat => write!(f, "hex float"),
+            TokenKind::Float => write!(f, "float"),
             TokenKind::OctInteger => write!(f, "octal integer"),
             TokenKind::BinInteger => write!(f, "binary integer"),
             TokenKind::String => write!(f, "string"),
@@ -336,6 +389,7 @@ impl fmt::Display for TokenKind {
             TokenKind::LBracket => write!(f, "["),
             TokenKind::RBracket => write!(f, "]"),
             TokenKind::Dot => write!(f, "."),
+            TokenKind::Ellipsis => write!(f, "..."),
             TokenKind::At => write!(f, "@"),
             TokenKind::Arrow => write!(f, "->"),
             TokenKind::Question => write!(f, "?"),
@@ -388,34 +442,140 @@ impl TokenKind {
     }
 }
 
-pub fn lex(source: &str) -> Result<Vec<Token>, Vec<()>> {
+fn check_hex_float(lex: &mut logos::Lexer<TokenKind>) -> Result<(), LexError> {
+    let text = lex.slice();
+    match parse_hex_float(strip_float_suffix(text)) {
+        Some(_) => Ok(()),
+        None => Err(LexError {
+            message: format!("Hex float literal {} is out of range for f64", text),
+            span: Span::default(),
+        }),
+    }
+}
+
+fn check_float(lex: &mut logos::Lexer<TokenKind>) -> Result<(), LexError> {
+    let text = lex.slice();
+    match parse_float(strip_float_suffix(text)) {
+        Some(_) => Ok(()),
+        None => Err(LexError {
+            message: format!("Float literal {} is out of range for f64", text),
+            span: Span::default(),
+        }),
+    }
+}
+
+fn strip_float_suffix(text: &str) -> &str {
+    text.strip_suffix("f32")
+        .or_else(|| text.strip_suffix("f64"))
+        .unwrap_or(text)
+}
+
+/// Value of a decimal float literal such as `1.5` or `2e-3` (no suffix),
+/// correctly rounded to f64. `None` when it overflows to infinity.
+pub fn parse_float(text: &str) -> Option<f64> {
+    text.replace('_', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|val| val.is_finite())
+}
+
+/// Value of a hex float literal such as `0x1.8p3` (no suffix): the hex
+/// mantissa scaled by two to the decimal exponent, rounded once to the
+/// nearest f64. `None` when the value overflows or underflows to zero.
+pub fn parse_hex_float(text: &str) -> Option<f64> {
+    let body = text.strip_prefix("0x")?;
+    let (mantissa, exponent) = body.split_once(['p', 'P'])?;
+    let mut scale: i64 = exponent.replace('_', "").parse().ok()?;
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    // Keep the first 60 bits of mantissa exactly; later digits only need to
+    // say whether anything nonzero was dropped, for rounding
+    let mut bits: u64 = 0;
+    let mut sticky = false;
+    for (c, fraction) in int_part
+        .chars()
+        .map(|c| (c, false))
+        .chain(frac_part.chars().map(|c| (c, true)))
+        .filter(|(c, _)| *c != '_')
+    {
+        let digit = c.to_digit(16)? as u64;
+        if bits >> 60 == 0 {
+            bits = bits << 4 | digit;
+            if fraction {
+                scale -= 4;
+            }
+        } else {
+            sticky |= digit != 0;
+            if !fraction {
+                scale += 4;
+            }
+        }
+    }
+    if bits == 0 {
+        return Some(0.0);
+    }
+    if sticky {
+        bits |= 1;
+    }
+
+    // Scaling by a power of two is exact; steps of 2^±1000 stay normal
+    let mut value = bits as f64;
+    while scale != 0 {
+        let step = scale.clamp(-1000, 1000);
+        value *= 2f64.powi(step as i32);
+        scale -= step;
+        if value == 0.0 || value.is_infinite() {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+pub fn lex(source: &str) -> Result<Vec<Token>, LexError> {
     let mut lexer = TokenKind::lexer(source);
     let mut tokens = Vec::new();
     let mut line = 1;
     let mut column = 1;
+    let mut counted = 0;
 
     while let Some(result) = lexer.next() {
+        let range = lexer.span();
+        // Walk everything since the previous token (its text plus any skipped
+        // whitespace and comments) so positions stay accurate across lines.
+        for c in source[counted..range.start].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        counted = range.start;
+
         match result {
             Ok(kind) => {
                 let text = lexer.slice().to_string();
                 let span = Span {
-                    start: lexer.span().start,
-                    end: lexer.span().end,
+                    start: range.start,
+                    end: range.end,
                     line,
                     column,
                 };
                 tokens.push(Token { kind, text, span });
-                column += lexer.slice().len();
             }
-            Err(()) => {
-                return Err(vec![]);
+            Err(mut e) => {
+                if e.message.is_empty() {
+                    e.message = format!("Unrecognized token '{}'", lexer.slice());
+                }
+                e.span = Span {
+                    start: range.start,
+                    end: range.end,
+                    line,
+                    column,
+                };
+                return Err(e);
             }
         }
-
-        if lexer.slice().contains('\n') {
-            line += 1;
-            column = 1;
-        }
     }
 
     Ok(tokens)
@@ -1,47 +1,200 @@
 pub mod ast;
 pub mod codegen;
+pub mod consteval;
+pub mod diagnostic;
+pub mod layout;
 pub mod lexer;
 pub mod parser;
 pub mod typecheck;
 
+use diagnostic::{Diagnostic, Phase};
 use std::fs;
 use std::path::Path;
 
-pub fn compile_file(source_path: &str, output_path: Option<&str>) -> Result<(), anyhow::Error> {
-    let source = fs::read_to_string(source_path)?;
+// Lex, parse, const-fold and typecheck `source`, keeping any warnings
+fn frontend(source: &str) -> Result<(ast::Program, Vec<Diagnostic>), Diagnostic> {
+    let tokens = lexer::lex(source)?;
+    let mut ast = parser::parse(&tokens).map_err(|e| Diagnostic::from(e).locate(source))?;
+    consteval::fold_program(&mut ast)?;
+    let (typed_ast, warnings) = typecheck::typecheck_with_warnings(&ast)?;
+    Ok((
+        typed_ast,
+        warnings.into_iter().map(Diagnostic::from).collect(),
+    ))
+}
 
-    let tokens = lexer::lex(&source).map_err(|e| anyhow::anyhow!("Lexing failed: {:?}", e))?;
+/// Runs every phase up to and including typechecking, reporting the first
+/// failure as a [`Diagnostic`]. On success, returns the warnings raised.
+pub fn check_source(source: &str) -> Result<Vec<Diagnostic>, Diagnostic> {
+    frontend(source).map(|(_, warnings)| warnings)
+}
+
+/// Compiles `source` to an object and the warnings raised on the way,
+/// reporting the first failure from any phase as a [`Diagnostic`].
+pub fn compile_source(
+    source: &str,
+    options: &codegen::CodegenOptions,
+) -> Result<(codegen::AuraObject, Vec<Diagnostic>), Diagnostic> {
+    let (typed_ast, warnings) = frontend(source)?;
+    let object = codegen::generate_with_options(&typed_ast, options)?;
+    Ok((object, warnings))
+}
 
-    let ast = parser::parse(&tokens).map_err(|e| anyhow::anyhow!("Parsing failed: {}", e))?;
+pub fn compile_file(source_path: &str, output_path: Option<&str>) -> Result<(), anyhow::Error> {
+    compile_file_with_options(
+        source_path,
+        output_path,
+        &codegen::CodegenOptions::default(),
+    )
+}
 
-    let typed_ast =
-        typecheck::typecheck(&ast).map_err(|e| anyhow::anyhow!("Type checking failed: {}", e))?;
+pub fn compile_file_with_options(
+    source_path: &str,
+    output_path: Option<&str>,
+    options: &codegen::CodegenOptions,
+) -> Result<(), anyhow::Error> {
+    compile_files_with_options(&[source_path], output_path, options)
+}
 
-    let object = codegen::generate(&typed_ast)
-        .map_err(|e| anyhow::anyhow!("Code generation failed: {}", e))?;
+/// Compiles each source file on its own and links the objects with
+/// [`codegen::link`], in order. A unit calls a function from another one
+/// through an `extern fn` declaration. The output defaults to the first
+/// source's path with the extension for `options.emit`. With
+/// `options.warnings_as_errors`, any warning fails the build before
+/// anything is written.
+pub fn compile_files_with_options(
+    source_paths: &[&str],
+    output_path: Option<&str>,
+    options: &codegen::CodegenOptions,
+) -> Result<(), anyhow::Error> {
+    let mut objects = Vec::with_capacity(source_paths.len());
+    let mut warning_count = 0;
+    for source_path in source_paths {
+        let source = fs::read_to_string(source_path)?;
+        let (object, warnings) = compile_source(&source, options)?;
+        for warning in &warnings {
+            eprintln!("{}", warning);
+        }
+        warning_count += warnings.len();
+        objects.push(object);
+    }
+    // Every unit is still compiled first so all of its warnings are shown
+    if options.warnings_as_errors && warning_count > 0 {
+        return Err(anyhow::anyhow!(
+            "{} warning(s) treated as errors (--warnings-as-errors)",
+            warning_count
+        ));
+    }
+    let mut object =
+        codegen::link(&objects).map_err(|e| Diagnostic::error(Phase::Link, e.message, e.span))?;
+    if options.strip {
+        object.strip_symbols();
+    }
 
+    let extension = match options.emit {
+        codegen::EmitKind::Aura => "aura",
+        codegen::EmitKind::Object => "o",
+        codegen::EmitKind::Executable => "",
+    };
     let output = match output_path {
         Some(p) => Path::new(p).to_path_buf(),
-        None => Path::new(source_path).with_extension("aura"),
+        None => Path::new(source_paths[0]).with_extension(extension),
     };
 
-    codegen::write_aura_binary(&object, &output)?;
+    match options.emit {
+        codegen::EmitKind::Aura => codegen::write_aura_binary(&object, &output)?,
+        codegen::EmitKind::Object => codegen::write_elf_object(&object, &output)?,
+        codegen::EmitKind::Executable => codegen::write_elf_binary(&object, &output)?,
+    }
 
-    println!("Compiled: {} -> {}", source_path, output.display());
+    println!(
+        "Compiled: {} -> {}",
+        source_paths.join(", "),
+        output.display()
+    );
     Ok(())
 }
 
+/// Runs the full pipeline on `source` and returns a linked image for address
+/// `base` instead of an `.aura` file. Execution starts at `base` plus the entry
+/// function's offset in text: the `@entry` function, else `main`, else the
+/// first function. The entry function ends the process with the `exit`
+/// syscall rather than returning.
+///
+/// The caller must place the bytes at `base` and make that memory executable;
+/// see [`codegen::link_in_memory`] for the layout. Warnings are dropped; use
+/// [`compile_source`] to see them.
+pub fn compile_to_memory(source: &str, base: u64) -> Result<Vec<u8>, anyhow::Error> {
+    let (object, _) = compile_source(source, &codegen::CodegenOptions::default())?;
+    codegen::link_in_memory(&object, base)
+        .map_err(|e| Diagnostic::error(Phase::Link, e.message, e.span).into())
+}
+
+/// Builds `path` as an executable and runs it with `args`, returning its
+/// exit status. `path` is either Aura source, compiled like `build` does,
+/// or an existing `.aura` binary, told apart by the binary's magic. The
+/// ELF is written to the temp directory and removed once the program ends.
+/// A program killed by a signal reports 128 plus the signal number, as a
+/// shell would.
+pub fn run_file(path: &str, args: &[String]) -> Result<i32, anyhow::Error> {
+    let bytes = fs::read(path)?;
+    let object = if bytes.starts_with(b"AURA") {
+        codegen::aura_object_from_bytes(&bytes)?
+    } else {
+        let source = String::from_utf8(bytes)?;
+        let (object, warnings) = compile_source(&source, &codegen::CodegenOptions::default())?;
+        for warning in &warnings {
+            eprintln!("{}", warning);
+        }
+        codegen::link(&[object]).map_err(|e| Diagnostic::error(Phase::Link, e.message, e.span))?
+    };
+
+    let stem = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("program");
+    let executable = std::env::temp_dir().join(format!("aura_run_{}_{}", stem, std::process::id()));
+    codegen::write_elf_binary(&object, &executable)?;
+    let status = std::process::Command::new(&executable).args(args).status();
+    let _ = fs::remove_file(&executable);
+    let status = status?;
+
+    if let Some(code) = status.code() {
+        return Ok(code);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Ok(128 + signal);
+        }
+    }
+    Err(anyhow::anyhow!("{} ended without an exit status", path))
+}
+
 pub fn typecheck_file(source_path: &str) -> Result<(), anyhow::Error> {
     let source = fs::read_to_string(source_path)?;
-    let tokens = lexer::lex(&source).map_err(|e| anyhow::anyhow!("Lexing failed: {:?}", e))?;
-    let ast = parser::parse(&tokens).map_err(|e| anyhow::anyhow!("Parsing failed: {}", e))?;
-    let _ =
-        typecheck::typecheck(&ast).map_err(|e| anyhow::anyhow!("Type checking failed: {}", e))?;
+    for warning in check_source(&source)? {
+        eprintln!("{}", warning);
+    }
     Ok(())
 }
 
 pub fn dump_binary(binary_path: &str) -> Result<(), anyhow::Error> {
+    dump_binary_with_options(binary_path, &codegen::DumpOptions::default())
+}
+
+pub fn dump_binary_with_options(
+    binary_path: &str,
+    options: &codegen::DumpOptions,
+) -> Result<(), anyhow::Error> {
+    let data = fs::read(binary_path)?;
+    codegen::AuraBinary::dump_with_options(&data, options)?;
+    Ok(())
+}
+
+pub fn disasm_binary(binary_path: &str) -> Result<(), anyhow::Error> {
     let data = fs::read(binary_path)?;
-    codegen::AuraBinary::dump(&data)?;
+    codegen::AuraBinary::disasm(&data)?;
     Ok(())
 }
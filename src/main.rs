@@ -7,12 +7,22 @@ fn main() {
         eprintln!("Aura Compiler v0.1.0");
         eprintln!("Usage: aura <command> [options]");
         eprintln!("Commands:");
-        eprintln!("  build [options] <source.aura>  Compile source to .aura binary");
-        eprintln!("  run <binary.aura>              Build and run");
+        eprintln!("  build [options] <sources...>   Compile and link sources to .aura binary");
+        eprintln!("  run <file.aura> [args...]      Build and run, exiting with its status");
         eprintln!("  check <source.aura>            Type check only");
-        eprintln!("  dump <binary.aura>             Dump binary info");
+        eprintln!("  dump [options] <binary.aura>   Dump binary info");
+        eprintln!("  disasm <binary.aura>           Disassemble the text section");
         eprintln!("Options:");
         eprintln!("  -o <output.aura>  Specify output file");
+        eprintln!("  --checked-arith   Trap on integer overflow instead of wrapping");
+        eprintln!("  --load-base <addr>  Virtual address .data is mapped at (page aligned)");
+        eprintln!("  --strip           Omit the symbol table from the binary");
+        eprintln!("  --emit <aura|obj|exe>  Output format: .aura binary, ELF relocatable or ELF executable");
+        eprintln!("  --format elf      Same as --emit exe");
+        eprintln!("  --warnings-as-errors, -Werror  Fail the build on any warning");
+        eprintln!("  --skip <n>        (dump) Start each hex view at section offset n");
+        eprintln!("  --length <n>      (dump) Show at most n bytes of each section");
+        eprintln!("  --annotate        (dump) List integer data symbols with their values");
         process::exit(1);
     }
 
@@ -20,36 +30,93 @@ fn main() {
 
     match command.as_str() {
         "build" => {
-            let mut source = None;
+            let mut sources = Vec::new();
             let mut output = None;
+            let mut options = aura_compiler::codegen::CodegenOptions::default();
             let mut i = 2;
             while i < args.len() {
                 if args[i] == "-o" && i + 1 < args.len() {
                     output = Some(args[i + 1].clone());
                     i += 2;
+                } else if args[i] == "--checked-arith" {
+                    options.checked_arith = true;
+                    i += 1;
+                } else if args[i] == "--strip" {
+                    options.strip = true;
+                    i += 1;
+                } else if args[i] == "--warnings-as-errors" || args[i] == "-Werror" {
+                    options.warnings_as_errors = true;
+                    i += 1;
+                } else if args[i] == "--emit" && i + 1 < args.len() {
+                    options.emit = match args[i + 1].as_str() {
+                        "aura" => aura_compiler::codegen::EmitKind::Aura,
+                        "obj" => aura_compiler::codegen::EmitKind::Object,
+                        "exe" => aura_compiler::codegen::EmitKind::Executable,
+                        other => {
+                            eprintln!("Unknown emit kind: {} (expected aura, obj or exe)", other);
+                            process::exit(1);
+                        }
+                    };
+                    i += 2;
+                } else if args[i] == "--format" && i + 1 < args.len() {
+                    if args[i + 1] != "elf" {
+                        eprintln!("Unknown format: {} (expected elf)", args[i + 1]);
+                        process::exit(1);
+                    }
+                    options.emit = aura_compiler::codegen::EmitKind::Executable;
+                    i += 2;
+                } else if args[i] == "--load-base" && i + 1 < args.len() {
+                    match parse_address(&args[i + 1]) {
+                        Some(addr) if addr % 4096 == 0 => options.load_base = addr,
+                        _ => {
+                            eprintln!(
+                                "Invalid load base: {} (expected a page-aligned address)",
+                                args[i + 1]
+                            );
+                            process::exit(1);
+                        }
+                    }
+                    i += 2;
                 } else if args[i].starts_with('-') {
                     eprintln!("Unknown option: {}", args[i]);
                     process::exit(1);
                 } else {
-                    source = Some(args[i].clone());
+                    sources.push(args[i].as_str());
                     i += 1;
                 }
             }
 
-            let source = match source {
-                Some(s) => s,
-                None => {
-                    eprintln!("Usage: aura build [-o <output.aura>] <source.aura>");
-                    process::exit(1);
-                }
-            };
+            if sources.is_empty() {
+                eprintln!(
+                    "Usage: aura build [-o <output.aura>] [--checked-arith] [--load-base <addr>] [--strip] [--emit <aura|obj|exe>] [--warnings-as-errors] <source.aura>..."
+                );
+                process::exit(1);
+            }
 
-            let result = aura_compiler::compile_file(&source, output.as_deref());
+            let result =
+                aura_compiler::compile_files_with_options(&sources, output.as_deref(), &options);
             if let Err(e) = result {
                 eprintln!("Error: {:?}", e);
                 process::exit(1);
             }
         }
+        "run" => {
+            if args.len() < 3 {
+                eprintln!("Usage: aura run <file.aura> [--] [args...]");
+                process::exit(1);
+            }
+            // Everything after the file goes to the program; a leading `--`
+            // is only a separator
+            let rest = &args[3..];
+            let program_args = rest.strip_prefix(&["--".to_string()]).unwrap_or(rest);
+            match aura_compiler::run_file(&args[2], program_args) {
+                Ok(code) => process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {:?}", e);
+                    process::exit(1);
+                }
+            }
+        }
         "check" => {
             if args.len() < 3 {
                 eprintln!("Usage: aura check <source.aura>");
@@ -63,11 +130,55 @@ fn main() {
             println!("Type check passed");
         }
         "dump" => {
+            let mut binary = None;
+            let mut options = aura_compiler::codegen::DumpOptions::default();
+            let mut i = 2;
+            while i < args.len() {
+                if (args[i] == "--skip" || args[i] == "--length") && i + 1 < args.len() {
+                    let value = match parse_address(&args[i + 1]) {
+                        Some(value) => value as usize,
+                        None => {
+                            eprintln!("Invalid {} value: {}", args[i], args[i + 1]);
+                            process::exit(1);
+                        }
+                    };
+                    if args[i] == "--skip" {
+                        options.skip = value;
+                    } else {
+                        options.length = Some(value);
+                    }
+                    i += 2;
+                } else if args[i] == "--annotate" {
+                    options.annotate = true;
+                    i += 1;
+                } else if args[i].starts_with('-') {
+                    eprintln!("Unknown option: {}", args[i]);
+                    process::exit(1);
+                } else {
+                    binary = Some(args[i].clone());
+                    i += 1;
+                }
+            }
+            let binary = match binary {
+                Some(b) => b,
+                None => {
+                    eprintln!(
+                        "Usage: aura dump [--skip <n>] [--length <n>] [--annotate] <binary.aura>"
+                    );
+                    process::exit(1);
+                }
+            };
+            if let Err(e) = aura_compiler::dump_binary_with_options(&binary, &options) {
+                eprintln!("Error: {:?}", e);
+                process::exit(1);
+            }
+        }
+        "disasm" => {
             if args.len() < 3 {
-                eprintln!("Usage: aura dump <binary.aura>");
+                eprintln!("Usage: aura disasm <binary.aura>");
                 process::exit(1);
             }
-            if let Err(e) = aura_compiler::dump_binary(&args[2]) {
+            if let Err(e) = aura_compiler::disasm_binary(&args[2]) {
                 eprintln!("Error: {:?}", e);
                 process::exit(1);
             }
@@ -78,3 +189,10 @@ fn main() {
         }
     }
 }
+
+fn parse_address(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(&hex.replace('_', ""), 16).ok(),
+        None => text.replace('_', "").parse().ok(),
+    }
+}
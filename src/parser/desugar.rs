@@ -0,0 +1,295 @@
+use crate::ast::*;
+use crate::lexer::Span;
+use std::collections::HashMap;
+
+/// Rewrites `value.method(args)` into `method(&value, args)` when `method` is
+/// a free function whose first parameter points at the struct type of
+/// `value` and that struct has no field of that name. The parser has no type
+/// information, so the type of `value` is read off declarations: parameters,
+/// annotated lets and globals, and what a field, element, dereference, cast,
+/// `zeroed` or call of those yields. A receiver whose type can't be told that
+/// way is left as a field access. Dispatch is purely static; no vtables are
+/// involved.
+pub(crate) fn desugar_method_calls(program: &mut Program) {
+    let structs: HashMap<String, Vec<(String, Type)>> = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(s) => {
+                let fields = s
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.clone(), (*field.ty).clone()))
+                    .collect();
+                Some((s.name.clone(), fields))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut methods = HashMap::new();
+    let mut functions = HashMap::new();
+    let mut globals = HashMap::new();
+    for item in &program.items {
+        match item {
+            Item::Function(f) => {
+                functions.insert(f.name.clone(), (*f.return_type).clone());
+                let receiver = f.params.first().map(|p| &*p.ty);
+                if let Some(Type::Ptr(inner) | Type::MutPtr(inner) | Type::ConstPtr(inner)) =
+                    receiver
+                {
+                    if let Type::Named(struct_name) = &**inner {
+                        let shadowed_by_field = structs
+                            .get(struct_name)
+                            .is_none_or(|fields| fields.iter().any(|(name, _)| *name == f.name));
+                        if !shadowed_by_field {
+                            methods.insert(f.name.clone(), struct_name.clone());
+                        }
+                    }
+                }
+            }
+            Item::Const(ConstDecl {
+                name, ty: Some(ty), ..
+            })
+            | Item::Var(VarDecl {
+                name, ty: Some(ty), ..
+            }) => {
+                globals.insert(name.clone(), Some((**ty).clone()));
+            }
+            _ => {}
+        }
+    }
+
+    if methods.is_empty() {
+        return;
+    }
+
+    let mut desugar = Desugar {
+        structs,
+        methods,
+        functions,
+        scopes: vec![globals],
+    };
+    for item in &mut program.items {
+        match item {
+            Item::Function(f) => {
+                let params = f
+                    .params
+                    .iter()
+                    .map(|p| (p.name.clone(), Some((*p.ty).clone())))
+                    .collect();
+                desugar.scopes.push(params);
+                desugar.walk_stmts(&mut f.body);
+                desugar.scopes.pop();
+            }
+            Item::Const(c) => desugar.walk_expr(&mut c.value),
+            Item::Var(v) => desugar.walk_expr(&mut v.value),
+            _ => {}
+        }
+    }
+}
+
+struct Desugar {
+    /// Field names and types of each struct
+    structs: HashMap<String, Vec<(String, Type)>>,
+    /// Method name to the struct its receiver points at
+    methods: HashMap<String, String>,
+    /// Function name to return type
+    functions: HashMap<String, Type>,
+    /// Declared types of the names in scope, innermost last
+    scopes: Vec<HashMap<String, Option<Type>>>,
+}
+
+impl Desugar {
+    fn walk_stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.walk_stmt(stmt);
+        }
+    }
+
+    fn walk_scope(&mut self, stmts: &mut [Stmt]) {
+        self.scopes.push(HashMap::new());
+        self.walk_stmts(stmts);
+        self.scopes.pop();
+    }
+
+    // A name of unknown type still shadows an outer one
+    fn declare(&mut self, name: &str, ty: Option<Type>) {
+        let scope = self.scopes.last_mut().expect("no scope");
+        scope.insert(name.to_string(), ty);
+    }
+
+    fn walk_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Let(l) => {
+                if let Some(value) = &mut l.value {
+                    self.walk_expr(value);
+                }
+                let ty = match (&l.ty, &l.value) {
+                    (Some(ty), _) => Some((**ty).clone()),
+                    (None, Some(value)) => self.type_of(value),
+                    (None, None) => None,
+                };
+                self.declare(&l.name, ty);
+            }
+            Stmt::Const(c) => {
+                self.walk_expr(&mut c.value);
+                let ty = match &c.ty {
+                    Some(ty) => Some((**ty).clone()),
+                    None => self.type_of(&c.value),
+                };
+                self.declare(&c.name, ty);
+            }
+            Stmt::Expr(e) | Stmt::Return(Some(e)) | Stmt::Break(Some(e), _) => self.walk_expr(e),
+            Stmt::Block(stmts) => self.walk_scope(stmts),
+            Stmt::If(i) => {
+                self.walk_expr(&mut i.condition);
+                self.walk_scope(&mut i.then_branch);
+                if let Some(else_branch) = &mut i.else_branch {
+                    self.walk_scope(else_branch);
+                }
+            }
+            Stmt::While(w) => {
+                self.walk_expr(&mut w.condition);
+                self.walk_scope(&mut w.body);
+            }
+            Stmt::For(f) => {
+                self.scopes.push(HashMap::new());
+                self.walk_stmt(&mut f.init);
+                self.walk_expr(&mut f.condition);
+                self.walk_stmt(&mut f.update);
+                self.walk_scope(&mut f.body);
+                self.scopes.pop();
+            }
+            Stmt::Asm(a) => {
+                for operand in a.inputs.iter_mut().chain(a.outputs.iter_mut()) {
+                    self.walk_expr(&mut operand.expr);
+                }
+            }
+            Stmt::Defer(s) => self.walk_stmt(s),
+            Stmt::EntropyAssert(e) => self.walk_expr(&mut e.expr),
+            Stmt::Return(None) | Stmt::Break(None, _) | Stmt::Continue(_) => {}
+        }
+    }
+
+    // The declared type of `expr`, as far as it can be told without type
+    // checking
+    fn type_of(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Identifier(name, _) => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name))
+                .cloned()
+                .flatten(),
+            Expr::Field(base, field) => self.field_type(self.type_of(base)?, field),
+            Expr::PtrField(ptr, field) => self.field_type(pointee(self.type_of(ptr)?)?, field),
+            Expr::Index(base, _) => match self.type_of(base)? {
+                Type::Array(_, elem) => Some(*elem),
+                ty => pointee(ty),
+            },
+            Expr::Unary(UnaryOp::Deref, ptr) => pointee(self.type_of(ptr)?),
+            Expr::Unary(UnaryOp::AddrOf, target) | Expr::AddrOf(target) => {
+                Some(Type::MutPtr(Box::new(self.type_of(target)?)))
+            }
+            Expr::Cast(_, ty) | Expr::Zeroed(ty) => Some(ty.clone()),
+            Expr::Call(callee, _) => match &**callee {
+                Expr::Identifier(name, _) => self.functions.get(name).cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn field_type(&self, ty: Type, field: &str) -> Option<Type> {
+        let Type::Named(name) = ty else {
+            return None;
+        };
+        self.structs
+            .get(&name)?
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, ty)| ty.clone())
+    }
+
+    fn walk_expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Unary(_, e)
+            | Expr::Field(e, _)
+            | Expr::PtrField(e, _)
+            | Expr::Cast(e, _)
+            | Expr::AddrOf(e)
+            | Expr::Alloc(_, e) => self.walk_expr(e),
+            Expr::Binary(_, a, b, _)
+            | Expr::Index(a, b)
+            | Expr::Assign(a, b)
+            | Expr::Free(a, b) => {
+                self.walk_expr(a);
+                self.walk_expr(b);
+            }
+            Expr::Call(callee, args) => {
+                self.walk_expr(callee);
+                for arg in args.iter_mut() {
+                    self.walk_expr(arg);
+                }
+            }
+            Expr::Syscall(_, args) | Expr::RawSyscall(args) | Expr::ArrayLit(args) => {
+                for arg in args {
+                    self.walk_expr(arg);
+                }
+            }
+            Expr::Block(stmts, result) => {
+                self.scopes.push(HashMap::new());
+                self.walk_stmts(stmts);
+                if let Some(result) = result {
+                    self.walk_expr(result);
+                }
+                self.scopes.pop();
+            }
+            Expr::Loop(body) => self.walk_scope(body),
+            Expr::If(i) => {
+                self.walk_expr(&mut i.condition);
+                self.walk_expr(&mut i.then_expr);
+                self.walk_expr(&mut i.else_expr);
+            }
+            Expr::EntropyCheck(e) => self.walk_expr(&mut e.expr),
+            Expr::BitRegionAccess(b) => self.walk_expr(&mut b.base),
+            Expr::TopologyCast(t) => self.walk_expr(&mut t.expr),
+            Expr::Literal(_)
+            | Expr::Identifier(..)
+            | Expr::Sizeof(_)
+            | Expr::Alignof(_)
+            | Expr::Offsetof(..)
+            | Expr::Zeroed(_)
+            | Expr::PhysAddr(_) => {}
+        }
+
+        if let Expr::Call(callee, args) = expr {
+            if let Expr::Field(receiver, method) = &mut **callee {
+                let is_method = match self.type_of(receiver) {
+                    Some(Type::Named(name)) => self.methods.get(method.as_str()) == Some(&name),
+                    _ => false,
+                };
+                if is_method {
+                    let span = match &**receiver {
+                        Expr::Identifier(_, span) => span.clone(),
+                        _ => Span::default(),
+                    };
+                    let receiver =
+                        std::mem::replace(&mut **receiver, Expr::Literal(Literal::Bool(false)));
+                    let name = std::mem::take(method);
+                    args.insert(0, Expr::Unary(UnaryOp::AddrOf, Box::new(receiver)));
+                    **callee = Expr::Identifier(name, span);
+                }
+            }
+        }
+    }
+}
+
+fn pointee(ty: Type) -> Option<Type> {
+    match ty {
+        Type::Ptr(inner) | Type::MutPtr(inner) | Type::ConstPtr(inner) => Some(*inner),
+        _ => None,
+    }
+}
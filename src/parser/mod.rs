@@ -1,10 +1,17 @@
+mod desugar;
+
 use crate::ast::*;
-use crate::lexer::{Token, TokenKind};
+use crate::consteval::{ConstEvaluator, ConstValue};
+use crate::layout::TypeLayouts;
+use crate::lexer::{parse_float, parse_hex_float, Span, Token, TokenKind};
+use std::collections::HashMap;
 use std::fmt;
 
 pub fn parse(tokens: &[Token]) -> Result<Program, ParseError> {
     let mut parser = Parser::new(tokens);
-    parser.parse_program()
+    let mut program = parser.parse_program()?;
+    desugar::desugar_method_calls(&mut program);
+    Ok(program)
 }
 
 #[derive(Debug)]
@@ -28,17 +35,32 @@ impl std::error::Error for ParseError {}
 struct Parser<'a> {
     tokens: &'a [Token],
     pos: usize,
+    // Const fns, const values and type layouts seen so far, so array
+    // lengths can be evaluated while parsing types.
+    const_fns: HashMap<String, Function>,
+    const_values: HashMap<String, ConstValue>,
+    layouts: TypeLayouts,
 }
 
 impl<'a> Parser<'a> {
     fn new(tokens: &'a [Token]) -> Self {
-        Parser { tokens, pos: 0 }
+        Parser {
+            tokens,
+            pos: 0,
+            const_fns: HashMap::new(),
+            const_values: HashMap::new(),
+            layouts: TypeLayouts::new(),
+        }
     }
 
     fn current(&self) -> Option<&Token> {
         self.tokens.get(self.pos)
     }
 
+    fn current_span(&self) -> Span {
+        self.current().map(|t| t.span.clone()).unwrap_or_default()
+    }
+
     fn current_kind(&self) -> TokenKind {
         self.current()
             .map(|t| t.kind.clone())
@@ -65,23 +87,51 @@ impl<'a> Parser<'a> {
     fn parse_program(&mut self) -> Result<Program, ParseError> {
         let mut items = Vec::new();
         while self.current().is_some() {
-            items.push(self.parse_item()?);
+            let item = self.parse_item()?;
+            match &item {
+                Item::Function(f) if f.attrs.contains(&FunctionAttribute::Const) => {
+                    self.const_fns.insert(f.name.clone(), f.clone());
+                }
+                Item::Const(c) => {
+                    let value =
+                        ConstEvaluator::new(&self.const_fns, &self.const_values, &self.layouts)
+                            .eval(&c.value);
+                    if let Ok(value) = value {
+                        self.const_values.insert(c.name.clone(), value);
+                    }
+                }
+                Item::Struct(_) | Item::Union(_) | Item::Enum(_) => self.layouts.declare(&item),
+                _ => {}
+            }
+            items.push(item);
         }
         Ok(Program { items })
     }
 
     fn parse_item(&mut self) -> Result<Item, ParseError> {
         match self.current_kind() {
-            TokenKind::Fn => self.parse_function(),
+            TokenKind::Fn | TokenKind::Extern => self.parse_function(),
             TokenKind::Struct => self.parse_struct(),
             TokenKind::Union => self.parse_union(),
             TokenKind::Enum => self.parse_enum(),
+            TokenKind::Const
+                if self.tokens.get(self.pos + 1).map(|t| &t.kind) == Some(&TokenKind::Fn) =>
+            {
+                self.pos += 1;
+                let mut item = self.parse_function()?;
+                if let Item::Function(f) = &mut item {
+                    f.attrs.push(FunctionAttribute::Const);
+                }
+                Ok(item)
+            }
             TokenKind::Const => self.parse_const_decl(),
             TokenKind::Var => self.parse_var_decl(),
+            TokenKind::StaticAssert => self.parse_static_assert(),
             TokenKind::Capability => self.parse_capability_decl(),
             TokenKind::Topology => self.parse_topology_decl(),
             TokenKind::BitRegion => self.parse_bitregion_decl(),
             TokenKind::Entropy => self.parse_entropy_decl(),
+            TokenKind::At if self.at_struct_attribute() => self.parse_struct(),
             TokenKind::At => {
                 self.pos += 1;
                 match self.current() {
@@ -102,14 +152,14 @@ impl<'a> Parser<'a> {
                             };
                             self.expect(TokenKind::RParen)?;
                             self.expect(TokenKind::Fn)?;
-                            let name = match self.current() {
+                            let (name, name_span) = match self.current() {
                                 Some(Token {
                                     kind: TokenKind::Identifier,
                                     ..
                                 }) => {
                                     let name = self.tokens[self.pos].clone();
                                     self.pos += 1;
-                                    name.text.clone()
+                                    (name.text.clone(), name.span.clone())
                                 }
                                 _ => {
                                     return Err(ParseError {
@@ -182,7 +232,22 @@ impl<'a> Parser<'a> {
                                 return_type: Box::new(return_type),
                                 body,
                                 attrs,
+                                span: name_span,
+                                variadic: false,
                             }))
+                        } else if attr_name == "must_use" {
+                            let start = self.current().map(|t| (t.span.start, t.span.end));
+                            let mut item = self.parse_item()?;
+                            match &mut item {
+                                Item::Function(f) => {
+                                    f.attrs.push(FunctionAttribute::MustUse);
+                                    Ok(item)
+                                }
+                                _ => Err(ParseError {
+                                    message: "@must_use only applies to functions".to_string(),
+                                    span: start.unwrap_or((0, 0)),
+                                }),
+                            }
                         } else {
                             Err(ParseError {
                                 message: format!("Unknown attribute: @{}", attr_name),
@@ -213,16 +278,20 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_function(&mut self) -> Result<Item, ParseError> {
+        let is_extern = self.current_kind() == TokenKind::Extern;
+        if is_extern {
+            self.pos += 1;
+        }
         self.expect(TokenKind::Fn)?;
 
-        let name = match self.current() {
+        let (name, name_span) = match self.current() {
             Some(Token {
                 kind: TokenKind::Identifier,
                 ..
             }) => {
                 let name = self.tokens[self.pos].clone();
                 self.pos += 1;
-                name.text.clone()
+                (name.text.clone(), name.span.clone())
             }
             _ => {
                 return Err(ParseError {
@@ -237,8 +306,15 @@ impl<'a> Parser<'a> {
 
         self.expect(TokenKind::LParen)?;
         let mut params = Vec::new();
+        let mut variadic = false;
         if self.current_kind() != TokenKind::RParen {
             loop {
+                // `...` ends the parameter list
+                if self.current_kind() == TokenKind::Ellipsis {
+                    self.pos += 1;
+                    variadic = true;
+                    break;
+                }
                 let param_name = match self.current() {
                     Some(Token {
                         kind: TokenKind::Identifier,
@@ -283,14 +359,19 @@ impl<'a> Parser<'a> {
             Type::I32
         };
 
-        let attrs = self.parse_function_attributes()?;
+        let mut attrs = self.parse_function_attributes()?;
 
-        self.expect(TokenKind::LBrace)?;
         let mut body = Vec::new();
-        while self.current_kind() != TokenKind::RBrace {
-            body.push(self.parse_stmt()?);
+        if is_extern {
+            self.expect(TokenKind::Semi)?;
+            attrs.push(FunctionAttribute::Extern);
+        } else {
+            self.expect(TokenKind::LBrace)?;
+            while self.current_kind() != TokenKind::RBrace {
+                body.push(self.parse_stmt()?);
+            }
+            self.expect(TokenKind::RBrace)?;
         }
-        self.expect(TokenKind::RBrace)?;
 
         Ok(Item::Function(Function {
             name,
@@ -298,6 +379,8 @@ impl<'a> Parser<'a> {
             return_type: Box::new(return_type),
             body,
             attrs,
+            span: name_span,
+            variadic,
         }))
     }
 
@@ -327,6 +410,8 @@ impl<'a> Parser<'a> {
                             };
                             self.expect(TokenKind::RParen)?;
                             attrs.push(FunctionAttribute::Entry(entry_func_name));
+                        } else if attr_name == "must_use" {
+                            attrs.push(FunctionAttribute::MustUse);
                         } else {
                             return Err(ParseError {
                                 message: format!("Unknown attribute: @{}", attr_name),
@@ -354,7 +439,65 @@ impl<'a> Parser<'a> {
         Ok(attrs)
     }
 
+    // `@align(...)` or `@packed` before an item introduces a struct rather
+    // than a function
+    fn at_struct_attribute(&self) -> bool {
+        self.tokens.get(self.pos + 1).is_some_and(|t| {
+            t.kind == TokenKind::Identifier && (t.text == "align" || t.text == "packed")
+        })
+    }
+
+    // Parses `@align(N)`; N must be a power of two
+    fn parse_align_attribute(&mut self) -> Result<usize, ParseError> {
+        self.expect(TokenKind::At)?;
+        let is_align = self
+            .current()
+            .is_some_and(|t| t.kind == TokenKind::Identifier && t.text == "align");
+        if !is_align {
+            return Err(ParseError {
+                message: "Expected @align(N)".to_string(),
+                span: self
+                    .current()
+                    .map(|t| (t.span.start, t.span.end))
+                    .unwrap_or((0, 0)),
+            });
+        }
+        self.pos += 1;
+        self.expect(TokenKind::LParen)?;
+        let span = self
+            .current()
+            .map(|t| (t.span.start, t.span.end))
+            .unwrap_or((0, 0));
+        let align = match self.parse_primary_expr()? {
+            Expr::Literal(Literal::Int(n, _)) if n > 0 && (n as u64).is_power_of_two() => {
+                n as usize
+            }
+            _ => {
+                return Err(ParseError {
+                    message: "Alignment must be a power of two integer literal".to_string(),
+                    span,
+                });
+            }
+        };
+        self.expect(TokenKind::RParen)?;
+        Ok(align)
+    }
+
     fn parse_struct(&mut self) -> Result<Item, ParseError> {
+        let mut align = None;
+        let mut packed = false;
+        while self.current_kind() == TokenKind::At {
+            if self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|t| t.text == "packed")
+            {
+                self.pos += 2;
+                packed = true;
+            } else {
+                align = Some(self.parse_align_attribute()?);
+            }
+        }
         self.expect(TokenKind::Struct)?;
 
         let name = match self.current() {
@@ -380,6 +523,10 @@ impl<'a> Parser<'a> {
         self.expect(TokenKind::LBrace)?;
         let mut fields = Vec::new();
         while self.current_kind() != TokenKind::RBrace {
+            let mut field_align = None;
+            while self.current_kind() == TokenKind::At {
+                field_align = Some(self.parse_align_attribute()?);
+            }
             let field_name = match self.current() {
                 Some(Token {
                     kind: TokenKind::Identifier,
@@ -405,6 +552,7 @@ impl<'a> Parser<'a> {
             fields.push(StructField {
                 name: field_name,
                 ty: Box::new(field_type),
+                align: field_align,
             });
         }
         self.expect(TokenKind::RBrace)?;
@@ -413,6 +561,8 @@ impl<'a> Parser<'a> {
             name,
             fields,
             topology: None,
+            align,
+            packed,
         }))
     }
 
@@ -544,10 +694,10 @@ impl<'a> Parser<'a> {
                     }
                 }
             } else {
-                let val = next_value;
-                next_value += 1;
-                val
+                next_value
             };
+            // Implicit values continue from the previous variant, explicit or not
+            next_value = value + 1;
 
             if self.current_kind() == TokenKind::Comma {
                 self.pos += 1;
@@ -599,6 +749,36 @@ impl<'a> Parser<'a> {
         Ok(Item::Const(ConstDecl { name, ty, value }))
     }
 
+    fn parse_static_assert(&mut self) -> Result<Item, ParseError> {
+        self.expect(TokenKind::StaticAssert)?;
+        self.expect(TokenKind::LParen)?;
+        let condition = Box::new(self.parse_expr()?);
+        let message = if self.current_kind() == TokenKind::Comma {
+            self.pos += 1;
+            match self.parse_primary_expr()? {
+                Expr::Literal(Literal::String(bytes)) => {
+                    Some(String::from_utf8_lossy(&bytes).into_owned())
+                }
+                _ => {
+                    return Err(ParseError {
+                        message: "Expected a string message in static_assert".to_string(),
+                        span: self
+                            .tokens
+                            .get(self.pos - 1)
+                            .map(|t| (t.span.start, t.span.end))
+                            .unwrap_or((0, 0)),
+                    });
+                }
+            }
+        } else {
+            None
+        };
+        self.expect(TokenKind::RParen)?;
+        self.expect(TokenKind::Semi)?;
+
+        Ok(Item::StaticAssert(StaticAssert { condition, message }))
+    }
+
     fn parse_var_decl(&mut self) -> Result<Item, ParseError> {
         self.expect(TokenKind::Var)?;
 
@@ -645,18 +825,33 @@ impl<'a> Parser<'a> {
             TokenKind::For => self.parse_for_stmt(),
             TokenKind::Return => self.parse_return_stmt(),
             TokenKind::Break => {
+                let span = self.current_span();
                 self.pos += 1;
+                let value = if self.current_kind() == TokenKind::Semi {
+                    None
+                } else {
+                    Some(self.parse_expr()?)
+                };
                 self.expect(TokenKind::Semi)?;
-                Ok(Stmt::Break)
+                Ok(Stmt::Break(value, span))
             }
             TokenKind::Continue => {
+                let span = self.current_span();
                 self.pos += 1;
                 self.expect(TokenKind::Semi)?;
-                Ok(Stmt::Continue)
+                Ok(Stmt::Continue(span))
+            }
+            TokenKind::Loop => {
+                // A loop in statement position needs no trailing `;`
+                let expr = self.parse_loop_expr()?;
+                if self.current_kind() == TokenKind::Semi {
+                    self.pos += 1;
+                }
+                Ok(Stmt::Expr(expr))
             }
             TokenKind::Asm => self.parse_asm_stmt(),
             TokenKind::Defer => self.parse_defer_stmt(),
-            TokenKind::Let => self.parse_let_stmt(),
+            TokenKind::Let | TokenKind::Static => self.parse_let_stmt(),
             TokenKind::Identifier => {
                 let lookahead = self.pos + 1;
                 if lookahead < self.tokens.len() && self.tokens[lookahead].kind == TokenKind::Colon
@@ -692,7 +887,12 @@ impl<'a> Parser<'a> {
             }
             _ => false,
         };
+        let is_static = self.current_kind() == TokenKind::Static;
+        if is_static {
+            self.pos += 1;
+        }
 
+        let span = self.current_span();
         let name = match self.current() {
             Some(Token {
                 kind: TokenKind::Identifier,
@@ -713,18 +913,29 @@ impl<'a> Parser<'a> {
             }
         };
 
-        self.expect(TokenKind::Colon)?;
-        let ty = self.parse_type()?;
+        // The annotation may be left off when the initializer gives the type
+        let ty = if self.current_kind() != TokenKind::Eq {
+            self.expect(TokenKind::Colon)?;
+            Some(Box::new(self.parse_type()?))
+        } else {
+            None
+        };
 
-        self.expect(TokenKind::Eq)?;
-        let value = Box::new(self.parse_expr()?);
+        let value = if self.current_kind() == TokenKind::Eq {
+            self.pos += 1;
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
         self.expect(TokenKind::Semi)?;
 
         Ok(Stmt::Let(LetStmt {
             name,
-            ty: Some(Box::new(ty)),
+            ty,
             value,
             is_const,
+            is_static,
+            span,
         }))
     }
 
@@ -1010,6 +1221,43 @@ impl<'a> Parser<'a> {
 
     fn parse_type(&mut self) -> Result<Type, ParseError> {
         match self.current_kind() {
+            TokenKind::LBracket => {
+                self.pos += 1;
+                if self.current_kind() == TokenKind::Star {
+                    self.pos += 1;
+                    self.expect(TokenKind::RBracket)?;
+                    let elem = self.parse_type()?;
+                    return Ok(Type::MutPtr(Box::new(elem)));
+                }
+                let len_start = self
+                    .current()
+                    .map(|t| (t.span.start, t.span.end))
+                    .unwrap_or((0, 0));
+                let len_expr = self.parse_expr()?;
+                let mut evaluator =
+                    ConstEvaluator::new(&self.const_fns, &self.const_values, &self.layouts);
+                let len = match evaluator.eval(&len_expr).and_then(|v| v.as_int()) {
+                    Ok(n) if n >= 0 => n as usize,
+                    Ok(n) => {
+                        return Err(ParseError {
+                            message: format!("Array length must not be negative, got {}", n),
+                            span: len_start,
+                        });
+                    }
+                    Err(e) => {
+                        return Err(ParseError {
+                            message: format!(
+                                "Array length must be a compile-time constant: {}",
+                                e.message
+                            ),
+                            span: len_start,
+                        });
+                    }
+                };
+                self.expect(TokenKind::RBracket)?;
+                let elem = self.parse_type()?;
+                Ok(Type::Array(len, Box::new(elem)))
+            }
             TokenKind::Void => {
                 self.pos += 1;
                 Ok(Type::Void)
@@ -1068,6 +1316,11 @@ impl<'a> Parser<'a> {
             }
             TokenKind::Star => {
                 self.pos += 1;
+                // `*const T`, as the spec writes it, or the older `*T const`
+                if self.current_kind() == TokenKind::Const {
+                    self.pos += 1;
+                    return Ok(Type::ConstPtr(Box::new(self.parse_type()?)));
+                }
                 let inner = self.parse_type()?;
                 if self.current_kind() == TokenKind::Const {
                     self.pos += 1;
@@ -1088,7 +1341,7 @@ impl<'a> Parser<'a> {
                     {
                         let width: u16 = rest.parse().unwrap_or(0);
 
-                        if width >= 1 && width <= 256 {
+                        if (1..=256).contains(&width) {
                             let is_signed = first_char == 'i';
                             self.pos += 1;
                             return Ok(Type::BitInt(width as u8, is_signed));
@@ -1129,9 +1382,10 @@ impl<'a> Parser<'a> {
         let mut lhs = self.parse_logical_and_expr()?;
 
         while self.current_kind() == TokenKind::OrOr {
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_logical_and_expr()?);
-            lhs = Expr::Binary(BinaryOp::LogicalOr, Box::new(lhs), rhs);
+            lhs = Expr::Binary(BinaryOp::LogicalOr, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1141,9 +1395,10 @@ impl<'a> Parser<'a> {
         let mut lhs = self.parse_bitwise_or_expr()?;
 
         while self.current_kind() == TokenKind::AndAnd {
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_bitwise_or_expr()?);
-            lhs = Expr::Binary(BinaryOp::LogicalAnd, Box::new(lhs), rhs);
+            lhs = Expr::Binary(BinaryOp::LogicalAnd, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1153,9 +1408,10 @@ impl<'a> Parser<'a> {
         let mut lhs = self.parse_bitwise_xor_expr()?;
 
         while self.current_kind() == TokenKind::Pipe {
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_bitwise_xor_expr()?);
-            lhs = Expr::Binary(BinaryOp::BitOr, Box::new(lhs), rhs);
+            lhs = Expr::Binary(BinaryOp::BitOr, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1165,9 +1421,10 @@ impl<'a> Parser<'a> {
         let mut lhs = self.parse_bitwise_and_expr()?;
 
         while self.current_kind() == TokenKind::Caret {
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_bitwise_and_expr()?);
-            lhs = Expr::Binary(BinaryOp::BitXor, Box::new(lhs), rhs);
+            lhs = Expr::Binary(BinaryOp::BitXor, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1177,9 +1434,10 @@ impl<'a> Parser<'a> {
         let mut lhs = self.parse_equality_expr()?;
 
         while self.current_kind() == TokenKind::Ampersand {
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_equality_expr()?);
-            lhs = Expr::Binary(BinaryOp::BitAnd, Box::new(lhs), rhs);
+            lhs = Expr::Binary(BinaryOp::BitAnd, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1191,19 +1449,23 @@ impl<'a> Parser<'a> {
         loop {
             lhs = match self.current_kind() {
                 TokenKind::EqEq => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Eq,
                         Box::new(lhs),
                         Box::new(self.parse_relational_expr()?),
+                        span,
                     )
                 }
                 TokenKind::NotEq => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Neq,
                         Box::new(lhs),
                         Box::new(self.parse_relational_expr()?),
+                        span,
                     )
                 }
                 _ => break,
@@ -1213,48 +1475,52 @@ impl<'a> Parser<'a> {
         Ok(lhs)
     }
 
+    // Comparisons do not chain: `a < b < c` would compare the bool `a < b`
+    // with `c`, which is never what was meant, so it is rejected here with
+    // the `&&` form to use instead
     fn parse_relational_expr(&mut self) -> Result<Expr, ParseError> {
-        let mut lhs = self.parse_shift_expr()?;
+        let lhs_start = self.pos;
+        let lhs = self.parse_shift_expr()?;
+        let Some(op) = relational_op(self.current_kind()) else {
+            return Ok(lhs);
+        };
+        let lhs_end = self.pos;
+        self.pos += 1;
+        let rhs = self.parse_shift_expr()?;
+        let rhs_end = self.pos;
 
-        loop {
-            lhs = match self.current_kind() {
-                TokenKind::Lt => {
-                    self.pos += 1;
-                    Expr::Binary(
-                        BinaryOp::Lt,
-                        Box::new(lhs),
-                        Box::new(self.parse_shift_expr()?),
-                    )
-                }
-                TokenKind::Gt => {
-                    self.pos += 1;
-                    Expr::Binary(
-                        BinaryOp::Gt,
-                        Box::new(lhs),
-                        Box::new(self.parse_shift_expr()?),
-                    )
-                }
-                TokenKind::LtEq => {
-                    self.pos += 1;
-                    Expr::Binary(
-                        BinaryOp::LtEq,
-                        Box::new(lhs),
-                        Box::new(self.parse_shift_expr()?),
-                    )
-                }
-                TokenKind::GtEq => {
-                    self.pos += 1;
-                    Expr::Binary(
-                        BinaryOp::GtEq,
-                        Box::new(lhs),
-                        Box::new(self.parse_shift_expr()?),
-                    )
-                }
-                _ => break,
-            };
+        if relational_op(self.current_kind()).is_some() {
+            let second_op = self.pos;
+            self.pos += 1;
+            self.parse_shift_expr()?;
+            let a = self.source_text(lhs_start, lhs_end);
+            let b = self.source_text(lhs_end + 1, rhs_end);
+            let c = self.source_text(second_op + 1, self.pos);
+            let (op1, op2) = (&self.tokens[lhs_end].text, &self.tokens[second_op].text);
+            let token = &self.tokens[second_op];
+            return Err(ParseError {
+                message: format!(
+                    "Comparison operators cannot be chained: `{a} {op1} {b} {op2} {c}`; write `{a} {op1} {b} && {b} {op2} {c}` instead"
+                ),
+                span: (token.span.start, token.span.end),
+            });
         }
 
-        Ok(lhs)
+        let span = self.tokens[lhs_end].span.clone();
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs), span))
+    }
+
+    // Tokens `start..end` as written, with a space wherever the source had
+    // whitespace between them
+    fn source_text(&self, start: usize, end: usize) -> String {
+        let mut text = String::new();
+        for (i, token) in self.tokens[start..end].iter().enumerate() {
+            if i > 0 && self.tokens[start + i - 1].span.end < token.span.start {
+                text.push(' ');
+            }
+            text.push_str(&token.text);
+        }
+        text
     }
 
     fn parse_shift_expr(&mut self) -> Result<Expr, ParseError> {
@@ -1266,9 +1532,10 @@ impl<'a> Parser<'a> {
             } else {
                 BinaryOp::RShift
             };
+            let span = self.current_span();
             self.pos += 1;
             let rhs = Box::new(self.parse_additive_expr()?);
-            lhs = Expr::Binary(op, Box::new(lhs), rhs);
+            lhs = Expr::Binary(op, Box::new(lhs), rhs, span);
         }
 
         Ok(lhs)
@@ -1280,19 +1547,23 @@ impl<'a> Parser<'a> {
         loop {
             lhs = match self.current_kind() {
                 TokenKind::Plus => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Add,
                         Box::new(lhs),
                         Box::new(self.parse_multiplicative_expr()?),
+                        span,
                     )
                 }
                 TokenKind::Minus => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Sub,
                         Box::new(lhs),
                         Box::new(self.parse_multiplicative_expr()?),
+                        span,
                     )
                 }
                 _ => break,
@@ -1308,27 +1579,33 @@ impl<'a> Parser<'a> {
         loop {
             lhs = match self.current_kind() {
                 TokenKind::Star => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Mul,
                         Box::new(lhs),
                         Box::new(self.parse_cast_expr()?),
+                        span,
                     )
                 }
                 TokenKind::Slash => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Div,
                         Box::new(lhs),
                         Box::new(self.parse_cast_expr()?),
+                        span,
                     )
                 }
                 TokenKind::Percent => {
+                    let span = self.current_span();
                     self.pos += 1;
                     Expr::Binary(
                         BinaryOp::Mod,
                         Box::new(lhs),
                         Box::new(self.parse_cast_expr()?),
+                        span,
                     )
                 }
                 _ => break,
@@ -1406,27 +1683,41 @@ impl<'a> Parser<'a> {
                 self.expect(TokenKind::LParen)?;
                 let ty = self.parse_type()?;
                 self.expect(TokenKind::Comma)?;
-                let field_name = match self.current() {
-                    Some(Token {
-                        kind: TokenKind::Identifier,
-                        ..
-                    }) => {
-                        let f = self.tokens[self.pos].text.clone();
-                        self.pos += 1;
-                        f
+                // A field path: `value`, or `inner.value` through nested structs
+                let mut path = Vec::new();
+                loop {
+                    match self.current() {
+                        Some(Token {
+                            kind: TokenKind::Identifier,
+                            ..
+                        }) => {
+                            path.push(self.tokens[self.pos].text.clone());
+                            self.pos += 1;
+                        }
+                        _ => {
+                            return Err(ParseError {
+                                message: "Expected field name".to_string(),
+                                span: self
+                                    .current()
+                                    .map(|t| (t.span.start, t.span.end))
+                                    .unwrap_or((0, 0)),
+                            });
+                        }
                     }
-                    _ => {
-                        return Err(ParseError {
-                            message: "Expected field name".to_string(),
-                            span: self
-                                .current()
-                                .map(|t| (t.span.start, t.span.end))
-                                .unwrap_or((0, 0)),
-                        });
+                    if self.current_kind() != TokenKind::Dot {
+                        break;
                     }
-                };
+                    self.pos += 1;
+                }
+                self.expect(TokenKind::RParen)?;
+                Ok(Expr::Offsetof(ty, path.join(".")))
+            }
+            TokenKind::Zeroed => {
+                self.pos += 1;
+                self.expect(TokenKind::LParen)?;
+                let ty = self.parse_type()?;
                 self.expect(TokenKind::RParen)?;
-                Ok(Expr::Offsetof(ty, field_name))
+                Ok(Expr::Zeroed(ty))
             }
             _ => self.parse_postfix_expr(),
         }
@@ -1445,7 +1736,7 @@ impl<'a> Parser<'a> {
                 }
                 TokenKind::Dot => {
                     self.pos += 1;
-                    if let Expr::Identifier(ref name) = expr {
+                    if let Expr::Identifier(ref name, _) = expr {
                         if name == "syscall" {
                             return self.parse_syscall_call();
                         }
@@ -1508,7 +1799,12 @@ impl<'a> Parser<'a> {
                         }
                     }
                     self.expect(TokenKind::RParen)?;
-                    Expr::Call(Box::new(expr), args)
+                    match expr {
+                        Expr::Identifier(ref name, _) if name == "syscall" => {
+                            Expr::RawSyscall(args)
+                        }
+                        _ => Expr::Call(Box::new(expr), args),
+                    }
                 }
                 _ => break,
             };
@@ -1555,40 +1851,88 @@ impl<'a> Parser<'a> {
 
     fn parse_primary_expr(&mut self) -> Result<Expr, ParseError> {
         match self.current_kind() {
+            TokenKind::LBracket => {
+                self.pos += 1;
+                let mut elements = Vec::new();
+                while self.current_kind() != TokenKind::RBracket {
+                    elements.push(self.parse_expr()?);
+                    if self.current_kind() != TokenKind::Comma {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.expect(TokenKind::RBracket)?;
+                Ok(Expr::ArrayLit(elements))
+            }
             TokenKind::Integer => {
                 let s = self.tokens[self.pos].text.clone();
-                let val: i64 = s.parse().unwrap_or(0);
+                let (digits, suffix) = split_int_suffix(&s);
+                let val: i64 = digits.replace("_", "").parse().unwrap_or(0);
                 self.pos += 1;
-                Ok(Expr::Literal(Literal::Int(val, IntSuffix::None)))
+                Ok(Expr::Literal(Literal::Int(val, suffix)))
             }
             TokenKind::HexInteger => {
                 let s = self.tokens[self.pos].text.clone();
-                let val = i64::from_str_radix(&s[2..].replace("_", ""), 16).unwrap_or(0);
+                let (digits, suffix) = split_int_suffix(&s[2..]);
+                let val = i64::from_str_radix(&digits.replace("_", ""), 16).unwrap_or(0);
+                self.pos += 1;
+                Ok(Expr::Literal(Literal::Int(val, suffix)))
+            }
+            TokenKind::HexFloat => {
+                let s = self.tokens[self.pos].text.clone();
+                let (digits, suffix) = split_float_suffix(&s);
+                // The lexer only produces hex floats that are in range
+                let val = parse_hex_float(digits).unwrap_or(0.0);
+                self.pos += 1;
+                Ok(Expr::Literal(Literal::Float(val, suffix)))
+            }
+            TokenKind::Float => {
+                let s = self.tokens[self.pos].text.clone();
+                let (digits, suffix) = split_float_suffix(&s);
+                // The lexer only produces floats that are in range
+                let val = parse_float(digits).unwrap_or(0.0);
                 self.pos += 1;
-                Ok(Expr::Literal(Literal::Int(val, IntSuffix::None)))
+                Ok(Expr::Literal(Literal::Float(val, suffix)))
             }
             TokenKind::OctInteger => {
                 let s = self.tokens[self.pos].text.clone();
-                let val = i64::from_str_radix(&s[2..].replace("_", ""), 8).unwrap_or(0);
+                let (digits, suffix) = split_int_suffix(&s[2..]);
+                let val = i64::from_str_radix(&digits.replace("_", ""), 8).unwrap_or(0);
                 self.pos += 1;
-                Ok(Expr::Literal(Literal::Int(val, IntSuffix::None)))
+                Ok(Expr::Literal(Literal::Int(val, suffix)))
             }
             TokenKind::BinInteger => {
                 let s = self.tokens[self.pos].text.clone();
-                let val = i64::from_str_radix(&s[2..].replace("_", ""), 2).unwrap_or(0);
+                let (digits, suffix) = split_int_suffix(&s[2..]);
+                let val = i64::from_str_radix(&digits.replace("_", ""), 2).unwrap_or(0);
                 self.pos += 1;
-                Ok(Expr::Literal(Literal::Int(val, IntSuffix::None)))
+                Ok(Expr::Literal(Literal::Int(val, suffix)))
             }
             TokenKind::String => {
-                let s = self.tokens[self.pos].text.clone();
-                let bytes = s[1..s.len() - 1].as_bytes().to_vec();
+                let token = &self.tokens[self.pos];
+                let s = &token.text;
+                let bytes = unescape(&s[1..s.len() - 1]).map_err(|message| ParseError {
+                    message,
+                    span: (token.span.start, token.span.end),
+                })?;
                 self.pos += 1;
                 Ok(Expr::Literal(Literal::String(bytes)))
             }
             TokenKind::Char => {
-                let s = self.tokens[self.pos].text.clone();
+                let token = &self.tokens[self.pos];
+                let s = &token.text;
+                let bytes = unescape(&s[1..s.len() - 1]).map_err(|message| ParseError {
+                    message,
+                    span: (token.span.start, token.span.end),
+                })?;
+                let [byte] = bytes[..] else {
+                    return Err(ParseError {
+                        message: format!("Character literal {} is not a single byte", s),
+                        span: (token.span.start, token.span.end),
+                    });
+                };
                 self.pos += 1;
-                Ok(Expr::Literal(Literal::Char(s.as_bytes()[0])))
+                Ok(Expr::Literal(Literal::Char(byte)))
             }
             TokenKind::True => {
                 self.pos += 1;
@@ -1616,14 +1960,14 @@ impl<'a> Parser<'a> {
                 Ok(Expr::Free(ptr, size))
             }
             TokenKind::Identifier => {
-                let name = self.tokens[self.pos].text.clone();
+                let token = self.tokens[self.pos].clone();
                 self.pos += 1;
-                Ok(Expr::Identifier(name))
+                Ok(Expr::Identifier(token.text, token.span))
             }
             TokenKind::Syscall => {
-                let name = self.tokens[self.pos].text.clone();
+                let token = self.tokens[self.pos].clone();
                 self.pos += 1;
-                Ok(Expr::Identifier(name))
+                Ok(Expr::Identifier(token.text, token.span))
             }
             TokenKind::LParen => {
                 self.pos += 1;
@@ -1650,6 +1994,7 @@ impl<'a> Parser<'a> {
                 Ok(Expr::Block(stmts, result))
             }
             TokenKind::If => self.parse_if_expr(),
+            TokenKind::Loop => self.parse_loop_expr(),
             _ => Err(ParseError {
                 message: format!("Unexpected token in expression: {:?}", self.current_kind()),
                 span: self
@@ -1660,6 +2005,19 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_loop_expr(&mut self) -> Result<Expr, ParseError> {
+        self.expect(TokenKind::Loop)?;
+
+        self.expect(TokenKind::LBrace)?;
+        let mut body = Vec::new();
+        while self.current_kind() != TokenKind::RBrace {
+            body.push(self.parse_stmt()?);
+        }
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(Expr::Loop(body))
+    }
+
     fn parse_if_expr(&mut self) -> Result<Expr, ParseError> {
         self.expect(TokenKind::If)?;
 
@@ -1766,19 +2124,19 @@ impl<'a> Parser<'a> {
 
         if let TokenKind::KiB = self.current_kind() {
             self.pos += 1;
-            length = length * 1024;
+            length *= 1024;
         } else if let TokenKind::MiB = self.current_kind() {
             self.pos += 1;
-            length = length * 1024 * 1024;
+            length *= 1024 * 1024;
         } else if let TokenKind::GiB = self.current_kind() {
             self.pos += 1;
-            length = length * 1024 * 1024 * 1024;
+            length *= 1024 * 1024 * 1024;
         } else if let TokenKind::TiB = self.current_kind() {
             self.pos += 1;
-            length = length * 1024 * 1024 * 1024 * 1024;
+            length *= 1024 * 1024 * 1024 * 1024;
         } else if let TokenKind::PiB = self.current_kind() {
             self.pos += 1;
-            length = length * 1024 * 1024 * 1024 * 1024 * 1024;
+            length *= 1024 * 1024 * 1024 * 1024 * 1024;
         }
 
         self.expect(TokenKind::RBracket)?;
@@ -2119,3 +2477,89 @@ impl<'a> Parser<'a> {
         }))
     }
 }
+
+/// Splits a type suffix such as `u8` off an integer literal's digits.
+fn split_float_suffix(text: &str) -> (&str, FloatSuffix) {
+    if let Some(digits) = text.strip_suffix("f32") {
+        (digits, FloatSuffix::F32)
+    } else if let Some(digits) = text.strip_suffix("f64") {
+        (digits, FloatSuffix::F64)
+    } else {
+        (text, FloatSuffix::None)
+    }
+}
+
+fn relational_op(kind: TokenKind) -> Option<BinaryOp> {
+    match kind {
+        TokenKind::Lt => Some(BinaryOp::Lt),
+        TokenKind::Gt => Some(BinaryOp::Gt),
+        TokenKind::LtEq => Some(BinaryOp::LtEq),
+        TokenKind::GtEq => Some(BinaryOp::GtEq),
+        _ => None,
+    }
+}
+
+fn split_int_suffix(text: &str) -> (&str, IntSuffix) {
+    const SUFFIXES: [(&str, IntSuffix); 10] = [
+        ("isize", IntSuffix::Isize),
+        ("usize", IntSuffix::Usize),
+        ("i8", IntSuffix::I8),
+        ("i16", IntSuffix::I16),
+        ("i32", IntSuffix::I32),
+        ("i64", IntSuffix::I64),
+        ("u8", IntSuffix::U8),
+        ("u16", IntSuffix::U16),
+        ("u32", IntSuffix::U32),
+        ("u64", IntSuffix::U64),
+    ];
+    for (name, suffix) in SUFFIXES {
+        if let Some(digits) = text.strip_suffix(name) {
+            return (digits, suffix);
+        }
+    }
+    (text, IntSuffix::None)
+}
+
+/// Decodes the escape sequences of a string or character literal body:
+/// `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'` and `\xNN`.
+fn unescape(body: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(body.len());
+    let mut iter = body.bytes();
+    while let Some(b) = iter.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let escaped = match iter.next() {
+            Some(b'n') => b'\n',
+            Some(b't') => b'\t',
+            Some(b'r') => b'\r',
+            Some(b'0') => 0,
+            Some(b'\\') => b'\\',
+            Some(b'"') => b'"',
+            Some(b'\'') => b'\'',
+            Some(b'x') => {
+                let hex = [iter.next(), iter.next()];
+                let digits = match hex {
+                    [Some(hi), Some(lo)] => [hi, lo],
+                    _ => return Err("Incomplete \\x escape sequence".to_string()),
+                };
+                std::str::from_utf8(&digits)
+                    .ok()
+                    .and_then(|d| u8::from_str_radix(d, 16).ok())
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid \\x escape sequence: \\x{}",
+                            String::from_utf8_lossy(&digits)
+                        )
+                    })?
+            }
+            Some(other) => {
+                return Err(format!("Unknown escape sequence: \\{}", other as char));
+            }
+            None => return Err("String ends with a lone backslash".to_string()),
+        };
+        bytes.push(escaped);
+    }
+    Ok(bytes)
+}
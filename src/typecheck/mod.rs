@@ -1,11 +1,13 @@
 use crate::ast::*;
-use std::collections::HashMap;
+use crate::lexer::Span;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 #[derive(Debug)]
 pub struct TypeError {
     pub message: String,
     pub location: String,
+    pub span: Option<Span>,
 }
 
 impl fmt::Display for TypeError {
@@ -16,14 +18,65 @@ impl fmt::Display for TypeError {
 
 impl std::error::Error for TypeError {}
 
+/// Something suspicious that still typechecks.
+#[derive(Debug)]
+pub struct TypeWarning {
+    pub message: String,
+    pub location: String,
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for TypeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Warning at {}: {}", self.location, self.message)
+    }
+}
+
 pub fn typecheck(program: &Program) -> Result<Program, TypeError> {
+    typecheck_with_warnings(program).map(|(program, _)| program)
+}
+
+/// Like [`typecheck`], also returning the warnings raised along the way.
+pub fn typecheck_with_warnings(
+    program: &Program,
+) -> Result<(Program, Vec<TypeWarning>), TypeError> {
     let mut ctx = TypeContext::new();
     ctx.typecheck_program(program)?;
-    Ok(program.clone())
+    Ok((program.clone(), ctx.warnings))
+}
+
+// The kinds of type `check_cast` tells apart.
+#[derive(Clone, Copy, PartialEq)]
+enum CastClass {
+    Int,
+    Float,
+    Ptr,
+    Bool,
+    Enum,
+    Void,
+    Aggregate,
+    Other,
+}
+
+// What a `break` in the innermost enclosing loop leaves.
+enum BreakTarget {
+    // `while` and `for`: the loop can also end through its condition, so
+    // breaks carry no value.
+    Stmt,
+    // `loop` expressions: the type every break value so far has had, and
+    // the type the loop's value is expected to have, if known. An
+    // unsuffixed literal break value adopts the first, or else the second.
+    Expr {
+        ty: Option<Type>,
+        expected: Option<Type>,
+    },
 }
 
 struct TypeContext {
     scopes: Vec<HashMap<String, (Type, bool)>>,
+    // Parallel to `scopes`: bindings declared without an initializer that may
+    // not have been assigned yet on every path reaching the current point.
+    uninitialized: Vec<HashSet<String>>,
     struct_types: HashMap<String, Struct>,
     union_types: HashMap<String, Union>,
     enum_types: HashMap<String, Enum>,
@@ -31,13 +84,24 @@ struct TypeContext {
     topology_types: HashMap<String, MemoryTopology>,
     bit_region_types: HashMap<String, BitRegionType>,
     entropy_types: HashMap<String, EntropyType>,
+    noreturn_functions: HashSet<String>,
+    must_use_functions: HashSet<String>,
+    variadic_functions: HashSet<String>,
     current_function: Option<String>,
+    // Declared return type of the function being checked
+    current_return_type: Option<Type>,
+    break_targets: Vec<BreakTarget>,
+    // Locals of the current function that nothing has read yet, with where
+    // they are declared, in declaration order
+    unread_locals: Vec<(String, Span)>,
+    warnings: Vec<TypeWarning>,
 }
 
 impl TypeContext {
     fn new() -> Self {
         let mut ctx = TypeContext {
             scopes: Vec::new(),
+            uninitialized: Vec::new(),
             struct_types: HashMap::new(),
             union_types: HashMap::new(),
             enum_types: HashMap::new(),
@@ -45,7 +109,14 @@ impl TypeContext {
             topology_types: HashMap::new(),
             bit_region_types: HashMap::new(),
             entropy_types: HashMap::new(),
+            noreturn_functions: HashSet::new(),
+            must_use_functions: HashSet::new(),
+            variadic_functions: HashSet::new(),
             current_function: None,
+            current_return_type: None,
+            break_targets: Vec::new(),
+            unread_locals: Vec::new(),
+            warnings: Vec::new(),
         };
         ctx.push_scope();
         ctx
@@ -53,18 +124,56 @@ impl TypeContext {
 
     fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
+        self.uninitialized.push(HashSet::new());
     }
 
     fn pop_scope(&mut self) {
         self.scopes.pop();
+        self.uninitialized.pop();
     }
 
     fn add_variable(&mut self, name: String, ty: Type, is_const: bool) {
+        if let Some(uninit) = self.uninitialized.last_mut() {
+            uninit.remove(&name);
+        }
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name, (ty, is_const));
         }
     }
 
+    fn declare_uninitialized(&mut self, name: String, ty: Type, is_const: bool) {
+        self.add_variable(name.clone(), ty, is_const);
+        if let Some(uninit) = self.uninitialized.last_mut() {
+            uninit.insert(name);
+        }
+    }
+
+    // Index of the innermost scope that binds `name`.
+    fn binding_scope(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+    }
+
+    fn is_uninitialized(&self, name: &str) -> bool {
+        self.binding_scope(name)
+            .is_some_and(|i| self.uninitialized[i].contains(name))
+    }
+
+    fn mark_initialized(&mut self, name: &str) {
+        if let Some(i) = self.binding_scope(name) {
+            self.uninitialized[i].remove(name);
+        }
+    }
+
+    // A binding stays possibly-uninitialized after a branch point if it is
+    // uninitialized at the end of any path that falls through.
+    fn merge_uninitialized(&mut self, other: Vec<HashSet<String>>) {
+        for (mine, theirs) in self.uninitialized.iter_mut().zip(other) {
+            mine.extend(theirs);
+        }
+    }
+
     fn lookup_variable(&self, name: &str) -> Option<&(Type, bool)> {
         for scope in self.scopes.iter().rev() {
             if let Some(binding) = scope.get(name) {
@@ -74,6 +183,26 @@ impl TypeContext {
         None
     }
 
+    fn type_name_call_message(&self, name: &str) -> Option<String> {
+        let kind = if self.struct_types.contains_key(name) {
+            "struct"
+        } else if self.union_types.contains_key(name) {
+            "union"
+        } else if self.enum_types.contains_key(name) {
+            return Some(format!(
+                "Cannot call enum type '{}' as a function; enums have no constructor",
+                name
+            ));
+        } else {
+            return None;
+        };
+        Some(format!(
+            "Cannot call {} type '{}' as a function; {} values have no constructor, \
+             declare one with `let v: {} = zeroed({});` and assign its fields",
+            kind, name, kind, name, name
+        ))
+    }
+
     fn add_struct(&mut self, s: Struct) {
         self.struct_types.insert(s.name.clone(), s);
     }
@@ -99,6 +228,26 @@ impl TypeContext {
     }
 
     fn typecheck_program(&mut self, program: &Program) -> Result<(), TypeError> {
+        for item in &program.items {
+            if let Item::Function(f) = item {
+                if f.attrs.contains(&FunctionAttribute::Noreturn) {
+                    self.noreturn_functions.insert(f.name.clone());
+                }
+                if f.attrs.contains(&FunctionAttribute::MustUse) {
+                    self.must_use_functions.insert(f.name.clone());
+                }
+                if f.variadic {
+                    self.variadic_functions.insert(f.name.clone());
+                }
+                let params = f.params.iter().map(|p| *p.ty.clone()).collect();
+                self.add_variable(
+                    f.name.clone(),
+                    Type::Func(params, f.return_type.clone()),
+                    true,
+                );
+            }
+        }
+        check_type_cycles(program)?;
         for item in &program.items {
             self.typecheck_item(item)?;
         }
@@ -109,10 +258,24 @@ impl TypeContext {
         match item {
             Item::Function(f) => self.typecheck_function(f),
             Item::Struct(s) => {
+                for field in &s.fields {
+                    reject_void(
+                        &field.ty,
+                        &format!("Field '{}' of struct {}", field.name, s.name),
+                        format!("struct {}", s.name),
+                    )?;
+                }
                 self.add_struct(s.clone());
                 Ok(())
             }
             Item::Union(u) => {
+                for variant in &u.variants {
+                    reject_void(
+                        &variant.ty,
+                        &format!("Field '{}' of union {}", variant.name, u.name),
+                        format!("union {}", u.name),
+                    )?;
+                }
                 self.add_union(u.clone());
                 Ok(())
             }
@@ -126,25 +289,154 @@ impl TypeContext {
             Item::TopologyDecl(t) => self.typecheck_topology_decl(t),
             Item::BitRegionDecl(b) => self.typecheck_bit_region_decl(b),
             Item::EntropyDecl(e) => self.typecheck_entropy_decl(e),
+            // Already evaluated to true by the const-fold pass
+            Item::StaticAssert(_) => Ok(()),
         }
     }
 
     fn typecheck_function(&mut self, f: &Function) -> Result<(), TypeError> {
         let prev_fn = self.current_function.replace(f.name.clone());
+        let prev_return = self.current_return_type.replace(*f.return_type.clone());
         self.push_scope();
         for param in &f.params {
+            reject_void(
+                &param.ty,
+                &format!("Parameter '{}' of {}", param.name, f.name),
+                format!("fn {}", f.name),
+            )
+            .map_err(|mut e| {
+                e.span.get_or_insert_with(|| f.span.clone());
+                e
+            })?;
             self.add_variable(param.name.clone(), *param.ty.clone(), true);
         }
+        // An extern only declares the signature; its body lives elsewhere
+        if f.attrs.contains(&FunctionAttribute::Extern) {
+            self.pop_scope();
+            self.current_function = prev_fn;
+            self.current_return_type = prev_return;
+            return Ok(());
+        }
+        // Reading the extra arguments would need va_list support
+        if f.variadic {
+            return Err(TypeError {
+                message: format!(
+                    "Only extern functions can be variadic, but '{}' has a body",
+                    f.name
+                ),
+                location: format!("fn {}", f.name),
+                span: Some(f.span.clone()),
+            });
+        }
+        let first_warning = self.warnings.len();
+        self.unread_locals.clear();
         for stmt in &f.body {
-            self.typecheck_stmt(stmt)?;
+            // Errors without a more precise location point at the function.
+            self.typecheck_stmt(stmt).map_err(|mut e| {
+                e.span.get_or_insert_with(|| f.span.clone());
+                e
+            })?;
+        }
+        for (name, span) in std::mem::take(&mut self.unread_locals) {
+            self.warnings.push(TypeWarning {
+                message: format!(
+                    "Unused variable '{}' in '{}' (prefix it with an underscore to silence this)",
+                    name, f.name
+                ),
+                location: format!("let {}", name),
+                span: Some(span),
+            });
+        }
+        for warning in &mut self.warnings[first_warning..] {
+            warning.span.get_or_insert_with(|| f.span.clone());
         }
         self.pop_scope();
         self.current_function = prev_fn;
+        self.current_return_type = prev_return;
+
+        if *f.return_type != Type::Void
+            && !f.attrs.contains(&FunctionAttribute::Noreturn)
+            && !self.block_diverges(&f.body)
+        {
+            return Err(TypeError {
+                message: format!(
+                    "Function '{}' can reach the end of its body without returning a value of type {:?}",
+                    f.name, f.return_type
+                ),
+                location: format!("fn {}", f.name),
+                span: Some(f.span.clone()),
+            });
+        }
         Ok(())
     }
 
-    fn typecheck_const_decl(&mut self, c: &ConstDecl, _global: bool) -> Result<(), TypeError> {
-        let value_type = self.typecheck_expr(&c.value)?;
+    // A block diverges when control can never fall out of its end: every path
+    // returns, calls a noreturn function, or loops forever.
+    fn block_diverges(&self, stmts: &[Stmt]) -> bool {
+        stmts.iter().any(|s| self.stmt_diverges(s))
+    }
+
+    fn stmt_diverges(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Return(_) => true,
+            Stmt::Block(stmts) => self.block_diverges(stmts),
+            Stmt::If(if_stmt) => match &if_stmt.else_branch {
+                Some(else_branch) => {
+                    self.block_diverges(&if_stmt.then_branch) && self.block_diverges(else_branch)
+                }
+                None => false,
+            },
+            Stmt::While(w) => {
+                matches!(*w.condition, Expr::Literal(Literal::Bool(true)))
+                    && !Self::block_breaks(&w.body)
+            }
+            Stmt::Expr(Expr::Loop(body)) => !Self::block_breaks(body),
+            Stmt::Expr(Expr::Call(callee, _)) => match &**callee {
+                Expr::Identifier(name, _) => self.noreturn_functions.contains(name),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    // Whether control can leave the end of a branch and continue after it.
+    fn block_falls_through(&self, stmts: &[Stmt]) -> bool {
+        !self.block_diverges(stmts)
+            && !stmts
+                .iter()
+                .any(|s| matches!(s, Stmt::Break(..) | Stmt::Continue(_)))
+    }
+
+    // Whether a `break` in this loop body targets the loop itself (breaks
+    // inside nested loops leave only the inner loop).
+    fn block_breaks(stmts: &[Stmt]) -> bool {
+        stmts.iter().any(|s| match s {
+            Stmt::Break(..) => true,
+            Stmt::Block(stmts) => Self::block_breaks(stmts),
+            Stmt::If(if_stmt) => {
+                Self::block_breaks(&if_stmt.then_branch)
+                    || if_stmt
+                        .else_branch
+                        .as_ref()
+                        .is_some_and(|b| Self::block_breaks(b))
+            }
+            _ => false,
+        })
+    }
+
+    fn typecheck_const_decl(&mut self, c: &ConstDecl, global: bool) -> Result<(), TypeError> {
+        let value_type = match &*c.value {
+            Expr::Literal(Literal::String(_)) => self.typecheck_sized_expr(&c.value)?,
+            Expr::Unary(UnaryOp::AddrOf, target) | Expr::AddrOf(target) if global => {
+                self.typecheck_global_address(target, c.ty.as_deref())?
+            }
+            value => self.typecheck_expr_expecting(value, c.ty.as_deref())?,
+        };
+        reject_void(
+            &value_type,
+            &format!("Constant '{}'", c.name),
+            format!("const {}", c.name),
+        )?;
         if let Some(expected_ty) = &c.ty {
             if **expected_ty != value_type {
                 return Err(TypeError {
@@ -153,14 +445,46 @@ impl TypeContext {
                         expected_ty, value_type
                     ),
                     location: format!("const {}", c.name),
+                    span: None,
                 });
             }
         }
+        if global {
+            self.add_variable(c.name.clone(), value_type, true);
+        }
         Ok(())
     }
 
+    // `&name` as a global initializer is the address of name's storage. A
+    // string constant's storage is its bytes, so its address is a `*u8`
+    // rather than a pointer to the pointer the name reads as. The address
+    // may be declared `*const`, which only narrows what it allows.
+    fn typecheck_global_address(
+        &mut self,
+        target: &Expr,
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        let pointee = match target {
+            Expr::Identifier(name, _) => match self.lookup_variable(name) {
+                Some((Type::MutPtr(inner), _)) if **inner == Type::U8 => Type::U8,
+                Some((ty, _)) => ty.clone(),
+                None => self.typecheck_expr(target)?,
+            },
+            _ => self.typecheck_expr(target)?,
+        };
+        Ok(match expected {
+            Some(Type::ConstPtr(_)) => Type::ConstPtr(Box::new(pointee)),
+            _ => Type::MutPtr(Box::new(pointee)),
+        })
+    }
+
     fn typecheck_var_decl(&mut self, v: &VarDecl, _global: bool) -> Result<(), TypeError> {
-        let value_type = self.typecheck_expr(&v.value)?;
+        let value_type = self.typecheck_expr_expecting(&v.value, v.ty.as_deref())?;
+        reject_void(
+            &value_type,
+            &format!("Variable '{}'", v.name),
+            format!("var {}", v.name),
+        )?;
         if let Some(expected_ty) = &v.ty {
             if **expected_ty != value_type {
                 return Err(TypeError {
@@ -169,6 +493,7 @@ impl TypeContext {
                         expected_ty, value_type
                     ),
                     location: format!("var {}", v.name),
+                    span: None,
                 });
             }
         }
@@ -229,22 +554,18 @@ impl TypeContext {
             Stmt::Const(c) => self.typecheck_const_stmt(c),
             Stmt::Expr(e) => {
                 self.typecheck_expr(e)?;
+                self.check_unused_result(e);
                 Ok(())
             }
-            Stmt::Return(r) => {
-                if let Some(expr) = r {
-                    self.typecheck_expr(expr)?;
-                }
-                Ok(())
-            }
-            Stmt::Break => Ok(()),
-            Stmt::Continue => Ok(()),
-            Stmt::Block(stmts) => {
-                for s in stmts {
-                    self.typecheck_stmt(s)?;
-                }
-                Ok(())
-            }
+            Stmt::Return(r) => self.typecheck_return(r.as_ref()),
+            Stmt::Break(value, span) => self.typecheck_break(value.as_ref(), span),
+            Stmt::Continue(span) if self.break_targets.is_empty() => Err(TypeError {
+                message: "continue outside of loop".to_string(),
+                location: "continue".to_string(),
+                span: Some(span.clone()),
+            }),
+            Stmt::Continue(_) => Ok(()),
+            Stmt::Block(stmts) => self.typecheck_block(stmts),
             Stmt::If(if_stmt) => self.typecheck_if_stmt(if_stmt),
             Stmt::While(w) => self.typecheck_while_stmt(w),
             Stmt::For(f) => self.typecheck_for_stmt(f),
@@ -254,8 +575,81 @@ impl TypeContext {
         }
     }
 
+    // Names declared in a block go out of scope at its end, as they do in
+    // codegen
+    fn typecheck_block(&mut self, stmts: &[Stmt]) -> Result<(), TypeError> {
+        self.push_scope();
+        for stmt in stmts {
+            self.typecheck_stmt(stmt)?;
+        }
+        self.pop_scope();
+        Ok(())
+    }
+
+    fn typecheck_return(&mut self, value: Option<&Expr>) -> Result<(), TypeError> {
+        let name = self.current_function.clone().unwrap_or_default();
+        let expected = self.current_return_type.clone().unwrap_or(Type::Void);
+        let message = match value {
+            Some(expr) => {
+                let ty = self.typecheck_expr_expecting(expr, Some(&expected))?;
+                if expected == Type::Void {
+                    format!(
+                        "Function '{}' returns void, but this return has a value of type {:?}",
+                        name, ty
+                    )
+                } else if ty != expected {
+                    format!(
+                        "Return type mismatch in '{}': expected {:?}, got {:?}",
+                        name, expected, ty
+                    )
+                } else {
+                    return Ok(());
+                }
+            }
+            None if expected != Type::Void => format!(
+                "Function '{}' must return a value of type {:?}, but this return has none",
+                name, expected
+            ),
+            None => return Ok(()),
+        };
+        Err(TypeError {
+            message,
+            location: format!("fn {}", name),
+            span: None,
+        })
+    }
+
     fn typecheck_let_stmt(&mut self, l: &LetStmt) -> Result<(), TypeError> {
-        let value_type = self.typecheck_expr(&l.value)?;
+        let what = format!("Variable '{}'", l.name);
+        if !l.name.starts_with('_') {
+            self.unread_locals.push((l.name.clone(), l.span.clone()));
+        }
+        if let Some(ty) = &l.ty {
+            reject_void(ty, &what, format!("let {}", l.name))?;
+        }
+        if l.is_static {
+            let ty = l.ty.as_deref().cloned().ok_or_else(|| TypeError {
+                message: format!("static {} needs a type annotation", l.name),
+                location: format!("static {}", l.name),
+                span: None,
+            })?;
+            // Without an initializer the slot starts out zeroed
+            if l.value.is_none() {
+                self.add_variable(l.name.clone(), ty, false);
+                return Ok(());
+            }
+        }
+        let Some(value) = &l.value else {
+            let ty = l.ty.as_deref().cloned().ok_or_else(|| TypeError {
+                message: "A let without an initializer needs a type annotation".to_string(),
+                location: format!("let {}", l.name),
+                span: None,
+            })?;
+            self.declare_uninitialized(l.name.clone(), ty, l.is_const);
+            return Ok(());
+        };
+        let value_type = self.typecheck_expr_expecting(value, l.ty.as_deref())?;
+        reject_void(&value_type, &what, format!("let {}", l.name))?;
         if let Some(expected_ty) = &l.ty {
             if **expected_ty != value_type {
                 return Err(TypeError {
@@ -264,6 +658,7 @@ impl TypeContext {
                         expected_ty, value_type
                     ),
                     location: format!("let {}", l.name),
+                    span: None,
                 });
             }
         }
@@ -272,7 +667,15 @@ impl TypeContext {
     }
 
     fn typecheck_const_stmt(&mut self, c: &ConstStmt) -> Result<(), TypeError> {
-        let value_type = self.typecheck_expr(&c.value)?;
+        let value_type = match &*c.value {
+            Expr::Literal(Literal::String(_)) => self.typecheck_sized_expr(&c.value)?,
+            value => self.typecheck_expr_expecting(value, c.ty.as_deref())?,
+        };
+        reject_void(
+            &value_type,
+            &format!("Constant '{}'", c.name),
+            format!("const {}", c.name),
+        )?;
         if let Some(expected_ty) = &c.ty {
             if **expected_ty != value_type {
                 return Err(TypeError {
@@ -281,6 +684,7 @@ impl TypeContext {
                         expected_ty, value_type
                     ),
                     location: format!("const {}", c.name),
+                    span: None,
                 });
             }
         }
@@ -294,30 +698,69 @@ impl TypeContext {
             return Err(TypeError {
                 message: format!("If condition must be bool, got {:?}", cond_type),
                 location: "if condition".to_string(),
+                span: None,
             });
         }
-        for stmt in &if_stmt.then_branch {
-            self.typecheck_stmt(stmt)?;
-        }
+        let before = self.uninitialized.clone();
+        self.typecheck_block(&if_stmt.then_branch)?;
+        let after_then = std::mem::replace(&mut self.uninitialized, before);
         if let Some(else_branch) = &if_stmt.else_branch {
-            for stmt in else_branch {
-                self.typecheck_stmt(stmt)?;
-            }
+            self.typecheck_block(else_branch)?;
+        }
+        let else_falls_through = if_stmt
+            .else_branch
+            .as_ref()
+            .is_none_or(|b| self.block_falls_through(b));
+        // Paths that return or break out never reach the code after the if,
+        // so only their fall-through siblings contribute.
+        match (
+            self.block_falls_through(&if_stmt.then_branch),
+            else_falls_through,
+        ) {
+            (true, true) => self.merge_uninitialized(after_then),
+            (true, false) => self.uninitialized = after_then,
+            (false, _) => {}
         }
         Ok(())
     }
 
+    // A bare call to a `@must_use` function throws its result away;
+    // `f() as void` says that is intended
+    fn check_unused_result(&mut self, e: &Expr) {
+        let Expr::Call(callee, _) = e else {
+            return;
+        };
+        let Expr::Identifier(name, span) = &**callee else {
+            return;
+        };
+        if self.must_use_functions.contains(name) {
+            self.warnings.push(TypeWarning {
+                message: format!(
+                    "Result of '{}' is ignored, but it is marked @must_use (cast the call to void to discard it)",
+                    name
+                ),
+                location: format!("call to {}", name),
+                span: Some(span.clone()),
+            });
+        }
+    }
+
     fn typecheck_while_stmt(&mut self, w: &WhileStmt) -> Result<(), TypeError> {
         let cond_type = self.typecheck_expr(&w.condition)?;
         if cond_type != Type::Bool {
             return Err(TypeError {
                 message: format!("While condition must be bool, got {:?}", cond_type),
                 location: "while condition".to_string(),
+                span: None,
             });
         }
-        for stmt in &w.body {
-            self.typecheck_stmt(stmt)?;
-        }
+        // The body may run zero times, so assignments in it do not count
+        // after the loop.
+        let before = self.uninitialized.clone();
+        self.break_targets.push(BreakTarget::Stmt);
+        self.typecheck_block(&w.body)?;
+        self.break_targets.pop();
+        self.merge_uninitialized(before);
         Ok(())
     }
 
@@ -328,15 +771,77 @@ impl TypeContext {
             return Err(TypeError {
                 message: format!("For condition must be bool, got {:?}", cond_type),
                 location: "for condition".to_string(),
+                span: None,
             });
         }
+        let before = self.uninitialized.clone();
         self.typecheck_stmt(&f.update)?;
-        for stmt in &f.body {
-            self.typecheck_stmt(stmt)?;
-        }
+        self.break_targets.push(BreakTarget::Stmt);
+        self.typecheck_block(&f.body)?;
+        self.break_targets.pop();
+        self.merge_uninitialized(before);
         Ok(())
     }
 
+    // A loop expression's type is the type shared by all of its break
+    // values; a loop left only by bare breaks (or never) is void.
+    fn typecheck_loop_expr(
+        &mut self,
+        body: &[Stmt],
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        // A break may run before any assignment in the body
+        let before = self.uninitialized.clone();
+        self.break_targets.push(BreakTarget::Expr {
+            ty: None,
+            expected: expected.cloned(),
+        });
+        self.typecheck_block(body)?;
+        let target = self.break_targets.pop();
+        self.merge_uninitialized(before);
+        match target {
+            Some(BreakTarget::Expr { ty: Some(ty), .. }) => Ok(ty),
+            _ => Ok(Type::Void),
+        }
+    }
+
+    fn typecheck_break(&mut self, value: Option<&Expr>, span: &Span) -> Result<(), TypeError> {
+        if self.break_targets.is_empty() {
+            return Err(TypeError {
+                message: "break outside of loop".to_string(),
+                location: "break".to_string(),
+                span: Some(span.clone()),
+            });
+        }
+        let hint = match self.break_targets.last() {
+            Some(BreakTarget::Expr { ty, expected }) => ty.as_ref().or(expected.as_ref()).cloned(),
+            _ => None,
+        };
+        let ty = match value {
+            Some(value) => self.typecheck_expr_expecting(value, hint.as_ref())?,
+            None => Type::Void,
+        };
+        match self.break_targets.last_mut() {
+            Some(BreakTarget::Expr {
+                ty: Some(first), ..
+            }) if *first != ty => Err(TypeError {
+                message: format!("Loop breaks with different types: {:?} and {:?}", first, ty),
+                location: "break".to_string(),
+                span: None,
+            }),
+            Some(BreakTarget::Expr { ty: first, .. }) => {
+                *first = Some(ty);
+                Ok(())
+            }
+            _ if value.is_some() => Err(TypeError {
+                message: "break with a value is only allowed inside a loop expression".to_string(),
+                location: "break".to_string(),
+                span: None,
+            }),
+            _ => Ok(()),
+        }
+    }
+
     fn typecheck_entropy_assert(&mut self, ea: &EntropyAssertStmt) -> Result<(), TypeError> {
         let expr_ty = self.typecheck_expr(&ea.expr)?;
         match &expr_ty {
@@ -348,6 +853,7 @@ impl TypeContext {
                             ea.expected, entropy.state
                         ),
                         location: "entropy_assert".to_string(),
+                        span: None,
                     });
                 }
             }
@@ -355,6 +861,7 @@ impl TypeContext {
                 return Err(TypeError {
                     message: "Entropy assert on non-entropy type".to_string(),
                     location: "entropy_assert".to_string(),
+                    span: None,
                 });
             }
         }
@@ -364,22 +871,112 @@ impl TypeContext {
     fn typecheck_expr(&mut self, expr: &Expr) -> Result<Type, TypeError> {
         match expr {
             Expr::Literal(l) => self.typecheck_literal(l),
-            Expr::Identifier(name) => {
+            Expr::Identifier(name, span) => {
+                if self.is_uninitialized(name) {
+                    return Err(TypeError {
+                        message: format!("Use of possibly-uninitialized variable: {}", name),
+                        location: name.clone(),
+                        span: Some(span.clone()),
+                    });
+                }
                 if let Some((ty, _)) = self.lookup_variable(name) {
-                    Ok(ty.clone())
+                    let ty = ty.clone();
+                    self.unread_locals.retain(|(local, _)| local != name);
+                    Ok(ty)
+                } else if let Some(en) = self.enum_with_variant(name) {
+                    // Variants are only in scope qualified by their enum
+                    Err(TypeError {
+                        message: format!(
+                            "Undefined variable: {}; to name the variant of enum {}, write `{}.{}`",
+                            name, en, en, name
+                        ),
+                        location: name.clone(),
+                        span: Some(span.clone()),
+                    })
                 } else {
                     Err(TypeError {
                         message: format!("Undefined variable: {}", name),
                         location: name.clone(),
+                        span: Some(span.clone()),
                     })
                 }
             }
             Expr::Unary(op, e) => self.typecheck_unary(op, e),
-            Expr::Binary(op, l, r) => self.typecheck_binary(op, l, r),
+            Expr::Binary(op, l, r, span) => self.typecheck_binary(op, l, r, span),
             Expr::Call(f, args) => self.typecheck_call(f, args),
             Expr::Syscall(name, args) => {
-                for arg in args {
-                    self.typecheck_expr(arg)?;
+                for (i, arg) in args.iter().enumerate() {
+                    // read fills its buffer, so a local array passed there is
+                    // initialized by the call rather than read
+                    if let Expr::Identifier(buf, _) = arg {
+                        let array = matches!(self.lookup_variable(buf), Some((Type::Array(..), _)));
+                        if name == "read" && i == 1 && array {
+                            self.mark_initialized(buf);
+                        }
+                    }
+                    let ty = self.typecheck_sized_expr(arg)?;
+                    if name == "write" && i == 2 && !ty.is_integer() {
+                        return Err(TypeError {
+                            message: format!("write length must be an integer, got {:?}", ty),
+                            location: "syscall.write".to_string(),
+                            span: None,
+                        });
+                    }
+                    if name == "read" && i != 1 && !ty.is_integer() {
+                        let what = if i == 0 { "fd" } else { "length" };
+                        return Err(TypeError {
+                            message: format!("read {} must be an integer, got {:?}", what, ty),
+                            location: "syscall.read".to_string(),
+                            span: None,
+                        });
+                    }
+                    if name == "exit" && !ty.is_integer() {
+                        return Err(TypeError {
+                            message: format!("exit status must be an integer, got {:?}", ty),
+                            location: "syscall.exit".to_string(),
+                            span: None,
+                        });
+                    }
+                }
+                Ok(Type::Isize)
+            }
+            Expr::RawSyscall(args) => {
+                if args.is_empty() || args.len() > 7 {
+                    return Err(TypeError {
+                        message: format!(
+                            "syscall takes a number and at most 6 arguments, got {} argument(s)",
+                            args.len().saturating_sub(1)
+                        ),
+                        location: "syscall".to_string(),
+                        span: None,
+                    });
+                }
+                for (i, arg) in args.iter().enumerate() {
+                    let ty = self.typecheck_sized_expr(arg)?;
+                    if i == 0 && !ty.is_integer() {
+                        return Err(TypeError {
+                            message: format!("syscall number must be an integer, got {:?}", ty),
+                            location: "syscall".to_string(),
+                            span: None,
+                        });
+                    }
+                    // Only what fits a general-purpose register can be passed
+                    let scalar = ty.is_integer()
+                        || ty == Type::Bool
+                        || matches!(
+                            ty,
+                            Type::Ptr(_) | Type::MutPtr(_) | Type::ConstPtr(_) | Type::Array(..)
+                        );
+                    if !scalar {
+                        return Err(TypeError {
+                            message: format!(
+                                "syscall argument {} must be an integer, pointer or array, got {:?}",
+                                i, ty
+                            ),
+                            location: "syscall".to_string(),
+                            span: None,
+                        });
+                    }
                 }
                 Ok(Type::Isize)
             }
@@ -387,34 +984,35 @@ impl TypeContext {
             Expr::Field(e, field) => self.typecheck_field(e, field),
             Expr::PtrField(e, field) => self.typecheck_ptr_field(e, field),
             Expr::Cast(e, ty) => {
-                self.typecheck_expr(e)?;
+                let from = self.typecheck_expr(e)?;
+                self.check_cast(&from, ty)?;
+                Ok(ty.clone())
+            }
+            Expr::Sizeof(ty) => self.typecheck_layout_query("sizeof", ty),
+            Expr::Alignof(ty) => self.typecheck_layout_query("alignof", ty),
+            Expr::Offsetof(ty, path) => self.typecheck_offsetof(ty, path),
+            Expr::Zeroed(ty) => {
+                if let Type::Named(name) = ty {
+                    if self.lookup_struct(name).is_none()
+                        && self.lookup_union(name).is_none()
+                        && self.lookup_enum(name).is_none()
+                    {
+                        return Err(TypeError {
+                            message: format!("Unknown type {} in zeroed", name),
+                            location: "zeroed".to_string(),
+                            span: None,
+                        });
+                    }
+                }
                 Ok(ty.clone())
             }
-            Expr::Sizeof(_ty) => Ok(Type::Usize),
-            Expr::Alignof(_ty) => Ok(Type::Usize),
-            Expr::Offsetof(_ty, _field) => Ok(Type::Usize),
+            Expr::ArrayLit(elements) => self.typecheck_array_literal(elements, None),
             Expr::Assign(l, r) => self.typecheck_assign(l, r),
             Expr::AddrOf(e) => {
+                self.mark_place_initialized(e);
                 let inner = self.typecheck_expr(e)?;
                 Ok(Type::MutPtr(Box::new(inner)))
             }
-            Expr::Deref(e) => {
-                let ptr_type = self.typecheck_expr(e)?;
-                match ptr_type {
-                    Type::MutPtr(inner) | Type::ConstPtr(inner) => Ok(*inner),
-                    Type::PhysAddr(p) => {
-                        if let Some(elem) = p.element_type {
-                            Ok(*elem)
-                        } else {
-                            Ok(Type::U8)
-                        }
-                    }
-                    _ => Err(TypeError {
-                        message: format!("Cannot dereference non-pointer type {:?}", ptr_type),
-                        location: "deref".to_string(),
-                    }),
-                }
-            }
             Expr::Block(stmts, result) => {
                 for s in stmts {
                     self.typecheck_stmt(s)?;
@@ -435,6 +1033,7 @@ impl TypeContext {
                 Ok(Type::Void)
             }
             Expr::If(if_expr) => self.typecheck_if_expr(if_expr),
+            Expr::Loop(body) => self.typecheck_loop_expr(body, None),
             Expr::PhysAddr(p) => {
                 let phys_type = PhysAddrType {
                     base_address: p.base_address,
@@ -450,6 +1049,40 @@ impl TypeContext {
         }
     }
 
+    // Like `typecheck_expr`, but for positions that keep a string literal's
+    // length (const initializers, syscall arguments), where embedded NULs are
+    // just data.
+    fn typecheck_sized_expr(&mut self, expr: &Expr) -> Result<Type, TypeError> {
+        match expr {
+            Expr::Literal(Literal::String(_)) => Ok(Type::MutPtr(Box::new(Type::U8))),
+            _ => self.typecheck_expr(expr),
+        }
+    }
+
+    // Literals are parsed as f64; an `f32` suffix must not overflow when
+    // narrowed, and should not silently round
+    fn check_f32_literal(&mut self, val: f64) -> Result<(), TypeError> {
+        let narrowed = val as f32;
+        if narrowed.is_infinite() && val.is_finite() {
+            return Err(TypeError {
+                message: format!("Float literal {:e} overflows f32", val),
+                location: "float literal".to_string(),
+                span: None,
+            });
+        }
+        if narrowed as f64 != val {
+            self.warnings.push(TypeWarning {
+                message: format!(
+                    "Float literal {:e} is not exactly representable as f32 and rounds to {:e}",
+                    val, narrowed
+                ),
+                location: "float literal".to_string(),
+                span: None,
+            });
+        }
+        Ok(())
+    }
+
     fn typecheck_literal(&mut self, l: &Literal) -> Result<Type, TypeError> {
         match l {
             Literal::Int(_, suffix) => match suffix {
@@ -465,18 +1098,101 @@ impl TypeContext {
                 IntSuffix::Isize => Ok(Type::Isize),
                 IntSuffix::None => Ok(Type::I32),
             },
-            Literal::Float(_, suffix) => match suffix {
-                FloatSuffix::F32 => Ok(Type::F32),
+            Literal::Float(val, suffix) => match suffix {
+                FloatSuffix::F32 => {
+                    self.check_f32_literal(*val)?;
+                    Ok(Type::F32)
+                }
                 FloatSuffix::F64 => Ok(Type::F64),
                 FloatSuffix::None => Ok(Type::F64),
             },
             Literal::Bool(_) => Ok(Type::Bool),
+            // Elsewhere a string is only reachable through its pointer, as a
+            // NUL-terminated C string, so a NUL inside it would truncate it.
+            Literal::String(bytes) if bytes.contains(&0) => Err(TypeError {
+                message: "String literal with an embedded NUL cannot be used as a C string"
+                    .to_string(),
+                location: format!("\"{}\"", bytes.escape_ascii()),
+                span: None,
+            }),
             Literal::String(_) => Ok(Type::MutPtr(Box::new(Type::U8))),
             Literal::Char(_) => Ok(Type::U8),
         }
     }
 
+    // Every element must have the type of the first. With an expected
+    // element type, unsuffixed literal elements adopt it the way a scalar
+    // initializer does.
+    fn typecheck_array_literal(
+        &mut self,
+        elements: &[Expr],
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        let Some((first, rest)) = elements.split_first() else {
+            return Err(TypeError {
+                message: "Array literal must have at least one element".to_string(),
+                location: "array literal".to_string(),
+                span: None,
+            });
+        };
+        let elem_type = self.typecheck_expr_expecting(first, expected)?;
+        for (i, element) in rest.iter().enumerate() {
+            let ty = self.typecheck_expr_expecting(element, expected)?;
+            if ty != elem_type {
+                return Err(TypeError {
+                    message: format!(
+                        "Array literal element {} has type {:?}, expected {:?}",
+                        i + 1,
+                        ty,
+                        elem_type
+                    ),
+                    location: "array literal".to_string(),
+                    span: None,
+                });
+            }
+        }
+        Ok(Type::Array(elements.len(), Box::new(elem_type)))
+    }
+
+    // An unsuffixed integer literal, negated or not, takes the integer type
+    // its context expects and only defaults to i32 without one. An array
+    // literal passes the expected element type on to its elements.
+    fn typecheck_expr_expecting(
+        &mut self,
+        expr: &Expr,
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        if let (Expr::ArrayLit(elements), Some(Type::Array(_, elem))) = (expr, expected) {
+            return self.typecheck_array_literal(elements, Some(elem));
+        }
+        if let Expr::Loop(body) = expr {
+            return self.typecheck_loop_expr(body, expected);
+        }
+        let value = unsuffixed_int_literal(expr);
+        let (Some(value), Some(expected)) = (value, expected.filter(|ty| ty.is_integer())) else {
+            return self.typecheck_expr(expr);
+        };
+        let (bits, signed) = int_shape(expected).unwrap_or((64, true));
+        if !int_literal_fits(value, bits, signed) {
+            return Err(TypeError {
+                message: format!(
+                    "Integer literal {} does not fit in {}",
+                    value,
+                    int_type_name(expected)
+                ),
+                location: "integer literal".to_string(),
+                span: None,
+            });
+        }
+        Ok(expected.clone())
+    }
+
     fn typecheck_unary(&mut self, op: &UnaryOp, e: &Expr) -> Result<Type, TypeError> {
+        // `&x` is not a read of x: the pointer may be how it gets its value,
+        // as in `fill(&x)`
+        if matches!(op, UnaryOp::AddrOf) {
+            self.mark_place_initialized(e);
+        }
         let ty = self.typecheck_expr(e)?;
         match op {
             UnaryOp::Neg => {
@@ -486,6 +1202,7 @@ impl TypeContext {
                     Err(TypeError {
                         message: format!("Cannot negate type {:?}", ty),
                         location: "neg".to_string(),
+                        span: None,
                     })
                 }
             }
@@ -496,6 +1213,7 @@ impl TypeContext {
                     Err(TypeError {
                         message: format!("Cannot logical NOT type {:?}", ty),
                         location: "not".to_string(),
+                        span: None,
                     })
                 }
             }
@@ -506,11 +1224,12 @@ impl TypeContext {
                     Err(TypeError {
                         message: format!("Cannot bitwise NOT type {:?}", ty),
                         location: "bitnot".to_string(),
+                        span: None,
                     })
                 }
             }
             UnaryOp::Deref => match ty {
-                Type::MutPtr(inner) | Type::ConstPtr(inner) => Ok(*inner),
+                Type::Ptr(inner) | Type::MutPtr(inner) | Type::ConstPtr(inner) => Ok(*inner),
                 Type::PhysAddr(p) => {
                     if let Some(elem) = p.element_type {
                         Ok(*elem)
@@ -521,21 +1240,46 @@ impl TypeContext {
                 _ => Err(TypeError {
                     message: format!("Cannot dereference non-pointer type {:?}", ty),
                     location: "deref".to_string(),
+                    span: None,
                 }),
             },
             UnaryOp::AddrOf => Ok(Type::MutPtr(Box::new(ty))),
         }
     }
 
-    fn typecheck_binary(&mut self, op: &BinaryOp, l: &Expr, r: &Expr) -> Result<Type, TypeError> {
-        let left = self.typecheck_expr(l)?;
+    fn typecheck_binary(
+        &mut self,
+        op: &BinaryOp,
+        l: &Expr,
+        r: &Expr,
+        span: &Span,
+    ) -> Result<Type, TypeError> {
+        let mut left = self.typecheck_expr(l)?;
         let right = self.typecheck_expr(r)?;
 
+        if !matches!(
+            op,
+            BinaryOp::LShift | BinaryOp::RShift | BinaryOp::LogicalAnd | BinaryOp::LogicalOr
+        ) {
+            check_bit_int_operands(op, &left, &right)?;
+            // A bit-precise integer combined with the primitive of the same
+            // shape yields the primitive
+            if matches!(left, Type::BitInt(..))
+                && !matches!(right, Type::BitInt(..))
+                && right.is_integer()
+            {
+                left = right.clone();
+            }
+        }
+
         match op {
+            BinaryOp::Add | BinaryOp::Sub if left.is_pointer() || right.is_pointer() => {
+                pointer_arithmetic(op, &left, &right)
+            }
             BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
-                if left.is_integer() && right.is_integer() {
-                    Ok(left)
-                } else if left.is_float() && right.is_float() {
+                if (left.is_integer() && right.is_integer())
+                    || (left.is_float() && right.is_float())
+                {
                     Ok(left)
                 } else {
                     Err(TypeError {
@@ -544,11 +1288,33 @@ impl TypeContext {
                             left, right
                         ),
                         location: format!("{:?}", op),
+                        span: None,
                     })
                 }
             }
             BinaryOp::LShift | BinaryOp::RShift => {
                 if left.is_integer() && right.is_integer() {
+                    let bits = left.int_bits().unwrap_or(64);
+                    let amount = match r {
+                        Expr::Literal(Literal::Int(n, _)) => Some(*n),
+                        Expr::Unary(UnaryOp::Neg, inner) => match &**inner {
+                            Expr::Literal(Literal::Int(n, _)) => Some(-*n),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if let Some(amount) = amount {
+                        if amount < 0 || amount >= bits as i64 {
+                            return Err(TypeError {
+                                message: format!(
+                                    "Shift amount {} out of range for {:?} (must be 0..{})",
+                                    amount, left, bits
+                                ),
+                                location: format!("{:?}", op),
+                                span: Some(span.clone()),
+                            });
+                        }
+                    }
                     Ok(left)
                 } else {
                     Err(TypeError {
@@ -557,6 +1323,7 @@ impl TypeContext {
                             left, right
                         ),
                         location: format!("{:?}", op),
+                        span: None,
                     })
                 }
             }
@@ -570,6 +1337,7 @@ impl TypeContext {
                             left, right
                         ),
                         location: format!("{:?}", op),
+                        span: None,
                     })
                 }
             }
@@ -579,6 +1347,9 @@ impl TypeContext {
             | BinaryOp::Gt
             | BinaryOp::LtEq
             | BinaryOp::GtEq => {
+                if let Some(result) = self.enum_comparison(op, &left, &right) {
+                    return result;
+                }
                 if (left.is_integer() || left.is_float())
                     && (right.is_integer() || right.is_float())
                 {
@@ -590,6 +1361,7 @@ impl TypeContext {
                             left, right
                         ),
                         location: format!("{:?}", op),
+                        span: None,
                     })
                 }
             }
@@ -603,6 +1375,7 @@ impl TypeContext {
                             left, right
                         ),
                         location: format!("{:?}", op),
+                        span: None,
                     })
                 }
             }
@@ -610,11 +1383,42 @@ impl TypeContext {
     }
 
     fn typecheck_call(&mut self, f: &Expr, args: &[Expr]) -> Result<Type, TypeError> {
+        // `Point(1, 2)` is a common stand-in for constructor syntax
+        if let Expr::Identifier(name, span) = f {
+            if self.lookup_variable(name).is_none() {
+                if let Some(message) = self.type_name_call_message(name) {
+                    return Err(TypeError {
+                        message,
+                        location: "function call".to_string(),
+                        span: Some(span.clone()),
+                    });
+                }
+            }
+        }
+
         let func_type = self.typecheck_expr(f)?;
+        // Errors about the arguments point at the call through its callee
+        let call_span = match f {
+            Expr::Identifier(_, span) => Some(span.clone()),
+            _ => None,
+        };
+        let variadic =
+            matches!(f, Expr::Identifier(name, _) if self.variadic_functions.contains(name));
 
         match func_type {
             Type::Func(params, ret) => {
-                if params.len() != args.len() {
+                if variadic && args.len() < params.len() {
+                    return Err(TypeError {
+                        message: format!(
+                            "Wrong number of arguments: expected at least {}, got {}",
+                            params.len(),
+                            args.len()
+                        ),
+                        location: "function call".to_string(),
+                        span: call_span.clone(),
+                    });
+                }
+                if !variadic && params.len() != args.len() {
                     return Err(TypeError {
                         message: format!(
                             "Wrong number of arguments: expected {}, got {}",
@@ -622,11 +1426,12 @@ impl TypeContext {
                             args.len()
                         ),
                         location: "function call".to_string(),
+                        span: call_span.clone(),
                     });
                 }
 
                 for (i, (arg, expected)) in args.iter().zip(params.iter()).enumerate() {
-                    let arg_type = self.typecheck_expr(arg)?;
+                    let arg_type = self.typecheck_expr_expecting(arg, Some(expected))?;
                     if arg_type != *expected {
                         return Err(TypeError {
                             message: format!(
@@ -634,6 +1439,26 @@ impl TypeContext {
                                 i, expected, arg_type
                             ),
                             location: format!("argument {}", i),
+                            span: call_span.clone(),
+                        });
+                    }
+                }
+
+                // The variadic tail is passed in registers like any other
+                // argument, which only works for scalars and pointers
+                for (i, arg) in args.iter().enumerate().skip(params.len()) {
+                    let arg_type = self.typecheck_sized_expr(arg)?;
+                    if matches!(
+                        arg_type,
+                        Type::Void | Type::Named(_) | Type::Array(..) | Type::Func(..)
+                    ) {
+                        return Err(TypeError {
+                            message: format!(
+                                "Variadic argument {} must be a scalar or pointer, got {:?}",
+                                i, arg_type
+                            ),
+                            location: format!("argument {}", i),
+                            span: call_span.clone(),
                         });
                     }
                 }
@@ -643,6 +1468,7 @@ impl TypeContext {
             _ => Err(TypeError {
                 message: format!("Cannot call non-function type {:?}", func_type),
                 location: "function call".to_string(),
+                span: None,
             }),
         }
     }
@@ -652,23 +1478,55 @@ impl TypeContext {
         let idx_type = self.typecheck_expr(idx)?;
 
         match arr_type {
-            Type::Array(_, elem_type) => {
+            Type::Array(len, elem_type) => {
                 if idx_type.is_integer() {
+                    let constant = match idx {
+                        Expr::Literal(Literal::Int(i, _)) => Some(*i),
+                        Expr::Unary(UnaryOp::Neg, inner) => match &**inner {
+                            Expr::Literal(Literal::Int(i, _)) => Some(i.wrapping_neg()),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if let Some(i) = constant {
+                        if i < 0 {
+                            return Err(TypeError {
+                                message: format!(
+                                    "Index {} is negative, so it is out of bounds for any array",
+                                    i
+                                ),
+                                location: "array index".to_string(),
+                                span: None,
+                            });
+                        }
+                        if i as u64 >= len as u64 {
+                            return Err(TypeError {
+                                message: format!(
+                                    "Index {} out of bounds for array of length {}",
+                                    i, len
+                                ),
+                                location: "array index".to_string(),
+                                span: None,
+                            });
+                        }
+                    }
                     Ok(*elem_type)
                 } else {
                     Err(TypeError {
                         message: format!("Array index must be integer, got {:?}", idx_type),
                         location: "array index".to_string(),
+                        span: None,
                     })
                 }
             }
-            Type::MutPtr(inner) | Type::ConstPtr(inner) => {
+            Type::Ptr(inner) | Type::MutPtr(inner) | Type::ConstPtr(inner) => {
                 if idx_type.is_integer() {
                     Ok(*inner)
                 } else {
                     Err(TypeError {
                         message: format!("Pointer index must be integer, got {:?}", idx_type),
                         location: "pointer index".to_string(),
+                        span: None,
                     })
                 }
             }
@@ -681,19 +1539,36 @@ impl TypeContext {
                     }
                 } else {
                     Err(TypeError {
-                        message: format!("Physical capability index must be integer"),
+                        message: "Physical capability index must be integer".to_string(),
                         location: "physaddr index".to_string(),
+                        span: None,
                     })
                 }
             }
             _ => Err(TypeError {
                 message: format!("Cannot index non-array/non-pointer type {:?}", arr_type),
                 location: "array index".to_string(),
+                span: None,
             }),
         }
     }
 
     fn typecheck_field(&mut self, e: &Expr, field: &str) -> Result<Type, TypeError> {
+        // `Color.Red`: a variant of an enum no variable shadows
+        if let Expr::Identifier(name, _) = e {
+            if self.lookup_variable(name).is_none() {
+                if let Some(en) = self.lookup_enum(name) {
+                    if en.variants.iter().any(|v| v.name == field) {
+                        return Ok(Type::Named(name.clone()));
+                    }
+                    return Err(TypeError {
+                        message: format!("Enum {} has no variant {}", name, field),
+                        location: format!("{}.{}", name, field),
+                        span: None,
+                    });
+                }
+            }
+        }
         let base_type = self.typecheck_expr(e)?;
 
         match base_type {
@@ -707,11 +1582,13 @@ impl TypeContext {
                     Err(TypeError {
                         message: format!("Struct {} has no field {}", name, field),
                         location: format!(".{}", field),
+                        span: None,
                     })
                 } else {
                     Err(TypeError {
                         message: format!("Unknown struct type {}", name),
                         location: format!(".{}", field),
+                        span: None,
                     })
                 }
             }
@@ -724,11 +1601,13 @@ impl TypeContext {
                 Err(TypeError {
                     message: format!("Bit region {} has no field {}", br.base_type.size(), field),
                     location: format!(".{}", field),
+                    span: None,
                 })
             }
             _ => Err(TypeError {
                 message: format!("Cannot access field on non-struct type {:?}", base_type),
                 location: format!(".{}", field),
+                span: None,
             }),
         }
     }
@@ -750,33 +1629,78 @@ impl TypeContext {
                             Err(TypeError {
                                 message: format!("Struct {} has no field {}", name, field),
                                 location: format!("->{}", field),
+                                span: None,
                             })
                         } else {
                             Err(TypeError {
                                 message: format!("Unknown struct type {}", name),
                                 location: format!("->{}", field),
+                                span: None,
                             })
                         }
                     }
                     _ => Err(TypeError {
                         message: "Cannot access field through non-struct pointer".to_string(),
                         location: format!("->{}", field),
+                        span: None,
                     }),
                 }
             }
             _ => Err(TypeError {
                 message: "Cannot use -> on non-pointer type".to_string(),
                 location: format!("->{}", field),
+                span: None,
             }),
         }
     }
 
+    // A local named by `place`, or the local aggregate it is an element or
+    // field of, counts as initialized once the place is written.
+    fn mark_place_initialized(&mut self, place: &Expr) {
+        let Some(name) = lvalue_root(place) else {
+            return;
+        };
+        let whole = matches!(place, Expr::Identifier(..));
+        let aggregate = self
+            .lookup_variable(name)
+            .is_some_and(|(ty, _)| matches!(ty, Type::Array(..) | Type::Named(_)));
+        if whole || aggregate {
+            self.mark_initialized(name);
+        }
+    }
+
     fn typecheck_assign(&mut self, l: &Expr, r: &Expr) -> Result<Type, TypeError> {
-        let left_type = self.typecheck_expr(l)?;
         let right_type = self.typecheck_expr(r)?;
+        // Assigning a plain local is not a read of it.
+        let left_type = match l {
+            Expr::Identifier(name, _) => {
+                let ty = self.lookup_variable(name).map(|(ty, _)| ty.clone());
+                self.mark_initialized(name);
+                match ty {
+                    Some(ty) => ty,
+                    None => self.typecheck_expr(l)?,
+                }
+            }
+            // Storing into an element or field of a local aggregate counts
+            // as initializing it; elements aren't tracked individually.
+            Expr::Index(..) | Expr::Field(..) => {
+                self.mark_place_initialized(l);
+                self.typecheck_expr(l)?
+            }
+            _ => self.typecheck_expr(l)?,
+        };
+
+        // The value is checked before the target so that reading an unassigned
+        // variable in it is still caught; a bare literal then adopts the
+        // target's integer type like an initializer does
+        let right_type = if unsuffixed_int_literal(r).is_some() {
+            self.typecheck_expr_expecting(r, Some(&left_type))?
+        } else {
+            right_type
+        };
 
         match l {
-            Expr::Identifier(name) => {
+            Expr::Identifier(name, _) => {
                 if let Some((_, is_const_binding)) = self.lookup_variable(name) {
                     if !*is_const_binding {
                         if left_type == right_type {
@@ -788,6 +1712,7 @@ impl TypeContext {
                                     left_type, right_type
                                 ),
                                 location: "assignment".to_string(),
+                                span: None,
                             })
                         }
                     } else {
@@ -797,12 +1722,14 @@ impl TypeContext {
                                 name
                             ),
                             location: "assignment".to_string(),
+                            span: None,
                         })
                     }
                 } else {
                     Err(TypeError {
                         message: format!("Undefined variable: {}", name),
                         location: "assignment".to_string(),
+                        span: None,
                     })
                 }
             }
@@ -816,6 +1743,7 @@ impl TypeContext {
                             left_type, right_type
                         ),
                         location: "assignment".to_string(),
+                        span: None,
                     })
                 }
             }
@@ -829,10 +1757,11 @@ impl TypeContext {
                             left_type, right_type
                         ),
                         location: "assignment".to_string(),
+                        span: None,
                     })
                 }
             }
-            Expr::Deref(_) => {
+            Expr::Unary(UnaryOp::Deref, _) => {
                 if left_type == right_type {
                     Ok(Type::Void)
                 } else {
@@ -842,22 +1771,202 @@ impl TypeContext {
                             left_type, right_type
                         ),
                         location: "assignment".to_string(),
+                        span: None,
                     })
                 }
             }
             _ => Err(TypeError {
-                message: "Invalid assignment target".to_string(),
+                message: format!("Invalid assignment target: {}", unassignable_reason(l)),
                 location: "assignment".to_string(),
+                span: match l {
+                    Expr::Call(callee, _) => match &**callee {
+                        Expr::Identifier(_, span) => Some(span.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                },
             }),
         }
     }
 
+    // A named type must be declared for its layout to be known; anything
+    // else would silently size as 0
+    fn typecheck_layout_query(&self, query: &str, ty: &Type) -> Result<Type, TypeError> {
+        let mut inner = ty;
+        while let Type::Array(_, elem) = inner {
+            inner = elem;
+        }
+        if let Type::Named(name) = inner {
+            if !self.struct_types.contains_key(name)
+                && !self.union_types.contains_key(name)
+                && !self.enum_types.contains_key(name)
+            {
+                return Err(TypeError {
+                    message: format!("{}: unknown type '{}'", query, name),
+                    location: format!("{}({:?})", query, ty),
+                    span: None,
+                });
+            }
+        }
+        Ok(Type::Usize)
+    }
+
+    // Every step of the path must name a field of the struct reached so far
+    fn typecheck_offsetof(&self, ty: &Type, path: &str) -> Result<Type, TypeError> {
+        let mut current = ty.clone();
+        for field in path.split('.') {
+            let fields = match &current {
+                Type::Named(name) => self.struct_types.get(name).map(|s| &s.fields),
+                _ => None,
+            };
+            let Some(fields) = fields else {
+                return Err(TypeError {
+                    message: format!(
+                        "offsetof: {:?} is not a struct, so it has no field '{}'",
+                        current, field
+                    ),
+                    location: format!("offsetof({:?}, {})", ty, path),
+                    span: None,
+                });
+            };
+            let Some(f) = fields.iter().find(|f| f.name == field) else {
+                let struct_name = match &current {
+                    Type::Named(name) => name.as_str(),
+                    _ => "",
+                };
+                return Err(TypeError {
+                    message: format!("offsetof: struct {} has no field '{}'", struct_name, field),
+                    location: format!("offsetof({:?}, {})", ty, path),
+                    span: None,
+                });
+            };
+            current = (*f.ty).clone();
+        }
+        Ok(Type::Usize)
+    }
+
+    // An enum declaring a variant called `variant`, the first by name when
+    // several do, so the message does not depend on hash order
+    fn enum_with_variant(&self, variant: &str) -> Option<&str> {
+        self.enum_types
+            .values()
+            .filter(|e| e.variants.iter().any(|v| v.name == variant))
+            .map(|e| e.name.as_str())
+            .min()
+    }
+
+    // Integers convert to and from every other integer, float, pointer and
+    // enum; bools convert to integers as 0 or 1, and pointers to any other
+    // pointer. Casting to `void` discards any value. Aggregates have no
+    // conversion at all. Capability, topology, entropy and bit-region types
+    // carry their own checks and are passed through here.
+    fn check_cast(&self, from: &Type, to: &Type) -> Result<(), TypeError> {
+        if from == to || *to == Type::Void {
+            return Ok(());
+        }
+        let (from_class, to_class) = (self.cast_class(from), self.cast_class(to));
+        let allowed = match (from_class, to_class) {
+            (CastClass::Other, _) | (_, CastClass::Other) => true,
+            (CastClass::Aggregate, _) | (_, CastClass::Aggregate) => {
+                return Err(TypeError {
+                    message: format!(
+                        "Cannot cast {:?} to {:?}: structs, unions, arrays and functions have no conversion",
+                        from, to
+                    ),
+                    location: "cast".to_string(),
+                    span: None,
+                });
+            }
+            (CastClass::Int, to) => to != CastClass::Bool,
+            (CastClass::Float, to) => matches!(to, CastClass::Int | CastClass::Float),
+            (CastClass::Ptr, to) => matches!(to, CastClass::Int | CastClass::Ptr),
+            (CastClass::Bool | CastClass::Enum, to) => to == CastClass::Int,
+            (CastClass::Void, _) => false,
+        };
+        if allowed {
+            return Ok(());
+        }
+        let hint = if to_class == CastClass::Bool {
+            " (compare against zero to get a bool)"
+        } else {
+            ""
+        };
+        Err(TypeError {
+            message: format!("Cannot cast {:?} to {:?}{}", from, to, hint),
+            location: "cast".to_string(),
+            span: None,
+        })
+    }
+
+    fn cast_class(&self, ty: &Type) -> CastClass {
+        match ty {
+            _ if ty.is_integer() => CastClass::Int,
+            _ if ty.is_float() => CastClass::Float,
+            _ if ty.is_pointer() => CastClass::Ptr,
+            Type::Bool => CastClass::Bool,
+            Type::Void => CastClass::Void,
+            Type::Named(_) if self.enum_name(ty).is_some() => CastClass::Enum,
+            Type::Named(_) | Type::Array(..) | Type::Func(..) => CastClass::Aggregate,
+            _ => CastClass::Other,
+        }
+    }
+
+    fn enum_name<'t>(&self, ty: &'t Type) -> Option<&'t str> {
+        match ty {
+            Type::Named(name) if self.enum_types.contains_key(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    // Enum values compare only for (in)equality with values of the same
+    // enum; anything else needs an explicit cast to an integer. `None` when
+    // neither side is an enum.
+    fn enum_comparison(
+        &self,
+        op: &BinaryOp,
+        left: &Type,
+        right: &Type,
+    ) -> Option<Result<Type, TypeError>> {
+        let message = match (self.enum_name(left), self.enum_name(right)) {
+            (None, None) => return None,
+            (Some(a), Some(b)) if a == b => {
+                if matches!(op, BinaryOp::Eq | BinaryOp::Neq) {
+                    return Some(Ok(Type::Bool));
+                }
+                format!(
+                    "Enum {} values only support == and != (cast to an integer to use {:?})",
+                    a, op
+                )
+            }
+            (Some(a), Some(b)) => {
+                format!("Cannot compare values of different enums {} and {}", a, b)
+            }
+            (Some(name), None) | (None, Some(name)) => {
+                let other = if self.enum_name(left).is_some() {
+                    right
+                } else {
+                    left
+                };
+                format!(
+                    "Cannot compare enum {} with {:?} (cast the enum to an integer type explicitly)",
+                    name, other
+                )
+            }
+        };
+        Some(Err(TypeError {
+            message,
+            location: format!("{:?}", op),
+            span: None,
+        }))
+    }
+
     fn typecheck_if_expr(&mut self, if_expr: &IfExpr) -> Result<Type, TypeError> {
         let cond_type = self.typecheck_expr(&if_expr.condition)?;
         if cond_type != Type::Bool {
             return Err(TypeError {
                 message: format!("If condition must be bool, got {:?}", cond_type),
                 location: "if expression".to_string(),
+                span: None,
             });
         }
 
@@ -873,6 +1982,7 @@ impl TypeContext {
                     then_type, else_type
                 ),
                 location: "if expression".to_string(),
+                span: None,
             })
         }
     }
@@ -880,16 +1990,11 @@ impl TypeContext {
     fn typecheck_entropy_check(&mut self, ec: &EntropyCheckExpr) -> Result<Type, TypeError> {
         let ty = self.typecheck_expr(&ec.expr)?;
         match &ty {
-            Type::Entropy(entropy) => {
-                if entropy.state == ec.expected {
-                    Ok(Type::Bool)
-                } else {
-                    Ok(Type::Bool)
-                }
-            }
+            Type::Entropy(_) => Ok(Type::Bool),
             _ => Err(TypeError {
                 message: "Entropy check on non-entropy type".to_string(),
                 location: "entropy_check".to_string(),
+                span: None,
             }),
         }
     }
@@ -909,11 +2014,13 @@ impl TypeContext {
                 Err(TypeError {
                     message: format!("No bit region {}", bra.region_name),
                     location: "bit_region_access".to_string(),
+                    span: None,
                 })
             }
             _ => Err(TypeError {
                 message: "Bit region access on non-bit-region type".to_string(),
                 location: "bit_region_access".to_string(),
+                span: None,
             }),
         }
     }
@@ -928,3 +2035,235 @@ impl TypeContext {
         ))
     }
 }
+
+// Bit-precise integers only combine with the exact same width and
+// signedness (another bit-precise integer, or the primitive of that shape,
+// such as `i32` for a 32-bit signed one); anything else needs an explicit
+// cast on one side.
+fn check_bit_int_operands(op: &BinaryOp, left: &Type, right: &Type) -> Result<(), TypeError> {
+    let involves_bit_int = matches!(left, Type::BitInt(..)) || matches!(right, Type::BitInt(..));
+    if !involves_bit_int || !left.is_integer() || !right.is_integer() {
+        return Ok(());
+    }
+    if int_shape(left) == int_shape(right) {
+        return Ok(());
+    }
+    Err(TypeError {
+        message: format!(
+            "Mismatched integer operands for {:?}: {} and {} (cast one side explicitly)",
+            op,
+            int_type_name(left),
+            int_type_name(right)
+        ),
+        location: format!("{:?}", op),
+        span: None,
+    })
+}
+
+// `p + n`, `n + p` and `p - n` move a pointer by whole elements and keep its
+// type; `p - q` counts the elements between two pointers to the same type.
+// Elements of `*void` have no size, so it must be cast first.
+fn pointer_arithmetic(op: &BinaryOp, left: &Type, right: &Type) -> Result<Type, TypeError> {
+    let pointee = |ty: &Type| match ty {
+        Type::Ptr(inner) | Type::MutPtr(inner) | Type::ConstPtr(inner) => Some((**inner).clone()),
+        _ => None,
+    };
+    let error = |message: String| TypeError {
+        message,
+        location: format!("{:?}", op),
+        span: None,
+    };
+    let (l, r) = (pointee(left), pointee(right));
+    if l == Some(Type::Void) || r == Some(Type::Void) {
+        return Err(error(
+            "Pointer arithmetic on *void has no element size; cast to *u8 first".to_string(),
+        ));
+    }
+    match (op, l, r) {
+        (BinaryOp::Add | BinaryOp::Sub, Some(_), None) if right.is_integer() => Ok(left.clone()),
+        (BinaryOp::Add, None, Some(_)) if left.is_integer() => Ok(right.clone()),
+        (BinaryOp::Sub, Some(l), Some(r)) if l == r => Ok(Type::Isize),
+        (BinaryOp::Sub, Some(_), Some(_)) => Err(error(format!(
+            "Cannot subtract pointers to different types: {:?} and {:?}",
+            left, right
+        ))),
+        _ => Err(error(format!(
+            "Invalid operand types for pointer arithmetic: {:?} and {:?}",
+            left, right
+        ))),
+    }
+}
+
+// Why `target` names no place in memory that an assignment could store to
+fn unassignable_reason(target: &Expr) -> String {
+    match target {
+        Expr::Binary(op, ..) => format!(
+            "the result of a binary expression ({:?}) is a temporary value, not a place in memory",
+            op
+        ),
+        Expr::Call(callee, _) => match &**callee {
+            Expr::Identifier(name, _) => format!(
+                "a call to '{}' returns a temporary value; to store through a returned pointer, write `*{}(...) = value`",
+                name, name
+            ),
+            _ => "a call returns a temporary value, not a place in memory".to_string(),
+        },
+        Expr::Literal(_) => "a literal is a constant value, not a place in memory".to_string(),
+        Expr::Cast(..) => {
+            "a cast produces a new value; cast the value being assigned instead".to_string()
+        }
+        Expr::Unary(op, _) => format!(
+            "the result of a unary expression ({:?}) is a temporary value, not a place in memory",
+            op
+        ),
+        Expr::AddrOf(_) => "an address is a temporary value, not a place in memory".to_string(),
+        Expr::If(_) | Expr::Block(..) | Expr::Loop(_) => {
+            "the value of a block, if or loop expression is a temporary value".to_string()
+        }
+        _ => "only variables, fields, array elements and dereferenced pointers can be assigned"
+            .to_string(),
+    }
+}
+
+// The value of an unsuffixed integer literal, negated or not
+fn unsuffixed_int_literal(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(Literal::Int(val, IntSuffix::None)) => Some(*val),
+        Expr::Unary(UnaryOp::Neg, inner) => match &**inner {
+            Expr::Literal(Literal::Int(val, IntSuffix::None)) => Some(val.wrapping_neg()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Whether `value` is representable in an integer of the given shape
+fn int_literal_fits(value: i64, bits: u32, signed: bool) -> bool {
+    match (bits, signed) {
+        (64.., true) => true,
+        (64.., false) => value >= 0,
+        (_, true) => (-(1i64 << (bits - 1))..1i64 << (bits - 1)).contains(&value),
+        (_, false) => (0..1i64 << bits).contains(&value),
+    }
+}
+
+// Width and signedness of an integer type
+fn int_shape(ty: &Type) -> Option<(u32, bool)> {
+    let signed = matches!(
+        ty,
+        Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::Isize | Type::BitInt(_, true)
+    );
+    ty.int_bits().map(|bits| (bits, signed))
+}
+
+fn int_type_name(ty: &Type) -> String {
+    match ty {
+        Type::BitInt(bits, true) => format!("i{} (signed, {} bits)", bits, bits),
+        Type::BitInt(bits, false) => format!("u{} (unsigned, {} bits)", bits, bits),
+        other => format!("{:?}", other),
+    }
+}
+
+// The local a `.field` / `[index]` lvalue path is rooted at, if any. Paths
+// through `->` or `*p` read a pointer instead, so they have no root.
+fn lvalue_root(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Identifier(name, _) => Some(name),
+        Expr::Field(base, _) | Expr::Index(base, _) => lvalue_root(base),
+        _ => None,
+    }
+}
+
+// Void has no values, so it only makes sense as a return type. Arrays are
+// looked through, since `[4]void` is just as empty.
+fn reject_void(ty: &Type, what: &str, location: String) -> Result<(), TypeError> {
+    let mut elem = ty;
+    while let Type::Array(_, inner) = elem {
+        elem = inner;
+    }
+    if *elem != Type::Void {
+        return Ok(());
+    }
+    let message = if elem == ty {
+        format!(
+            "{} cannot have type void; void is only valid as a return type",
+            what
+        )
+    } else {
+        format!(
+            "{} is an array of void; void is only valid as a return type",
+            what
+        )
+    };
+    Err(TypeError {
+        message,
+        location,
+        span: None,
+    })
+}
+
+// A struct or union containing itself by value, directly or through other
+// aggregates, has no finite layout. Pointer fields break the cycle since
+// they are 8 bytes whatever they point at.
+fn check_type_cycles(program: &Program) -> Result<(), TypeError> {
+    let mut members: HashMap<&str, Vec<&Type>> = HashMap::new();
+    for item in &program.items {
+        match item {
+            Item::Struct(s) => {
+                members.insert(&s.name, s.fields.iter().map(|f| &*f.ty).collect());
+            }
+            Item::Union(u) => {
+                members.insert(&u.name, u.variants.iter().map(|v| &*v.ty).collect());
+            }
+            _ => {}
+        }
+    }
+
+    let mut done = HashSet::new();
+    for item in &program.items {
+        if let Item::Struct(Struct { name, .. }) | Item::Union(Union { name, .. }) = item {
+            visit_by_value(name, &members, &mut Vec::new(), &mut done)?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_by_value<'a>(
+    name: &'a str,
+    members: &HashMap<&'a str, Vec<&'a Type>>,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Result<(), TypeError> {
+    if done.contains(name) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|n| *n == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name);
+        return Err(TypeError {
+            message: format!(
+                "Recursive type '{}' has infinite size: {} (use a pointer field to break the cycle)",
+                name,
+                cycle.join(" -> ")
+            ),
+            location: format!("struct {}", name),
+            span: None,
+        });
+    }
+    let Some(fields) = members.get(name) else {
+        return Ok(());
+    };
+    path.push(name);
+    for ty in fields {
+        let mut ty = *ty;
+        while let Type::Array(_, elem) = ty {
+            ty = elem;
+        }
+        if let Type::Named(inner) = ty {
+            visit_by_value(inner, members, path, done)?;
+        }
+    }
+    path.pop();
+    done.insert(name);
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// `AuraObject::apply_relocations` writes each relocation's resolved value
+// into text: absolute addresses for Absolute64/Absolute32 and
+// `target - (site + 4)` for Relative32.
+
+use aura_compiler::codegen::{
+    AuraObject, Relocation, RelocationKind, RelocationSection, Symbol, SymbolKind,
+};
+
+const BASE: u64 = 0x40_0000;
+// 0x30 bytes of text, so .data starts on the next page
+const DATA_BASE: u64 = BASE + 0x1000;
+
+fn symbol(name: &str, offset: u64, kind: SymbolKind) -> Symbol {
+    Symbol {
+        name: name.to_string(),
+        offset,
+        size: 8,
+        kind,
+        int_type: None,
+    }
+}
+
+fn relocation(offset: usize, symbol: &str, kind: RelocationKind) -> Relocation {
+    Relocation {
+        offset,
+        symbol: symbol.to_string(),
+        kind,
+        section: RelocationSection::Text,
+    }
+}
+
+fn object(relocations: Vec<Relocation>) -> AuraObject {
+    AuraObject {
+        entry_point: 0,
+        load_base: 0x6000_0000_0000,
+        text: vec![0xcc; 0x30],
+        data: vec![0; 16],
+        bss_size: 0,
+        relocations,
+        symbols: vec![
+            symbol("target", 0x20, SymbolKind::Function),
+            symbol("value", 8, SymbolKind::Data),
+        ],
+        capability_sections: Vec::new(),
+        topology_sections: Vec::new(),
+        bit_region_sections: Vec::new(),
+    }
+}
+
+#[test]
+fn absolute64_gets_the_data_address() {
+    let mut object = object(vec![relocation(2, "value", RelocationKind::Absolute64)]);
+    object.apply_relocations(BASE).unwrap();
+    assert_eq!(object.text[2..10], (DATA_BASE + 8).to_le_bytes());
+    assert!(object.relocations.is_empty());
+}
+
+#[test]
+fn absolute32_gets_the_function_address() {
+    let mut object = object(vec![relocation(4, "target", RelocationKind::Absolute32)]);
+    object.apply_relocations(BASE).unwrap();
+    assert_eq!(object.text[4..8], (BASE as u32 + 0x20).to_le_bytes());
+}
+
+#[test]
+fn relative32_is_measured_from_the_end_of_the_field() {
+    // call rel32 at 0x10: the field spans 0x11..0x15, so 0x20 - 0x15
+    let mut object = object(vec![
+        relocation(0x11, "target", RelocationKind::Relative32),
+        relocation(0x25, "target", RelocationKind::Relative32),
+    ]);
+    object.apply_relocations(BASE).unwrap();
+    assert_eq!(object.text[0x11..0x15], 0x0bi32.to_le_bytes());
+    // A backward reference is negative: 0x20 - 0x29
+    assert_eq!(object.text[0x25..0x29], (-9i32).to_le_bytes());
+    // Bytes outside the fields are untouched
+    assert_eq!(object.text[0x10], 0xcc);
+    assert_eq!(object.text[0x15], 0xcc);
+}
+
+#[test]
+fn unresolved_symbol_leaves_the_object_untouched() {
+    let mut object = object(vec![
+        relocation(2, "value", RelocationKind::Absolute64),
+        relocation(0x11, "missing", RelocationKind::Relative32),
+    ]);
+    let error = object.apply_relocations(BASE).unwrap_err();
+    assert!(error.message.contains("'missing'"), "{}", error.message);
+    assert_eq!(object.text, vec![0xcc; 0x30]);
+    assert_eq!(object.relocations.len(), 2);
+}
+
+#[test]
+fn relocation_past_the_text_is_rejected() {
+    let mut object = object(vec![relocation(0x2c, "value", RelocationKind::Absolute64)]);
+    let error = object.apply_relocations(BASE).unwrap_err();
+    assert!(
+        error.message.contains("outside the text section"),
+        "{}",
+        error.message
+    );
+}
@@ -0,0 +1,46 @@
+// A constant index into an array of known length must be in bounds; a
+// computed index is left unchecked.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn constant_index_past_the_end_is_an_error() {
+    let error = check(
+        "fn main() i32 {
+             let a: [3]u8 = zeroed([3]u8);
+             return a[3] as i32;
+         }",
+    )
+    .expect_err("a[3] of a [3]u8 passed");
+    assert!(
+        error.contains("Index 3 out of bounds for array of length 3"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn constant_index_in_bounds_passes() {
+    check(
+        "fn main() i32 {
+             let a: [3]u8 = zeroed([3]u8);
+             a[2] = 1;
+             return a[0] as i32 + a[2] as i32;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn computed_index_is_not_checked() {
+    check(
+        "fn main() i32 {
+             let a: [3]u8 = zeroed([3]u8);
+             let i: i64 = 5;
+             return a[i] as i32;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
@@ -0,0 +1,53 @@
+// `x = v;` stores to the target at its width: a local's slot, an element
+// (its address computed from the index), or through a pointer. Element
+// stores count as initializing an uninitialized array. See
+// tests/assign_stmt.aura.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn element_stores_initialize_the_array() {
+    check(
+        "fn main() i32 {
+             let a: [2]i32;
+             a[1] = 3;
+             return a[1];
+         }",
+    )
+    .unwrap();
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn assigned_local_reads_back() {
+    let source = "fn main() i32 { let x: i32 = 0; x = 5; return x; }";
+    assert_eq!(common::exit_code("assign_local", source), Some(5));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn narrow_stores_leave_neighbours_alone() {
+    // Storing 0x1ff into a u8 element keeps 0xff and the next byte
+    let source = "fn main() i32 {
+                      let a: [2]u8 = [7u8, 9u8];
+                      let wide: i32 = 0x1ff;
+                      a[0] = wide as u8;
+                      return a[1] as i32 * 10 + (a[0] == 0xffu8) as i32;
+                  }";
+    assert_eq!(common::exit_code("assign_narrow", source), Some(91));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn stores_through_a_pointer_reach_the_local() {
+    let source = "fn main() i32 {
+                      let n: i64 = 1i64;
+                      let p: *i64 = &n;
+                      *p = 40i64;
+                      n = n + 2i64;
+                      return n as i32;
+                  }";
+    assert_eq!(common::exit_code("assign_deref", source), Some(42));
+}
@@ -0,0 +1,68 @@
+// Assigning to something that is not a place in memory is an error that
+// says why the target cannot be stored to.
+
+fn assign_error(statement: &str) -> String {
+    let source = format!(
+        "fn two() -> i64 {{ return 2; }}
+         fn main() i32 {{
+             let a: i64 = 1;
+             let b: i64 = 2;
+             {}
+             return 0;
+         }}",
+        statement
+    );
+    aura_compiler::check_source(&source)
+        .expect_err("assignment should be rejected")
+        .to_string()
+}
+
+#[test]
+fn binary_expression_is_not_assignable() {
+    let message = assign_error("(a + b) = 5;");
+    assert!(
+        message.contains(
+            "Invalid assignment target: the result of a binary expression (Add) is a temporary value"
+        ),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn call_result_is_not_assignable() {
+    let message = assign_error("two() = 3;");
+    assert!(
+        message.contains("a call to 'two' returns a temporary value"),
+        "{}",
+        message
+    );
+    // Points at the call, not just the function
+    assert!(message.contains("at 5:14"), "{}", message);
+}
+
+#[test]
+fn cast_is_not_assignable() {
+    let message = assign_error("(a as i32) = 3i32;");
+    assert!(
+        message.contains("cast the value being assigned instead"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn places_are_still_assignable() {
+    aura_compiler::check_source(
+        "fn main() i32 {
+             let a: [2]i64 = zeroed([2]i64);
+             let x: i64 = 1;
+             let p: *i64 = &x;
+             x = 2i64;
+             a[1] = 3i64;
+             *p = 4i64;
+             return 0;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
@@ -0,0 +1,82 @@
+// A pass built on ast::visit::Visitor overrides only the nodes it looks at
+// and still reaches every nested statement and expression.
+
+use aura_compiler::ast::visit::{walk_expr, Visitor};
+use aura_compiler::ast::{Expr, Program, Stmt};
+
+fn parse(source: &str) -> Program {
+    let tokens = aura_compiler::lexer::lex(source).unwrap();
+    aura_compiler::parser::parse(&tokens).unwrap()
+}
+
+#[derive(Default)]
+struct CallCounter {
+    calls: usize,
+    names: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for CallCounter {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let Expr::Call(callee, _) = expr {
+            self.calls += 1;
+            if let Expr::Identifier(name, _) = &**callee {
+                self.names.push(name.clone());
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn counts_every_call_node() {
+    let program = parse(
+        "const LIMIT: i64 = 3i64;
+         fn one() -> i64 { return 1i64; }
+         fn add(a: i64, b: i64) -> i64 { return a + b; }
+         fn main() -> i64 {
+             let x: i64 = add(one(), one());
+             for (let i: i64 = 0i64; i < LIMIT; i = i + 1i64;) {
+                 if one() == 1i64 {
+                     defer add(x, 2i64);
+                 }
+             }
+             while false {
+                 x = loop { break add(x, one()); };
+             }
+             return add(x, { add(1i64, 2i64); return one(); });
+         }",
+    );
+    let mut counter = CallCounter::default();
+    counter.visit_program(&program);
+    assert_eq!(counter.calls, 10, "{:?}", counter.names);
+    assert_eq!(
+        counter.names,
+        ["add", "one", "one", "one", "add", "add", "one", "add", "add", "one"]
+    );
+}
+
+#[test]
+fn overriding_a_node_can_prune_the_walk() {
+    // Skips everything under a deferred statement
+    struct OutsideDefers(usize);
+    impl<'ast> Visitor<'ast> for OutsideDefers {
+        fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+            if !matches!(stmt, Stmt::Defer(_)) {
+                aura_compiler::ast::visit::walk_stmt(self, stmt);
+            }
+        }
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if matches!(expr, Expr::Call(..)) {
+                self.0 += 1;
+            }
+            walk_expr(self, expr);
+        }
+    }
+    let program = parse(
+        "fn f() -> i64 { return 0i64; }
+         fn main() -> i64 { defer f(); f(); return f(); }",
+    );
+    let mut counter = OutsideDefers(0);
+    counter.visit_program(&program);
+    assert_eq!(counter.0, 2);
+}
@@ -0,0 +1,90 @@
+// Every tests/*.aura program is checked against its header comment: a
+// `// error: <message>` fixture must be rejected with that message, any
+// other must compile, and one whose header says "Returns N." must exit
+// with status N when run.
+
+mod common;
+
+use std::path::PathBuf;
+
+use aura_compiler::codegen::CodegenOptions;
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir("tests")
+        .expect("read tests directory")
+        .map(|entry| entry.expect("read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "aura"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures found");
+    paths
+}
+
+fn header(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .lines()
+        .map_while(|line| line.strip_prefix("//"))
+        .map(str::trim)
+}
+
+fn expected_error(source: &str) -> Option<&str> {
+    header(source).find_map(|line| line.strip_prefix("error: "))
+}
+
+// "Returns 42." anywhere in the header
+fn expected_status(source: &str) -> Option<i32> {
+    let header: Vec<&str> = header(source).collect();
+    let header = header.join(" ");
+    let rest = &header[header.find("Returns ")? + "Returns ".len()..];
+    rest.split('.').next()?.parse().ok()
+}
+
+#[test]
+fn error_fixtures_fail_with_their_message() {
+    for path in fixtures() {
+        let source = std::fs::read_to_string(&path).unwrap();
+        let Some(expected) = expected_error(&source) else {
+            continue;
+        };
+        match aura_compiler::compile_source(&source, &CodegenOptions::default()) {
+            Ok(_) => panic!("{} compiled", path.display()),
+            Err(error) => assert!(
+                error.to_string().contains(expected),
+                "{}: expected `{}`, got `{}`",
+                path.display(),
+                expected,
+                error
+            ),
+        }
+    }
+}
+
+#[test]
+fn other_fixtures_compile() {
+    for path in fixtures() {
+        let source = std::fs::read_to_string(&path).unwrap();
+        if expected_error(&source).is_some() {
+            continue;
+        }
+        if let Err(error) = aura_compiler::compile_source(&source, &CodegenOptions::default()) {
+            panic!("{}: {}", path.display(), error);
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn fixtures_exit_with_the_status_they_document() {
+    let mut ran = 0;
+    for path in fixtures() {
+        let source = std::fs::read_to_string(&path).unwrap();
+        let Some(expected) = expected_status(&source) else {
+            continue;
+        };
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let status = common::run(&format!("fixture_{}", name), &common::compile(&source)).status;
+        assert_eq!(status.code(), Some(expected), "{}", path.display());
+        ran += 1;
+    }
+    assert!(ran > 0, "no fixture documents its exit status");
+}
@@ -0,0 +1,174 @@
+// Round trip of the .aura writer and reader over randomly generated objects.
+// The generator is seeded and bounded so every record fits the fixed record
+// size; overlong records are checked separately.
+
+use aura_compiler::codegen::{
+    aura_object_from_bytes, aura_object_to_bytes, AuraObject, BitRegionInfo, BitRegionSection,
+    CapabilitySection, IntType, Relocation, RelocationKind, RelocationSection, Symbol, SymbolKind,
+    TopologySection, DEFAULT_LOAD_BASE,
+};
+
+// xorshift64*, so failures reproduce from the printed seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn bytes(&mut self, max_len: u64) -> Vec<u8> {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+
+    // Names mix ASCII with multi-byte UTF-8 and may be empty
+    fn name(&mut self, max_chars: u64) -> String {
+        const CHARS: &[char] = &['a', 'z', '_', ':', '0', '9', 'é', 'λ', '→'];
+        let len = self.below(max_chars + 1);
+        (0..len)
+            .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+            .collect()
+    }
+}
+
+fn random_object(rng: &mut Rng) -> AuraObject {
+    let relocations = (0..rng.below(8))
+        .map(|_| Relocation {
+            offset: rng.below(1 << 20) as usize,
+            symbol: rng.name(40),
+            kind: match rng.below(3) {
+                0 => RelocationKind::Absolute64,
+                1 => RelocationKind::Relative32,
+                _ => RelocationKind::Absolute32,
+            },
+            section: match rng.below(2) {
+                0 => RelocationSection::Text,
+                _ => RelocationSection::Data,
+            },
+        })
+        .collect();
+    let symbols = (0..rng.below(8))
+        .map(|_| Symbol {
+            name: rng.name(40),
+            offset: rng.next(),
+            size: rng.next(),
+            kind: match rng.below(6) {
+                0 => SymbolKind::Function,
+                1 => SymbolKind::Data,
+                2 => SymbolKind::Object,
+                3 => SymbolKind::Capability,
+                4 => SymbolKind::Topology,
+                _ => SymbolKind::BitRegion,
+            },
+            int_type: match rng.below(3) {
+                0 => None,
+                _ => Some(IntType {
+                    bits: 1 + rng.below(64) as u8,
+                    signed: rng.below(2) == 0,
+                }),
+            },
+        })
+        .collect();
+    let capability_sections = (0..rng.below(4))
+        .map(|_| CapabilitySection {
+            name: rng.name(40),
+            base_address: rng.next(),
+            length: rng.next(),
+            mode: rng.next() as u8,
+            element_size: rng.next() as u32,
+            element_count: rng.next(),
+        })
+        .collect();
+    let topology_sections = (0..rng.below(4))
+        .map(|_| TopologySection {
+            name: rng.name(40),
+            numa_node: rng.next() as u8,
+            cache_level: rng.next() as u8,
+            memory_class: rng.next() as u8,
+        })
+        .collect();
+    let bit_region_sections = (0..rng.below(3))
+        .map(|_| BitRegionSection {
+            name: rng.name(12),
+            base_type: rng.name(12),
+            regions: (0..rng.below(6))
+                .map(|_| BitRegionInfo {
+                    name: rng.name(8),
+                    bit_offset: rng.next() as u8,
+                    bit_width: rng.next() as u8,
+                    access: rng.next() as u8,
+                })
+                .collect(),
+        })
+        .collect();
+
+    AuraObject {
+        entry_point: rng.next(),
+        load_base: match rng.below(2) {
+            0 => DEFAULT_LOAD_BASE,
+            _ => rng.below(1 << 36) << 12,
+        },
+        text: rng.bytes(300),
+        data: rng.bytes(100),
+        bss_size: rng.below(1 << 16) as usize,
+        relocations,
+        symbols,
+        capability_sections,
+        topology_sections,
+        bit_region_sections,
+    }
+}
+
+#[test]
+fn random_objects_round_trip() {
+    for seed in 1..=500u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9e3779b97f4a7c15));
+        let mut object = random_object(&mut rng);
+        let bytes = aura_object_to_bytes(&object)
+            .unwrap_or_else(|e| panic!("seed {}: write failed: {}", seed, e));
+        let read = aura_object_from_bytes(&bytes)
+            .unwrap_or_else(|e| panic!("seed {}: read failed: {}", seed, e));
+
+        // The writer sorts symbols by offset, then name
+        object
+            .symbols
+            .sort_by(|a, b| a.offset.cmp(&b.offset).then_with(|| a.name.cmp(&b.name)));
+        assert_eq!(read, object, "seed {}", seed);
+    }
+}
+
+#[test]
+fn truncated_binaries_are_rejected() {
+    let mut rng = Rng(0x5eed);
+    let object = random_object(&mut rng);
+    let bytes = aura_object_to_bytes(&object).unwrap();
+    for len in 0..bytes.len() {
+        assert!(
+            aura_object_from_bytes(&bytes[..len]).is_err(),
+            "prefix of {} bytes read back",
+            len
+        );
+    }
+}
+
+#[test]
+fn overlong_records_are_rejected() {
+    let mut rng = Rng(7);
+    let mut object = random_object(&mut rng);
+    object.symbols.push(Symbol {
+        name: "x".repeat(300),
+        offset: 0,
+        size: 0,
+        kind: SymbolKind::Function,
+        int_type: None,
+    });
+    let error = aura_object_to_bytes(&object).unwrap_err();
+    assert!(error.to_string().contains("record size"), "{}", error);
+}
@@ -0,0 +1,57 @@
+// Consecutive BitInt struct fields share a storage unit, filled from the
+// least significant bit; loads shift and mask a field out and stores rewrite
+// only its bits. See tests/bitfield_struct.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+#[test]
+fn fields_fill_one_byte_from_the_low_bits() {
+    // 0b11_10_01
+    let source = "struct Flags { a: u2; b: u2; c: u2; }
+                  fn main() i32 {
+                      let f: Flags = zeroed(Flags);
+                      f.a = 1 as u2;
+                      f.b = 2 as u2;
+                      f.c = 3 as u2;
+                      let raw: *u8 = &f as *u8;
+                      return *raw as i32;
+                  }";
+    assert_eq!(common::exit_code("bitfield_bits", source), Some(0b11_10_01));
+}
+
+#[test]
+fn stores_leave_neighbouring_fields_alone() {
+    let source = "struct Flags { a: u2; b: u2; c: u2; }
+                  fn main() i32 {
+                      let f: Flags = zeroed(Flags);
+                      f.a = 3 as u2;
+                      f.b = 3 as u2;
+                      f.c = 3 as u2;
+                      f.b = 0 as u2;
+                      return f.a as i32 * 10 + f.b as i32 * 5 + f.c as i32;
+                  }";
+    assert_eq!(common::exit_code("bitfield_neighbours", source), Some(33));
+}
+
+#[test]
+fn signed_fields_sign_extend() {
+    let source = "struct Signed { lo: u1; x: i3; }
+                  fn main() i32 {
+                      let s: Signed = zeroed(Signed);
+                      s.lo = 1 as u1;
+                      s.x = -2 as i3;
+                      return s.x as i32 + 10;
+                  }";
+    assert_eq!(common::exit_code("bitfield_signed", source), Some(8));
+}
+
+#[test]
+fn a_field_that_does_not_fit_opens_a_new_unit() {
+    let source = "struct Wide { a: u5; b: u5; c: u2; }
+                  fn main() i32 {
+                      return (sizeof(Wide) * 10 + offsetof(Wide, b)) as i32;
+                  }";
+    assert_eq!(common::exit_code("bitfield_units", source), Some(21));
+}
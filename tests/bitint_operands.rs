@@ -0,0 +1,60 @@
+// Both operands of an arithmetic, bitwise or comparison operator on a
+// BitInt must agree in width and signedness; shift counts are exempt. See
+// tests/bitint_mixed_width_error.aura and tests/bitint_same_width.aura.
+
+mod common;
+
+use common::check;
+
+fn main_body(body: &str) -> String {
+    format!("fn main() i32 {{\n{}\nreturn 0;\n}}", body)
+}
+
+#[test]
+fn different_widths_name_both_sides() {
+    let error = check(&main_body(
+        "let a: u3 = 5 as u3; let b: u7 = 100 as u7; let c: u7 = (a as u7) + b; let d: bool = a < b;",
+    ))
+    .unwrap_err();
+    assert!(
+        error.contains(
+            "Mismatched integer operands for Lt: u3 (unsigned, 3 bits) and u7 (unsigned, 7 bits)"
+        ),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn different_signedness_is_an_error() {
+    let error = check(&main_body(
+        "let a: u7 = 1 as u7; let b: i7 = 1 as i7; let c: u7 = a & b;",
+    ))
+    .unwrap_err();
+    assert!(
+        error.contains("u7 (unsigned, 7 bits) and i7 (signed, 7 bits)"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn primitive_integers_do_not_mix_with_bit_ints() {
+    let error = check(&main_body(
+        "let a: u7 = 1 as u7; let n: i32 = 2; let c: i32 = n * a;",
+    ))
+    .unwrap_err();
+    assert!(
+        error.contains("Mismatched integer operands for Mul: I32 and u7"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn shift_counts_and_casts_are_exempt() {
+    check(&main_body(
+        "let a: u7 = 1 as u7; let n: u8 = 3; let b: u7 = a << n; let c: i32 = (a as i32) + 5;",
+    ))
+    .unwrap();
+}
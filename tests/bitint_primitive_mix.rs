@@ -0,0 +1,62 @@
+// A bit-precise integer mixes with the primitive of the same width and
+// signedness without a cast, and the result has the primitive type; any
+// other primitive still needs one. See tests/bitint_primitive_mix.aura.
+
+mod common;
+
+use common::check;
+
+fn main_body(body: &str) -> String {
+    format!("fn main() i32 {{\n{}\nreturn 0;\n}}", body)
+}
+
+#[test]
+fn either_side_may_be_the_primitive() {
+    check(&main_body(
+        "let a: u08 = 3 as u08; let b: u8 = 4u8; let x: u8 = a * b; let y: u8 = b - a; let e: bool = a != b;",
+    ))
+    .unwrap();
+}
+
+#[test]
+fn the_result_is_the_primitive() {
+    let error = check(&main_body(
+        "let a: i032 = 1 as i032; let b: i32 = 2; let c: i032 = a + b;",
+    ))
+    .unwrap_err();
+    assert!(
+        error.contains("expected BitInt(32, true), got I32"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn other_shapes_still_need_a_cast() {
+    for (left, right) in [("i032", "i64"), ("i032", "u32"), ("u08", "i8")] {
+        let error = check(&main_body(&format!(
+            "let a: {l} = 1 as {l}; let b: {r} = 1 as {r}; let c: bool = a == b;",
+            l = left,
+            r = right
+        )))
+        .unwrap_err();
+        assert!(
+            error.contains("Mismatched integer operands for Eq"),
+            "{} == {}: {}",
+            left,
+            right,
+            error
+        );
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn mixed_arithmetic_keeps_the_sign() {
+    let source = "fn main() i32 {
+                      let a: i032 = -50 as i032;
+                      let b: i32 = 8;
+                      return a + b + 100;
+                  }";
+    assert_eq!(common::exit_code("bitint_primitive_sign", source), Some(58));
+}
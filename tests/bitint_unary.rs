@@ -0,0 +1,70 @@
+// `~` and unary `-` on a BitInt wrap the result to its width: masked for
+// unsigned widths, sign-extended from the top bit for signed ones, so the
+// value reads back the same whether it stays in rax or goes through memory.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+fn exit_code(name: &str, body: &str) -> i32 {
+    let source = format!("fn main() i32 {{\n{}\n}}", body);
+    common::exit_code(name, &source).expect("killed by a signal")
+}
+
+#[test]
+fn bitnot_of_unsigned_stays_within_its_bits() {
+    // ~0b0101 in four bits is 0b1010, not 0xffff_fff...a
+    assert_eq!(
+        exit_code(
+            "bitnot_u4",
+            "let x: u4 = 5 as u4; let y: u4 = ~x; return y as i32;"
+        ),
+        10
+    );
+    assert_eq!(
+        exit_code(
+            "bitnot_u4_temporary",
+            "let x: u4 = 5 as u4; return (~x) as i32;"
+        ),
+        10
+    );
+    assert_eq!(
+        exit_code(
+            "bitnot_u4_compare",
+            "let x: u4 = 0 as u4; if (~x == (15 as u4)) { return 1; } return 0;"
+        ),
+        1
+    );
+}
+
+#[test]
+fn negation_of_unsigned_wraps_modulo_its_width() {
+    // -5 is 16 - 5 in u4
+    assert_eq!(
+        exit_code(
+            "neg_u4",
+            "let x: u4 = 5 as u4; let y: u4 = -x; return y as i32;"
+        ),
+        11
+    );
+}
+
+#[test]
+fn signed_results_are_sign_extended() {
+    // Offset by 100 so negative results show as a positive exit status
+    assert_eq!(
+        exit_code(
+            "bitnot_i4",
+            "let x: i4 = 3 as i4; let y: i4 = ~x; return (y as i32) + 100;"
+        ),
+        96
+    );
+    // -(-8) does not fit i4 and wraps back to -8
+    assert_eq!(
+        exit_code(
+            "neg_i4",
+            "let x: i4 = -8 as i4; let y: i4 = -x; return (y as i32) + 100;"
+        ),
+        92
+    );
+}
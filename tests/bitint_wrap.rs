@@ -0,0 +1,50 @@
+// Arithmetic on a BitInt wraps to its width: unsigned results are masked
+// and signed ones sign-extended from their top bit. See
+// tests/bitint_wrap.aura, which aura_fixtures runs.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+fn exit_code(name: &str, body: &str) -> i32 {
+    let source = format!("fn main() i32 {{\n{}\n}}", body);
+    common::exit_code(name, &source).expect("killed by a signal")
+}
+
+#[test]
+fn unsigned_u3_wraps_at_8() {
+    assert_eq!(
+        exit_code(
+            "bitint_u3_add",
+            "let a: u3 = 7 as u3; let b: u3 = a + (1 as u3); return b as i32;"
+        ),
+        0
+    );
+    assert_eq!(
+        exit_code(
+            "bitint_u3_sub",
+            "let a: u3 = 0 as u3; let b: u3 = a - (1 as u3); return b as i32;"
+        ),
+        7
+    );
+}
+
+#[test]
+fn signed_i4_sign_extends() {
+    // 7 + 1 wraps to -8
+    assert_eq!(
+        exit_code(
+            "bitint_i4_add",
+            "let c: i4 = 7 as i4; let d: i4 = c + (1 as i4); return (d as i32) + 100;"
+        ),
+        92
+    );
+    // 4 * 3 = 12 is 0b1100, -4 in four bits
+    assert_eq!(
+        exit_code(
+            "bitint_i4_mul",
+            "let c: i4 = 4 as i4; let d: i4 = c * (3 as i4); return (d as i32) + 100;"
+        ),
+        96
+    );
+}
@@ -0,0 +1,33 @@
+// A comparison evaluates to exactly 0 or 1 at bool width, whether it is
+// returned from a `-> bool` function or stored in a bool local. See
+// tests/bool_compare_return.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+#[test]
+fn comparison_is_set_and_zero_extended() {
+    let source = std::fs::read_to_string("tests/bool_compare_return.aura").unwrap();
+    let object = compile(&source);
+    let is_less = function_text(&object, "is_less");
+    // cmp eax, ecx; setl al; movzx eax, al
+    assert!(
+        contains(is_less, &[0x39, 0xc8, 0x0f, 0x9c, 0xc0, 0x0f, 0xb6, 0xc0]),
+        "{:02x?}",
+        is_less
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn bool_results_are_zero_or_one() {
+    let source = "fn less(a: i32, b: i32) -> bool { return a < b; }
+                  fn main() i32 {
+                      let big: bool = less(-3, 5);
+                      let small: bool = less(5, -3);
+                      let stored: bool = 700 >= 700;
+                      return big as i32 * 100 + small as i32 * 10 + stored as i32;
+                  }";
+    assert_eq!(common::exit_code("bool_results", source), Some(101));
+}
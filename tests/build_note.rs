@@ -0,0 +1,48 @@
+// A written `.aura` binary sets the build-note flag and ends with a note
+// naming the compiler version and target and hashing text and data, which
+// `aura dump` prints after the record tables.
+
+mod common;
+
+use aura_compiler::codegen::{aura_object_to_bytes, AuraBinary, BuildNote, DumpOptions};
+use common::compile;
+
+const SOURCE: &str = "const answer: i64 = 42i64;
+                      fn main() i64 { return answer; }";
+
+fn dump(bytes: &[u8]) -> String {
+    let mut out = Vec::new();
+    AuraBinary::dump_to(&mut out, bytes, &DumpOptions::default()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn dump_prints_the_compiler_version() {
+    let object = compile(SOURCE);
+    let dump = dump(&aura_object_to_bytes(&object).unwrap());
+    assert!(dump.contains("Flags: 0x01\n"), "{}", dump);
+    let note: Vec<&str> = dump
+        .lines()
+        .skip_while(|line| *line != "=== Build Note ===")
+        .collect();
+    let hash = BuildNote::for_object(&object).content_hash;
+    assert_eq!(
+        note[1..4],
+        [
+            format!("Compiler: aura {}", env!("CARGO_PKG_VERSION")).as_str(),
+            "Target: x86_64",
+            format!("Content Hash: 0x{:016x}", hash).as_str(),
+        ],
+        "{}",
+        dump
+    );
+}
+
+#[test]
+fn content_hash_follows_text_and_data() {
+    let first = BuildNote::for_object(&compile(SOURCE));
+    assert_eq!(first, BuildNote::for_object(&compile(SOURCE)));
+    let other = BuildNote::for_object(&compile(&SOURCE.replace("42i64", "43i64")));
+    assert_ne!(first.content_hash, other.content_hash);
+    assert_eq!(first.compiler, other.compiler);
+}
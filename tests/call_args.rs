@@ -0,0 +1,51 @@
+// A call to a function of the program moves its arguments into rdi and rsi
+// and emits `call rel32`, with a Relative32 relocation naming the callee so
+// a linker moving the code can redo the displacement.
+
+mod common;
+
+use aura_compiler::codegen::{RelocationKind, RelocationSection};
+use common::{compile, contains, function_text, symbol};
+
+fn fixture() -> String {
+    std::fs::read_to_string("tests/call_args.aura").unwrap()
+}
+
+#[test]
+fn arguments_are_moved_into_rdi_and_rsi() {
+    let object = compile(&fixture());
+    let text = function_text(&object, "main");
+    // pop rax; mov rsi, rax; pop rax; mov rdi, rax; call
+    let call = [0x58, 0x48, 0x89, 0xc6, 0x58, 0x48, 0x89, 0xc7, 0xe8];
+    assert!(contains(text, &call), "{:02x?}", text);
+}
+
+#[test]
+fn call_carries_a_relocation_naming_the_callee() {
+    let object = compile(&fixture());
+    let relocs: Vec<_> = object
+        .relocations
+        .iter()
+        .filter(|r| r.symbol == "add")
+        .collect();
+    assert_eq!(relocs.len(), 1, "{:?}", object.relocations);
+    let reloc = relocs[0];
+    assert_eq!(reloc.kind, RelocationKind::Relative32);
+    assert_eq!(reloc.section, RelocationSection::Text);
+    assert_eq!(object.text[reloc.offset - 1], 0xe8, "not a call rel32");
+
+    // The displacement already reaches add, measured from the next instruction
+    let rel = i32::from_le_bytes(
+        object.text[reloc.offset..reloc.offset + 4]
+            .try_into()
+            .unwrap(),
+    );
+    let target = reloc.offset as i64 + 4 + rel as i64;
+    assert_eq!(target, symbol(&object, "add").offset as i64);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn call_returns_the_sum() {
+    assert_eq!(common::exit_code("call_args", &fixture()), Some(7));
+}
@@ -0,0 +1,48 @@
+// Calling a struct, union or enum name as if it were a constructor gets a
+// message naming the type instead of the generic non-function error. See
+// tests/call_struct_name_error.aura.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn union_names_suggest_zeroed() {
+    let error = check(
+        "union Either { small: i8; big: i64; }
+         fn main() i32 { let e: Either = Either(1); return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains(
+            "Cannot call union type 'Either' as a function; union values have no constructor, \
+             declare one with `let v: Either = zeroed(Either);`"
+        ),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn enum_names_have_no_constructor() {
+    let error = check(
+        "enum Color { Red, Green }
+         fn main() i32 { let c: Color = Color(0); return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains("Cannot call enum type 'Color' as a function; enums have no constructor"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn other_callees_keep_the_generic_error() {
+    let error = check("fn main() i32 { let n: i32 = 1; return n(2); }").unwrap_err();
+    assert!(
+        error.contains("Cannot call non-function type I32"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,217 @@
+// Casts are checked against a conversion matrix: integers, floats, pointers
+// and enums convert where the conversion has a meaning, and aggregates do not
+// convert at all. Integer and float casts convert the value itself, and a
+// cast to a narrower integer keeps only the bits that fit, extended back
+// over the register by the target's sign.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+// `from` is the type of the local being cast
+fn cast_program(from: &str, init: &str, to: &str) -> String {
+    format!(
+        "struct Point {{ x: i64; y: i64; }}
+         enum Color {{ Red, Green, }}
+         fn main() i32 {{
+             let value: {} = {};
+             let cast: {} = value as {};
+             return 0;
+         }}",
+        from, init, to, to
+    )
+}
+
+#[test]
+fn allowed_conversions_typecheck() {
+    let pairs = [
+        // int <-> int
+        ("i64", "7", "u8"),
+        ("u8", "7u8", "i64"),
+        ("i32", "7i32", "usize"),
+        // int <-> float
+        ("i64", "7", "f64"),
+        ("f64", "7.5", "i32"),
+        ("f32", "1.5f32", "f64"),
+        // int <-> pointer
+        ("usize", "4096usize", "*u8"),
+        ("*u8", "0 as *u8", "u64"),
+        // pointer <-> pointer
+        ("*u8", "0 as *u8", "*i64"),
+        ("*i64", "0 as *i64", "*const i64"),
+        // enum and bool to int, int to enum
+        ("Color", "Color.Green", "i64"),
+        ("i64", "1", "Color"),
+        ("bool", "true", "u8"),
+    ];
+    for (from, init, to) in pairs {
+        check(&cast_program(from, init, to))
+            .unwrap_or_else(|e| panic!("{} as {} was rejected: {}", from, to, e));
+    }
+}
+
+#[test]
+fn casting_to_void_discards_any_value() {
+    check(
+        "struct Point { x: i64; y: i64; }
+         fn main() i32 {
+             let p: Point = zeroed(Point);
+             p as void;
+             return 0;
+         }",
+    )
+    .unwrap();
+}
+
+#[test]
+fn struct_casts_are_rejected() {
+    let message = check(&cast_program("i64", "3", "Point")).unwrap_err();
+    assert!(
+        message.contains("structs, unions, arrays and functions have no conversion"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn array_casts_are_rejected() {
+    let message = check(&cast_program("[2]i32", "[1, 2]", "i64")).unwrap_err();
+    assert!(
+        message.contains("Cannot cast Array(2, I32) to I64"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn pointer_to_bool_is_rejected() {
+    let message = check(&cast_program("*u8", "0 as *u8", "bool")).unwrap_err();
+    assert!(
+        message.contains("compare against zero to get a bool"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn float_to_pointer_is_rejected() {
+    let message = check(&cast_program("f64", "1.0", "*u8")).unwrap_err();
+    assert!(
+        message.contains("Cannot cast F64 to MutPtr(U8)"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn enums_do_not_convert_to_other_enums() {
+    let message = check(
+        "enum Color { Red, Green, }
+         enum Shape { Circle, Square, }
+         fn main() i32 {
+             let s: Shape = Color.Red as Shape;
+             return 0;
+         }",
+    )
+    .unwrap_err();
+    assert!(
+        message.contains("Cannot cast Named(\"Color\") to Named(\"Shape\")"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn numeric_conversions_emit_sse_conversions() {
+    let object = compile(
+        "fn to_int(x: f64) -> i32 { return x as i32; }
+         fn to_float(n: i64) -> f64 { return n as f64; }
+         fn main() i32 { return 0; }",
+    );
+    // cvttsd2si rax, xmm0
+    let to_int = function_text(&object, "to_int");
+    assert!(
+        contains(to_int, &[0xf2, 0x48, 0x0f, 0x2c, 0xc0]),
+        "{:02x?}",
+        to_int
+    );
+    // cvtsi2sd xmm0, rax
+    let to_float = function_text(&object, "to_float");
+    assert!(
+        contains(to_float, &[0xf2, 0x48, 0x0f, 0x2a, 0xc0]),
+        "{:02x?}",
+        to_float
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn float_to_int_truncates_toward_zero() {
+    let code = common::exit_code(
+        "cast_float_to_int",
+        "fn main() i32 {
+             let x: f64 = 10.0;
+             let y: f64 = 0.0 - 7.9;
+             let z: f32 = 2.5f32;
+             return (x as i32) * 10 + (y as i32) + (z as i32);
+         }",
+    );
+    assert_eq!(code, Some(100 - 7 + 2));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn int_to_float_keeps_the_value() {
+    let code = common::exit_code(
+        "cast_int_to_float",
+        "fn main() i32 {
+             let n: i32 = 0 - 3;
+             let f: f64 = n as f64;
+             let small: u8 = 200u8;
+             let g: f32 = small as f32;
+             let h: f64 = g as f64;
+             let big: u64 = 1u64 << 63u64;
+             let b: f64 = big as f64;
+             if (f != 0.0 - 3.0) { return 1; }
+             if (h != 200.0) { return 2; }
+             if (b != 9223372036854775808.0) { return 3; }
+             return (h as f32) as i32 - 100;
+         }",
+    );
+    assert_eq!(code, Some(100));
+}
+
+#[test]
+fn narrowing_casts_extend_by_the_target_sign() {
+    let object = compile(
+        "fn to_i8(n: i64) -> i8 { return n as i8; }
+         fn to_u32(n: i64) -> u32 { return n as u32; }
+         fn main() i32 { return to_i8(1) as i32 + to_u32(2) as i32; }",
+    );
+    // movsx rax, al
+    let to_i8 = function_text(&object, "to_i8");
+    assert!(contains(to_i8, &[0x48, 0x0f, 0xbe, 0xc0]), "{:02x?}", to_i8);
+    // mov eax, eax
+    let to_u32 = function_text(&object, "to_u32");
+    assert!(contains(to_u32, &[0x89, 0xc0]), "{:02x?}", to_u32);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn narrowed_values_drop_their_high_bits() {
+    // Each result is widened again and compared in 64 bits
+    let code = common::exit_code(
+        "cast_narrowing",
+        "fn main() i32 {
+             let big: i64 = 4294967297;
+             let all_ones: i64 = 511;
+             let n: i64 = 300;
+             if ((big as u32) as u64 != 1u64) { return 1; }
+             if ((all_ones as i8) as i64 != 0 - 1) { return 2; }
+             if ((n as u8) as i64 != 44) { return 3; }
+             if ((big as i32) as i64 != 1) { return 4; }
+             return 42;
+         }",
+    );
+    assert_eq!(code, Some(42));
+}
@@ -0,0 +1,36 @@
+// `a < b < c` is a parse error that spells out the `&&` form to write
+// instead, rather than a confusing bool-versus-integer type mismatch.
+
+fn parse_error(source: &str) -> String {
+    let tokens = aura_compiler::lexer::lex(source).unwrap();
+    aura_compiler::parser::parse(&tokens).unwrap_err().message
+}
+
+#[test]
+fn chained_comparison_suggests_and() {
+    let source = std::fs::read_to_string("tests/chained_comparison_error.aura").unwrap();
+    let message = parse_error(&source);
+    assert!(
+        message.contains("write `0 < x && x < 10` instead"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn suggestion_keeps_the_operands_as_written() {
+    let message = parse_error("fn f(a: i64, n: i64) -> bool { return a[0] >= n+1 <= f(a, n); }");
+    assert!(
+        message.contains("write `a[0] >= n+1 && n+1 <= f(a, n)` instead"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn single_comparisons_still_parse() {
+    let tokens =
+        aura_compiler::lexer::lex("fn f(x: i64) -> bool { return 0i64 < x && x < 10i64 == true; }")
+            .unwrap();
+    aura_compiler::parser::parse(&tokens).unwrap();
+}
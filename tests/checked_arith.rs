@@ -0,0 +1,58 @@
+// With `checked_arith`, integer add, sub and mul are done at the operand
+// width and followed by a jo (signed, and every mul) or jc (unsigned) to a
+// single ud2 after all code; without it they wrap as 64-bit operations.
+
+mod common;
+
+use aura_compiler::codegen::{AuraObject, CodegenOptions};
+use common::{contains, function_text};
+
+const SOURCE: &str = "fn add(a: u8, b: u8) u8 { return a + b; }
+     fn main() i32 { return add(250, 10) as i32; }";
+
+fn compile(checked_arith: bool) -> AuraObject {
+    let options = CodegenOptions {
+        checked_arith,
+        ..CodegenOptions::default()
+    };
+    aura_compiler::compile_source(SOURCE, &options)
+        .unwrap_or_else(|e| panic!("compile failed: {}", e))
+        .0
+}
+
+#[test]
+fn checked_add_jumps_to_the_trap() {
+    let object = compile(true);
+    let add = function_text(&object, "add");
+    // add al, cl; jc rel32
+    let at = add
+        .windows(4)
+        .position(|w| w == [0x00, 0xc8, 0x0f, 0x82])
+        .unwrap_or_else(|| panic!("no checked add in {:02x?}", add));
+    let rel_at = common::symbol(&object, "add").offset as usize + at + 4;
+    let rel = i32::from_le_bytes(object.text[rel_at..rel_at + 4].try_into().unwrap());
+    let target = (rel_at as i64 + 4 + rel as i64) as usize;
+    // ud2
+    assert_eq!(&object.text[target..target + 2], &[0x0f, 0x0b]);
+}
+
+#[test]
+fn unchecked_add_wraps_without_a_trap() {
+    let object = compile(false);
+    let add = function_text(&object, "add");
+    // add rax, rcx
+    assert!(contains(add, &[0x48, 0x01, 0xc8]), "{:02x?}", add);
+    assert!(!contains(&object.text, &[0x0f, 0x0b]), "unexpected ud2");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn overflow_traps_only_when_checked() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let status = common::run("checked_arith_wrap", &compile(false)).status;
+    assert_eq!(status.code(), Some(4));
+    let status = common::run("checked_arith_trap", &compile(true)).status;
+    // ud2 raises SIGILL
+    assert_eq!(status.signal(), Some(4), "{:?}", status);
+}
@@ -0,0 +1,51 @@
+// Codegen errors carry the span of the node they are about, or else of the
+// function being generated, so they print with a line and column.
+
+use aura_compiler::codegen::CodegenOptions;
+use aura_compiler::diagnostic::{Diagnostic, Phase};
+
+#[test]
+fn unknown_identifier_error_points_at_the_identifier() {
+    // The type checker would reject `missing` first, so generate code for
+    // the parsed program directly
+    let tokens = aura_compiler::lexer::lex(
+        "fn main() i64 {
+    let n: i64 = 1;
+    return n + missing;
+}",
+    )
+    .unwrap();
+    let program = aura_compiler::parser::parse(&tokens).unwrap();
+    let error = Diagnostic::from(
+        aura_compiler::codegen::generate(&program)
+            .map(|_| ())
+            .expect_err("an unknown identifier compiled"),
+    );
+    assert_eq!(error.phase, Phase::Codegen);
+    assert_eq!(error.message, "Unknown identifier: missing");
+    let span = error.span.as_ref().expect("codegen error without a span");
+    assert_eq!((span.line, span.column), (3, 16));
+    assert_eq!(
+        error.to_string(),
+        "error[codegen] at 3:16: Unknown identifier: missing"
+    );
+}
+
+#[test]
+fn write_of_a_scalar_local_asks_for_a_length() {
+    let error = aura_compiler::compile_source(
+        "fn main() i32 {
+    let n: i64 = 1;
+    syscall.write(1, n);
+    return 0;
+}",
+        &CodegenOptions::default(),
+    )
+    .map(|_| ())
+    .expect_err("write of a scalar without a length compiled");
+    assert_eq!(error.phase, Phase::Codegen);
+    assert_eq!(
+        error.to_string(),
+        "error[codegen] at 3:22: write cannot tell the length of 'n'; pass it as the third argument"
+    );
+}
@@ -0,0 +1,109 @@
+// Helpers shared by the integration tests: compiling a source string,
+// looking into the resulting object, and running it as an executable.
+// Each test binary builds its own copy and uses only part of it.
+// external_mnemonics cross-checks emitted machine code against a system
+// disassembler; see tests/external_disasm.rs.
+
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::Command;
+
+use aura_compiler::codegen::{AuraObject, CodegenOptions, Symbol};
+
+pub fn compile(source: &str) -> AuraObject {
+    aura_compiler::compile_source(source, &CodegenOptions::default())
+        .unwrap_or_else(|e| panic!("compile failed: {}", e))
+        .0
+}
+
+/// Frontend result of `source`, with the diagnostic rendered as text.
+pub fn check(source: &str) -> Result<(), String> {
+    aura_compiler::check_source(source)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+pub fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+pub fn symbol<'a>(object: &'a AuraObject, name: &str) -> &'a Symbol {
+    object
+        .symbols
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("no symbol {}", name))
+}
+
+pub fn function_text<'a>(object: &'a AuraObject, name: &str) -> &'a [u8] {
+    let symbol = symbol(object, name);
+    let start = symbol.offset as usize;
+    &object.text[start..start + symbol.size as usize]
+}
+
+/// Writes `object` as an executable named after `name`, runs it with stdin
+/// closed and returns what it printed and how it exited.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub fn run(name: &str, object: &AuraObject) -> std::process::Output {
+    let path = std::env::temp_dir().join(format!("aura_{}_{}", name, std::process::id()));
+    aura_compiler::codegen::write_elf_binary(object, &path).expect("write executable");
+    let output = Command::new(&path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("run");
+    let _ = std::fs::remove_file(&path);
+    output
+}
+
+/// Exit status of `source` compiled and run; `None` if a signal killed it.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub fn exit_code(name: &str, source: &str) -> Option<i32> {
+    run(name, &compile(source)).status.code()
+}
+
+/// Mnemonics of `text` as decoded by objdump, or ndisasm if objdump is
+/// missing. `None` when neither is installed, so callers can skip.
+pub fn external_mnemonics(text: &[u8]) -> Option<Vec<String>> {
+    let mut file = tempfile::NamedTempFile::new().expect("create temp file");
+    file.write_all(text).expect("write text section");
+    let path = file.path();
+
+    if let Ok(output) = Command::new("objdump")
+        .args(["-D", "-b", "binary", "-m", "i386:x86-64", "-M", "intel"])
+        .arg("--insn-width=15")
+        .arg(path)
+        .output()
+    {
+        assert!(output.status.success(), "objdump failed: {:?}", output);
+        // `   0:\t55                   \tpush   rbp`
+        let listing = String::from_utf8_lossy(&output.stdout);
+        return Some(
+            listing
+                .lines()
+                .filter_map(|line| line.split('\t').nth(2))
+                .filter_map(|insn| insn.split_whitespace().next())
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+
+    if let Ok(output) = Command::new("ndisasm")
+        .args(["-b", "64"])
+        .arg(path)
+        .output()
+    {
+        assert!(output.status.success(), "ndisasm failed: {:?}", output);
+        // `00000000  55                push rbp`
+        let listing = String::from_utf8_lossy(&output.stdout);
+        return Some(
+            listing
+                .lines()
+                .filter_map(|line| line.split_whitespace().nth(2))
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+
+    None
+}
@@ -0,0 +1,71 @@
+// A comparison used directly as an `if`/`while` condition branches on the
+// flags of its cmp with the inverted condition code, instead of
+// materializing a bool with setcc and testing it. See
+// tests/compare_branch.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+fn has_setcc(text: &[u8]) -> bool {
+    text.windows(3)
+        .any(|w| w[0] == 0x0f && (0x90..=0x9f).contains(&w[1]) && w[2] == 0xc0)
+}
+
+#[test]
+fn signed_less_than_jumps_on_greater_or_equal() {
+    let object = compile(
+        "fn f(a: i64, b: i64) -> i64 {
+             if a < b { return 1i64; }
+             return 0i64;
+         }
+         fn main() i32 { return 0; }",
+    );
+    let f = function_text(&object, "f");
+    // cmp rax, rcx; jge rel32
+    assert!(contains(f, &[0x48, 0x39, 0xc8, 0x0f, 0x8d]), "{:02x?}", f);
+    assert!(!has_setcc(f), "{:02x?}", f);
+    assert!(!contains(f, &[0x48, 0x85, 0xc0]), "{:02x?}", f);
+}
+
+#[test]
+fn unsigned_while_jumps_on_above_or_equal() {
+    let object = compile(
+        "fn count(n: usize) -> usize {
+             let i: usize = 0usize;
+             while i < n { i = i + 1usize; }
+             return i;
+         }
+         fn main() i32 { return 0; }",
+    );
+    let count = function_text(&object, "count");
+    // cmp rax, rcx; jae rel32
+    assert!(
+        contains(count, &[0x48, 0x39, 0xc8, 0x0f, 0x83]),
+        "{:02x?}",
+        count
+    );
+    assert!(!has_setcc(count), "{:02x?}", count);
+}
+
+#[test]
+fn stored_comparisons_still_use_setcc() {
+    let object =
+        compile("fn f(a: i64, b: i64) -> bool { return a < b; } fn main() i32 { return 0; }");
+    let f = function_text(&object, "f");
+    assert!(has_setcc(f), "{:02x?}", f);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn unsigned_conditions_do_not_see_a_sign() {
+    let source = "fn main() i32 {
+                      let a: u8 = 200u8;
+                      if a > 100u8 { return 1; }
+                      return 2;
+                  }";
+    assert_eq!(
+        common::exit_code("compare_branch_unsigned", source),
+        Some(1)
+    );
+}
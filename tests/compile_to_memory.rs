@@ -0,0 +1,64 @@
+// compile_to_memory links a program for a fixed address: text first, data
+// from the next page boundary, and every relocation patched with the
+// address its target has in that image.
+
+mod common;
+
+use aura_compiler::codegen::{RelocationKind, RelocationSection};
+use common::{compile, symbol};
+
+const SOURCE: &str = "const answer: i64 = 42i64;
+                      fn main() i64 { return answer; }";
+
+const BASE: u64 = 0x40_0000;
+
+#[test]
+fn image_holds_text_then_page_aligned_data() {
+    let object = compile(SOURCE);
+    let image = aura_compiler::compile_to_memory(SOURCE, BASE).unwrap();
+    let data_offset = object.text.len().next_multiple_of(4096);
+    assert_eq!(
+        image.len(),
+        data_offset + object.data.len() + object.bss_size
+    );
+    assert_eq!(
+        &image[data_offset..data_offset + object.data.len()],
+        object.data
+    );
+    assert!(image[object.text.len()..data_offset]
+        .iter()
+        .all(|&b| b == 0));
+}
+
+#[test]
+fn relocations_point_into_the_image() {
+    let object = compile(SOURCE);
+    let image = aura_compiler::compile_to_memory(SOURCE, BASE).unwrap();
+    let data_base = BASE + object.text.len().next_multiple_of(4096) as u64;
+    let answer = data_base + symbol(&object, "answer").offset;
+
+    let reloc = object
+        .relocations
+        .iter()
+        .find(|r| r.section == RelocationSection::Text)
+        .expect("main loads answer through a relocation");
+    assert_eq!(reloc.kind, RelocationKind::Absolute64);
+    let at = reloc.offset;
+    let patched = u64::from_le_bytes(image[at..at + 8].try_into().unwrap());
+    assert_eq!(patched, answer);
+
+    // Outside the relocated field the text is unchanged
+    assert_eq!(image[..at], object.text[..at]);
+    assert_eq!(image[at + 8..object.text.len()], object.text[at + 8..]);
+}
+
+#[test]
+fn unresolved_externs_are_rejected() {
+    let error = aura_compiler::compile_to_memory(
+        "extern fn missing() -> i64;
+         fn main() i64 { return missing(); }",
+        BASE,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("missing"), "{}", error);
+}
@@ -0,0 +1,40 @@
+// `const b = a;` between globals copies a's bytes in .data, so b reads back
+// the same value from storage of its own.
+
+use aura_compiler::codegen::{AuraObject, CodegenOptions};
+
+fn data_of<'a>(object: &'a AuraObject, name: &str) -> (u64, &'a [u8]) {
+    let symbol = object
+        .symbols
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("no symbol {}", name));
+    let start = symbol.offset as usize;
+    (
+        symbol.offset,
+        &object.data[start..start + symbol.size as usize],
+    )
+}
+
+#[test]
+fn const_from_const_copies_the_value() {
+    let (object, _) = aura_compiler::compile_source(
+        "const a: i64 = -5i64;
+         const b = a;
+         const row: [2]i32 = [7, 9];
+         const row_copy: [2]i32 = row;
+         fn main() i64 { return b + row_copy[0] as i64; }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    for (original, copy) in [("a", "b"), ("row", "row_copy")] {
+        let (original_offset, original_bytes) = data_of(&object, original);
+        let (copy_offset, copy_bytes) = data_of(&object, copy);
+        assert_ne!(
+            original_offset, copy_offset,
+            "{} aliases {}",
+            copy, original
+        );
+        assert_eq!(copy_bytes, original_bytes, "{}", copy);
+    }
+}
@@ -0,0 +1,74 @@
+// `const fn`s run at compile time wherever a constant is needed, such as an
+// array length; they must be pure, and runaway recursion or loops are
+// reported instead of hanging. See tests/const_fn_array_len.aura.
+
+mod common;
+
+use common::check;
+
+const SQUARE: &str = "const fn square(n: i32) i32 { return n * n; }";
+
+#[test]
+fn const_fn_sizes_an_array() {
+    let source = std::fs::read_to_string("tests/const_fn_array_len.aura").unwrap();
+    check(&source).unwrap_or_else(|e| panic!("check failed: {}", e));
+    // The length is 4, so a [5]u8 value does not fit the annotation
+    let error = check(&format!(
+        "{}
+         fn main() i32 {{
+             let t: [square(2)]u8 = zeroed([5]u8);
+             return 0;
+         }}",
+        SQUARE
+    ))
+    .expect_err("a [5]u8 passed as a [4]u8");
+    assert!(error.contains("Type mismatch in let"), "{}", error);
+}
+
+#[test]
+fn runaway_recursion_is_an_error() {
+    let error = check(
+        "const fn forever(n: i32) i32 { return forever(n + 1); }
+         const X: i32 = forever(0);
+         fn main() i32 { return X; }",
+    )
+    .expect_err("unbounded recursion passed");
+    assert!(error.contains("Const fn call depth exceeded"), "{}", error);
+}
+
+#[test]
+fn unbounded_loop_is_an_error() {
+    let error = check(
+        "const fn spin() i32 {
+             let i: i32 = 0;
+             while true { i = i + 1; }
+             return i;
+         }
+         const X: i32 = spin();
+         fn main() i32 { return X; }",
+    )
+    .expect_err("unbounded loop passed");
+    assert!(error.contains("Evaluation exceeded"), "{}", error);
+}
+
+#[test]
+fn side_effects_are_rejected() {
+    let error = check(
+        "const fn noisy() i32 { syscall.write(\"x\"); return 1; }
+         fn main() i32 { return 0; }",
+    )
+    .expect_err("a syscall in a const fn passed");
+    assert!(error.contains("const fn 'noisy' cannot use"), "{}", error);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn const_fn_result_is_a_constant() {
+    let source = format!(
+        "{}
+         const NINE: i32 = square(3);
+         fn main() i32 {{ return NINE; }}",
+        SQUARE
+    );
+    assert_eq!(common::exit_code("const_fn", &source), Some(9));
+}
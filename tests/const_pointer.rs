@@ -0,0 +1,120 @@
+// `const p: *const T = &global;` is an 8-byte slot in .data holding the
+// global's address, with an Absolute64 relocation in .data so linking or
+// loading elsewhere moves the address along with the data.
+
+mod common;
+
+use aura_compiler::codegen::{
+    AuraObject, CodegenOptions, RelocationKind, RelocationSection, DEFAULT_LOAD_BASE,
+};
+use common::{compile, symbol};
+
+fn slot_value(object: &AuraObject, name: &str) -> u64 {
+    let start = symbol(object, name).offset as usize;
+    u64::from_le_bytes(object.data[start..start + 8].try_into().unwrap())
+}
+
+const SOURCE: &str = "const message = \"hi\";
+     const msg_ptr: *const u8 = &message;
+     fn main() i32 { return *msg_ptr as i32; }";
+
+#[test]
+fn const_pointer_slot_carries_a_relocation_to_its_target() {
+    let object = compile(SOURCE);
+    let slot = symbol(&object, "msg_ptr");
+    assert_eq!(slot.size, 8);
+    assert_eq!(slot.offset % 8, 0, "slot is not 8-byte aligned");
+
+    let reloc = object
+        .relocations
+        .iter()
+        .find(|r| r.section == RelocationSection::Data)
+        .expect("no relocation in .data");
+    assert_eq!(reloc.offset, slot.offset as usize);
+    assert_eq!(reloc.symbol, "message");
+    assert_eq!(reloc.kind, RelocationKind::Absolute64);
+
+    // Baked against the load base, so the unlinked binary works as is
+    let message = symbol(&object, "message").offset;
+    assert_eq!(slot_value(&object, "msg_ptr"), DEFAULT_LOAD_BASE + message);
+}
+
+#[test]
+fn const_pointer_to_an_integer_global() {
+    let object = compile(
+        "const answer: i64 = 42;
+         const answer_ptr: *const i64 = &answer;
+         fn main() i64 { return *answer_ptr; }",
+    );
+    let answer = symbol(&object, "answer").offset;
+    assert_eq!(
+        slot_value(&object, "answer_ptr"),
+        DEFAULT_LOAD_BASE + answer
+    );
+}
+
+#[test]
+fn linking_moves_the_address_with_the_data() {
+    let first = compile("const pad = \"padding\"; fn main() i32 { return 0; }");
+    let second = compile(
+        "const message = \"hi\";
+         const msg_ptr: *const u8 = &message;
+         fn second() -> i32 { return *msg_ptr as i32; }",
+    );
+    let linked = aura_compiler::codegen::link(&[first, second]).expect("link");
+
+    let slot = symbol(&linked, "msg_ptr").offset;
+    let reloc = linked
+        .relocations
+        .iter()
+        .find(|r| r.section == RelocationSection::Data)
+        .expect("no relocation in .data");
+    assert_eq!(reloc.offset, slot as usize);
+    let message = symbol(&linked, "message").offset;
+    assert_eq!(slot_value(&linked, "msg_ptr"), DEFAULT_LOAD_BASE + message);
+}
+
+#[test]
+fn applying_relocations_patches_the_slot() {
+    let mut object = compile(SOURCE);
+    let base = 0x40_0000;
+    object.apply_relocations(base).expect("apply relocations");
+    let data_base = base + object.text.len().next_multiple_of(4096) as u64;
+    let message = symbol(&object, "message").offset;
+    assert_eq!(slot_value(&object, "msg_ptr"), data_base + message);
+}
+
+#[test]
+fn address_of_an_undeclared_global_is_an_error() {
+    let result = aura_compiler::compile_source(
+        "const msg_ptr: *const u8 = &missing;
+         fn main() i32 { return 0; }",
+        &CodegenOptions::default(),
+    );
+    assert!(
+        result.is_err(),
+        "taking the address of an unknown name compiled"
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn reading_through_a_const_pointer() {
+    let object = compile(SOURCE);
+    let status = common::run("const_pointer", &object).status;
+    assert_eq!(status.code(), Some(i32::from(b'h')));
+}
+
+// The syscall gets the address the slot holds, not the slot's own
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn writing_through_a_const_pointer() {
+    let object = compile(
+        "const msg = \"hey\\n\";
+         const p: *const u8 = &msg;
+         fn main() i32 { syscall.write(1, p, 4); return 0; }",
+    );
+    let output = common::run("const_pointer_write", &object);
+    assert_eq!(output.stdout, b"hey\n");
+    assert_eq!(output.status.code(), Some(0));
+}
@@ -0,0 +1,127 @@
+// Layout queries over declared structs are compile-time constants: they fold
+// into `.data` and size arrays exactly as the struct is laid out, and become
+// immediates inside function bodies.
+
+mod common;
+
+use aura_compiler::codegen::{AuraObject, CodegenOptions, IntType};
+use common::compile;
+
+fn data_of<'a>(object: &'a AuraObject, name: &str) -> &'a [u8] {
+    let symbol = object
+        .symbols
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("no symbol {}", name));
+    let start = symbol.offset as usize;
+    &object.data[start..start + symbol.size as usize]
+}
+
+#[test]
+fn sizeof_struct_folds_to_its_layout_size() {
+    let object = compile(
+        "struct Header { magic: u32; kind: u8; len: u64; }
+         const HEADER_SIZE = sizeof(Header);
+         const LEN_OFFSET: usize = offsetof(Header, len);
+         fn main() i32 { return 0; }",
+    );
+    assert_eq!(data_of(&object, "HEADER_SIZE"), &16u64.to_le_bytes());
+    assert_eq!(data_of(&object, "LEN_OFFSET"), &8u64.to_le_bytes());
+    let symbol = object
+        .symbols
+        .iter()
+        .find(|s| s.name == "HEADER_SIZE")
+        .unwrap();
+    assert_eq!(
+        symbol.int_type,
+        Some(IntType {
+            bits: 64,
+            signed: false
+        })
+    );
+}
+
+#[test]
+fn array_length_from_sizeof_struct() {
+    let object = compile(
+        "@packed struct Tag { kind: u8; value: u16; }
+         const raw: [sizeof(Tag)]u8 = [1u8, 2u8, 3u8];
+         fn main() i32 { return 0; }",
+    );
+    assert_eq!(data_of(&object, "raw"), &[1, 2, 3]);
+}
+
+#[test]
+fn sizeof_struct_declared_later_is_not_a_constant() {
+    let error = aura_compiler::compile_source(
+        "const raw: [sizeof(Tag)]u8 = [1u8, 2u8, 3u8];
+         struct Tag { kind: u8; value: u16; }
+         fn main() i32 { return 0; }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        error.message.contains("is not a type declared before it"),
+        "{}",
+        error.message
+    );
+}
+
+#[test]
+fn mixed_width_fields_are_padded_to_their_alignment() {
+    // a at 0, b padded up to 8, c at 16, then the size rounds up to 24
+    let object = compile(
+        "struct Mixed { a: i8; b: i64; c: i8; }
+         const MIXED_SIZE = sizeof(Mixed);
+         const MIXED_ALIGN = alignof(Mixed);
+         const C_OFFSET: usize = offsetof(Mixed, c);
+         fn main() i32 { return 0; }",
+    );
+    assert_eq!(data_of(&object, "MIXED_SIZE"), &24u64.to_le_bytes());
+    assert_eq!(data_of(&object, "MIXED_ALIGN"), &8u64.to_le_bytes());
+    assert_eq!(data_of(&object, "C_OFFSET"), &16u64.to_le_bytes());
+}
+
+#[test]
+fn sizeof_in_a_function_body_is_an_immediate() {
+    let object = compile(
+        "struct Mixed { a: i8; b: i64; }
+         union Either { small: i8; big: i64; }
+         fn size() -> usize { return sizeof(Mixed); }
+         fn align() -> usize { return alignof(Either); }
+         fn main() i32 { return 0; }",
+    );
+    let text_of = |name: &str| {
+        let symbol = object.symbols.iter().find(|s| s.name == name).unwrap();
+        let start = symbol.offset as usize;
+        &object.text[start..start + symbol.size as usize]
+    };
+    // mov rax, 16 / mov rax, 8
+    let contains = |text: &[u8], needle: &[u8]| text.windows(needle.len()).any(|w| w == needle);
+    let size = text_of("size");
+    assert!(
+        contains(size, &[0x48, 0xc7, 0xc0, 16, 0, 0, 0]),
+        "{:02x?}",
+        size
+    );
+    let align = text_of("align");
+    assert!(
+        contains(align, &[0x48, 0xc7, 0xc0, 8, 0, 0, 0]),
+        "{:02x?}",
+        align
+    );
+}
+
+#[test]
+fn sizeof_undeclared_type_is_an_error() {
+    let error = aura_compiler::compile_source(
+        "fn main() -> usize { return sizeof(Missing); }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        error.message.contains("sizeof: unknown type 'Missing'"),
+        "{}",
+        error.message
+    );
+}
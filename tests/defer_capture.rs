@@ -0,0 +1,48 @@
+// A deferred call evaluates its arguments when the defer is registered and
+// makes the call when the scope ends, so assigning to an argument's
+// variable in between does not change what the call sees.
+
+mod common;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn arguments_are_captured_at_registration() {
+    let source = "@entry()
+                  fn main() -> i64 {
+                      let code: i64 = 1i64;
+                      defer syscall.exit(code);
+                      code = 2i64;
+                      return 0i64;
+                  }";
+    assert_eq!(common::exit_code("defer_capture", source), Some(1));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn call_arguments_are_evaluated_at_registration() {
+    let source = "fn twice(x: i64) -> i64 { return x * 2i64; }
+                  @entry()
+                  fn main() -> i64 {
+                      let n: i64 = 3i64;
+                      {
+                          defer syscall.exit(twice(n) + n);
+                          n = 20i64;
+                      }
+                      return 0i64;
+                  }";
+    // twice(3) + 3, not twice(20) + 20
+    assert_eq!(common::exit_code("defer_call", source), Some(9));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn block_defers_still_run_at_scope_end() {
+    let source = "@entry()
+                  fn main() -> i64 {
+                      let code: i64 = 1i64;
+                      defer { syscall.exit(code); }
+                      code = 3i64;
+                      return 0i64;
+                  }";
+    assert_eq!(common::exit_code("defer_block", source), Some(3));
+}
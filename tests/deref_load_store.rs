@@ -0,0 +1,52 @@
+// `*p` parses to the one dereference node, Unary(Deref), and loads and
+// stores through it use the pointee's width; see tests/deref_load_store.aura.
+
+mod common;
+
+use aura_compiler::ast::{Expr, Item, Stmt, UnaryOp};
+use common::{compile, contains, function_text};
+
+fn source() -> String {
+    std::fs::read_to_string("tests/deref_load_store.aura").unwrap()
+}
+
+#[test]
+fn deref_parses_to_unary_deref() {
+    let tokens = aura_compiler::lexer::lex("fn get(p: *i32) i32 { return *p; }").unwrap();
+    let program = aura_compiler::parser::parse(&tokens).unwrap();
+    let Item::Function(get) = &program.items[0] else {
+        panic!("expected a function");
+    };
+    let Stmt::Return(Some(value)) = &get.body[0] else {
+        panic!("expected a return");
+    };
+    assert!(
+        matches!(value, Expr::Unary(UnaryOp::Deref, operand) if matches!(**operand, Expr::Identifier(..))),
+        "{:?}",
+        value
+    );
+}
+
+#[test]
+fn loads_and_stores_use_the_pointee_width() {
+    let object = compile(&source());
+    let main = function_text(&object, "main");
+    // mov [rcx], eax
+    assert!(contains(main, &[0x89, 0x01]), "{:02x?}", main);
+    // movsxd rax, dword [rax]
+    assert!(contains(main, &[0x48, 0x63, 0x00]), "{:02x?}", main);
+}
+
+// The fixture's alloc needs a runtime allocator, so this runs on a local
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn stored_value_reads_back_sign_extended() {
+    let source = "fn main() i32 {
+             let x: i32 = 0;
+             let p: *i32 = &x;
+             *p = -5;
+             let v: i32 = *p;
+             return v + 10;
+         }";
+    assert_eq!(common::exit_code("deref_load_store", source), Some(5));
+}
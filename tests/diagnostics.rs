@@ -0,0 +1,44 @@
+// Errors from every phase come back as a Diagnostic carrying the phase and,
+// where the phase knows it, the line and column of the offending code.
+
+use aura_compiler::codegen::CodegenOptions;
+use aura_compiler::diagnostic::{Diagnostic, Phase, Severity};
+
+fn compile_error(fixture: &str) -> Diagnostic {
+    let source = std::fs::read_to_string(fixture).unwrap();
+    aura_compiler::compile_source(&source, &CodegenOptions::default())
+        .map(|_| ())
+        .expect_err("fixture compiled")
+}
+
+#[test]
+fn type_errors_point_at_the_offending_name() {
+    let error = compile_error("tests/diagnostic_type_error.aura");
+    assert_eq!(error.phase, Phase::Typecheck);
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(error.message, "Undefined variable: missing");
+    let span = error.span.as_ref().expect("type error without a span");
+    assert_eq!((span.line, span.column), (6, 12));
+    assert_eq!(
+        error.to_string(),
+        "error[typecheck] at 6:12: Undefined variable: missing"
+    );
+}
+
+#[test]
+fn codegen_errors_carry_a_position() {
+    let error = compile_error("tests/diagnostic_codegen_error.aura");
+    assert_eq!(error.phase, Phase::Codegen);
+    assert!(
+        error.message.contains("write syscall argument"),
+        "{}",
+        error
+    );
+    let span = error.span.as_ref().expect("codegen error without a span");
+    assert_eq!((span.line, span.column), (3, 4));
+    assert!(
+        error.to_string().starts_with("error[codegen] at 3:4: "),
+        "{}",
+        error
+    );
+}
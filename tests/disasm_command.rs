@@ -0,0 +1,53 @@
+// `aura disasm` decodes a built binary's text section into one instruction
+// per line, offset then mnemonic and operands, with no hex dump.
+
+mod common;
+
+use std::process::Command;
+
+use aura_compiler::codegen::{disasm::disassemble, write_aura_binary};
+use common::compile;
+
+const SOURCE: &str = "fn main() i32 { return 7; }";
+
+#[test]
+fn disasm_lists_mnemonics_without_a_hex_dump() {
+    let path = std::env::temp_dir().join(format!("aura_disasm_{}.aura", std::process::id()));
+    write_aura_binary(&compile(SOURCE), &path).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_aura"))
+        .arg("disasm")
+        .arg(&path)
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "00000000: xor ebp, ebp",
+            "00000002: push rbp",
+            "00000003: mov rbp, rsp",
+            "00000006: and rsp, -0x10",
+            "0000000a: sub rsp, 0x0",
+            "00000011: mov rax, 0x7",
+            "00000018: mov rsp, rbp",
+            "0000001b: pop rbp",
+            "0000001c: mov rdi, rax",
+            "0000001f: mov rax, 0x3c",
+            "00000026: syscall",
+        ],
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn unknown_bytes_are_shown_one_at_a_time() {
+    // 0x06 is invalid in 64-bit mode; the ret after it still decodes
+    let instructions = disassemble(&[0x06, 0xc3]);
+    let text: Vec<&str> = instructions.iter().map(|i| i.text.as_str()).collect();
+    assert_eq!(text, [".byte 0x06", "ret"]);
+    assert_eq!(instructions[1].offset, 1);
+}
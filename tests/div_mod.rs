@@ -0,0 +1,58 @@
+// Arithmetic on the left operand in rax and the right in rcx: `-` is
+// sub rax, rcx; `/` and `%` sign-extend into rdx with cqo and use idiv for
+// signed operands, or clear edx and use div for unsigned ones, and `%` reads
+// the remainder back from rdx.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+// cqo; idiv rcx
+const IDIV: [u8; 5] = [0x48, 0x99, 0x48, 0xf7, 0xf9];
+// cqo; idiv rcx; mov rax, rdx
+const IMOD: [u8; 8] = [0x48, 0x99, 0x48, 0xf7, 0xf9, 0x48, 0x89, 0xd0];
+// xor edx, edx; div rcx
+const DIV: [u8; 5] = [0x31, 0xd2, 0x48, 0xf7, 0xf1];
+// xor edx, edx; div rcx; mov rax, rdx
+const MOD: [u8; 8] = [0x31, 0xd2, 0x48, 0xf7, 0xf1, 0x48, 0x89, 0xd0];
+
+fn fixture() -> String {
+    std::fs::read_to_string("tests/div_mod.aura").unwrap()
+}
+
+#[test]
+fn subtraction_evaluates_left_then_right() {
+    let object = compile("fn main() i32 { return 7 - 2; }");
+    let text = function_text(&object, "main");
+    // mov rax, 7; push rax; mov rax, 2; mov rcx, rax; pop rax; sub rax, rcx
+    let body = [
+        0x48, 0xc7, 0xc0, 0x07, 0x00, 0x00, 0x00, 0x50, 0x48, 0xc7, 0xc0, 0x02, 0x00, 0x00, 0x00,
+        0x48, 0x89, 0xc1, 0x58, 0x48, 0x29, 0xc8,
+    ];
+    assert!(contains(text, &body), "{:02x?}", text);
+}
+
+#[test]
+fn signed_division_uses_cqo_and_idiv() {
+    let object = compile(&fixture());
+    let text = function_text(&object, "main");
+    assert!(contains(text, &IDIV), "{:02x?}", text);
+    assert!(contains(text, &IMOD), "{:02x?}", text);
+}
+
+#[test]
+fn unsigned_division_clears_rdx_and_uses_div() {
+    let object = compile(&fixture());
+    let text = function_text(&object, "main");
+    assert!(contains(text, &DIV), "{:02x?}", text);
+    assert!(contains(text, &MOD), "{:02x?}", text);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn subtraction_runs() {
+    assert_eq!(
+        common::exit_code("sub", "fn main() i32 { return 7 - 2; }"),
+        Some(5)
+    );
+}
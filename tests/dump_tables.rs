@@ -0,0 +1,163 @@
+// `aura dump` decodes the relocation and symbol records that follow .data,
+// and a truncated file ends the dump early instead of panicking. Section
+// bytes are shown as hex rows with an ASCII column beside them, and
+// `--annotate` adds the decoded value of each integer data symbol.
+
+mod common;
+
+use aura_compiler::codegen::{
+    aura_object_to_bytes, AuraBinary, AuraObject, DumpOptions, IntType, Relocation, RelocationKind,
+    RelocationSection, Symbol, SymbolKind, DEFAULT_LOAD_BASE,
+};
+
+fn object() -> AuraObject {
+    AuraObject {
+        entry_point: 0,
+        load_base: DEFAULT_LOAD_BASE,
+        text: vec![0x48, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0xc3],
+        data: 42u64.to_le_bytes().to_vec(),
+        bss_size: 0,
+        relocations: vec![Relocation {
+            offset: 2,
+            symbol: "answer".to_string(),
+            kind: RelocationKind::Absolute64,
+            section: RelocationSection::Text,
+        }],
+        symbols: vec![
+            Symbol {
+                name: "main".to_string(),
+                offset: 0,
+                size: 11,
+                kind: SymbolKind::Function,
+                int_type: None,
+            },
+            Symbol {
+                name: "answer".to_string(),
+                offset: 0,
+                size: 8,
+                kind: SymbolKind::Data,
+                int_type: Some(IntType {
+                    bits: 64,
+                    signed: true,
+                }),
+            },
+        ],
+        capability_sections: Vec::new(),
+        topology_sections: Vec::new(),
+        bit_region_sections: Vec::new(),
+    }
+}
+
+fn dump(bytes: &[u8]) -> String {
+    let mut out = Vec::new();
+    AuraBinary::dump_to(&mut out, bytes, &DumpOptions::default()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// The lines of `dump` under the `=== title ===` heading
+fn section<'a>(dump: &'a str, title: &str) -> Vec<&'a str> {
+    dump.lines()
+        .skip_while(|line| *line != format!("=== {} ===", title))
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .collect()
+}
+
+#[test]
+fn dump_lists_relocations_and_symbols() {
+    let bytes = aura_object_to_bytes(&object()).unwrap();
+    let dump = dump(&bytes);
+    assert_eq!(
+        section(&dump, "Relocations"),
+        ["0x00000002 Absolute64   answer = 0x0000000000000000"],
+        "{}",
+        dump
+    );
+    // Sorted by offset, then name, as the writer stores them
+    assert_eq!(
+        section(&dump, "Symbols"),
+        [
+            "0x00000000        8 Data       answer",
+            "0x00000000       11 Function   main",
+        ],
+        "{}",
+        dump
+    );
+}
+
+#[test]
+fn truncated_dump_stops_without_panicking() {
+    let bytes = aura_object_to_bytes(&object()).unwrap();
+    let full = dump(&bytes);
+    for len in 0..bytes.len() {
+        // A table is listed in full or not at all, never partly
+        let dump = dump(&bytes[..len]);
+        for title in ["Relocations", "Symbols"] {
+            let listed = section(&dump, title);
+            assert!(
+                listed.is_empty() || listed == section(&full, title),
+                "prefix of {} bytes:\n{}",
+                len,
+                dump
+            );
+        }
+    }
+}
+
+#[test]
+fn data_section_shows_strings_in_the_ascii_column() {
+    let object = common::compile(
+        "const greeting = \"Hello, Aura!\\n\";
+         fn main() i32 { return 0; }",
+    );
+    let bytes = aura_object_to_bytes(&object).unwrap();
+    // The newline and the terminating NUL are unprintable, so they show as '.'
+    assert_eq!(
+        section(&dump(&bytes), "Data Section (14 bytes)"),
+        ["00000000: 48 65 6c 6c 6f 2c 20 41 75 72 61 21 0a 00        Hello, Aura!.."]
+    );
+
+    let mut out = Vec::new();
+    let options = DumpOptions {
+        skip: 4,
+        length: Some(6),
+        ..DumpOptions::default()
+    };
+    AuraBinary::dump_to(&mut out, &bytes, &options).unwrap();
+    let window = String::from_utf8(out).unwrap();
+    assert_eq!(
+        section(&window, "Data Section (14 bytes)"),
+        ["00000004: 6f 2c 20 41 75 72                                o, Aur"]
+    );
+}
+
+#[test]
+fn annotate_decodes_integer_symbols_at_their_type() {
+    let source = std::fs::read_to_string("tests/dump_data_values.aura").unwrap();
+    let bytes = aura_object_to_bytes(&common::compile(&source)).unwrap();
+    let options = DumpOptions {
+        annotate: true,
+        ..DumpOptions::default()
+    };
+    let mut out = Vec::new();
+    AuraBinary::dump_to(&mut out, &bytes, &options).unwrap();
+    let annotated = String::from_utf8(out).unwrap();
+    // The string constant has no integer type, so it is left out
+    let values: Vec<&str> = section(&annotated, "Data Values")
+        .into_iter()
+        .map(|line| line.split_once(": ").unwrap().1)
+        .collect();
+    assert_eq!(
+        values,
+        [
+            "N = 42 (i32)",
+            "BIG = 4000000000 (u32)",
+            "NEG = -7 (i64)",
+            "main::counter = 255 (u8)",
+        ],
+        "{}",
+        annotated
+    );
+    // Without the option the section is not listed
+    assert!(!dump(&bytes).contains("Data Values"));
+}
@@ -0,0 +1,59 @@
+// A `return` nested in loops and blocks drops the temporaries pushed around
+// it and runs every open defer, innermost first, before the epilogue; a
+// `break` only runs the defers inside the loop. See
+// tests/early_return_nested_loop.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+use common::compile;
+
+#[test]
+fn return_runs_every_open_defer() {
+    let source = std::fs::read_to_string("tests/early_return_nested_loop.aura").unwrap();
+    let output = common::run("early_return_nested_loop", &compile(&source));
+    assert_eq!(output.stdout, b"inner\ncleanup\n");
+    assert_eq!(output.status.code(), Some(7));
+}
+
+#[test]
+fn caller_frame_survives_repeated_early_returns() {
+    // If a return left the pushed `1 +` operand behind, the caller's rsp
+    // and its locals would be off after the first call
+    let source = "fn find(n: i32) -> i32 {
+                      let r: i32 = 1 + loop {
+                          loop {
+                              if n > 0 { return n; }
+                              break;
+                          }
+                          break 0;
+                      };
+                      return r;
+                  }
+                  fn main() i32 {
+                      let total: i32 = 0;
+                      let i: i32 = 0;
+                      while i < 5 {
+                          total = total + find(i);
+                          i = i + 1;
+                      }
+                      return total;
+                  }";
+    assert_eq!(common::exit_code("early_return_frames", source), Some(11));
+}
+
+#[test]
+fn break_runs_only_the_loop_defers() {
+    let source = "fn main() i32 {
+                      defer syscall.write(1, \"fn\\n\", 3);
+                      loop {
+                          defer syscall.write(1, \"loop\\n\", 5);
+                          break;
+                      }
+                      syscall.write(1, \"after\\n\", 6);
+                      return 0;
+                  }";
+    let output = common::run("break_defers", &compile(source));
+    assert_eq!(output.stdout, b"loop\nafter\nfn\n");
+}
@@ -0,0 +1,60 @@
+// The executable writer produces a static ET_EXEC ELF64 whose single
+// PT_LOAD segment maps .data at the object's load base.
+
+mod common;
+
+use aura_compiler::codegen::elf_executable_bytes;
+use common::compile;
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+const SOURCE: &str = "const answer: i64 = 42i64;
+                      fn main() i64 { return answer; }";
+
+#[test]
+fn header_describes_one_loadable_segment() {
+    let object = compile(SOURCE);
+    let elf = elf_executable_bytes(&object).unwrap();
+    assert_eq!(&elf[..4], b"\x7fELF");
+    assert_eq!(elf[4], 2, "ELFCLASS64");
+    assert_eq!(u16_at(&elf, 16), 2, "ET_EXEC");
+    assert_eq!(u16_at(&elf, 18), 62, "EM_X86_64");
+    assert_eq!(u16_at(&elf, 56), 1, "e_phnum");
+
+    let phdr = u64_at(&elf, 32) as usize;
+    assert_eq!(
+        u32::from_le_bytes(elf[phdr..phdr + 4].try_into().unwrap()),
+        1
+    );
+    let offset = u64_at(&elf, phdr + 8);
+    let vaddr = u64_at(&elf, phdr + 16);
+    assert_eq!(offset % 4096, vaddr % 4096);
+    // .data is on the page after text, at the load base codegen assumed
+    assert_eq!(vaddr + 4096, object.load_base);
+    assert_eq!(u64_at(&elf, 24), vaddr + object.entry_point);
+    let data = offset as usize + 4096;
+    assert_eq!(&elf[data..data + 8], &42i64.to_le_bytes());
+}
+
+#[test]
+fn unresolved_externs_are_rejected() {
+    let object = compile(
+        "extern fn missing() -> i64;
+         fn main() i64 { return missing(); }",
+    );
+    let error = elf_executable_bytes(&object).unwrap_err();
+    assert!(error.message.contains("missing"), "{}", error.message);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn executable_runs_and_exits_with_mains_result() {
+    let status = common::run("elf_exec", &compile(SOURCE)).status;
+    assert_eq!(status.code(), Some(42));
+}
@@ -0,0 +1,107 @@
+// `--emit obj` writes an ELF64 relocatable: .text, .data and .rela.text
+// sections, data symbols as OBJECTs and extern functions as undefined
+// symbols for the system linker. See tests/emit_object.aura.
+
+mod common;
+
+use aura_compiler::codegen::elf::elf_object_bytes;
+use common::compile;
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+fn c_str(bytes: &[u8], at: usize) -> &str {
+    let end = bytes[at..].iter().position(|&b| b == 0).unwrap();
+    std::str::from_utf8(&bytes[at..at + end]).unwrap()
+}
+
+struct Section {
+    name: String,
+    kind: u32,
+    offset: usize,
+    size: usize,
+    link: usize,
+}
+
+fn sections(elf: &[u8]) -> Vec<Section> {
+    let shoff = u64_at(elf, 0x28) as usize;
+    let shnum = u16_at(elf, 0x3c) as usize;
+    let shstrndx = u16_at(elf, 0x3e) as usize;
+    let header = |i: usize| shoff + i * 64;
+    let names = u64_at(elf, header(shstrndx) + 0x18) as usize;
+    (0..shnum)
+        .map(|i| Section {
+            name: c_str(elf, names + u32_at(elf, header(i)) as usize).to_string(),
+            kind: u32_at(elf, header(i) + 4),
+            offset: u64_at(elf, header(i) + 0x18) as usize,
+            size: u64_at(elf, header(i) + 0x20) as usize,
+            link: u32_at(elf, header(i) + 0x28) as usize,
+        })
+        .collect()
+}
+
+fn object_bytes() -> Vec<u8> {
+    let source = std::fs::read_to_string("tests/emit_object.aura").unwrap();
+    elf_object_bytes(&compile(&source))
+}
+
+#[test]
+fn header_is_a_relocatable() {
+    let elf = object_bytes();
+    assert_eq!(&elf[..4], b"\x7fELF");
+    assert_eq!(elf[4], 2, "ELFCLASS64");
+    assert_eq!(u16_at(&elf, 16), 1, "ET_REL");
+    assert_eq!(u16_at(&elf, 18), 62, "EM_X86_64");
+    assert_eq!(u64_at(&elf, 24), 0, "no entry point");
+}
+
+#[test]
+fn sections_include_text_data_and_relocations() {
+    let elf = object_bytes();
+    let names: Vec<String> = sections(&elf).into_iter().map(|s| s.name).collect();
+    for name in [".text", ".data", ".rela.text", ".symtab", ".strtab"] {
+        assert!(
+            names.iter().any(|n| n == name),
+            "{} missing: {:?}",
+            name,
+            names
+        );
+    }
+}
+
+#[test]
+fn symbols_define_data_and_leave_externs_undefined() {
+    let elf = object_bytes();
+    let sections = sections(&elf);
+    let symtab = sections.iter().find(|s| s.kind == 2).expect("SHT_SYMTAB");
+    let strtab = &sections[symtab.link];
+    // (name, type, section index) of each 24-byte entry
+    let symbols: Vec<(String, u8, u16)> = (0..symtab.size / 24)
+        .map(|i| {
+            let at = symtab.offset + i * 24;
+            let name = c_str(&elf, strtab.offset + u32_at(&elf, at) as usize).to_string();
+            (name, elf[at + 4] & 0xf, u16_at(&elf, at + 6))
+        })
+        .collect();
+    let find = |name: &str| {
+        symbols
+            .iter()
+            .find(|s| s.0 == name)
+            .unwrap_or_else(|| panic!("no symbol {} in {:?}", name, symbols))
+    };
+    // STT_OBJECT and STT_FUNC, both defined
+    assert_eq!(find("bump::total").1, 1);
+    assert_ne!(find("bump::total").2, 0);
+    assert_eq!(find("bump").1, 2);
+    // SHN_UNDEF
+    assert_eq!(find("puts").2, 0);
+}
@@ -0,0 +1,33 @@
+// An empty void function still gets a full frame and returns; an empty
+// function with a return type falls off its end and is rejected. See
+// tests/empty_void_fn.aura and tests/empty_fn_missing_return_error.aura.
+
+mod common;
+
+use common::{check, compile, function_text};
+
+#[test]
+fn empty_void_body_is_a_frame_and_ret() {
+    let source = std::fs::read_to_string("tests/empty_void_fn.aura").unwrap();
+    let object = compile(&source);
+    let nothing = function_text(&object, "nothing");
+    // push rbp; mov rbp, rsp
+    assert_eq!(&nothing[..4], &[0x55, 0x48, 0x89, 0xe5], "{:02x?}", nothing);
+    // mov rsp, rbp; pop rbp; ret
+    assert!(
+        nothing.ends_with(&[0x48, 0x89, 0xec, 0x5d, 0xc3]),
+        "{:02x?}",
+        nothing
+    );
+}
+
+#[test]
+fn empty_body_with_a_return_type_is_an_error() {
+    let error = check("fn answer() i32 {} fn main() i32 { return answer(); }")
+        .expect_err("empty i32 function passed");
+    assert!(
+        error.contains("Function 'answer' can reach the end of its body"),
+        "{}",
+        error
+    );
+}
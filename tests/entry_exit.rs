@@ -0,0 +1,124 @@
+// The entry function ends the process with its return value as the exit
+// status (mov rdi, rax; mov rax, 60; syscall) instead of returning; every
+// other function still returns with `ret`. Nothing called it either, so its
+// prologue zeroes rbp and aligns rsp itself.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+// mov rdi, rax; mov rax, 60; syscall
+const EXIT: [u8; 12] = [
+    0x48, 0x89, 0xc7, 0x48, 0xc7, 0xc0, 0x3c, 0x00, 0x00, 0x00, 0x0f, 0x05,
+];
+
+#[test]
+fn only_the_entry_function_exits() {
+    let object = compile(
+        "fn helper() i64 { return 2i64; }
+         @entry()
+         fn start() -> i64 { return helper(); }",
+    );
+    let start = function_text(&object, "start");
+    assert!(start.ends_with(&EXIT), "{:02x?}", start);
+    let helper = function_text(&object, "helper");
+    assert!(!contains(helper, &EXIT), "{:02x?}", helper);
+    assert_eq!(helper.last(), Some(&0xc3), "{:02x?}", helper);
+}
+
+#[test]
+fn main_is_the_default_entry() {
+    let object = compile(
+        "fn helper() i64 { return 2i64; }
+         fn main() i64 { return helper(); }",
+    );
+    let main = function_text(&object, "main");
+    assert!(main.ends_with(&EXIT), "{:02x?}", main);
+    let main_offset = object
+        .symbols
+        .iter()
+        .find(|s| s.name == "main")
+        .unwrap()
+        .offset;
+    assert_eq!(object.entry_point, main_offset);
+    assert!(!contains(function_text(&object, "helper"), &EXIT));
+}
+
+#[test]
+fn exit_syscall_takes_the_status_in_rdi() {
+    let object = compile(
+        "fn helper() i64 { syscall.exit(3i64); return 0i64; }
+         fn main() i64 { return helper(); }",
+    );
+    let helper = function_text(&object, "helper");
+    // mov rax, 3; then the exit sequence
+    let mut expected = vec![0x48, 0xc7, 0xc0, 0x03, 0x00, 0x00, 0x00];
+    expected.extend_from_slice(&EXIT);
+    assert!(contains(helper, &expected), "{:02x?}", helper);
+}
+
+#[test]
+fn entry_prologue_starts_the_outermost_frame_aligned() {
+    let object = compile(
+        "fn helper() i64 { return 2i64; }
+         fn main() i64 { return helper(); }",
+    );
+    // xor ebp, ebp; push rbp; mov rbp, rsp; and rsp, -16; sub rsp, imm32
+    let main = function_text(&object, "main");
+    assert!(
+        main.starts_with(&[
+            0x31, 0xed, 0x55, 0x48, 0x89, 0xe5, 0x48, 0x83, 0xe4, 0xf0, 0x48, 0x81, 0xec
+        ]),
+        "{:02x?}",
+        main
+    );
+    // A called function keeps the plain prologue
+    let helper = function_text(&object, "helper");
+    assert!(
+        helper.starts_with(&[0x55, 0x48, 0x89, 0xe5, 0x48, 0x81, 0xec]),
+        "{:02x?}",
+        helper
+    );
+}
+
+#[test]
+fn every_entry_return_exits_instead_of_returning() {
+    let object = compile(
+        "fn main() i64 {
+             let x: i64 = 1i64;
+             if x == 1i64 { return 3i64; }
+             if x == 2i64 { return 4i64; }
+             return 5i64;
+         }",
+    );
+    let main = function_text(&object, "main");
+    let exits = main.windows(EXIT.len()).filter(|w| *w == EXIT).count();
+    assert_eq!(exits, 3, "{:02x?}", main);
+    // Every exit is preceded by the frame teardown, never followed by ret
+    let leave_exit: Vec<u8> = [0x48, 0x89, 0xec, 0x5d]
+        .iter()
+        .chain(&EXIT)
+        .copied()
+        .collect();
+    let torn_down = main
+        .windows(leave_exit.len())
+        .filter(|w| *w == leave_exit.as_slice())
+        .count();
+    assert_eq!(torn_down, 3, "{:02x?}", main);
+    assert!(!contains(main, &[0x5d, 0xc3]), "{:02x?}", main);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn executable_entry_calls_and_exits_cleanly() {
+    let object = compile(
+        "fn half(x: f64) -> f64 { return x / 2.0; }
+         fn add(a: i64, b: i64) -> i64 { return a + b; }
+         fn main() -> i64 {
+             if half(3.0) != 1.5 { return 1i64; }
+             return add(40i64, 2i64);
+         }",
+    );
+    let status = common::run("entry_exit", &object).status;
+    assert_eq!(status.code(), Some(42));
+}
@@ -0,0 +1,65 @@
+// Values of one enum compare with `==` and `!=` by their backing integers;
+// ordering, another enum or an integer needs an explicit cast. See
+// tests/enum_compare.aura and tests/enum_int_compare_error.aura.
+
+mod common;
+
+const COLOR: &str = "enum Color { Red, Green = 5, Blue }
+                     enum Shape { Circle, Square }";
+
+fn check_error(body: &str) -> String {
+    common::check(&format!(
+        "{}\nfn main() i32 {{\nlet c: Color = Color.Blue;\n{}\nreturn 0;\n}}",
+        COLOR, body
+    ))
+    .unwrap_err()
+}
+
+#[test]
+fn ordering_needs_a_cast() {
+    let error = check_error("let less: bool = c < Color.Red;");
+    assert!(
+        error.contains("Enum Color values only support == and != (cast to an integer to use Lt)"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn different_enums_do_not_compare() {
+    let error = check_error("let same: bool = c == Shape.Square;");
+    assert!(
+        error.contains("Cannot compare values of different enums Color and Shape"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn an_integer_on_the_left_needs_a_cast_too() {
+    let error = check_error("let six: bool = 6i64 == c;");
+    assert!(
+        error.contains("Cannot compare enum Color with I64"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn comparisons_use_the_backing_values() {
+    let source = format!(
+        "{}
+         fn main() i32 {{
+             let c: Color = Color.Green;
+             let d: Color = Color.Blue;
+             let same: bool = c == Color.Green;
+             let differ: bool = c != d;
+             let is_five: bool = (c as i64) == 5i64;
+             let ordered: bool = (c as i64) < (d as i64);
+             return same as i32 * 8 + differ as i32 * 4 + is_five as i32 * 2 + ordered as i32;
+         }}",
+        COLOR
+    );
+    assert_eq!(common::exit_code("enum_compare", &source), Some(15));
+}
@@ -0,0 +1,117 @@
+// `Enum.Variant` names a variant: it typechecks to the enum and codegen folds
+// it to the variant's integer, taken from an explicit `= value` or one past
+// the previous variant (0 for the first).
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+
+fn check_error(source: &str) -> String {
+    aura_compiler::check_source(source)
+        .expect_err("expected a type error")
+        .to_string()
+}
+
+const OPCODE: &str = "enum Opcode {
+        Nop,
+        Load = 10,
+        Store,
+        Jump = 40,
+        Halt,
+    }";
+
+#[test]
+fn variant_reference_typechecks_to_its_enum() {
+    aura_compiler::check_source(&format!(
+        "{}
+         fn main() i32 {{
+             let op: Opcode = Opcode.Store;
+             let same: bool = op == Opcode.Store;
+             return 0;
+         }}",
+        OPCODE
+    ))
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn variant_reference_is_folded_to_an_immediate() {
+    let (object, _) = aura_compiler::compile_source(
+        &format!(
+            "{}
+             fn main() i32 {{ return Opcode.Jump as i32; }}",
+            OPCODE
+        ),
+        &CodegenOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    // mov rax, 40
+    assert!(
+        object
+            .text
+            .windows(7)
+            .any(|w| w == [0x48, 0xc7, 0xc0, 40, 0, 0, 0]),
+        "{:02x?}",
+        object.text
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn explicit_values_are_kept() {
+    for (variant, value) in [("Load", 10), ("Jump", 40)] {
+        let source = format!(
+            "{}
+             fn main() i32 {{ return Opcode.{} as i32; }}",
+            OPCODE, variant
+        );
+        assert_eq!(
+            common::exit_code(&format!("enum_{}", variant), &source),
+            Some(value),
+            "Opcode.{}",
+            variant
+        );
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn implicit_values_count_up_from_the_previous_variant() {
+    for (variant, value) in [("Nop", 0), ("Store", 11), ("Halt", 41)] {
+        let source = format!(
+            "{}
+             fn main() i32 {{ return Opcode.{} as i32; }}",
+            OPCODE, variant
+        );
+        assert_eq!(
+            common::exit_code(&format!("enum_{}", variant), &source),
+            Some(value),
+            "Opcode.{}",
+            variant
+        );
+    }
+}
+
+#[test]
+fn unknown_variant_is_an_error() {
+    let source = std::fs::read_to_string("tests/enum_unknown_variant_error.aura").unwrap();
+    let message = check_error(&source);
+    assert!(
+        message.contains("Enum Color has no variant Purple"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn bare_variant_name_points_at_the_qualified_form() {
+    let message = check_error(&format!(
+        "{}
+         fn main() i32 {{
+             let op: Opcode = Halt;
+             return 0;
+         }}",
+        OPCODE
+    ));
+    assert!(message.contains("write `Opcode.Halt`"), "{}", message);
+}
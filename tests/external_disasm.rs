@@ -0,0 +1,36 @@
+// Emitted code decoded by a system disassembler matches the instructions the
+// generator means to emit. Run with `cargo test --features external-disasm`;
+// the test is skipped when neither objdump nor ndisasm is installed.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+
+#[test]
+fn return_zero_decodes_to_the_expected_instructions() {
+    let (object, _) = aura_compiler::compile_source(
+        "fn zero() i32 { return 0; }
+         fn main() i32 { return zero(); }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    let symbol = object
+        .symbols
+        .iter()
+        .find(|s| s.name == "zero")
+        .expect("no symbol zero");
+    let start = symbol.offset as usize;
+    let text = &object.text[start..start + symbol.size as usize];
+
+    let Some(mnemonics) = common::external_mnemonics(text) else {
+        eprintln!("skipping: no objdump or ndisasm on PATH");
+        return;
+    };
+    // Prologue, rax = 0, epilogue
+    assert_eq!(
+        mnemonics,
+        ["push", "mov", "sub", "mov", "mov", "pop", "ret"],
+        "{:02x?}",
+        text
+    );
+}
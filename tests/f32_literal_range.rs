@@ -0,0 +1,44 @@
+// An `f32`-suffixed literal must fit in an f32: overflow is a type error,
+// and a value f32 cannot hold exactly compiles with a warning naming what it
+// rounds to. See tests/f32_literal_overflow_error.aura and
+// tests/f32_literal_rounding.aura.
+
+fn warnings(source: &str) -> Vec<String> {
+    aura_compiler::check_source(source)
+        .unwrap_or_else(|e| panic!("check failed: {}", e))
+        .iter()
+        .map(|w| w.to_string())
+        .collect()
+}
+
+#[test]
+fn decimal_overflow_is_rejected() {
+    let error = aura_compiler::check_source("fn main() i32 { let x: f32 = 3.5e38f32; return 0; }")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("Float literal 3.5e38 overflows f32"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn rounding_warns_with_the_rounded_value() {
+    let source = std::fs::read_to_string("tests/f32_literal_rounding.aura").unwrap();
+    let warnings = warnings(&source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("Float literal 1.0000000596046448e0 is not exactly representable as f32 and rounds to 1e0"),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn exact_and_unsuffixed_literals_are_quiet() {
+    assert!(
+        warnings("const A: f32 = 0.5f32; const B: f64 = 0.1; fn main() i32 { return 0; }")
+            .is_empty()
+    );
+}
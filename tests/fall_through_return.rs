@@ -0,0 +1,62 @@
+// A function with a return type must not reach the end of its body: every
+// path has to return, call a noreturn function or loop forever.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn branch_without_a_return_is_an_error() {
+    let error = check(
+        "fn pick(flag: bool) i32 {
+             if flag {
+                 return 1;
+             } else {
+                 let _other: i32 = 2;
+             }
+         }
+         fn main() i32 { return pick(true); }",
+    )
+    .expect_err("fall-through compiled");
+    assert!(
+        error.contains("Function 'pick' can reach the end of its body"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn if_without_else_falls_through() {
+    let error = check(
+        "fn pick(flag: bool) i32 {
+             if flag { return 1; }
+         }
+         fn main() i32 { return pick(true); }",
+    )
+    .expect_err("fall-through compiled");
+    assert!(error.contains("can reach the end of its body"), "{}", error);
+}
+
+#[test]
+fn every_branch_returning_is_accepted() {
+    check(
+        "fn pick(flag: bool) i32 {
+             if flag {
+                 return 1;
+             } else {
+                 return 2;
+             }
+         }
+         fn spin() i32 {
+             while true {}
+         }
+         fn main() i32 { return pick(true); }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn void_functions_may_fall_through() {
+    check("fn nothing() void { let _x: i32 = 1; } fn main() i32 { nothing(); return 0; }")
+        .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
@@ -0,0 +1,61 @@
+// Float comparisons go through ucomisd/ucomiss and pick their flags so a
+// NaN operand (unordered) makes ==, <, <=, > and >= false and != true. See
+// tests/float_nan_compare.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+const COMPARES: &str = "fn eq(a: f64, b: f64) -> bool { return a == b; }
+                        fn ne(a: f64, b: f64) -> bool { return a != b; }
+                        fn lt(a: f64, b: f64) -> bool { return a < b; }
+                        fn main() i32 { return 0; }";
+
+#[test]
+fn equality_also_requires_an_ordered_result() {
+    let object = compile(COMPARES);
+    // ucomisd xmm0, xmm1; sete al; setnp cl; and al, cl
+    let eq = function_text(&object, "eq");
+    assert!(
+        contains(
+            eq,
+            &[0x66, 0x0f, 0x2e, 0xc1, 0x0f, 0x94, 0xc0, 0x0f, 0x9b, 0xc1, 0x20, 0xc8]
+        ),
+        "{:02x?}",
+        eq
+    );
+    // setne al; setp cl; or al, cl
+    let ne = function_text(&object, "ne");
+    assert!(
+        contains(ne, &[0x0f, 0x95, 0xc0, 0x0f, 0x9a, 0xc1, 0x08, 0xc8]),
+        "{:02x?}",
+        ne
+    );
+}
+
+#[test]
+fn less_than_swaps_the_operands_to_use_above() {
+    let object = compile(COMPARES);
+    // ucomisd xmm1, xmm0; seta al
+    let lt = function_text(&object, "lt");
+    assert!(
+        contains(lt, &[0x66, 0x0f, 0x2e, 0xc8, 0x0f, 0x97, 0xc0]),
+        "{:02x?}",
+        lt
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn f32_nan_is_unordered_too() {
+    let source = "fn main() i32 {
+                      let zero: f32 = 0.0f32;
+                      let nan: f32 = zero / zero;
+                      let eq: bool = nan == nan;
+                      let ne: bool = nan != nan;
+                      let ge: bool = nan >= zero;
+                      let ordered: bool = zero <= zero;
+                      return eq as i32 * 8 + ne as i32 * 4 + ge as i32 * 2 + ordered as i32;
+                  }";
+    assert_eq!(common::exit_code("f32_nan", source), Some(5));
+}
@@ -0,0 +1,91 @@
+// Float literals travel through rax as their bits; a function returning a
+// float hands the result over in xmm0 (movq for f64, movd for f32) and the
+// caller moves it back into rax after the call.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+// movq xmm0, rax; mov rsp, rbp; pop rbp; ret
+const RETURN_F64: [u8; 10] = [0x66, 0x48, 0x0f, 0x6e, 0xc0, 0x48, 0x89, 0xec, 0x5d, 0xc3];
+
+#[test]
+fn f64_sum_is_returned_in_xmm0() {
+    let object = compile(
+        "fn five() -> f64 { return 2.0 + 3.0; }
+         fn main() -> i64 { return 0i64; }",
+    );
+    let five = function_text(&object, "five");
+    // mov rax, 2.0; push rax; mov rax, 3.0; mov rcx, rax; pop rax
+    assert!(
+        contains(five, &2.0f64.to_bits().to_le_bytes()),
+        "{:02x?}",
+        five
+    );
+    assert!(
+        contains(five, &3.0f64.to_bits().to_le_bytes()),
+        "{:02x?}",
+        five
+    );
+    // addsd xmm0, xmm1; movq rax, xmm0
+    assert!(
+        contains(
+            five,
+            &[0xf2, 0x0f, 0x58, 0xc1, 0x66, 0x48, 0x0f, 0x7e, 0xc0]
+        ),
+        "{:02x?}",
+        five
+    );
+    assert!(five.ends_with(&RETURN_F64), "{:02x?}", five);
+}
+
+#[test]
+fn f32_result_uses_movd() {
+    let object = compile(
+        "fn half(x: f32) -> f32 { return x / 2.0f32; }
+         fn main() -> i64 { return 0i64; }",
+    );
+    let half = function_text(&object, "half");
+    // divss xmm0, xmm1
+    assert!(contains(half, &[0xf3, 0x0f, 0x5e, 0xc1]), "{:02x?}", half);
+    // movd xmm0, eax; mov rsp, rbp; pop rbp; ret
+    assert!(
+        half.ends_with(&[0x66, 0x0f, 0x6e, 0xc0, 0x48, 0x89, 0xec, 0x5d, 0xc3]),
+        "{:02x?}",
+        half
+    );
+}
+
+#[test]
+fn caller_reads_float_results_from_xmm0() {
+    let object = compile(
+        "fn five() -> f64 { return 5.0; }
+         fn main() -> i64 {
+             if five() > 4.5 { return 1i64; }
+             return 0i64;
+         }",
+    );
+    let main = function_text(&object, "main");
+    let call = main
+        .iter()
+        .position(|&b| b == 0xe8)
+        .unwrap_or_else(|| panic!("no call in {:02x?}", main));
+    // call rel32; movq rax, xmm0
+    assert_eq!(&main[call + 5..call + 10], &[0x66, 0x48, 0x0f, 0x7e, 0xc0]);
+    // ucomisd: the comparison knows the call's result is a float
+    assert!(contains(main, &[0x66, 0x0f, 0x2e]), "{:02x?}", main);
+}
+
+#[test]
+fn integer_functions_do_not_touch_xmm0() {
+    let object = compile(
+        "fn two() -> i64 { return 2i64; }
+         fn main() -> i64 { return two(); }",
+    );
+    let two = function_text(&object, "two");
+    assert!(
+        !contains(two, &[0x66, 0x48, 0x0f, 0x6e, 0xc0]),
+        "{:02x?}",
+        two
+    );
+}
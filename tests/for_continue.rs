@@ -0,0 +1,55 @@
+// `continue` in a `for` loop jumps to the update step, so the loop variable
+// still advances, while `continue` in a `while` goes back to the condition.
+// See tests/for_continue.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+#[test]
+fn conditional_continue_skips_the_rest_of_the_body() {
+    // 1 + 3 + 5 + 7 + 9
+    let source = "fn main() i64 {
+                      let sum: i64 = 0i64;
+                      for (let i: i64 = 0i64; i < 10i64; i = i + 1i64;) {
+                          if i % 2i64 == 0i64 { continue; }
+                          sum = sum + i;
+                      }
+                      return sum;
+                  }";
+    assert_eq!(common::exit_code("for_continue_odd", source), Some(25));
+}
+
+#[test]
+fn continue_targets_the_innermost_loop() {
+    // The inner continue advances j; the outer loop still runs 4 times
+    let source = "fn main() i64 {
+                      let count: i64 = 0i64;
+                      for (let i: i64 = 0i64; i < 4i64; i = i + 1i64;) {
+                          for (let j: i64 = 0i64; j < 5i64; j = j + 1i64;) {
+                              if j < 3i64 { continue; }
+                              count = count + 1i64;
+                          }
+                          count = count + 10i64;
+                      }
+                      return count;
+                  }";
+    assert_eq!(common::exit_code("for_continue_nested", source), Some(48));
+}
+
+#[test]
+fn while_inside_for_continues_at_its_condition() {
+    let source = "fn main() i64 {
+                      let steps: i64 = 0i64;
+                      for (let i: i64 = 0i64; i < 3i64; i = i + 1i64;) {
+                          let n: i64 = 0i64;
+                          while n < 4i64 {
+                              n = n + 1i64;
+                              if n == 2i64 { continue; }
+                              steps = steps + 1i64;
+                          }
+                      }
+                      return steps;
+                  }";
+    assert_eq!(common::exit_code("for_continue_while", source), Some(9));
+}
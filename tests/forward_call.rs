@@ -0,0 +1,40 @@
+// Every function is in scope before any body is checked, so calls may go
+// forward and functions may be mutually recursive; a bad call is reported
+// at the callee's name. See tests/forward_call.aura,
+// tests/call_arity_error.aura and tests/call_arg_type_error.aura.
+
+mod common;
+
+fn fixture_error(name: &str) -> String {
+    let source = std::fs::read_to_string(format!("tests/{}.aura", name)).unwrap();
+    common::check(&source).unwrap_err()
+}
+
+#[test]
+fn arity_errors_point_at_the_call() {
+    let error = fixture_error("call_arity_error");
+    assert!(
+        error.contains("at 5:12: Wrong number of arguments: expected 1, got 2"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn argument_type_errors_point_at_the_call() {
+    let error = fixture_error("call_arg_type_error");
+    assert!(
+        error.contains("at 5:12: Argument 0 type mismatch: expected I64, got Bool"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn mutual_recursion_runs_both_ways() {
+    let source = "fn main() i64 { return is_even(7i64) * 10i64 + is_odd(7i64); }
+                  fn is_even(n: i64) i64 { if n == 0i64 { return 1i64; } return is_odd(n - 1i64); }
+                  fn is_odd(n: i64) i64 { if n == 0i64 { return 0i64; } return is_even(n - 1i64); }";
+    assert_eq!(common::exit_code("mutual_recursion", source), Some(1));
+}
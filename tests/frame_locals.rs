@@ -0,0 +1,86 @@
+// Locals live in their function's frame at rbp-relative slots. Each
+// function numbers its slots from scratch, and a name declared in a block
+// goes out of scope at the block's end, so an outer variable it shadowed is
+// the one read afterwards. Reading a block's local after the block is an
+// error. See tests/block_local_scope_error.aura.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+// mov [rbp-8], rax
+const STORE_FIRST_SLOT: [u8; 7] = [0x48, 0x89, 0x85, 0xf8, 0xff, 0xff, 0xff];
+
+#[test]
+fn each_function_starts_its_own_frame() {
+    let object = compile(&std::fs::read_to_string("tests/frame_locals.aura").unwrap());
+    for name in ["helper", "main"] {
+        let text = function_text(&object, name);
+        assert!(contains(text, &STORE_FIRST_SLOT), "{}: {:02x?}", name, text);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn same_named_locals_in_two_functions_do_not_collide() {
+    let source = std::fs::read_to_string("tests/frame_locals.aura").unwrap();
+    assert_eq!(common::exit_code("frame_locals", &source), Some(42));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn sibling_blocks_keep_separate_locals() {
+    let source = "fn main() i64 {
+                      let total: i64 = 0;
+                      { let a: i64 = 5; total = total + a; }
+                      { let a: i64 = 30; total = total + a; }
+                      return total;
+                  }";
+    assert_eq!(common::exit_code("sibling_blocks", source), Some(35));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn shadowing_ends_with_the_block() {
+    let source = "fn main() i64 {
+                      let x: i64 = 1;
+                      { let x: i64 = 50; x = x + 1; }
+                      return x;
+                  }";
+    assert_eq!(common::exit_code("shadowed_block", source), Some(1));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn shadowing_in_a_branch_leaves_the_outer_variable() {
+    let source = "fn main() i64 {
+                      let x: i64 = 2;
+                      if (x > 1) { let x: i64 = 90; x = x + 1; }
+                      let i: i64 = 0;
+                      while (i < 3) { let x: i64 = 70; i = i + 1; }
+                      return x;
+                  }";
+    assert_eq!(common::exit_code("shadowed_branch", source), Some(2));
+}
+
+#[test]
+fn block_locals_are_unknown_after_the_block() {
+    let bodies = [
+        "{ let inner: i32 = 1; }",
+        "if true { let inner: i32 = 1; } else { let other: i32 = 2; }",
+        "if false { } else { let inner: i32 = 2; }",
+        "while false { let inner: i32 = 1; }",
+        "for (let i: i32 = 0; i < 1; i = i + 1;) { let inner: i32 = i; }",
+        "let done = loop { let inner: i32 = 1; break inner; };",
+    ];
+    for body in bodies {
+        let source = format!("fn main() i32 {{ {} return inner; }}", body);
+        let error = check(&source).expect_err(&source);
+        assert!(
+            error.contains("Undefined variable: inner"),
+            "{}: {}",
+            body,
+            error
+        );
+    }
+}
@@ -0,0 +1,36 @@
+// A global `const` array initialized from a literal is emitted into .data
+// with its elements back to back at the element width. See
+// tests/global_array.aura.
+
+mod common;
+
+use common::{check, compile, symbol};
+
+#[test]
+fn elements_are_laid_out_in_order() {
+    let source = std::fs::read_to_string("tests/global_array.aura").unwrap();
+    let object = compile(&source);
+
+    let table = symbol(&object, "table");
+    let start = table.offset as usize;
+    assert_eq!(table.size, 12);
+    assert_eq!(
+        &object.data[start..start + 12],
+        &[1, 0, 0, 0, 2, 0, 0, 0, 0xfd, 0xff, 0xff, 0xff]
+    );
+
+    let masks = symbol(&object, "masks");
+    let start = masks.offset as usize;
+    assert_eq!(masks.size, 4);
+    assert_eq!(&object.data[start..start + 4], &[1, 2, 4, 8]);
+}
+
+#[test]
+fn elements_must_share_a_type() {
+    let error = check("const mixed: [2]i32 = [1, true]; fn main() i32 { return 0; }").unwrap_err();
+    assert!(
+        error.contains("Array literal element 1 has type Bool, expected I32"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,51 @@
+// Hex float literals (`0x1.8p3`) are a hex mantissa scaled by a power of
+// two, rounded once to the nearest f64; one that overflows or underflows is
+// a lex error. See tests/hex_float.aura.
+
+mod common;
+
+use aura_compiler::lexer::parse_hex_float;
+use common::{compile, symbol};
+
+#[test]
+fn mantissa_and_exponent_are_exact() {
+    assert_eq!(parse_hex_float("0x1.8p1"), Some(3.0));
+    assert_eq!(parse_hex_float("0x1p-3"), Some(0.125));
+    assert_eq!(parse_hex_float("0xAp0"), Some(10.0));
+    assert_eq!(parse_hex_float("0x1_0.8P+2"), Some(66.0));
+    assert_eq!(parse_hex_float("0x1.fffffffffffffp1023"), Some(f64::MAX));
+    assert_eq!(parse_hex_float("0x1p-1074"), Some(f64::from_bits(1)));
+}
+
+#[test]
+fn out_of_range_exponents_have_no_value() {
+    assert_eq!(parse_hex_float("0x1p1024"), None);
+    assert_eq!(parse_hex_float("0x1p-1100"), None);
+}
+
+#[test]
+fn constants_hold_the_exact_bits() {
+    let source = std::fs::read_to_string("tests/hex_float.aura").unwrap();
+    let object = compile(&source);
+    let data = |name: &str| {
+        let sym = symbol(&object, name);
+        let start = sym.offset as usize;
+        object.data[start..start + sym.size as usize].to_vec()
+    };
+    assert_eq!(data("THREE"), 3.0f64.to_le_bytes());
+    assert_eq!(data("EIGHTH"), 0.125f32.to_le_bytes());
+    assert_eq!(data("SCALED"), 66.0f64.to_le_bytes());
+}
+
+#[test]
+fn out_of_range_literal_is_reported_where_it_is() {
+    let error = aura_compiler::check_source("const HUGE: f64 = 0x1p2000;")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("1:19")
+            && error.contains("Hex float literal 0x1p2000 is out of range for f64"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,53 @@
+// An if-expression whose arms are both literals or variables is evaluated
+// without a branch, picking the else value with cmove; any other arm gets a
+// conditional jump. See tests/if_expr_cmov.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+// test rcx, rcx; cmove rax, rdx
+const CMOVE: [u8; 7] = [0x48, 0x85, 0xc9, 0x48, 0x0f, 0x44, 0xc2];
+// jz rel32
+const JZ: [u8; 2] = [0x0f, 0x84];
+
+#[test]
+fn simple_arms_use_cmove() {
+    let object = compile(
+        "fn pick(flag: bool) i32 {
+             let v: i32 = if flag { 10 } else { 20 };
+             return v;
+         }
+         fn main() i32 { return pick(true); }",
+    );
+    let pick = function_text(&object, "pick");
+    assert!(contains(pick, &CMOVE), "{:02x?}", pick);
+    assert!(!contains(pick, &JZ), "{:02x?}", pick);
+}
+
+#[test]
+fn other_arms_branch() {
+    let object = compile(
+        "fn bump(flag: bool, x: i32) i32 {
+             let v: i32 = if flag { x + 1 } else { 0 };
+             return v;
+         }
+         fn main() i32 { return bump(true, 1); }",
+    );
+    let bump = function_text(&object, "bump");
+    assert!(contains(bump, &JZ), "{:02x?}", bump);
+    assert!(!contains(bump, &CMOVE), "{:02x?}", bump);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn both_forms_pick_the_right_arm() {
+    let source = std::fs::read_to_string("tests/if_expr_cmov.aura").unwrap();
+    assert_eq!(common::exit_code("if_expr_cmov", &source), Some(21));
+    let source = "fn pick(flag: bool) i32 {
+             let v: i32 = if flag { 10 } else { 20 };
+             return v;
+         }
+         fn main() i32 { return pick(false); }";
+    assert_eq!(common::exit_code("if_expr_cmov_else", source), Some(20));
+}
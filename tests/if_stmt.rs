@@ -0,0 +1,36 @@
+// An if statement tests its condition and `je rel32` skips to the else
+// branch; the then branch ends with `jmp rel32` over it. A return inside a
+// branch leaves the function from there, so the jump after it is dead but
+// still emitted.
+
+mod common;
+
+use common::{compile, function_text};
+
+const SOURCE: &str = "fn pick(x: bool) i64 { if (x) { return 1; } else { return 2; } }
+                      fn main() i64 { return pick(true) * 10 + pick(false); }";
+
+#[test]
+fn branches_are_laid_out_then_before_else() {
+    let object = compile(SOURCE);
+    let text = function_text(&object, "pick");
+    // From the condition load to the end of the function
+    let body = [
+        0x48, 0x8b, 0x85, 0xf8, 0xff, 0xff, 0xff, // mov rax, [rbp-8]
+        0x48, 0x85, 0xc0, // test rax, rax
+        0x0f, 0x84, 0x11, 0x00, 0x00, 0x00, // je else
+        0x48, 0xc7, 0xc0, 0x01, 0x00, 0x00, 0x00, // mov rax, 1
+        0x48, 0x89, 0xec, 0x5d, 0xc3, // mov rsp, rbp; pop rbp; ret
+        0xe9, 0x0c, 0x00, 0x00, 0x00, // jmp end
+        0x48, 0xc7, 0xc0, 0x02, 0x00, 0x00, 0x00, // else: mov rax, 2
+        0x48, 0x89, 0xec, 0x5d, 0xc3, // mov rsp, rbp; pop rbp; ret
+        0x48, 0x31, 0xc0, 0x48, 0x89, 0xec, 0x5d, 0xc3, // end: xor rax, rax; ret
+    ];
+    assert!(text.ends_with(&body), "{:02x?}", text);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn each_branch_returns_its_own_value() {
+    assert_eq!(common::exit_code("if_stmt", SOURCE), Some(12));
+}
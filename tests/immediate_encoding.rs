@@ -0,0 +1,71 @@
+// mov rax, imm picks the sign-extended imm32 form when the value fits and
+// the 10-byte movabs otherwise. Narrower literals are written to the whole
+// of rax, never just al or ax.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::{compile, contains, function_text};
+
+// Text of `value`, which returns `literal`; main is the entry point, so its
+// exit sequence stays out of the slice
+fn value_text(literal: &str) -> Vec<u8> {
+    let source = format!(
+        "fn value() i64 {{ return {}; }}\nfn main() i64 {{ return value(); }}",
+        literal
+    );
+    let (object, _) = aura_compiler::compile_source(&source, &CodegenOptions::default())
+        .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    let symbol = object.symbols.iter().find(|s| s.name == "value").unwrap();
+    let start = symbol.offset as usize;
+    object.text[start..start + symbol.size as usize].to_vec()
+}
+
+#[test]
+fn small_immediates_use_imm32() {
+    let text = value_text("5i64");
+    assert!(
+        contains(&text, &[0x48, 0xc7, 0xc0, 5, 0, 0, 0]),
+        "{:02x?}",
+        text
+    );
+    assert!(!contains(&text, &[0x48, 0xb8]), "{:02x?}", text);
+}
+
+#[test]
+fn negative_immediates_use_imm32() {
+    let text = value_text("-1i64");
+    assert!(!contains(&text, &[0x48, 0xb8]), "{:02x?}", text);
+}
+
+#[test]
+fn wide_immediates_use_imm64() {
+    let text = value_text("0x1_0000_0000i64");
+    assert!(
+        contains(&text, &[0x48, 0xb8, 0, 0, 0, 0, 1, 0, 0, 0]),
+        "{:02x?}",
+        text
+    );
+    assert!(!contains(&text, &[0x48, 0xc7, 0xc0]), "{:02x?}", text);
+}
+
+#[test]
+fn narrow_immediates_fill_rax() {
+    let object = compile(
+        "fn byte() u8 { return 200u8; }
+         fn word() i16 { return 300i16; }
+         fn main() i32 { return byte() as i32 + word() as i32; }",
+    );
+    // mov eax, 200 zero-extends; mov al, 200 would keep bits 8..63
+    let byte = function_text(&object, "byte");
+    assert!(contains(byte, &[0xb8, 200, 0, 0, 0]), "{:02x?}", byte);
+    assert!(!contains(byte, &[0xb0, 200]), "{:02x?}", byte);
+    // signed values take the sign-extended mov rax, imm32
+    let word = function_text(&object, "word");
+    assert!(
+        contains(word, &[0x48, 0xc7, 0xc0, 0x2c, 0x01, 0, 0]),
+        "{:02x?}",
+        word
+    );
+    assert!(!contains(word, &[0x66, 0xb8]), "{:02x?}", word);
+}
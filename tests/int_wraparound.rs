@@ -0,0 +1,48 @@
+// Add, sub and mul run on all of rax, so a result whose type is narrower
+// than 64 bits is wrapped back to that width before anything reads it:
+// masked when unsigned, sign-extended when signed.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+#[test]
+fn u8_results_wrap_at_256() {
+    let source = "fn main() i32 {
+                      let max: u8 = 255u8;
+                      let sum: u64 = (max + 1u8) as u64;
+                      let product: u64 = (16u8 * max) as u64;
+                      if sum == 0u64 {
+                          return product as i32;
+                      }
+                      return 1;
+                  }";
+    // 16 * 255 = 4080 = 0xff0
+    assert_eq!(common::exit_code("wrap_u8", source), Some(0xf0));
+}
+
+#[test]
+fn u32_results_wrap_at_2_to_the_32() {
+    let source = "fn main() i32 {
+                      let zero: u32 = 0u32;
+                      let below: i64 = (zero - 1u32) as i64;
+                      if below == 4294967295 {
+                          return 42;
+                      }
+                      return 1;
+                  }";
+    assert_eq!(common::exit_code("wrap_u32", source), Some(42));
+}
+
+#[test]
+fn signed_results_wrap_to_negative() {
+    let source = "fn main() i32 {
+                      let max: i8 = 127i8;
+                      let wrapped: i64 = (max + 1i8) as i64;
+                      if wrapped == 0 - 128 {
+                          return 9;
+                      }
+                      return 1;
+                  }";
+    assert_eq!(common::exit_code("wrap_i8", source), Some(9));
+}
@@ -0,0 +1,70 @@
+// `isize` is signed 64-bit in codegen, so an isize literal shifts right
+// arithmetically and compares as signed. Casts widening to 64 bits
+// sign-extend a signed source and zero-extend an unsigned one. See
+// tests/isize_sign.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+fn source() -> String {
+    std::fs::read_to_string("tests/isize_sign.aura").unwrap()
+}
+
+#[test]
+fn isize_shift_is_arithmetic() {
+    let object = compile(&source());
+    let main = function_text(&object, "main");
+    // sar rax, cl
+    assert!(contains(main, &[0x48, 0xd3, 0xf8]), "{:02x?}", main);
+    // shr rax, cl
+    assert!(!contains(main, &[0x48, 0xd3, 0xe8]), "{:02x?}", main);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn negative_isize_round_trips() {
+    assert_eq!(common::exit_code("isize_sign", &source()), Some(1));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn negative_isize_compares_below_zero() {
+    let source = "fn main() i32 {
+             if -1isize < 0isize { return 7; }
+             return 3;
+         }";
+    assert_eq!(common::exit_code("isize_compare", source), Some(7));
+}
+
+#[test]
+fn widening_casts_extend_by_the_source_sign() {
+    let object = compile(
+        "fn signed(x: i32) i64 { return x as i64; }
+         fn unsigned(x: u32) u64 { return x as u64; }
+         fn main() i32 { return (signed(1) + unsigned(2u32) as i64) as i32; }",
+    );
+    // movsxd rax, eax
+    let signed = function_text(&object, "signed");
+    assert!(contains(signed, &[0x48, 0x63, 0xc0]), "{:02x?}", signed);
+    // mov eax, eax
+    let unsigned = function_text(&object, "unsigned");
+    assert!(contains(unsigned, &[0x89, 0xc0]), "{:02x?}", unsigned);
+    assert!(
+        !contains(unsigned, &[0x48, 0x63, 0xc0]),
+        "{:02x?}",
+        unsigned
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn widened_values_keep_their_meaning() {
+    let source = "fn main() i32 {
+             let minus_one: i64 = (-1i32) as i64;
+             let max: u64 = (0xFFFFFFFFu32) as u64;
+             if minus_one == -1 && max == 4294967295u64 { return 5; }
+             return 3;
+         }";
+    assert_eq!(common::exit_code("widening_casts", source), Some(5));
+}
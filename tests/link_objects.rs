@@ -0,0 +1,60 @@
+// codegen::link concatenates the text and data of several objects, moves
+// their symbols and `.data` addresses, turns a call through an `extern fn`
+// into a direct call to the unit defining it, and rejects names defined
+// twice. See tests/link/main.aura and tests/link/answer.aura.
+
+mod common;
+
+use aura_compiler::codegen::{aura_object_from_bytes, link, AuraObject};
+use common::{compile, symbol};
+
+fn unit(name: &str) -> AuraObject {
+    compile(&std::fs::read_to_string(format!("tests/link/{}.aura", name)).unwrap())
+}
+
+#[test]
+fn second_unit_is_placed_after_the_first() {
+    let main = unit("main");
+    let (text_len, data_len, entry_point) = (main.text.len(), main.data.len(), main.entry_point);
+    let linked = link(&[main, unit("answer")]).expect("link");
+    assert!(symbol(&linked, "answer").offset >= text_len as u64);
+    assert!(symbol(&linked, "answer::calls").offset >= data_len as u64);
+    assert_eq!(linked.entry_point, entry_point);
+}
+
+#[test]
+fn duplicate_definitions_are_rejected() {
+    let error = link(&[unit("answer"), unit("answer")]).unwrap_err();
+    assert!(
+        error
+            .message
+            .contains("Duplicate symbol 'OFFSET' defined in objects 0 and 1"),
+        "{}",
+        error.message
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn cross_unit_call_runs() {
+    let linked = link(&[unit("main"), unit("answer")]).expect("link");
+    let status = common::run("link_objects", &linked).status;
+    assert_eq!(status.code(), Some(42));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn build_links_every_source_file() {
+    let output = std::env::temp_dir().join(format!("aura_link_build_{}.aura", std::process::id()));
+    aura_compiler::compile_files_with_options(
+        &["tests/link/main.aura", "tests/link/answer.aura"],
+        output.to_str(),
+        &Default::default(),
+    )
+    .expect("build");
+    let bytes = std::fs::read(&output).unwrap();
+    let _ = std::fs::remove_file(&output);
+    let linked = aura_object_from_bytes(&bytes).unwrap();
+    let status = common::run("link_build", &linked).status;
+    assert_eq!(status.code(), Some(42));
+}
@@ -0,0 +1,125 @@
+// An unsuffixed integer literal adopts the integer type of the binding,
+// parameter or assignment target it flows into when it fits, instead of
+// having to match its i32 default exactly; so does a `break` value of a
+// `loop` expression whose type is known.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn literals_adopt_annotated_integer_types() {
+    check(
+        "const LIMIT: u64 = 4000000000;
+         var mask: u16 = 65535;
+         fn take(a: i64, b: u8) i64 { return a + b as i64; }
+         fn main() i64 {
+             let x: i64 = 5;
+             let small: i8 = -128;
+             const c: usize = 7;
+             return take(x, 255) + small as i64 + c as i64 + LIMIT as i64;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn assignment_adopts_the_target_type() {
+    check(
+        "struct P { x: i64; y: u8; }
+         fn main() i64 {
+             let x: i64 = 0;
+             x = 2;
+             x = -3;
+             let p: P = zeroed(P);
+             p.y = 200;
+             let q: *i64 = &p.x;
+             *q = 9;
+             return x + p.x;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn loop_break_values_adopt_the_binding_type() {
+    check(
+        "fn main() i64 {
+             let x: u8 = loop { break 5; };
+             let flag: bool = true;
+             let y: i64 = loop {
+                 if flag { break 200; }
+                 break -1;
+             };
+             return x as i64 + y;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+    let error = check("fn main() i64 { let x: u8 = loop { break 300; }; return 0; }")
+        .expect_err("break 300 into a u8 passed");
+    assert!(
+        error.contains("Integer literal 300 does not fit"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn out_of_range_literal_is_an_error() {
+    for (source, literal) in [
+        ("fn main() i64 { let b: u8 = 256; return 0; }", "256"),
+        ("fn main() i64 { let b: i8 = -129; return 0; }", "-129"),
+        ("fn main() i64 { let u: u32 = -1; return 0; }", "-1"),
+        ("fn main() i64 { let b: u8 = 0; b = 300; return 0; }", "300"),
+    ] {
+        let error = check(source).expect_err(source);
+        assert!(
+            error.contains(&format!("Integer literal {} does not fit", literal)),
+            "{}: {}",
+            source,
+            error
+        );
+    }
+}
+
+#[test]
+fn incompatible_types_are_still_rejected() {
+    for source in [
+        "fn main() i64 { let flag: bool = 1; return 0; }",
+        "fn main() i64 { let x: i64 = 1; let flag: bool = x; return 0; }",
+        "fn main() i64 { let flag: bool = true; flag = 0; return 0; }",
+        "fn main() i64 { let x: i32 = 1; let y: i64 = x; return y; }",
+    ] {
+        assert!(check(source).is_err(), "compiled: {}", source);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn adopted_literals_store_full_width() {
+    let object = common::compile(
+        "struct P { x: i64; y: u8; }
+         fn main() i64 {
+             let x: i64 = 0;
+             x = 2;
+             let p: P = zeroed(P);
+             p.y = 200;
+             return x + p.y as i64;
+         }",
+    );
+    let status = common::run("literal_assign", &object).status;
+    assert_eq!(status.code(), Some(202));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn loop_break_literal_is_stored_at_the_binding_width() {
+    let status = common::exit_code(
+        "literal_break",
+        "fn main() i32 {
+             let x: u8 = loop { break 250; };
+             return x as i32 + 1;
+         }",
+    );
+    assert_eq!(status, Some(251));
+}
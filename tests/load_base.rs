@@ -0,0 +1,53 @@
+// `load_base` is the address data references are baked against: moving it
+// moves every Absolute64 immediate by the same amount, and a binary built
+// with a custom base records it. See tests/load_base.aura.
+
+mod common;
+
+use aura_compiler::codegen::{
+    AuraObject, CodegenOptions, RelocationKind, RelocationSection, DEFAULT_LOAD_BASE,
+};
+
+fn compile(load_base: u64) -> AuraObject {
+    let source = std::fs::read_to_string("tests/load_base.aura").unwrap();
+    let options = CodegenOptions {
+        load_base,
+        ..CodegenOptions::default()
+    };
+    aura_compiler::compile_source(&source, &options)
+        .unwrap_or_else(|e| panic!("compile failed: {}", e))
+        .0
+}
+
+// The immediates of the text relocations against .data
+fn data_addresses(object: &AuraObject) -> Vec<u64> {
+    object
+        .relocations
+        .iter()
+        .filter(|r| r.section == RelocationSection::Text && r.symbol == ".data")
+        .map(|r| {
+            assert_eq!(r.kind, RelocationKind::Absolute64);
+            u64::from_le_bytes(object.text[r.offset..r.offset + 8].try_into().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn data_addresses_move_with_the_load_base() {
+    let default = compile(DEFAULT_LOAD_BASE);
+    let moved = compile(0x40_0000);
+    let before = data_addresses(&default);
+    let after = data_addresses(&moved);
+    assert!(!before.is_empty(), "no data relocations");
+    for (before, after) in before.iter().zip(&after) {
+        assert_eq!(before - DEFAULT_LOAD_BASE, after - 0x40_0000);
+    }
+}
+
+#[test]
+fn binary_records_a_custom_load_base() {
+    let object = compile(0x40_0000);
+    let bytes = aura_compiler::codegen::aura_object_to_bytes(&object).unwrap();
+    let read = aura_compiler::codegen::aura_object_from_bytes(&bytes).unwrap();
+    assert_eq!(read.load_base, 0x40_0000);
+}
@@ -0,0 +1,140 @@
+// A local `let a = [x, y, z];` stores its elements contiguously in the
+// frame, and `a[i]` loads from base + i * sizeof(element), with a narrow i
+// widened to 64 bits first. A constant index past the end of an array of
+// known length is a type error.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+#[test]
+fn constant_index_past_the_end_is_an_error() {
+    let error = check("fn main() i64 { let a = [1, 2, 3]; return a[3] as i64; }")
+        .expect_err("out-of-bounds index compiled");
+    assert!(
+        error.contains("Index 3 out of bounds for array of length 3"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn literal_length_must_match_the_annotation() {
+    let error = check("fn main() i64 { let a: [4]i64 = [1, 2, 3]; return a[0]; }")
+        .expect_err("a 3-element literal initialized a [4]i64");
+    assert!(error.contains("Type mismatch in let"), "{}", error);
+}
+
+#[test]
+fn elements_adopt_the_annotated_element_type() {
+    check("fn main() i64 { let a: [3]u8 = [1, 2, 255]; return a[2] as i64; }")
+        .unwrap_or_else(|e| panic!("check failed: {}", e));
+    let error = check("fn main() i64 { let a: [2]u8 = [1, 256]; return 0; }")
+        .expect_err("256 was stored in a u8 element");
+    assert!(
+        error.contains("Integer literal 256 does not fit"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn mixed_element_types_are_an_error() {
+    let error = check("fn main() i64 { let a = [1i64, true]; return 0; }")
+        .expect_err("an i64 and a bool shared an array");
+    assert!(error.contains("Array literal element 1"), "{}", error);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn reading_the_middle_element() {
+    let code = common::exit_code(
+        "array_lit_middle",
+        "fn main() i64 {
+             let a: [3]i64 = [10, 20, 30];
+             return a[1];
+         }",
+    );
+    assert_eq!(code, Some(20));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn narrow_elements_are_packed() {
+    // Each element is stored at its own width: an 8-byte store of b[2]
+    // would run past the end of b and into a, which sits right above it
+    let code = common::exit_code(
+        "array_lit_packed",
+        "fn main() i64 {
+             let a = [1, -2, 3];
+             let b: [3]u16 = [7, 300, 340];
+             let i: i64 = 2;
+             return (a[0] + a[1] + a[i]) as i64 + (b[2] - b[1] + b[0]) as i64;
+         }",
+    );
+    assert_eq!(code, Some(2 + 47));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn nested_literals_fill_rows_in_order() {
+    let code = common::exit_code(
+        "array_lit_nested",
+        "fn main() i64 {
+             let m: [2][3]u8 = [[1, 2, 3], [4, 5, 6]];
+             return m[1][2] as i64 * 10 + m[0][1] as i64;
+         }",
+    );
+    assert_eq!(code, Some(62));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn computed_elements() {
+    let code = common::exit_code(
+        "array_lit_computed",
+        "fn twice(n: i64) i64 { return n * 2; }
+         fn main() i64 {
+             let n: i64 = 7;
+             let a: [3]i64 = [n, twice(n), n + 1];
+             return a[0] + a[1] + a[2];
+         }",
+    );
+    assert_eq!(code, Some(7 + 14 + 8));
+}
+
+#[test]
+fn narrow_indices_are_extended_before_scaling() {
+    let object = compile(
+        "fn main() i64 {
+             let a: [3]i64 = [10, 20, 30];
+             return a[2u8];
+         }",
+    );
+    // movzx rax, al; imul rax, rax, 8
+    let text = function_text(&object, "main");
+    assert!(
+        contains(
+            text,
+            &[0x48, 0x0f, 0xb6, 0xc0, 0x48, 0x69, 0xc0, 8, 0, 0, 0]
+        ),
+        "{:02x?}",
+        text
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn narrow_index_after_a_large_value() {
+    // `big` is still in rax when 2u8 is loaded
+    let code = common::exit_code(
+        "array_lit_narrow_index",
+        "fn main() i64 {
+             let big: u64 = 81985529216486895u64;
+             let a: [3]i64 = [10, 20, 30];
+             let copy: u64 = big;
+             return a[2u8] + (copy - big) as i64;
+         }",
+    );
+    assert_eq!(code, Some(30));
+}
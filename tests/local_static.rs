@@ -0,0 +1,54 @@
+// A `static` in a function body has one `.data` slot, symbol
+// `function::name`, that keeps its value across calls, while its name is
+// only in scope in that function. See tests/local_static.aura.
+
+mod common;
+
+use common::{check, compile, symbol};
+
+#[test]
+fn slot_holds_the_folded_initializer() {
+    let object = compile(
+        "fn tick() i64 { static count: i64 = 6i64 * 7i64; count = count + 1i64; return count; }
+         fn main() i32 { return 0; }",
+    );
+    let count = symbol(&object, "tick::count");
+    let start = count.offset as usize;
+    assert_eq!(count.size, 8);
+    assert_eq!(object.data[start..start + 8], 42i64.to_le_bytes());
+}
+
+#[test]
+fn name_is_scoped_to_its_function() {
+    let error = check(
+        "fn tick() i64 { static count: i64 = 0i64; return count; }
+         fn main() i32 { return count as i32; }",
+    )
+    .unwrap_err();
+    assert!(error.contains("Undefined variable: count"), "{}", error);
+}
+
+#[test]
+fn const_fns_cannot_have_statics() {
+    let error = check(
+        "const fn f() i64 { static n: i64 = 1i64; return n; }
+         const N: i64 = f();
+         fn main() i32 { return 0; }",
+    )
+    .unwrap_err();
+    assert!(error.contains("static variables"), "{}", error);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn same_names_in_two_functions_are_separate() {
+    let source = "fn a() i64 { static n: i64; n = n + 1i64; return n; }
+                  fn b() i64 { static n: i64 = 10i64; n = n + 10i64; return n; }
+                  fn main() i64 {
+                      a();
+                      b();
+                      a();
+                      return a() + b();
+                  }";
+    assert_eq!(common::exit_code("local_static_scopes", source), Some(33));
+}
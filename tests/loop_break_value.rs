@@ -0,0 +1,56 @@
+// `loop` is an expression whose value is its `break` value: every break in
+// the loop must agree on the type, and codegen moves the value into rax
+// before jumping out. See tests/loop_break_value.aura and its error
+// fixtures.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn a_bare_break_disagrees_with_a_valued_one() {
+    let error = check(
+        "fn main() i32 {
+             let flag: bool = true;
+             let x = loop {
+                 if flag { break 1; }
+                 break;
+             };
+             return 0;
+         }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains("Loop breaks with different types: I32 and Void"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn the_break_that_runs_gives_the_value() {
+    let source = "fn pick(n: i32) -> i32 {
+                      let i: i32 = 0;
+                      return loop {
+                          if i == n { break i * 10; }
+                          if i > 3 { break 99; }
+                          i = i + 1;
+                      };
+                  }
+                  fn main() i32 { return pick(2) + pick(8); }";
+    assert_eq!(common::exit_code("loop_break_pick", source), Some(119));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn inner_breaks_leave_only_the_inner_loop() {
+    let source = "fn main() i32 {
+                      let x = 100 + loop {
+                          let inner = loop { break 5; };
+                          break inner * 2;
+                      };
+                      return x;
+                  }";
+    assert_eq!(common::exit_code("loop_break_nested", source), Some(110));
+}
@@ -0,0 +1,80 @@
+// `break` and `continue` need an enclosing loop to jump to; outside one they
+// are type errors, reported at the keyword, rather than statements code
+// generation has to drop.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn top_level_break_is_an_error() {
+    let error = check("fn main() i64 { break; return 0; }").expect_err("stray break compiled");
+    assert!(
+        error.contains("at 1:17: break outside of loop"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn top_level_continue_is_an_error() {
+    let error = check("fn main() i64 { if true { continue; } return 0; }")
+        .expect_err("stray continue compiled");
+    assert!(
+        error.contains("at 1:27: continue outside of loop"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn break_after_a_loop_is_an_error() {
+    let error = check(
+        "fn main() i64 {
+             while false { break; }
+             break;
+             return 0;
+         }",
+    )
+    .expect_err("break after the loop compiled");
+    assert!(
+        error.contains("at 3:14: break outside of loop"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn continue_in_a_nested_loop_is_allowed() {
+    check(
+        "fn main() i64 {
+             let total: i64 = 0;
+             for (let i: i64 = 0; i < 3; i = i + 1;) {
+                 let j: i64 = 0;
+                 while j < 3 {
+                     j = j + 1;
+                     if j == 2 { continue; }
+                     total = total + 1;
+                 }
+             }
+             return total;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn break_in_an_if_inside_a_loop_is_allowed() {
+    check(
+        "fn main() i64 {
+             let n: i64 = 0;
+             while true {
+                 n = n + 1;
+                 if n == 5 { break; }
+             }
+             let m = loop { if n > 0 { break n; } };
+             return m;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
@@ -0,0 +1,55 @@
+// `value.f(args)` is `f(&value, args)` only when f's first parameter points
+// at the struct type of `value`; the receiver may be a local, a field or an
+// element, and a function taking some other struct is not a method of it.
+
+mod common;
+
+use common::check;
+
+const SHAPES: &str = "struct Point { x: i32; y: i32; }
+     struct Size { w: i32; h: i32; }
+     struct Box2 { origin: Point; size: Size; }
+     fn translate(p: *Point, dx: i32, dy: i32) void {
+         p->x = p->x + dx;
+         p->y = p->y + dy;
+     }
+     fn grow(s: *Size, by: i32) void { s->w = s->w + by; }";
+
+#[test]
+fn function_for_another_struct_is_not_a_method() {
+    let message = check(&format!(
+        "{}
+         fn main() i32 {{
+             let p: Point = zeroed(Point);
+             p.grow(1);
+             return 0;
+         }}",
+        SHAPES
+    ))
+    .expect_err("grow was called on a Point");
+    assert!(
+        message.contains("Struct Point has no field grow"),
+        "{}",
+        message
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn receivers_are_locals_fields_and_elements() {
+    let source = format!(
+        "{}
+         fn main() i32 {{
+             let p: Point = zeroed(Point);
+             p.translate(1, 2);
+             let b: Box2 = zeroed(Box2);
+             b.origin.translate(10, 0);
+             b.size.grow(20);
+             let ps: [2]Point = zeroed([2]Point);
+             ps[1].translate(0, 4);
+             return p.x + p.y + b.origin.x + b.size.w + ps[1].y;
+         }}",
+        SHAPES
+    );
+    assert_eq!(common::exit_code("method_call_sugar", &source), Some(37));
+}
@@ -0,0 +1,43 @@
+// Calling a `@must_use` function as a bare statement warns at the call;
+// casting it to void, using the result, or calling an unmarked function
+// does not. See tests/must_use_dropped.aura.
+
+fn warnings(source: &str) -> Vec<String> {
+    aura_compiler::check_source(source)
+        .unwrap_or_else(|e| panic!("check failed: {}", e))
+        .iter()
+        .map(|w| w.to_string())
+        .collect()
+}
+
+#[test]
+fn dropped_result_warns_at_the_call() {
+    let source = std::fs::read_to_string("tests/must_use_dropped.aura").unwrap();
+    let warnings = warnings(&source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("at 9:5: ")
+            && warnings[0]
+                .contains("Result of 'checked_len' is ignored, but it is marked @must_use"),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn unmarked_functions_may_be_dropped() {
+    let source = "fn len() usize { return 4usize; }
+                  fn main() i32 { len(); return 0; }";
+    assert!(warnings(source).is_empty());
+}
+
+#[test]
+fn results_used_in_expressions_are_kept() {
+    let source = "@must_use
+                  fn len() usize { return 4usize; }
+                  fn main() i32 {
+                      if len() > 2usize { return 1; }
+                      return len() as i32;
+                  }";
+    assert!(warnings(source).is_empty());
+}
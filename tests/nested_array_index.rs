@@ -0,0 +1,53 @@
+// `a[i][j]` on a nested array steps by the inner array's size at the outer
+// level and by the element size at the inner one. See
+// tests/nested_array_index.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+#[test]
+fn reads_m_1_2_of_a_2x3_matrix() {
+    let source = "fn main() i32 {
+                      let m: [2][3]i32 = [[1, 2, 3], [4, 5, 6]];
+                      return m[1][2];
+                  }";
+    assert_eq!(common::exit_code("matrix_1_2", source), Some(6));
+}
+
+#[test]
+fn rows_do_not_overlap() {
+    // With a stride of 12, m[0][2] and m[1][0] are distinct elements
+    let source = "fn main() i32 {
+                      let m: [2][3]i32;
+                      let i: usize = 1usize;
+                      let j: usize = 2usize;
+                      m[1][0] = 40;
+                      m[0][j] = 1;
+                      m[i][j] = 2;
+                      return m[i][0] + m[0][2] + m[1][2] - 1;
+                  }";
+    assert_eq!(common::exit_code("matrix_rows", source), Some(42));
+}
+
+#[test]
+fn three_levels_multiply_the_strides() {
+    // c[1][0][1] is at 1 * 4 + 0 * 2 + 1 = byte 5
+    let source = "fn main() i32 {
+                      let c: [2][2][2]u8 = [[[0u8, 1u8], [2u8, 3u8]], [[4u8, 5u8], [6u8, 7u8]]];
+                      return c[1][0][1] as i32 * 10 + c[0][1][1] as i32;
+                  }";
+    assert_eq!(common::exit_code("cube", source), Some(53));
+}
+
+#[test]
+fn narrow_literal_indices_ignore_what_rax_held() {
+    // `big` is still in rax when the indices are loaded
+    let source = "fn main() i32 {
+                      let big: u64 = 81985529216486895u64;
+                      let m: [2][3]i32 = [[1, 2, 3], [4, 5, 6]];
+                      let copy: u64 = big;
+                      return m[1u8][2u16] + (copy - big) as i32;
+                  }";
+    assert_eq!(common::exit_code("matrix_narrow_index", source), Some(6));
+}
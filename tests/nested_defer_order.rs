@@ -0,0 +1,33 @@
+// Leaving a function unwinds every open scope innermost first, each in
+// reverse registration order; a scope that already closed ran its defers
+// then and does not run them again. See tests/nested_defer_order.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+use common::compile;
+
+#[test]
+fn return_unwinds_blocks_then_the_function() {
+    let source = std::fs::read_to_string("tests/nested_defer_order.aura").unwrap();
+    let output = common::run("nested_defer_order", &compile(&source));
+    assert_eq!(output.stdout, b"nested\nblock 2\nblock 1\nfn 2\nfn 1\n");
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn closed_blocks_do_not_run_again() {
+    let source = "fn main() i32 {
+                      defer syscall.write(1, \"fn\\n\", 3);
+                      {
+                          defer syscall.write(1, \"a\\n\", 2);
+                      }
+                      {
+                          defer syscall.write(1, \"b\\n\", 2);
+                          return 0;
+                      }
+                  }";
+    let output = common::run("closed_block_defers", &compile(source));
+    assert_eq!(output.stdout, b"a\nb\nfn\n");
+}
@@ -0,0 +1,89 @@
+// offsetof(T, field) in a function body folds to the field's byte offset in
+// the struct's layout, the one sizeof uses, as a mov rax, imm32. A dotted
+// path (`offsetof(T, a.b)`) adds up the offset of each step.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::{compile, function_text};
+
+const SOURCE: &str = "struct Record { tag: u8; id: i64; flags: u16; }
+                      fn tag_offset() -> usize { return offsetof(Record, tag); }
+                      fn id_offset() -> usize { return offsetof(Record, id); }
+                      fn flags_offset() -> usize { return offsetof(Record, flags); }
+                      fn main() i32 { return 0; }";
+
+fn loads(text: &[u8], value: u32) -> bool {
+    // mov rax, imm32
+    let mut mov = vec![0x48, 0xc7, 0xc0];
+    mov.extend_from_slice(&value.to_le_bytes());
+    text.windows(mov.len()).any(|w| w == mov.as_slice())
+}
+
+#[test]
+fn first_field_is_at_offset_zero() {
+    let object = compile(SOURCE);
+    let text = function_text(&object, "tag_offset");
+    assert!(loads(text, 0), "{:02x?}", text);
+}
+
+#[test]
+fn field_after_padding_is_aligned() {
+    let object = compile(SOURCE);
+    // tag takes one byte, then seven bytes of padding align id to 8
+    let text = function_text(&object, "id_offset");
+    assert!(loads(text, 8), "{:02x?}", text);
+    let text = function_text(&object, "flags_offset");
+    assert!(loads(text, 16), "{:02x?}", text);
+}
+
+#[test]
+fn unknown_field_is_a_type_error() {
+    let error = aura_compiler::compile_source(
+        "struct Record { tag: u8; id: i64; }
+         fn main() -> usize { return offsetof(Record, missing); }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        error
+            .message
+            .contains("offsetof: struct Record has no field 'missing'"),
+        "{}",
+        error.message
+    );
+}
+
+const NESTED: &str = "struct Inner { tag: u8; value: u32; }
+                      struct Middle { pad: u8; inner: Inner; }
+                      struct Outer { flags: u16; middle: Middle; }
+                      fn value_offset() -> usize { return offsetof(Outer, middle.inner.value); }
+                      fn main() i32 { return 0; }";
+
+#[test]
+fn dotted_path_sums_each_step() {
+    let object = compile(NESTED);
+    // middle at 4, inner at 4 within it, value at 4 within that
+    let text = function_text(&object, "value_offset");
+    assert!(loads(text, 12), "{:02x?}", text);
+}
+
+#[test]
+fn each_step_must_name_a_field() {
+    let error = aura_compiler::check_source(&NESTED.replace("inner.value", "inner.missing"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("offsetof: struct Inner has no field 'missing'"),
+        "{}",
+        error
+    );
+    let error = aura_compiler::check_source(&NESTED.replace("inner.value", "pad.value"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("offsetof: U8 is not a struct, so it has no field 'value'"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,41 @@
+// `@packed` lays fields out back to back with alignment 1, and field
+// accesses use those unaligned offsets. See tests/packed_struct.aura.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+const WIRE: &str = "@packed struct Wire { tag: u8; value: u32; }";
+
+fn exit_code(name: &str, source: &str) -> Option<i32> {
+    common::exit_code(name, &format!("{}\n{}", WIRE, source))
+}
+
+#[test]
+fn layout_has_no_padding() {
+    let source = "fn main() i32 {
+                      return (sizeof(Wire) * 20 + offsetof(Wire, value) * 10 + alignof(Wire)) as i32;
+                  }";
+    assert_eq!(exit_code("packed_layout", source), Some(111));
+}
+
+#[test]
+fn explicit_alignment_still_applies() {
+    let source = "@packed @align(4) struct Padded { tag: u8; value: u32; }
+                  fn main() i32 { return (sizeof(Padded) * 10 + alignof(Padded)) as i32; }";
+    assert_eq!(exit_code("packed_align", source), Some(84));
+}
+
+#[test]
+fn elements_and_fields_use_the_packed_offsets() {
+    // The second element starts at byte 5, its value at byte 6
+    let source = "fn main() i32 {
+                      let a: [2]Wire = zeroed([2]Wire);
+                      a[0].value = 0xFFFFFFFFu32;
+                      a[1].tag = 3u8;
+                      a[1].value = 40u32;
+                      a[0].value = 2u32;
+                      return (a[0].value + a[1].value) as i32 + a[1].tag as i32;
+                  }";
+    assert_eq!(exit_code("packed_elements", source), Some(45));
+}
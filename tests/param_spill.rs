@@ -0,0 +1,21 @@
+// A parameter is spilled from its argument register into an rbp slot by the
+// prologue and read back from that slot like any local.
+
+use aura_compiler::codegen::CodegenOptions;
+
+#[test]
+fn parameter_is_spilled_and_reloaded() {
+    let source = "fn id(x: i32) -> i32 { return x; }";
+    let (object, _) = aura_compiler::compile_source(source, &CodegenOptions::default())
+        .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    let text = object.text;
+
+    // mov [rbp-8], rdi
+    let spill = [0x48, 0x89, 0xbd, 0xf8, 0xff, 0xff, 0xff];
+    // mov rax, [rbp-8]
+    let reload = [0x48, 0x8b, 0x85, 0xf8, 0xff, 0xff, 0xff];
+    let find = |needle: &[u8]| text.windows(needle.len()).position(|w| w == needle);
+    let spilled = find(&spill).unwrap_or_else(|| panic!("no spill in {:02x?}", text));
+    let reloaded = find(&reload).unwrap_or_else(|| panic!("no reload in {:02x?}", text));
+    assert!(spilled < reloaded, "{:02x?}", text);
+}
@@ -0,0 +1,131 @@
+// `p + n` and `p - n` move a pointer by n elements, so n is scaled by the
+// pointee size; `p - q` is the element count between them, as an isize.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::{check, contains};
+
+#[test]
+fn offset_is_scaled_by_the_pointee_size() {
+    let (object, _) = aura_compiler::compile_source(
+        "fn advance(p: *i32) -> *i32 { return p + 2; }
+         fn main() i32 { return 0; }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    // imul rcx, rcx, 4; add rax, rcx
+    assert!(
+        contains(
+            &object.text,
+            &[0x48, 0x69, 0xc9, 4, 0, 0, 0, 0x48, 0x01, 0xc8]
+        ),
+        "{:02x?}",
+        object.text
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn adding_two_to_an_i32_pointer_moves_eight_bytes() {
+    let code = common::exit_code(
+        "ptr_add_offset",
+        "fn main() i32 {
+             let a: [4]i32 = zeroed([4]i32);
+             let p: *i32 = &a[0];
+             let q: *i32 = p + 2;
+             return ((q as usize) - (p as usize)) as i32;
+         }",
+    );
+    assert_eq!(code, Some(8));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn moved_pointers_read_the_right_elements() {
+    let code = common::exit_code(
+        "ptr_add_read",
+        "fn third(p: *i32) -> i32 { return *(p + 2); }
+         fn main() i32 {
+             let a: [4]i32 = zeroed([4]i32);
+             a[0] = 10;
+             a[1] = 20;
+             a[2] = 30;
+             a[3] = 40;
+             let p: *i32 = &a[0];
+             let back: *i32 = (p + 3) - 1;
+             return third(p) + *back;
+         }",
+    );
+    assert_eq!(code, Some(60));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn pointer_difference_divides_by_the_element_size() {
+    // 12-byte elements, so the 36-byte distance is 3 elements
+    let code = common::exit_code(
+        "ptr_difference",
+        "struct Triple { a: i32; b: i32; c: i32; }
+         fn main() i32 {
+             let items: [4]Triple = zeroed([4]Triple);
+             let first: *Triple = &items[0];
+             let last: *Triple = &items[3];
+             let n: isize = last - first;
+             return n as i32;
+         }",
+    );
+    assert_eq!(code, Some(3));
+}
+
+#[test]
+fn pointer_arithmetic_types() {
+    check(
+        "fn f(p: *i64, q: *i64, n: i64) -> isize {
+             let later: *i64 = p + n;
+             let earlier: *i64 = p - n;
+             let swapped: *i64 = n + p;
+             return q - p;
+         }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap();
+}
+
+#[test]
+fn subtracting_pointers_to_different_types_is_an_error() {
+    let message = check(
+        "fn f(p: *i64, q: *i32) -> isize { return p - q; }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        message.contains("Cannot subtract pointers to different types"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn adding_two_pointers_is_an_error() {
+    let message = check(
+        "fn f(p: *i64, q: *i64) -> *i64 { return p + q; }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        message.contains("Invalid operand types for pointer arithmetic"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn void_pointers_must_be_cast_first() {
+    let message = check(
+        "fn f(p: *void) -> *void { return p + 1; }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap_err();
+    assert!(message.contains("cast to *u8 first"), "{}", message);
+}
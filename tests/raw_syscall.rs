@@ -0,0 +1,102 @@
+// syscall(n, a1..a6) passes its arguments in rdi, rsi, rdx, r10, r8, r9 and
+// the number in rax. Arguments are evaluated left to right: all but the last
+// are pushed, the last moves straight from rax, and the rest are popped back
+// in reverse. The named syscalls go through the same path.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::{compile, contains};
+
+#[test]
+fn fourth_argument_goes_in_r10() {
+    let text = compile(
+        "@entry()
+         fn main() -> i64 { return syscall(17, 0, 0, 0, 5) as i64; }",
+    )
+    .text;
+    // mov r10, rax; pop rdx; pop rsi; pop rdi; pop rax; syscall
+    let tail = [0x49, 0x89, 0xc2, 0x5a, 0x5e, 0x5f, 0x58, 0x0f, 0x05];
+    assert!(contains(&text, &tail), "{:02x?}", text);
+    // rcx is clobbered by syscall, so nothing is passed in it
+    assert!(!contains(&text, &[0x48, 0x89, 0xc1]), "{:02x?}", text);
+}
+
+#[test]
+fn six_arguments_fill_r8_and_r9() {
+    let text = compile(
+        "@entry()
+         fn main() -> i64 { return syscall(9, 0, 4096, 3, 34, -1, 0) as i64; }",
+    )
+    .text;
+    // mov r9, rax; pop r8; pop r10; pop rdx; pop rsi; pop rdi; pop rax
+    let tail = [
+        0x49, 0x89, 0xc1, 0x41, 0x58, 0x41, 0x5a, 0x5a, 0x5e, 0x5f, 0x58, 0x0f, 0x05,
+    ];
+    assert!(contains(&text, &tail), "{:02x?}", text);
+}
+
+#[test]
+fn named_write_is_sugar_for_syscall_one() {
+    let text = compile(
+        "@entry()
+         fn main() -> i64 { return syscall.write(1, \"hi\", 2usize) as i64; }",
+    )
+    .text;
+    // mov rdx, rax; pop rsi; pop rdi; mov rax, 1; syscall
+    let tail = [
+        0x48, 0x89, 0xc2, 0x5e, 0x5f, 0x48, 0xc7, 0xc0, 0x01, 0x00, 0x00, 0x00, 0x0f, 0x05,
+    ];
+    assert!(contains(&text, &tail), "{:02x?}", text);
+}
+
+#[test]
+fn more_than_six_arguments_are_rejected() {
+    let error = aura_compiler::compile_source(
+        "fn main() i64 { return syscall(1, 1, 2, 3, 4, 5, 6, 7) as i64; }",
+        &CodegenOptions::default(),
+    )
+    .map(|_| ())
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("at most 6 arguments, got 7"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn computed_arguments_are_evaluated_into_their_registers() {
+    let object = compile(
+        "const msg = \"hello, world\\n\";
+         fn twice(n: i64) i64 { return n * 2; }
+         fn main() i64 {
+             let n: i64 = 5;
+             let a = syscall.write(1, msg, n + 1);
+             let b = syscall.write(twice(1) - 1, msg, twice(3) + 1);
+             return (a * 10 + b) as i64;
+         }",
+    );
+    let output = common::run("syscall_computed", &object);
+    assert_eq!(output.stdout, b"hello,hello, ");
+    assert_eq!(output.status.code(), Some(67));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn kernel_reads_the_fourth_argument_from_r10() {
+    // rt_sigprocmask(how, set, oldset, sigsetsize) fails with -EINVAL unless
+    // the fourth argument is the kernel's sigset size, 8
+    let object = compile(
+        "fn twice(n: i64) i64 { return n * 2; }
+         fn main() i64 {
+             let good = syscall(14, 0, 0, 0, twice(4));
+             let bad = syscall(14, 0, 0, 0, twice(3));
+             return (good * 100 - bad) as i64;
+         }",
+    );
+    let status = common::run("syscall_r10", &object).status;
+    // good is 0 and bad is -EINVAL
+    assert_eq!(status.code(), Some(22));
+}
@@ -0,0 +1,53 @@
+// syscall.read(fd, buf, len) loads fd into rdi, the buffer's address into rsi
+// and the length into rdx, then issues syscall 0 with the byte count left in
+// rax.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::contains;
+
+// mov rdx, rax; pop rsi; pop rdi; mov rax, 0; syscall
+const READ: [u8; 14] = [
+    0x48, 0x89, 0xc2, 0x5e, 0x5f, 0x48, 0xc7, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x05,
+];
+
+#[test]
+fn read_into_a_stack_buffer() {
+    let (object, _) = aura_compiler::compile_source(
+        "@entry()
+         fn main() -> i64 {
+             let buf: [64]u8;
+             return syscall.read(0, buf, 64usize) as i64;
+         }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("compile failed: {}", e));
+    let text = &object.text;
+    assert!(contains(text, &READ), "{:02x?}", text);
+    // The buffer is passed by address: lea rax, [rbp - 64]
+    assert!(
+        contains(text, &[0x48, 0x8d, 0x85, 0xc0, 0xff, 0xff, 0xff]),
+        "{:02x?}",
+        text
+    );
+}
+
+#[test]
+fn read_needs_three_arguments() {
+    let error = aura_compiler::compile_source(
+        "fn main() i64 {
+             let buf: [64]u8;
+             return syscall.read(0, buf) as i64;
+         }",
+        &CodegenOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("read syscall takes three arguments (fd, buf, len), got 2"),
+        "{}",
+        error
+    );
+}
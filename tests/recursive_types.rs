@@ -0,0 +1,65 @@
+// A struct or union may refer to itself through a pointer, which lays out as
+// 8 bytes without resolving the pointee; containing itself by value is a
+// type error naming the cycle. See tests/linked_list_node.aura and
+// tests/recursive_struct_error.aura.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn direct_self_containment_is_an_error() {
+    let error = check("struct Loop { next: Loop; } fn main() i32 { return 0; }").unwrap_err();
+    assert!(
+        error.contains("Recursive type 'Loop' has infinite size: Loop -> Loop"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn unions_join_the_cycle() {
+    let error = check(
+        "union Tree { leaf: i64; pair: Pair; }
+         struct Pair { left: Tree; right: *Tree; }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains("Recursive type 'Tree' has infinite size: Tree -> Pair -> Tree"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn pointers_through_other_structs_are_allowed() {
+    check(
+        "struct A { b: B; }
+         struct B { back: *A; self_ref: *B; }
+         fn main() i32 { return 0; }",
+    )
+    .unwrap();
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn lists_walk_through_the_pointer_field() {
+    let source = "struct Node { value: i64; next: *Node; }
+                  fn sum3(head: *Node) -> i64 {
+                      return head->value + head->next->value + head->next->next->value;
+                  }
+                  @entry()
+                  fn main() -> i64 {
+                      let c: Node = zeroed(Node);
+                      c.value = 30i64;
+                      let b: Node = zeroed(Node);
+                      b.value = 10i64;
+                      b.next = &c;
+                      let a: Node = zeroed(Node);
+                      a.value = 2i64;
+                      a.next = &b;
+                      return sum3(&a) + sizeof(Node) as i64;
+                  }";
+    assert_eq!(common::exit_code("linked_list", source), Some(58));
+}
@@ -0,0 +1,49 @@
+// Building the same source twice writes byte-identical binaries: the symbol
+// table is sorted by address, then name, rather than kept in the order
+// codegen discovered the symbols.
+
+mod common;
+
+use aura_compiler::codegen::{aura_object_from_bytes, aura_object_to_bytes, CodegenOptions};
+
+const SOURCE: &str = "tests/reproducible_symbols.aura";
+
+fn build(name: &str) -> Vec<u8> {
+    let output = std::env::temp_dir().join(format!(
+        "aura_reproducible_{}_{}.aura",
+        name,
+        std::process::id()
+    ));
+    aura_compiler::compile_files_with_options(
+        &[SOURCE],
+        output.to_str(),
+        &CodegenOptions::default(),
+    )
+    .unwrap();
+    let bytes = std::fs::read(&output).unwrap();
+    let _ = std::fs::remove_file(&output);
+    bytes
+}
+
+#[test]
+fn two_builds_are_byte_identical() {
+    assert_eq!(build("first"), build("second"));
+}
+
+#[test]
+fn symbol_order_does_not_reach_the_binary() {
+    let mut object = common::compile(&std::fs::read_to_string(SOURCE).unwrap());
+    let bytes = aura_object_to_bytes(&object).unwrap();
+    object.symbols.reverse();
+    assert_eq!(aura_object_to_bytes(&object).unwrap(), bytes);
+
+    let written = aura_object_from_bytes(&bytes).unwrap().symbols;
+    assert!(written.len() > 1, "{:?}", written);
+    assert!(
+        written
+            .windows(2)
+            .all(|w| (w[0].offset, &w[0].name) <= (w[1].offset, &w[1].name)),
+        "{:?}",
+        written
+    );
+}
@@ -0,0 +1,49 @@
+// `return if c { a } else { b };` returns from each arm: every arm loads
+// its value and runs its own copy of the exit (defers, then the epilogue)
+// instead of jumping to a join point. Arms simple enough for a cmov keep the
+// branchless form. See tests/return_if_expr.aura.
+
+mod common;
+
+use common::{compile, function_text};
+
+// mov rsp, rbp; pop rbp: shared by a ret and main's exit syscall
+const EPILOGUE: [u8; 4] = [0x48, 0x89, 0xec, 0x5d];
+
+fn epilogues(text: &[u8]) -> usize {
+    text.windows(EPILOGUE.len())
+        .filter(|w| *w == EPILOGUE)
+        .count()
+}
+
+#[test]
+fn each_arm_reaches_an_epilogue() {
+    let source = std::fs::read_to_string("tests/return_if_expr.aura").unwrap();
+    let object = compile(&source);
+    let main = function_text(&object, "main");
+    // The outer then-arm, and both arms of the nested if
+    assert_eq!(epilogues(main), 3, "{:02x?}", main);
+}
+
+#[test]
+fn simple_arms_share_one_exit() {
+    let object = compile(
+        "fn pick(c: bool, a: i64, b: i64) -> i64 { return if c { a } else { b }; }
+         fn main() i32 { return 0; }",
+    );
+    let pick = function_text(&object, "pick");
+    assert_eq!(epilogues(pick), 1, "{:02x?}", pick);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn both_arms_run_the_defers() {
+    let source = "fn pick(a: i64) -> i64 {
+                      defer syscall.write(1, \"x\", 1);
+                      return if a > 0i64 { a * 2i64 } else { 0i64 - a + 1i64 };
+                  }
+                  fn main() i64 { return pick(10i64) + pick(-4i64); }";
+    let output = common::run("return_if_defers", &compile(source));
+    assert_eq!(output.stdout, b"xx");
+    assert_eq!(output.status.code(), Some(25));
+}
@@ -0,0 +1,49 @@
+// Every `return` is checked against the declared return type, and the error
+// points at the function's name. See tests/return_missing_value_error.aura,
+// tests/return_void_value_error.aura and tests/return_type_mismatch_error.aura.
+
+mod common;
+
+use common::check;
+
+fn fixture_error(name: &str) -> String {
+    let source = std::fs::read_to_string(format!("tests/{}.aura", name)).unwrap();
+    check(&source).unwrap_err()
+}
+
+#[test]
+fn errors_point_at_the_function_name() {
+    for (name, message) in [
+        (
+            "return_missing_value_error",
+            "Function 'pick' must return a value of type I64",
+        ),
+        (
+            "return_void_value_error",
+            "Function 'log' returns void, but this return has a value of type I64",
+        ),
+        (
+            "return_type_mismatch_error",
+            "Return type mismatch in 'answer': expected I32, got Bool",
+        ),
+    ] {
+        let error = fixture_error(name);
+        assert!(
+            error.contains(&format!("at 4:4: {}", message)),
+            "{}: {}",
+            name,
+            error
+        );
+    }
+}
+
+#[test]
+fn matching_returns_pass() {
+    check(
+        "fn log(x: i64) void { if x > 0i64 { return; } }
+         fn wide() i64 { return 7; }
+         fn flag() bool { return 1 < 2; }
+         fn main() i32 { log(1i64); return 0; }",
+    )
+    .unwrap();
+}
@@ -0,0 +1,60 @@
+// `aura run` builds source or loads a built `.aura` binary, runs it as an
+// ELF executable and exits with the program's status.
+
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+use aura_compiler::codegen::CodegenOptions;
+use std::path::PathBuf;
+use std::process::Command;
+
+const SOURCE: &str = "@entry()
+                      fn main() -> i64 { return 42i64; }";
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aura_run_command_{}_{}", std::process::id(), name))
+}
+
+fn aura_run(path: &PathBuf, args: &[&str]) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_aura"))
+        .arg("run")
+        .arg(path)
+        .args(args)
+        .status()
+        .unwrap()
+        .code()
+}
+
+#[test]
+fn run_source_exits_with_mains_result() {
+    let path = temp_path("source.aura");
+    std::fs::write(&path, SOURCE).unwrap();
+    let code = aura_run(&path, &["--", "ignored", "args"]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(code, Some(42));
+}
+
+#[test]
+fn run_built_binary_exits_with_mains_result() {
+    let source = temp_path("binary_source.aura");
+    let binary = temp_path("binary.aura");
+    std::fs::write(&source, SOURCE).unwrap();
+    aura_compiler::compile_files_with_options(
+        &[source.to_str().unwrap()],
+        binary.to_str(),
+        &CodegenOptions::default(),
+    )
+    .unwrap();
+    let code = aura_run(&binary, &[]);
+    std::fs::remove_file(&source).unwrap();
+    std::fs::remove_file(&binary).unwrap();
+    assert_eq!(code, Some(42));
+}
+
+#[test]
+fn run_reports_compile_errors() {
+    let path = temp_path("broken.aura");
+    std::fs::write(&path, "fn main() -> i64 { return missing; }").unwrap();
+    let code = aura_run(&path, &[]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(code, Some(1));
+}
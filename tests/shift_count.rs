@@ -0,0 +1,54 @@
+// Shift counts must be less than the left operand's bit width: a constant
+// count outside that range is an error at the shift, and a computed one is
+// masked to the width at runtime.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+#[test]
+fn constant_count_out_of_range_is_an_error_at_the_shift() {
+    let message = check(
+        "fn main() i32 {
+             let x: i32 = 1;
+             return x << 32;
+         }",
+    )
+    .expect_err("shift by 32 of an i32 passed");
+    assert!(
+        message.contains("at 3:23: Shift amount 32 out of range for I32"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn negative_constant_count_is_an_error() {
+    let message = check(
+        "fn main() i32 {
+             let x: i32 = 1;
+             return x >> -1;
+         }",
+    )
+    .expect_err("shift by -1 passed");
+    assert!(message.contains("at 3:23: Shift amount -1"), "{}", message);
+}
+
+#[test]
+fn computed_count_is_masked_to_the_width() {
+    let object = compile(
+        "fn shift(x: i32, n: i32) i32 { return x << n; }
+         fn main() i32 { return shift(1, 33); }",
+    );
+    let text = function_text(&object, "shift");
+    // and cl, 31
+    assert!(contains(text, &[0x80, 0xe1, 0x1f]), "{:02x?}", text);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn masked_count_wraps_at_runtime() {
+    let source = "fn shift(x: i32, n: i32) i32 { return x << n; }
+         fn main() i32 { return shift(1, 33); }";
+    assert_eq!(common::exit_code("shift_count", source), Some(2));
+}
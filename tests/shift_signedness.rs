@@ -0,0 +1,38 @@
+// `>>` shifts a signed value with sar, after sign-extending it to 64 bits,
+// and an unsigned one with shr, after zero-extending it, so the bits shifted
+// in match the operand's type.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+// movsxd rax, eax; sar rax, cl
+const SAR_I32: [u8; 6] = [0x48, 0x63, 0xc0, 0x48, 0xd3, 0xf8];
+// movzx rax, al; shr rax, cl
+const SHR_U8: [u8; 7] = [0x48, 0x0f, 0xb6, 0xc0, 0x48, 0xd3, 0xe8];
+
+fn source() -> String {
+    std::fs::read_to_string("tests/shift_signedness.aura").unwrap()
+}
+
+#[test]
+fn signed_operands_use_sar() {
+    let object = compile(&source());
+    let text = function_text(&object, "main");
+    assert!(contains(text, &SAR_I32), "{:02x?}", text);
+}
+
+#[test]
+fn unsigned_operands_use_shr() {
+    let object = compile(&source());
+    let text = function_text(&object, "main");
+    assert!(contains(text, &SHR_U8), "{:02x?}", text);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn shifted_values_keep_their_sign() {
+    // -8 >> 1 is -4 and 0xff >> 1 is 127
+    let status = common::run("shift_signedness", &compile(&source())).status;
+    assert_eq!(status.code(), Some(123));
+}
@@ -0,0 +1,51 @@
+// `&&` and `||` evaluate their right operand only when the left one does
+// not decide the result, and leave 0 or 1 in rax. See
+// tests/short_circuit.aura.
+
+mod common;
+
+use common::{compile, contains, function_text};
+
+#[test]
+fn left_operand_is_tested_then_jumped_on() {
+    let object = compile(
+        "fn both(a: bool, b: bool) -> bool { return a && b; }
+         fn either(a: bool, b: bool) -> bool { return a || b; }
+         fn main() i32 { return 0; }",
+    );
+    // test rax, rax; jz / jnz rel32
+    let both = function_text(&object, "both");
+    assert!(
+        contains(both, &[0x48, 0x85, 0xc0, 0x0f, 0x84]),
+        "{:02x?}",
+        both
+    );
+    let either = function_text(&object, "either");
+    assert!(
+        contains(either, &[0x48, 0x85, 0xc0, 0x0f, 0x85]),
+        "{:02x?}",
+        either
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn right_operands_run_only_when_needed() {
+    let source = "fn mark(c: u8) -> bool {
+                      let buf: [1]u8 = [c];
+                      syscall.write(1, &buf[0], 1);
+                      return true;
+                  }
+                  fn main() i32 {
+                      let f: bool = false;
+                      let t: bool = true;
+                      let a: bool = f && mark(97u8);
+                      let b: bool = t && mark(98u8);
+                      let c: bool = t || mark(99u8);
+                      let d: bool = f || mark(100u8);
+                      return a as i32 * 8 + b as i32 * 4 + c as i32 * 2 + d as i32;
+                  }";
+    let output = common::run("short_circuit_effects", &compile(source));
+    assert_eq!(output.stdout, b"bd");
+    assert_eq!(output.status.code(), Some(7));
+}
@@ -0,0 +1,69 @@
+// `static_assert` checks a layout against the C ABI it has to match while
+// compiling, using the same offsets and sizes code generation lays out.
+
+mod common;
+
+use aura_compiler::codegen::CodegenOptions;
+use common::check;
+
+const HEADER: &str = "struct Header { kind: u8; len: i64; }";
+
+#[test]
+fn passing_offsetof_assert_compiles() {
+    let source = format!(
+        "{}
+         static_assert(offsetof(Header, len) == 8);
+         static_assert(sizeof(Header) == 16, \"Header must match struct header\");
+         fn main() i32 {{ return 0; }}",
+        HEADER
+    );
+    aura_compiler::compile_source(&source, &CodegenOptions::default())
+        .unwrap_or_else(|e| panic!("compile failed: {}", e));
+}
+
+#[test]
+fn failing_offsetof_assert_is_an_error() {
+    let source = format!(
+        "{}
+         static_assert(offsetof(Header, len) == 1);
+         fn main() i32 {{ return 0; }}",
+        HEADER
+    );
+    let error = check(&source).expect_err("a false static_assert compiled");
+    assert!(error.contains("static_assert failed"), "{}", error);
+}
+
+#[test]
+fn failing_assert_reports_its_message() {
+    let source = format!(
+        "{}
+         static_assert(sizeof(Header) - offsetof(Header, len) == alignof(u8), \"no tail padding\");
+         fn main() i32 {{ return 0; }}",
+        HEADER
+    );
+    let error = check(&source).expect_err("a false static_assert compiled");
+    assert!(
+        error.contains("static_assert failed: no tail padding"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn assert_sees_consts_declared_before_it() {
+    let source = format!(
+        "{}
+         const PADDING: usize = offsetof(Header, len) - sizeof(u8);
+         static_assert(PADDING == 7);
+         fn main() i32 {{ return 0; }}",
+        HEADER
+    );
+    check(&source).unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn non_bool_condition_is_an_error() {
+    let error = check("static_assert(sizeof(i64)); fn main() i32 { return 0; }")
+        .expect_err("an integer condition compiled");
+    assert!(error.contains("Expected a bool"), "{}", error);
+}
@@ -0,0 +1,32 @@
+// A string written by length keeps its embedded NULs; used as a C string,
+// where a NUL would cut it short, it is rejected. See
+// tests/string_embedded_nul.aura.
+
+mod common;
+
+use common::check;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn write_sends_every_byte() {
+    let source = std::fs::read_to_string("tests/string_embedded_nul.aura").unwrap();
+    let output = common::run("string_embedded_nul", &common::compile(&source));
+    assert_eq!(output.stdout, b"a\0bx\0y\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn embedded_nul_in_a_c_string_is_an_error() {
+    let error = check(
+        "fn main() i32 {
+             let s: *u8 = \"a\\0b\";
+             return 0;
+         }",
+    )
+    .expect_err("embedded NUL in a C string passed");
+    assert!(
+        error.contains("String literal with an embedded NUL cannot be used as a C string"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,48 @@
+// `aura build --strip` writes the binary without its symbol table; text and
+// data keep their resolved addresses, so the stripped program is otherwise
+// the same and still runs.
+
+mod common;
+
+use aura_compiler::codegen::{aura_object_from_bytes, AuraBinary, CodegenOptions, DumpOptions};
+
+const SOURCE: &str = "tests/reproducible_symbols.aura";
+
+fn build(strip: bool) -> Vec<u8> {
+    let output =
+        std::env::temp_dir().join(format!("aura_strip_{}_{}.aura", strip, std::process::id()));
+    let options = CodegenOptions {
+        strip,
+        ..CodegenOptions::default()
+    };
+    aura_compiler::compile_files_with_options(&[SOURCE], output.to_str(), &options).unwrap();
+    let bytes = std::fs::read(&output).unwrap();
+    let _ = std::fs::remove_file(&output);
+    bytes
+}
+
+#[test]
+fn stripped_binary_has_no_symbols_and_the_same_text() {
+    let full = aura_object_from_bytes(&build(false)).unwrap();
+    let stripped_bytes = build(true);
+    let stripped = aura_object_from_bytes(&stripped_bytes).unwrap();
+    assert!(!full.symbols.is_empty());
+    assert!(stripped.symbols.is_empty(), "{:?}", stripped.symbols);
+    assert_eq!(stripped.text, full.text);
+    assert_eq!(stripped.data, full.data);
+    assert_eq!(stripped.entry_point, full.entry_point);
+
+    let mut dump = Vec::new();
+    AuraBinary::dump_to(&mut dump, &stripped_bytes, &DumpOptions::default()).unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    assert!(dump.contains("\nSymbols: 0\n"), "{}", dump);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn stripped_binary_still_runs() {
+    let object = aura_object_from_bytes(&build(true)).unwrap();
+    let status = common::run("strip_run", &object).status;
+    // helper_a(1) is (1 + 3) * 4
+    assert_eq!(status.code(), Some(16));
+}
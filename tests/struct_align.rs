@@ -0,0 +1,36 @@
+// `@align(N)` raises the alignment of a struct or a field, never lowers it,
+// and sizeof pads the struct to a multiple of it. See tests/struct_align.aura.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn alignment_must_be_a_power_of_two() {
+    let error = check("@align(12) struct S { x: i32; } fn main() i32 { return 0; }").unwrap_err();
+    assert!(
+        error.contains("Alignment must be a power of two integer literal"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn forced_alignment_pads_the_size() {
+    // 20 bytes of fields round up to 32 under @align(16)
+    let source = "@align(16) struct Five { a: i32; b: i32; c: i32; d: i32; e: i32; }
+                  fn main() i32 { return (sizeof(Five) + alignof(Five)) as i32; }";
+    assert_eq!(common::exit_code("struct_align_pad", source), Some(48));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn smaller_alignment_keeps_the_natural_one() {
+    // id stays at offset 8 and the struct at alignment 8
+    let source = "@align(2) struct Record { tag: u8; @align(1) id: i64; }
+                  fn main() i32 {
+                      return (offsetof(Record, id) * 10 + alignof(Record)) as i32;
+                  }";
+    assert_eq!(common::exit_code("struct_align_natural", source), Some(88));
+}
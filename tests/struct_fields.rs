@@ -0,0 +1,124 @@
+// Field reads and writes address the struct in place: `.` starts from the
+// struct's own address (a variable's slot, an element, a dereference), `->`
+// from the pointer's value, and both add the field's layout offset before
+// loading or storing at the field's width.
+
+mod common;
+
+use aura_compiler::codegen::{AuraObject, CodegenOptions};
+use common::{contains, function_text};
+
+// `b` follows one byte of `a` and seven bytes of padding
+const PAIR: &str = "struct Pair { a: u8; b: i64; }";
+
+fn compile(source: &str) -> AuraObject {
+    aura_compiler::compile_source(&format!("{}\n{}", PAIR, source), &CodegenOptions::default())
+        .unwrap_or_else(|e| panic!("compile failed: {}", e))
+        .0
+}
+
+// add rax, 8
+const ADD_B_OFFSET: [u8; 6] = [0x48, 0x05, 0x08, 0x00, 0x00, 0x00];
+// mov rax, [rbp-8]: the pointer parameter's slot
+const LOAD_PTR: [u8; 7] = [0x48, 0x8b, 0x85, 0xf8, 0xff, 0xff, 0xff];
+
+#[test]
+fn arrow_read_offsets_the_pointer_value() {
+    let object = compile(
+        "fn get_a(p: *Pair) -> u8 { return p->a; }
+         fn get_b(p: *Pair) -> i64 { return p->b; }",
+    );
+    let get_a = function_text(&object, "get_a");
+    assert!(contains(get_a, &LOAD_PTR), "{:02x?}", get_a);
+    // The first field needs no offset; movzx eax, byte [rax]
+    assert!(!contains(get_a, &[0x48, 0x05]), "{:02x?}", get_a);
+    assert!(contains(get_a, &[0x0f, 0xb6, 0x00]), "{:02x?}", get_a);
+
+    let get_b = function_text(&object, "get_b");
+    let mut read_b = LOAD_PTR.to_vec();
+    read_b.extend_from_slice(&ADD_B_OFFSET);
+    // mov rax, [rax]
+    read_b.extend_from_slice(&[0x48, 0x8b, 0x00]);
+    assert!(contains(get_b, &read_b), "{:02x?}", get_b);
+}
+
+#[test]
+fn dot_write_offsets_the_slot_address() {
+    let object = compile(
+        "fn set() -> i64 {
+             let s: Pair = zeroed(Pair);
+             s.b = 5i64;
+             return s.b;
+         }",
+    );
+    let set = function_text(&object, "set");
+    // lea rax, [rbp-disp32]; add rax, 8
+    let at = set
+        .windows(ADD_B_OFFSET.len())
+        .position(|w| w == ADD_B_OFFSET)
+        .unwrap_or_else(|| panic!("no field offset in {:02x?}", set));
+    assert_eq!(&set[at - 7..at - 4], &[0x48, 0x8d, 0x85], "{:02x?}", set);
+    // mov [rcx], rax stores the full 8-byte field
+    assert!(contains(set, &[0x48, 0x89, 0x01]), "{:02x?}", set);
+}
+
+#[test]
+fn address_of_a_local_is_its_slot() {
+    let object = compile(
+        "fn take() -> i64 {
+             let n: i64 = 1i64;
+             let p: *i64 = &n;
+             return *p;
+         }",
+    );
+    let take = function_text(&object, "take");
+    // lea rax, [rbp-8]
+    assert!(
+        contains(take, &[0x48, 0x8d, 0x85, 0xf8, 0xff, 0xff, 0xff]),
+        "{:02x?}",
+        take
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn fields_round_trip_through_dot_and_arrow() {
+    let object = compile(
+        "fn get_b(p: *Pair) -> i64 { return p->b; }
+         fn set_a(p: *Pair, v: u8) -> void { p->a = v; }
+         @entry()
+         fn main() -> i64 {
+             let s: Pair = zeroed(Pair);
+             s.a = 2u8;
+             s.b = 40i64;
+             let p: *Pair = &s;
+             set_a(p, 7u8);
+             if s.a != 7u8 { return 1i64; }
+             p->b = p->b + 2i64;
+             return get_b(p);
+         }",
+    );
+    let status = common::run("struct_fields", &object).status;
+    assert_eq!(status.code(), Some(42));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn fields_of_elements_and_dereferenced_pointers() {
+    let object = compile(
+        "@entry()
+         fn main() -> i64 {
+             let a: [2]Pair = zeroed([2]Pair);
+             let i: i64 = 1i64;
+             a[i].b = 30i64;
+             a[0].a = 4u8;
+             let p: *Pair = &a[1];
+             let r: **Pair = &p;
+             (*r)->b = (*r)->b + 5i64;
+             (*p).a = 3u8;
+             return a[1].b + a[0].a as i64 + (*p).a as i64;
+         }",
+    );
+    let status = common::run("struct_fields_lvalues", &object).status;
+    assert_eq!(status.code(), Some(42));
+}
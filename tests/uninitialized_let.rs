@@ -0,0 +1,40 @@
+// A `let` without an initializer must be assigned on every path before it
+// is read; writing it through its address counts as assigning it.
+
+mod common;
+
+use common::check;
+
+const FILL: &str = "fn fill(p: *i32) void { *p = 42; }
+     fn main() i32 {
+         let x: i32;
+         fill(&x);
+         return x;
+     }";
+
+#[test]
+fn reading_an_unassigned_let_is_an_error() {
+    let message = check(
+        "fn main() i32 {
+             let x: i32;
+             return x;
+         }",
+    )
+    .expect_err("read of an unassigned let passed");
+    assert!(
+        message.contains("Use of possibly-uninitialized variable: x"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn taking_the_address_initializes() {
+    check(FILL).unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn filled_through_a_pointer() {
+    assert_eq!(common::exit_code("uninitialized_fill", FILL), Some(42));
+}
@@ -0,0 +1,77 @@
+// A call to a variadic extern passes every argument in System V registers,
+// sets al to the number of vector registers used, promotes an f32 in the
+// variadic tail to f64, and calls through a relocation on the name. See
+// tests/variadic_extern.aura.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+// mov al, imm8; call [rip + rel32]
+fn sets_al_then_calls(text: &[u8], vector_registers: u8) -> bool {
+    contains(text, &[0xb0, vector_registers, 0xff, 0x15])
+}
+
+#[test]
+fn extra_integer_arguments_follow_the_format() {
+    let source = std::fs::read_to_string("tests/variadic_extern.aura").unwrap();
+    let object = compile(&source);
+    let main = function_text(&object, "main");
+    // Popped last to first: rdx, rsi, then the format in rdi
+    assert!(
+        contains(
+            main,
+            &[0x58, 0x48, 0x89, 0xc2, 0x58, 0x48, 0x89, 0xc6, 0x58, 0x48, 0x89, 0xc7]
+        ),
+        "{:02x?}",
+        main
+    );
+    assert!(sets_al_then_calls(main, 0), "{:02x?}", main);
+    assert!(
+        object.relocations.iter().any(|r| r.symbol == "printf"),
+        "{:?}",
+        object.relocations
+    );
+}
+
+#[test]
+fn an_f32_extra_is_promoted_and_counted() {
+    let object = compile(
+        "extern fn printf(fmt: *u8, ...) -> i32;
+         fn main() i32 { let x: f32 = 1.5f32; printf(\"%f\\n\", x); return 0; }",
+    );
+    let main = function_text(&object, "main");
+    // movd xmm0, eax; cvtss2sd xmm0, xmm0
+    assert!(
+        contains(main, &[0x66, 0x0f, 0x6e, 0xc0, 0xf3, 0x0f, 0x5a, 0xc0]),
+        "{:02x?}",
+        main
+    );
+    assert!(sets_al_then_calls(main, 1), "{:02x?}", main);
+}
+
+#[test]
+fn only_externs_can_be_variadic() {
+    let error =
+        check("fn sum(n: i64, ...) -> i64 { return n; } fn main() i32 { return 0; }").unwrap_err();
+    assert!(
+        error.contains("Only extern functions can be variadic, but 'sum' has a body"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn variadic_arguments_must_be_scalars() {
+    let error = check(
+        "struct P { x: i64; }
+         extern fn printf(fmt: *u8, ...) -> i32;
+         fn main() i32 { let p: P = zeroed(P); printf(\"%d\", p); return 0; }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains("Variadic argument 1 must be a scalar or pointer, got Named(\"P\")"),
+        "{}",
+        error
+    );
+}
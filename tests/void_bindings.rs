@@ -0,0 +1,53 @@
+// `void` is only valid as a return type: a variable, parameter, constant,
+// field, union variant or array element of type void is a type error. See
+// tests/void_let_error.aura and tests/void_field_error.aura.
+
+mod common;
+
+use common::check;
+
+fn void_error(source: &str, what: &str) {
+    let error = check(source).unwrap_err();
+    assert!(
+        error.contains(what) && error.contains("void is only valid as a return type"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn parameters_cannot_be_void() {
+    void_error(
+        "fn f(x: void) -> i32 { return 0; } fn main() i32 { return 0; }",
+        "Parameter 'x' of f cannot have type void",
+    );
+}
+
+#[test]
+fn union_variants_cannot_be_void() {
+    void_error(
+        "union U { a: i32; b: void; } fn main() i32 { return 0; }",
+        "Field 'b' of union U cannot have type void",
+    );
+}
+
+#[test]
+fn arrays_of_void_are_rejected() {
+    void_error(
+        "fn main() i32 { let a: [2]void; return 0; }",
+        "Variable 'a' is an array of void",
+    );
+}
+
+#[test]
+fn inferred_void_lets_are_rejected() {
+    void_error(
+        "fn nothing() -> void { } fn main() i32 { let v = nothing(); return 0; }",
+        "Variable 'v' cannot have type void",
+    );
+}
+
+#[test]
+fn void_returns_are_still_fine() {
+    check("fn nothing() -> void { } fn main() i32 { nothing(); return 0; }").unwrap();
+}
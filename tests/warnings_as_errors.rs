@@ -0,0 +1,40 @@
+// --warnings-as-errors turns the unused-variable warning of
+// tests/unused_variable.aura into a build failure; without it the same
+// source builds.
+
+use aura_compiler::codegen::CodegenOptions;
+
+const SOURCE: &str = "tests/unused_variable.aura";
+
+fn build(warnings_as_errors: bool) -> Result<(), anyhow::Error> {
+    let output = std::env::temp_dir().join(format!(
+        "aura_unused_variable_{}_{}.aura",
+        std::process::id(),
+        warnings_as_errors
+    ));
+    let options = CodegenOptions {
+        warnings_as_errors,
+        ..CodegenOptions::default()
+    };
+    let result = aura_compiler::compile_files_with_options(&[SOURCE], output.to_str(), &options);
+    let _ = std::fs::remove_file(&output);
+    result
+}
+
+#[test]
+fn unused_variable_only_warns_by_default() {
+    let source = std::fs::read_to_string(SOURCE).unwrap();
+    let warnings = aura_compiler::check_source(&source).unwrap();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].message.contains("Unused variable 'unused'"));
+    // At the name in its `let`, not at the function
+    let warning = warnings[0].to_string();
+    assert!(warning.contains("at 5:9: "), "{}", warning);
+    build(false).unwrap();
+}
+
+#[test]
+fn warnings_as_errors_fails_the_build() {
+    let error = build(true).unwrap_err();
+    assert!(error.to_string().contains("treated as errors"), "{}", error);
+}
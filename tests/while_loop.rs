@@ -0,0 +1,69 @@
+// A `while` loop checks its condition at the top, jumps past the body when
+// it is false and jumps back to the check after the body; `continue` goes to
+// the check and `break` past the end. See tests/while_loop.aura.
+
+mod common;
+
+use common::{compile, function_text};
+
+fn rel32(text: &[u8], at: usize) -> i64 {
+    i32::from_le_bytes(text[at..at + 4].try_into().unwrap()) as i64
+}
+
+#[test]
+fn body_is_bracketed_by_the_exit_and_the_back_edge() {
+    let object = compile(
+        "fn spin(flag: bool) -> i32 {
+             while flag { }
+             return 0;
+         }
+         fn main() i32 { return 0; }",
+    );
+    let spin = function_text(&object, "spin");
+    // test rax, rax; jz rel32
+    let jz = spin
+        .windows(5)
+        .position(|w| w == [0x48, 0x85, 0xc0, 0x0f, 0x84])
+        .unwrap_or_else(|| panic!("no exit jump in {:02x?}", spin))
+        + 3;
+    // The empty body leaves the back edge right after the exit jump
+    let jmp = jz + 6;
+    assert_eq!(spin[jmp], 0xe9, "{:02x?}", spin);
+    let after_loop = jmp + 5;
+    assert_eq!(jz as i64 + 6 + rel32(spin, jz + 2), after_loop as i64);
+    // Back to the condition load, before the test
+    let top = after_loop as i64 + rel32(spin, jmp + 1);
+    assert!(top < jz as i64 - 3, "{:02x?}", spin);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn false_condition_skips_the_body() {
+    let source = "fn main() i32 {
+                      let n: i32 = 5;
+                      while n > 10 { n = 0; }
+                      return n;
+                  }";
+    assert_eq!(common::exit_code("while_false", source), Some(5));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn nested_loops_break_and_continue_their_own_loop() {
+    let source = "fn main() i32 {
+                      let outer: i32 = 0;
+                      let total: i32 = 0;
+                      while outer < 3 {
+                          outer = outer + 1;
+                          let inner: i32 = 0;
+                          while true {
+                              inner = inner + 1;
+                              if inner == 2 { continue; }
+                              if inner > 4 { break; }
+                              total = total + 1;
+                          }
+                      }
+                      return total * 10 + outer;
+                  }";
+    assert_eq!(common::exit_code("while_nested", source), Some(93));
+}
@@ -0,0 +1,74 @@
+// `syscall.write(fd, buf, len)` evaluates `len` at runtime into rdx instead
+// of using the buffer's static size; without `len`, a local array writes
+// all of its bytes. See tests/write_computed_len.aura.
+
+mod common;
+
+use common::{check, compile, contains, function_text};
+
+// mov rax, [rbp-8]; mov rdx, rax: `n` is the last argument evaluated
+const LENGTH_FROM_N: [u8; 10] = [0x48, 0x8b, 0x85, 0xf8, 0xff, 0xff, 0xff, 0x48, 0x89, 0xc2];
+
+#[test]
+fn length_is_loaded_into_rdx() {
+    let object = compile(
+        "fn main() i32 {
+             let n: usize = 3usize;
+             syscall.write(1, \"hello\", n);
+             return 0;
+         }",
+    );
+    let main = function_text(&object, "main");
+    assert!(contains(main, &LENGTH_FROM_N), "{:02x?}", main);
+}
+
+#[test]
+fn length_must_be_an_integer() {
+    let error = check(
+        "fn main() i32 {
+             let b: bool = true;
+             syscall.write(1, \"hello\", b);
+             return 0;
+         }",
+    )
+    .unwrap_err();
+    assert!(
+        error.contains("write length must be an integer, got Bool"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn only_the_computed_length_is_written() {
+    let source = std::fs::read_to_string("tests/write_computed_len.aura").unwrap();
+    let output = common::run("write_computed_len", &compile(&source));
+    assert_eq!(output.stdout, b"hello!\n");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn pointer_buffers_take_a_length_too() {
+    let source = "fn main() i32 {
+                      let buf: [4]u8 = [104u8, 105u8, 10u8, 0u8];
+                      let p: *u8 = &buf[0];
+                      let n: i32 = 1;
+                      syscall.write(1, p, n + 2);
+                      return 0;
+                  }";
+    let output = common::run("write_pointer_len", &compile(source));
+    assert_eq!(output.stdout, b"hi\n");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn local_arrays_default_to_their_size() {
+    let source = "fn main() i32 {
+                      let buf: [3]u8 = [104u8, 105u8, 10u8];
+                      syscall.write(1, buf);
+                      return 0;
+                  }";
+    let output = common::run("write_local_array", &compile(source));
+    assert_eq!(output.stdout, b"hi\n");
+}
@@ -0,0 +1,45 @@
+// `zeroed(T)` clears every byte of a T, padding included, so a local
+// initialized with it reads back 0 even from a frame slot an earlier call
+// left dirty.
+
+mod common;
+
+use common::check;
+
+#[test]
+fn zeroed_has_the_named_type() {
+    check(
+        "struct Point { x: i32; y: i32; }
+         fn main() i32 {
+             let origin: Point = zeroed(Point);
+             let grid: [4]i64 = zeroed([4]i64);
+             let n: u8 = zeroed(u8);
+             return origin.x + grid[3] as i32 + n as i32;
+         }",
+    )
+    .unwrap_or_else(|e| panic!("check failed: {}", e));
+}
+
+#[test]
+fn zeroed_of_another_type_is_a_mismatch() {
+    let error = check("fn main() i32 { let n: i32 = zeroed(i64); return n; }")
+        .expect_err("zeroed i64 into an i32 passed");
+    assert!(error.contains("Type mismatch in let"), "{}", error);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn zeroed_local_reads_back_zero() {
+    let source = "struct Point { x: i64; tag: u8; y: i64; }
+         fn dirty() i64 {
+             let junk: [4]i64 = [7i64, 7i64, 7i64, 7i64];
+             return junk[0] + junk[3];
+         }
+         fn fresh() i64 {
+             let p: Point = zeroed(Point);
+             let a: [4]i64 = zeroed([4]i64);
+             return p.x + p.tag as i64 + p.y + a[0] + a[1] + a[2] + a[3];
+         }
+         fn main() i64 { return dirty() + fresh(); }";
+    assert_eq!(common::exit_code("zeroed", source), Some(14));
+}